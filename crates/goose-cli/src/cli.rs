@@ -2,23 +2,35 @@ use anyhow::Result;
 use clap::{Args, Parser, Subcommand};
 
 use goose::config::{Config, ExtensionConfig};
+use goose::scheduler_notifiers::NotifierTarget;
 
 use crate::commands::bench::agent_generator;
 use crate::commands::configure::handle_configure;
 use crate::commands::info::handle_info;
 use crate::commands::mcp::run_server;
+use crate::commands::profile::{
+    check_profile_env, handle_profile_add, handle_profile_list, handle_profile_use, select_profile,
+};
 use crate::commands::project::{handle_project_default, handle_projects_interactive};
-use crate::commands::recipe::{handle_deeplink, handle_validate};
+use crate::commands::recipe::{handle_deeplink, handle_lock, handle_run_workflow, handle_validate};
+use crate::commands::recipe_library::{
+    handle_install, handle_list, handle_remove, handle_search, handle_upgrade,
+};
+use crate::commands::recipe_test::handle_test;
 // Import the new handlers from commands::schedule
 use crate::commands::schedule::{
-    handle_schedule_add, handle_schedule_cron_help, handle_schedule_list, handle_schedule_remove,
-    handle_schedule_run_now, handle_schedule_services_status, handle_schedule_services_stop,
-    handle_schedule_sessions,
+    handle_schedule_add, handle_schedule_cron_help, handle_schedule_history, handle_schedule_list,
+    handle_schedule_remove, handle_schedule_run_now, handle_schedule_services_status,
+    handle_schedule_services_stop, handle_schedule_sessions, parse_notifier, schedule_notifiers,
+    schedule_policy, schedule_trigger,
 };
 use crate::commands::session::{handle_session_list, handle_session_remove};
 use crate::logging::setup_logging;
 use crate::recipes::extract_from_cli::extract_recipe_info_from_cli;
-use crate::recipes::recipe::{explain_recipe_with_parameters, load_recipe_content_as_template};
+use crate::recipes::recipe::{
+    apply_recipe_lock, explain_recipe_with_parameters, is_workflow_recipe, load_recipe_as_template,
+    load_recipe_content_as_template,
+};
 use crate::session;
 use crate::session::{build_session, SessionBuilderConfig, SessionSettings};
 use goose_bench::bench_config::BenchRunConfig;
@@ -32,6 +44,15 @@ use std::path::PathBuf;
 #[derive(Parser)]
 #[command(author, version, display_name = "", about, long_about = None)]
 struct Cli {
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Configuration profile to use (overrides GOOSE_PROFILE)",
+        long_help = "Use the settings, extensions and secrets of this named profile. Profiles are managed with 'goose configure profile'."
+    )]
+    profile: Option<String>,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -129,14 +150,115 @@ enum SchedulerCommand {
         #[arg(
             long,
             help = "Cron expression for the schedule",
-            long_help = "Cron expression for when to run the job. Examples:\n  '0 * * * *'     - Every hour at minute 0\n  '0 */2 * * *'   - Every 2 hours\n  '@hourly'       - Every hour (shorthand)\n  '0 9 * * *'     - Every day at 9:00 AM\n  '0 9 * * 1'     - Every Monday at 9:00 AM\n  '0 0 1 * *'     - First day of every month at midnight"
+            long_help = "Cron expression for when to run the job. Examples:\n  '0 * * * *'     - Every hour at minute 0\n  '0 */2 * * *'   - Every 2 hours\n  '@hourly'       - Every hour (shorthand)\n  '0 9 * * *'     - Every day at 9:00 AM\n  '0 9 * * 1'     - Every Monday at 9:00 AM\n  '0 0 1 * *'     - First day of every month at midnight",
+            required_unless_present_any = ["on_file_change", "on_webhook", "after"],
+            conflicts_with_all = ["on_file_change", "on_webhook", "after"]
         )]
-        cron: String,
+        cron: Option<String>,
         #[arg(
             long,
             help = "Recipe source (path to file, or base64 encoded recipe string)"
         )]
         recipe_source: String,
+        #[arg(
+            long,
+            value_name = "GLOB",
+            help = "Run the job when files matching a path or glob change",
+            long_help = "Run the job when files matching a path or glob, such as '~/notes/**/*.md', change. The changed file is passed to the recipe as the 'changed_path' parameter.",
+            conflicts_with_all = ["on_webhook", "after"]
+        )]
+        on_file_change: Option<String>,
+        #[arg(
+            long,
+            help = "Run the job when goose-server receives a signed webhook for it",
+            conflicts_with = "after"
+        )]
+        on_webhook: bool,
+        #[arg(
+            long,
+            value_name = "SECRET",
+            help = "Secret webhooks are signed with (generated if not given)",
+            requires = "on_webhook"
+        )]
+        webhook_secret: Option<String>,
+        #[arg(
+            long = "webhook-param",
+            value_name = "KEY=POINTER",
+            help = "Fill a recipe parameter from the webhook payload, e.g. --webhook-param title=/pull_request/title",
+            action = clap::ArgAction::Append,
+            value_parser = parse_key_val,
+            requires = "on_webhook"
+        )]
+        webhook_params: Vec<(String, String)>,
+        #[arg(
+            long,
+            value_name = "JOB_ID",
+            help = "Run the job after another scheduled job finishes"
+        )]
+        after: Option<String>,
+        #[arg(
+            long,
+            value_name = "STATUS",
+            help = "Which outcome of the --after job runs this one",
+            value_parser = ["success", "failure", "any"],
+            default_value = "success",
+            requires = "after"
+        )]
+        after_status: String,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Attempts to make in total when a run fails, including the first"
+        )]
+        max_attempts: Option<u32>,
+        #[arg(
+            long,
+            value_name = "SECONDS",
+            help = "Delay before the first retry, doubled for each retry after it",
+            requires = "max_attempts"
+        )]
+        retry_backoff: Option<u64>,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Most runs of the job going at once",
+            default_value_t = 1
+        )]
+        max_concurrent: u32,
+        #[arg(
+            long,
+            value_name = "POLICY",
+            help = "What to do when the job is due while --max-concurrent runs are still going",
+            value_parser = ["skip", "queue", "cancel-previous"],
+            default_value = "skip"
+        )]
+        overlap: String,
+        #[arg(
+            long,
+            value_name = "POLICY",
+            help = "What to do about runs missed while goose wasn't running",
+            value_parser = ["skip", "run-once", "run-all"],
+            default_value = "skip"
+        )]
+        catch_up: String,
+        #[arg(
+            long,
+            value_name = "NOTIFIER",
+            help = "Tell someone when a run finishes: desktop, webhook:URL, email:ADDRESS[,ADDRESS...] or file:PATH",
+            long_help = "Tell someone when a run finishes. Can be given more than once.\n  desktop          - A desktop notification (Linux, over D-Bus)\n  webhook:URL      - POST a JSON summary of the run to URL\n  email:ADDRESSES  - Email a summary through the SMTP relay set by GOOSE_SMTP_HOST and GOOSE_SMTP_PORT\n  file:PATH        - Write a summary to PATH, as JSON if it ends in .json",
+            action = clap::ArgAction::Append,
+            value_parser = parse_notifier
+        )]
+        notify: Vec<NotifierTarget>,
+        #[arg(
+            long,
+            value_name = "WHEN",
+            help = "Which runs to notify about",
+            value_parser = ["always", "failure"],
+            default_value = "always",
+            requires = "notify"
+        )]
+        notify_on: String,
     },
     #[command(about = "List all scheduled jobs")]
     List {},
@@ -155,6 +277,16 @@ enum SchedulerCommand {
         #[arg(long, help = "Maximum number of sessions to return")]
         limit: Option<u32>,
     },
+    /// Show the run history of a schedule
+    #[command(about = "Show the run history of a schedule")]
+    History {
+        /// ID of the schedule
+        #[arg(long, help = "ID of the schedule")]
+        id: String,
+        /// Maximum number of runs to show
+        #[arg(long, help = "Maximum number of runs to show")]
+        limit: Option<u32>,
+    },
     /// Run a scheduled job immediately
     #[command(about = "Run a scheduled job immediately")]
     RunNow {
@@ -246,13 +378,136 @@ enum RecipeCommand {
         )]
         recipe_name: String,
     },
+
+    /// Run the tests of a recipe
+    #[command(about = "Run the tests of a recipe")]
+    Test {
+        /// Recipe name to get recipe file to test
+        #[arg(help = "recipe name to get recipe file or full path to the recipe file to test")]
+        recipe_name: String,
+
+        /// Write a JUnit XML report of the results
+        #[arg(
+            long = "junit",
+            value_name = "FILE",
+            help = "Write a JUnit XML report of the results to this file"
+        )]
+        junit: Option<PathBuf>,
+
+        /// Run tools without asking, as GOOSE_MODE=auto does
+        #[arg(
+            long = "auto",
+            help = "Let tools run without confirmation; otherwise calls that need it are denied"
+        )]
+        auto: bool,
+    },
+
+    /// Lock a recipe's extension versions, provider and model
+    #[command(about = "Lock a recipe's extension versions, provider and model")]
+    Lock {
+        /// Recipe name to get recipe file to lock
+        #[arg(help = "recipe name to get recipe file or full path to the recipe file to lock")]
+        recipe_name: String,
+    },
+
+    /// List the recipes in the recipe library
+    #[command(about = "List the recipes in the recipe library")]
+    List {
+        /// List the installed sources instead of their recipes
+        #[arg(long, help = "List the installed sources and their versions instead")]
+        sources: bool,
+    },
+
+    /// Search the recipe library
+    #[command(about = "Search the recipe library")]
+    Search {
+        #[arg(
+            help = "Words to find in recipe names, titles, descriptions, activities and parameters"
+        )]
+        query: String,
+    },
+
+    /// Install a directory or git repository of recipes into the library
+    #[command(about = "Install a directory or git repository of recipes")]
+    Install {
+        #[arg(help = "Local directory, git repository URL, or GitHub owner/repo")]
+        source: String,
+
+        /// Name for the source, defaulting to the directory or repository name
+        #[arg(long, help = "Name for the source in the library")]
+        name: Option<String>,
+
+        /// Tag, branch or commit to pin a git source to
+        #[arg(
+            long,
+            value_name = "REF",
+            help = "Pin a git source to this tag, branch or commit"
+        )]
+        version: Option<String>,
+    },
+
+    /// Remove a source from the recipe library
+    #[command(about = "Remove a source from the recipe library")]
+    Remove {
+        #[arg(help = "Name of the source to remove")]
+        name: String,
+    },
+
+    /// Upgrade git sources in the recipe library
+    #[command(about = "Upgrade git sources in the recipe library")]
+    Upgrade {
+        #[arg(help = "Source to upgrade; all unpinned sources when not given")]
+        name: Option<String>,
+
+        /// Tag, branch or commit to pin the source to
+        #[arg(
+            long,
+            value_name = "REF",
+            help = "Pin the source to this tag, branch or commit"
+        )]
+        version: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigureCommand {
+    /// Manage named configuration profiles
+    #[command(about = "Manage named configuration profiles")]
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum ProfileCommand {
+    /// Create a profile and configure its provider
+    #[command(about = "Create a profile and configure its provider")]
+    Add {
+        #[arg(help = "Name of the profile (e.g., 'work')")]
+        name: String,
+    },
+
+    /// List all profiles
+    #[command(about = "List all profiles")]
+    List {},
+
+    /// Select the profile used when --profile and GOOSE_PROFILE are not given
+    #[command(about = "Select the profile to use by default")]
+    Use {
+        #[arg(help = "Name of the profile")]
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
 enum Command {
     /// Configure Goose settings
     #[command(about = "Configure Goose settings")]
-    Configure {},
+    Configure {
+        #[command(subcommand)]
+        command: Option<ConfigureCommand>,
+    },
 
     /// Display Goose configuration information
     #[command(about = "Display Goose information")]
@@ -264,7 +519,88 @@ enum Command {
 
     /// Manage system prompts and behaviors
     #[command(about = "Run one of the mcp servers bundled with goose")]
-    Mcp { name: String },
+    Mcp {
+        name: String,
+
+        /// Serve over streamable HTTP on this address instead of stdio
+        #[arg(
+            long,
+            value_name = "ADDRESS",
+            help = "Serve over streamable HTTP on this address (e.g. 127.0.0.1:8700) instead of stdio"
+        )]
+        http: Option<std::net::SocketAddr>,
+
+        /// Allow serving HTTP on an address other than loopback
+        #[arg(
+            long,
+            requires = "http",
+            help = "Allow --http to bind an address other than localhost; clients still need the bearer token"
+        )]
+        allow_remote: bool,
+    },
+
+    /// Run goose itself as an MCP server
+    #[command(
+        name = "mcp-serve",
+        about = "Run goose as an MCP server so other agents and IDEs can hand it tasks"
+    )]
+    McpServe {
+        /// Serve over streamable HTTP on this address instead of stdio
+        #[arg(
+            long,
+            value_name = "ADDRESS",
+            help = "Serve over streamable HTTP on this address (e.g. 127.0.0.1:8701) instead of stdio"
+        )]
+        http: Option<std::net::SocketAddr>,
+
+        /// Allow serving HTTP on an address other than loopback
+        #[arg(
+            long,
+            requires = "http",
+            help = "Allow --http to bind an address other than localhost; clients still need the bearer token"
+        )]
+        allow_remote: bool,
+    },
+
+    /// Serve the tools of the configured extensions as a single MCP server
+    #[command(
+        name = "mcp-gateway",
+        about = "Serve the tools of your configured extensions as one MCP server"
+    )]
+    McpGateway {
+        /// Only include these extensions
+        #[arg(
+            long = "extension",
+            value_name = "NAME",
+            help = "Only include these extensions from config.yaml, even if disabled (e.g., 'developer' or multiple: 'developer,memory')",
+            value_delimiter = ','
+        )]
+        extensions: Vec<String>,
+
+        /// Serve over streamable HTTP on this address instead of stdio
+        #[arg(
+            long,
+            value_name = "ADDRESS",
+            help = "Serve over streamable HTTP on this address (e.g. 127.0.0.1:8702) instead of stdio"
+        )]
+        http: Option<std::net::SocketAddr>,
+
+        /// Allow serving HTTP on an address other than loopback
+        #[arg(
+            long,
+            requires = "http",
+            help = "Allow --http to bind an address other than localhost; clients still need the bearer token"
+        )]
+        allow_remote: bool,
+
+        /// Where to write the audit log
+        #[arg(
+            long,
+            value_name = "FILE",
+            help = "Append the audit log of tool calls to this file instead of the default location"
+        )]
+        audit_log: Option<PathBuf>,
+    },
 
     /// Start or resume interactive chat sessions
     #[command(
@@ -459,6 +795,15 @@ enum Command {
         )]
         render_recipe: bool,
 
+        /// Fail instead of warning when the recipe has drifted from its lock file
+        #[arg(
+            long = "locked",
+            help = "Fail if the recipe has drifted from its lock file, or has none",
+            long_help = "Require the recipe's lock file (written by `goose recipe lock`) and fail if the recipe's extensions, provider or model have drifted from it. Without this flag drift is reported as a warning.",
+            requires = "recipe"
+        )]
+        locked: bool,
+
         /// Maximum number of consecutive identical tool calls allowed
         #[arg(
             long = "max-tool-repetitions",
@@ -586,8 +931,10 @@ enum Command {
         model: Option<String>,
     },
 
-    /// Recipe utilities for validation and deeplinking
-    #[command(about = "Recipe utilities for validation and deeplinking")]
+    /// Recipe utilities for validation, deeplinking, testing and the recipe library
+    #[command(
+        about = "Recipe utilities for validation, deeplinking, testing and the recipe library"
+    )]
     Recipe {
         #[command(subcommand)]
         command: RecipeCommand,
@@ -667,22 +1014,56 @@ pub struct InputConfig {
 pub async fn cli() -> Result<()> {
     let cli = Cli::parse();
 
+    // Select the profile before anything reads the global config
+    if let Some(profile) = &cli.profile {
+        select_profile(profile)?;
+    } else {
+        check_profile_env()?;
+    }
+
     // Track the current directory in projects.json
     if let Err(e) = crate::project_tracker::update_project_tracker(None, None) {
         eprintln!("Warning: Failed to update project tracker: {}", e);
     }
 
     match cli.command {
-        Some(Command::Configure {}) => {
-            let _ = handle_configure().await;
+        Some(Command::Configure { command }) => {
+            match command {
+                Some(ConfigureCommand::Profile { command }) => match command {
+                    ProfileCommand::Add { name } => handle_profile_add(&name).await?,
+                    ProfileCommand::List {} => handle_profile_list()?,
+                    ProfileCommand::Use { name } => handle_profile_use(&name)?,
+                },
+                None => {
+                    let _ = handle_configure().await;
+                }
+            }
             return Ok(());
         }
         Some(Command::Info { verbose }) => {
             handle_info(verbose)?;
             return Ok(());
         }
-        Some(Command::Mcp { name }) => {
-            let _ = run_server(&name).await;
+        Some(Command::Mcp {
+            name,
+            http,
+            allow_remote,
+        }) => {
+            run_server(&name, http, allow_remote).await?;
+        }
+        Some(Command::McpGateway {
+            extensions,
+            http,
+            allow_remote,
+            audit_log,
+        }) => {
+            crate::commands::mcp_gateway::run_gateway(extensions, http, allow_remote, audit_log)
+                .await?;
+            return Ok(());
+        }
+        Some(Command::McpServe { http, allow_remote }) => {
+            crate::commands::mcp_serve::run_goose_server(http, allow_remote).await?;
+            return Ok(());
         }
         Some(Command::Session {
             command,
@@ -801,6 +1182,7 @@ pub async fn cli() -> Result<()> {
             params,
             explain,
             render_recipe,
+            locked,
             scheduled_job_id,
             quiet,
             additional_sub_recipes,
@@ -872,7 +1254,37 @@ pub async fn cli() -> Result<()> {
                         println!("{}", recipe);
                         return Ok(());
                     }
-                    extract_recipe_info_from_cli(recipe_name, params, additional_sub_recipes)?
+                    if is_workflow_recipe(&recipe_name)? {
+                        let recipe = load_recipe_as_template(&recipe_name, params)
+                            .and_then(|mut recipe| {
+                                apply_recipe_lock(&recipe_name, &mut recipe, locked)?;
+                                Ok(recipe)
+                            })
+                            .unwrap_or_else(|err| {
+                                eprintln!("{}: {}", console::style("Error").red().bold(), err);
+                                std::process::exit(1);
+                            });
+                        if let Err(err) = handle_run_workflow(
+                            recipe,
+                            no_session,
+                            provider,
+                            model,
+                            scheduled_job_id,
+                            quiet,
+                        )
+                        .await
+                        {
+                            eprintln!("{}: {}", console::style("Error").red().bold(), err);
+                            std::process::exit(1);
+                        }
+                        return Ok(());
+                    }
+                    extract_recipe_info_from_cli(
+                        recipe_name,
+                        params,
+                        additional_sub_recipes,
+                        locked,
+                    )?
                 }
                 (None, None, None) => {
                     eprintln!("Error: Must provide either --instructions (-i), --text (-t), or --recipe. Use -i - for stdin.");
@@ -930,8 +1342,38 @@ pub async fn cli() -> Result<()> {
                     id,
                     cron,
                     recipe_source,
+                    on_file_change,
+                    on_webhook,
+                    webhook_secret,
+                    webhook_params,
+                    after,
+                    after_status,
+                    max_attempts,
+                    retry_backoff,
+                    max_concurrent,
+                    overlap,
+                    catch_up,
+                    notify,
+                    notify_on,
                 } => {
-                    handle_schedule_add(id, cron, recipe_source).await?;
+                    let trigger = schedule_trigger(
+                        on_file_change,
+                        on_webhook,
+                        webhook_secret,
+                        webhook_params,
+                        after,
+                        &after_status,
+                    );
+                    let policy = schedule_policy(
+                        max_attempts,
+                        retry_backoff,
+                        max_concurrent,
+                        &overlap,
+                        &catch_up,
+                    );
+                    let notifiers = schedule_notifiers(notify, &notify_on);
+                    handle_schedule_add(id, cron, recipe_source, trigger, policy, notifiers)
+                        .await?;
                 }
                 SchedulerCommand::List {} => {
                     handle_schedule_list().await?;
@@ -943,6 +1385,9 @@ pub async fn cli() -> Result<()> {
                     // New arm
                     handle_schedule_sessions(id, limit).await?;
                 }
+                SchedulerCommand::History { id, limit } => {
+                    handle_schedule_history(id, limit).await?;
+                }
                 SchedulerCommand::RunNow { id } => {
                     // New arm
                     handle_schedule_run_now(id).await?;
@@ -995,6 +1440,35 @@ pub async fn cli() -> Result<()> {
                 RecipeCommand::Deeplink { recipe_name } => {
                     handle_deeplink(&recipe_name)?;
                 }
+                RecipeCommand::Test {
+                    recipe_name,
+                    junit,
+                    auto,
+                } => {
+                    handle_test(&recipe_name, junit, auto).await?;
+                }
+                RecipeCommand::Lock { recipe_name } => {
+                    handle_lock(&recipe_name).await?;
+                }
+                RecipeCommand::List { sources } => {
+                    handle_list(sources)?;
+                }
+                RecipeCommand::Search { query } => {
+                    handle_search(&query)?;
+                }
+                RecipeCommand::Install {
+                    source,
+                    name,
+                    version,
+                } => {
+                    handle_install(&source, name.as_deref(), version.as_deref())?;
+                }
+                RecipeCommand::Remove { name } => {
+                    handle_remove(&name)?;
+                }
+                RecipeCommand::Upgrade { name, version } => {
+                    handle_upgrade(name.as_deref(), version.as_deref())?;
+                }
             }
             return Ok(());
         }
@@ -35,6 +35,7 @@ fn get_display_name(extension_id: &str) -> String {
         "memory" => "Memory".to_string(),
         "tutorial" => "Tutorial".to_string(),
         "jetbrains" => "JetBrains".to_string(),
+        "lsp" => "Language Servers".to_string(),
         // Add other extensions as needed
         _ => {
             extension_id
@@ -528,6 +529,11 @@ pub fn configure_extensions_dialog() -> Result<(), Box<dyn Error>> {
                     "Search and read content from google drive - additional config required",
                 )
                 .item("jetbrains", "JetBrains", "Connect to jetbrains IDEs")
+                .item(
+                    "lsp",
+                    "Language Servers",
+                    "Diagnostics, definitions, references and renames from language servers",
+                )
                 .item(
                     "memory",
                     "Memory",
@@ -3,6 +3,7 @@ use console::style;
 use etcetera::{choose_app_strategy, AppStrategy};
 use goose::config::Config;
 use serde_yaml;
+use std::collections::HashMap;
 
 fn print_aligned(label: &str, value: &str, width: usize) {
     println!("  {:<width$} {}", label, value, width = width);
@@ -20,11 +21,15 @@ pub fn handle_info(verbose: bool) -> Result<()> {
     let config_file = config.path();
 
     // Define the labels and their corresponding path values once.
-    let paths = [
+    let mut paths = vec![
+        ("Profile:", config.profile().to_string()),
         ("Config file:", config_file.to_string()),
         ("Sessions dir:", sessions_dir.display().to_string()),
         ("Logs dir:", logs_dir.display().to_string()),
     ];
+    if let Some(project_path) = config.project_path() {
+        paths.insert(2, ("Project config:", project_path.display().to_string()));
+    }
 
     // Calculate padding: use the max length of the label plus extra space.
     let basic_padding = paths.iter().map(|(l, _)| l.len()).max().unwrap_or(0) + 4;
@@ -43,7 +48,7 @@ pub fn handle_info(verbose: bool) -> Result<()> {
     // Print verbose info if requested
     if verbose {
         println!("\n{}", style("Goose Configuration:").cyan().bold());
-        match config.load_values() {
+        match config.load_values_with_sources() {
             Ok(values) => {
                 if values.is_empty() {
                     println!("  No configuration values set");
@@ -51,9 +56,19 @@ pub fn handle_info(verbose: bool) -> Result<()> {
                         "  Run '{}' to configure goose",
                         style("goose configure").cyan()
                     );
-                } else if let Ok(yaml) = serde_yaml::to_string(&values) {
-                    for line in yaml.lines() {
-                        println!("  {}", line);
+                }
+                for (key, entry) in values {
+                    let sources: Vec<String> =
+                        entry.sources.iter().map(|s| s.to_string()).collect();
+                    println!(
+                        "  {}",
+                        style(format!("# from {}", sources.join(", merged over "))).dim()
+                    );
+                    let value = HashMap::from([(key, entry.value)]);
+                    if let Ok(yaml) = serde_yaml::to_string(&value) {
+                        for line in yaml.lines() {
+                            println!("  {}", line);
+                        }
                     }
                 }
             }
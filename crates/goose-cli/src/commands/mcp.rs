@@ -1,12 +1,15 @@
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
 use goose_mcp::{
-    ComputerControllerRouter, DeveloperRouter, GoogleDriveRouter, JetBrainsRouter, MemoryRouter,
-    TutorialRouter,
+    ComputerControllerRouter, DeveloperRouter, GoogleDriveRouter, JetBrainsRouter, LspRouter,
+    MemoryRouter, TutorialRouter,
 };
+use mcp_server::http::{generate_token, MCP_ENDPOINT};
 use mcp_server::router::RouterService;
-use mcp_server::{BoundedService, ByteTransport, Server};
+use mcp_server::{BoundedService, ByteTransport, Router, Server};
 use tokio::io::{stdin, stdout};
+use tokio::net::TcpListener;
 
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::sync::Notify;
 
@@ -17,12 +20,16 @@ use nix::unistd::getpgrp;
 #[cfg(unix)]
 use nix::unistd::Pid;
 
-pub async fn run_server(name: &str) -> Result<()> {
+pub async fn run_server(name: &str, http: Option<SocketAddr>, allow_remote: bool) -> Result<()> {
     // Initialize logging
     crate::logging::setup_logging(Some(&format!("mcp-{name}")), None)?;
 
     tracing::info!("Starting MCP server");
 
+    if let Some(address) = http {
+        return run_http_server(name, address, allow_remote).await;
+    }
+
     let router: Option<Box<dyn BoundedService>> = match name {
         "developer" => Some(Box::new(RouterService(DeveloperRouter::new()))),
         "computercontroller" => Some(Box::new(RouterService(ComputerControllerRouter::new()))),
@@ -31,6 +38,7 @@ pub async fn run_server(name: &str) -> Result<()> {
             let router = GoogleDriveRouter::new().await;
             Some(Box::new(RouterService(router)))
         }
+        "lsp" => Some(Box::new(RouterService(LspRouter::new()))),
         "memory" => Some(Box::new(RouterService(MemoryRouter::new()))),
         "tutorial" => Some(Box::new(RouterService(TutorialRouter::new()))),
         _ => None,
@@ -71,3 +79,75 @@ pub async fn run_server(name: &str) -> Result<()> {
         }
     }
 }
+
+/// Bind the streamable HTTP transport and print the bearer token clients need. Addresses other
+/// machines can reach are refused unless `allow_remote` is set, since the tools run as the user.
+/// The token is `GOOSE_MCP_HTTP_TOKEN` when set, so it can be handed to clients ahead of time.
+pub async fn bind_http(
+    what: &str,
+    address: SocketAddr,
+    allow_remote: bool,
+) -> Result<(TcpListener, String)> {
+    if !address.ip().is_loopback() && !allow_remote {
+        bail!(
+            "Refusing to serve on {}, which other machines can reach. Use a loopback address such as 127.0.0.1:{}, or pass --allow-remote",
+            address,
+            address.port()
+        );
+    }
+    let listener = TcpListener::bind(address)
+        .await
+        .with_context(|| format!("Failed to listen on {}", address))?;
+    let token = std::env::var("GOOSE_MCP_HTTP_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .unwrap_or_else(generate_token);
+    eprintln!(
+        "Serving {} at http://{}{}",
+        what,
+        listener.local_addr()?,
+        MCP_ENDPOINT
+    );
+    eprintln!(
+        "Clients must send the header: Authorization: Bearer {}",
+        token
+    );
+    Ok((listener, token))
+}
+
+/// Serve a bundled router over streamable HTTP, so several clients can share one instance
+async fn run_http_server(name: &str, address: SocketAddr, allow_remote: bool) -> Result<()> {
+    let (listener, token) =
+        bind_http(&format!("the {} extension", name), address, allow_remote).await?;
+
+    match name {
+        "developer" => serve_http(DeveloperRouter::new(), listener, token).await,
+        "computercontroller" => serve_http(ComputerControllerRouter::new(), listener, token).await,
+        "jetbrains" => serve_http(JetBrainsRouter::new(), listener, token).await,
+        "google_drive" | "googledrive" => {
+            serve_http(GoogleDriveRouter::new().await, listener, token).await
+        }
+        "lsp" => serve_http(LspRouter::new(), listener, token).await,
+        "memory" => serve_http(MemoryRouter::new(), listener, token).await,
+        "tutorial" => serve_http(TutorialRouter::new(), listener, token).await,
+        _ => Err(anyhow::anyhow!("Unknown server requested {}", name)),
+    }
+}
+
+async fn serve_http<R>(router: R, listener: TcpListener, token: String) -> Result<()>
+where
+    R: Router + Clone,
+{
+    let server = Server::new(RouterService(router));
+
+    tracing::info!("Server initialized and ready to handle requests");
+
+    tokio::select! {
+        result = server.run_http(listener, token) => {
+            Ok(result?)
+        }
+        _ = crate::signal::shutdown_signal() => {
+            Ok(())
+        }
+    }
+}
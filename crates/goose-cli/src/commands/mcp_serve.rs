@@ -0,0 +1,636 @@
+use crate::commands::mcp::bind_http;
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use goose::agents::{Agent, AgentEvent, SessionConfig};
+use goose::config::{Config, ExtensionConfigManager};
+use goose::message::{Message, MessageContent};
+use goose::permission::permission_confirmation::PrincipalType;
+use goose::permission::{Permission, PermissionConfirmation};
+use goose::recipe::Recipe;
+use goose::session::{self, Identifier, SessionMetadata};
+use mcp_core::{
+    handler::{PromptError, ResourceError, ToolError},
+    prompt::Prompt,
+    protocol::{JsonRpcMessage, JsonRpcNotification, ServerCapabilities},
+    resource::Resource,
+    role::Role,
+    tool::{Tool, ToolAnnotations},
+    Content,
+};
+use mcp_server::router::{CapabilitiesBuilder, RouterService};
+use mcp_server::{ByteTransport, Router, Server};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::{stdin, stdout};
+use tokio::sync::mpsc;
+
+const DEFAULT_SESSION_LIMIT: usize = 20;
+
+/// Run goose itself as an MCP server, so other agents and IDEs can hand it tasks
+pub async fn run_goose_server(http: Option<SocketAddr>, allow_remote: bool) -> Result<()> {
+    crate::logging::setup_logging(Some("mcp-serve"), None)?;
+
+    tracing::info!("Starting goose MCP server");
+
+    let server = Server::new(RouterService(GooseRouter::new()));
+
+    if let Some(address) = http {
+        let (listener, token) = bind_http("goose", address, allow_remote).await?;
+        return tokio::select! {
+            result = server.run_http(listener, token) => Ok(result?),
+            _ = crate::signal::shutdown_signal() => Ok(()),
+        };
+    }
+
+    let transport = ByteTransport::new(stdin(), stdout());
+    tracing::info!("Server initialized and ready to handle requests");
+
+    tokio::select! {
+        result = server.run(transport) => Ok(result?),
+        _ = crate::signal::shutdown_signal() => Ok(()),
+    }
+}
+
+/// Exposes goose sessions as MCP tools. Each call gets its own agent with the user's
+/// configured extensions, or the session's recipe, and drops it when the call ends so its
+/// extension processes don't outlive the task.
+#[derive(Clone)]
+pub struct GooseRouter {
+    tools: Vec<Tool>,
+    running: Arc<std::sync::Mutex<HashSet<String>>>,
+}
+
+impl Default for GooseRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GooseRouter {
+    pub fn new() -> Self {
+        let run_task = Tool::new(
+            "run_task".to_string(),
+            "Hand a task to goose, an autonomous agent with access to the user's configured extensions. \
+             goose works on the task until it is done and returns its final answer along with a session id. \
+             Progress is streamed as notifications while it works. Pass the session id to resume_session \
+             to ask follow-up questions in the same conversation."
+                .to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "prompt": {
+                        "type": "string",
+                        "description": "The task to perform. Optional when the recipe provides its own prompt."
+                    },
+                    "recipe": {
+                        "type": "string",
+                        "description": "Name or path of a recipe to configure the agent with"
+                    },
+                    "params": {
+                        "type": "object",
+                        "description": "Values for the recipe's parameters",
+                        "additionalProperties": {"type": "string"}
+                    }
+                }
+            }),
+            Some(ToolAnnotations {
+                title: Some("Run a goose task".to_string()),
+                read_only_hint: false,
+                destructive_hint: true,
+                idempotent_hint: false,
+                open_world_hint: true,
+            }),
+        );
+
+        let list_sessions = Tool::new(
+            "list_sessions".to_string(),
+            "List recent goose sessions, most recently updated first.".to_string(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "limit": {
+                        "type": "integer",
+                        "description": "Maximum number of sessions to return, defaults to 20"
+                    }
+                }
+            }),
+            Some(ToolAnnotations {
+                title: Some("List goose sessions".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
+        let resume_session = Tool::new(
+            "resume_session".to_string(),
+            "Send a follow-up message to an existing goose session and return goose's answer."
+                .to_string(),
+            json!({
+                "type": "object",
+                "required": ["id", "message"],
+                "properties": {
+                    "id": {
+                        "type": "string",
+                        "description": "Session id returned by run_task or list_sessions"
+                    },
+                    "message": {
+                        "type": "string",
+                        "description": "The message to send"
+                    }
+                }
+            }),
+            Some(ToolAnnotations {
+                title: Some("Resume a goose session".to_string()),
+                read_only_hint: false,
+                destructive_hint: true,
+                idempotent_hint: false,
+                open_world_hint: true,
+            }),
+        );
+
+        Self {
+            tools: vec![run_task, list_sessions, resume_session],
+            running: Arc::new(std::sync::Mutex::new(HashSet::new())),
+        }
+    }
+
+    async fn run_task(
+        &self,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let recipe = match arguments.get("recipe").and_then(|v| v.as_str()) {
+            Some(name) => {
+                let params = recipe_params(arguments.get("params"))?;
+                let recipe = crate::recipes::recipe::load_recipe_non_interactive(name, params)
+                    .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+                Some(recipe)
+            }
+            None => None,
+        };
+
+        let prompt = arguments
+            .get("prompt")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+            .or_else(|| recipe.as_ref().and_then(|r| r.prompt.clone()))
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("Missing 'prompt' parameter".to_string())
+            })?;
+
+        let agent = build_agent(recipe.as_ref())
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let (id, session_file) =
+            new_session().map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let working_dir =
+            std::env::current_dir().map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        // Keep the recipe with the session so resume_session can set the agent up the same way
+        let metadata = SessionMetadata {
+            recipe,
+            ..SessionMetadata::new(working_dir.clone())
+        };
+        session::storage::save_messages_with_metadata(&session_file, &metadata, &[])
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let messages = vec![Message::user().with_text(prompt)];
+        self.reply(&agent, &id, session_file, working_dir, messages, &notifier)
+            .await
+    }
+
+    async fn resume_session(
+        &self,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let id = arguments
+            .get("id")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'id' parameter".to_string()))?;
+        let message = arguments
+            .get("message")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("Missing 'message' parameter".to_string())
+            })?;
+
+        let session_file = session::get_path(Identifier::Name(id.to_string()))
+            .map_err(|e| ToolError::InvalidParameters(e.to_string()))?;
+        if !session_file.exists() {
+            return Err(ToolError::InvalidParameters(format!(
+                "No session found with id '{}'",
+                id
+            )));
+        }
+        let mut messages = session::read_messages(&session_file)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let metadata = session::read_metadata(&session_file)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let agent = build_agent(metadata.recipe.as_ref())
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        messages.push(Message::user().with_text(message));
+        self.reply(
+            &agent,
+            id,
+            session_file,
+            metadata.working_dir,
+            messages,
+            &notifier,
+        )
+        .await
+    }
+
+    fn list_sessions(&self, arguments: Value) -> Result<Vec<Content>, ToolError> {
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|limit| limit as usize)
+            .unwrap_or(DEFAULT_SESSION_LIMIT);
+
+        let sessions = session::get_valid_sorted_sessions(session::info::SortOrder::Descending)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let sessions: Vec<Value> = sessions
+            .into_iter()
+            .take(limit)
+            .map(|info| {
+                json!({
+                    "id": info.id,
+                    "description": info.metadata.description,
+                    "modified": info.modified,
+                    "message_count": info.metadata.message_count,
+                    "working_dir": info.metadata.working_dir,
+                })
+            })
+            .collect();
+
+        let text = serde_json::to_string_pretty(&sessions)
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        Ok(vec![Content::text(text)])
+    }
+
+    /// Drive the agent until it finishes replying, persisting the conversation as it goes
+    /// and forwarding progress to the client.
+    async fn reply(
+        &self,
+        agent: &Agent,
+        id: &str,
+        session_file: PathBuf,
+        working_dir: PathBuf,
+        mut messages: Vec<Message>,
+        notifier: &mpsc::Sender<JsonRpcMessage>,
+    ) -> Result<Vec<Content>, ToolError> {
+        let _guard = RunningGuard::acquire(&self.running, id)?;
+
+        let provider = agent.provider().await.ok();
+        session::persist_messages(&session_file, &messages, provider.clone())
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let session_config = SessionConfig {
+            id: Identifier::Path(session_file.clone()),
+            working_dir,
+            schedule_id: None,
+            execution_mode: None,
+            max_turns: None,
+        };
+
+        let mut stream = agent
+            .reply(&messages, Some(session_config))
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+
+        let mut answer = String::new();
+        while let Some(event) = stream.next().await {
+            match event {
+                Ok(AgentEvent::Message(message)) => {
+                    // Nobody can approve tools through MCP, so refuse calls that need approval,
+                    // the same way the gateway does. Like the CLI, don't persist the request.
+                    if let Some(MessageContent::ToolConfirmationRequest(confirmation)) =
+                        message.content.first()
+                    {
+                        notify_progress(
+                            notifier,
+                            json!({"session_id": id, "kind": "tool_denied", "tool": confirmation.tool_name}),
+                        );
+                        agent
+                            .handle_confirmation(
+                                confirmation.id.clone(),
+                                PermissionConfirmation {
+                                    principal_type: PrincipalType::Tool,
+                                    permission: Permission::DenyOnce,
+                                },
+                            )
+                            .await;
+                        continue;
+                    }
+
+                    for content in &message.content {
+                        match content {
+                            MessageContent::Text(text) if message.role == Role::Assistant => {
+                                notify_progress(
+                                    notifier,
+                                    json!({"session_id": id, "kind": "text", "text": text.text}),
+                                );
+                            }
+                            MessageContent::ToolRequest(request) => {
+                                if let Ok(call) = &request.tool_call {
+                                    notify_progress(
+                                        notifier,
+                                        json!({"session_id": id, "kind": "tool_call", "tool": call.name}),
+                                    );
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+
+                    if message.role == Role::Assistant && !message.as_concat_text().is_empty() {
+                        answer = message.as_concat_text();
+                    }
+                    messages.push(message);
+                    session::persist_messages(&session_file, &messages, provider.clone())
+                        .await
+                        .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+                }
+                Ok(AgentEvent::HistoryReplaced(new_messages)) => {
+                    messages = new_messages;
+                    session::persist_messages(&session_file, &messages, provider.clone())
+                        .await
+                        .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+                }
+                Ok(AgentEvent::McpNotification(_))
+                | Ok(AgentEvent::ModelChange { .. })
+                | Ok(AgentEvent::ExtensionRestarted(_)) => {}
+                Err(e) => {
+                    return Err(ToolError::ExecutionError(format!(
+                        "goose failed while working on session '{}': {}",
+                        id, e
+                    )));
+                }
+            }
+        }
+
+        if answer.is_empty() {
+            answer = "goose finished without a text response.".to_string();
+        }
+        Ok(vec![
+            Content::text(answer),
+            Content::text(format!("Session id: {}", id)).with_audience(vec![Role::Assistant]),
+        ])
+    }
+}
+
+impl Router for GooseRouter {
+    fn name(&self) -> String {
+        "goose".to_string()
+    }
+
+    fn instructions(&self) -> String {
+        "goose is a general purpose agent that can use the tools and extensions configured on this machine. \
+         Use run_task to delegate self-contained work to it, list_sessions to find earlier conversations, \
+         and resume_session to continue one of them."
+            .to_string()
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        CapabilitiesBuilder::new().with_tools(false).build()
+    }
+
+    fn list_tools(&self) -> Vec<Tool> {
+        self.tools.clone()
+    }
+
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        let this = self.clone();
+        let tool_name = tool_name.to_string();
+
+        Box::pin(async move {
+            match tool_name.as_str() {
+                "run_task" => this.run_task(arguments, notifier).await,
+                "list_sessions" => this.list_sessions(arguments),
+                "resume_session" => this.resume_session(arguments, notifier).await,
+                _ => Err(ToolError::NotFound(format!("Tool {} not found", tool_name))),
+            }
+        })
+    }
+
+    fn list_resources(&self) -> Vec<Resource> {
+        Vec::new()
+    }
+
+    fn read_resource(
+        &self,
+        _uri: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
+        Box::pin(async move { Ok("".to_string()) })
+    }
+
+    fn list_prompts(&self) -> Vec<Prompt> {
+        vec![]
+    }
+
+    fn get_prompt(
+        &self,
+        prompt_name: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
+        let prompt_name = prompt_name.to_string();
+        Box::pin(async move {
+            Err(PromptError::NotFound(format!(
+                "Prompt {} not found",
+                prompt_name
+            )))
+        })
+    }
+}
+
+/// Marks a session as busy for the duration of a reply, so two clients can't
+/// interleave messages in the same conversation.
+struct RunningGuard {
+    running: Arc<std::sync::Mutex<HashSet<String>>>,
+    id: String,
+}
+
+impl RunningGuard {
+    fn acquire(
+        running: &Arc<std::sync::Mutex<HashSet<String>>>,
+        id: &str,
+    ) -> Result<Self, ToolError> {
+        if !running.lock().unwrap().insert(id.to_string()) {
+            return Err(ToolError::ExecutionError(format!(
+                "Session '{}' is already working on a task",
+                id
+            )));
+        }
+        Ok(Self {
+            running: running.clone(),
+            id: id.to_string(),
+        })
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.running.lock().unwrap().remove(&self.id);
+    }
+}
+
+fn notify_progress(notifier: &mpsc::Sender<JsonRpcMessage>, mut data: Value) {
+    data["type"] = json!("goose");
+    notifier
+        .try_send(JsonRpcMessage::Notification(JsonRpcNotification {
+            jsonrpc: "2.0".to_string(),
+            method: "notifications/message".to_string(),
+            params: Some(json!({
+                "level": "info",
+                "logger": "goose",
+                "data": data,
+            })),
+        }))
+        .ok();
+}
+
+fn recipe_params(params: Option<&Value>) -> Result<Vec<(String, String)>, ToolError> {
+    let Some(params) = params else {
+        return Ok(Vec::new());
+    };
+    let params = params
+        .as_object()
+        .ok_or_else(|| ToolError::InvalidParameters("'params' must be an object".to_string()))?;
+    Ok(params
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect())
+}
+
+/// Pick a session id that isn't taken yet; generated ids only have second resolution
+fn new_session() -> Result<(String, PathBuf)> {
+    let base = session::generate_session_id();
+    let mut id = base.clone();
+    let mut suffix = 1;
+    loop {
+        let path = session::get_path(Identifier::Name(id.clone()))?;
+        if !path.exists() {
+            return Ok((id, path));
+        }
+        suffix += 1;
+        id = format!("{}_{}", base, suffix);
+    }
+}
+
+/// Create an agent the same way `goose run` would, without touching stdout since that
+/// may be the MCP transport.
+async fn build_agent(recipe: Option<&Recipe>) -> Result<Agent> {
+    let config = Config::global();
+    let settings = recipe.and_then(|r| r.settings.as_ref());
+
+    let provider_name: String = settings
+        .and_then(|s| s.goose_provider.clone())
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
+        .ok_or_else(|| anyhow!("No provider configured. Run 'goose configure' first"))?;
+    let model_name: String = settings
+        .and_then(|s| s.goose_model.clone())
+        .or_else(|| config.get_param("GOOSE_MODEL").ok())
+        .ok_or_else(|| anyhow!("No model configured. Run 'goose configure' first"))?;
+    let temperature = settings.and_then(|s| s.temperature);
+
+    let model_config = goose::model::ModelConfig::new(model_name).with_temperature(temperature);
+    let provider = goose::providers::create(&provider_name, model_config)?;
+
+    let agent = Agent::new();
+    agent.update_provider(provider).await?;
+
+    if let Some(recipe) = recipe {
+        if let Some(sub_recipes) = recipe.sub_recipes.clone() {
+            agent.add_sub_recipes(sub_recipes).await;
+        }
+        if let Some(response) = recipe.response.clone() {
+            agent.add_final_output_tool(response).await;
+        }
+    }
+
+    let extensions: Vec<_> = match recipe.and_then(|r| r.extensions.clone()) {
+        Some(extensions) => extensions,
+        None => ExtensionConfigManager::get_all()?
+            .into_iter()
+            .filter(|ext| ext.enabled)
+            .map(|ext| ext.config)
+            .collect(),
+    };
+    for extension in extensions {
+        if let Err(e) = agent.add_extension(extension.clone()).await {
+            tracing::warn!(
+                "Failed to start extension '{}', continuing without it: {}",
+                extension.name(),
+                e
+            );
+        }
+    }
+
+    if let Some(instructions) = recipe.and_then(|r| r.instructions.clone()) {
+        agent.extend_system_prompt(instructions).await;
+    }
+
+    Ok(agent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recipe_params_stringifies_values() {
+        let params = json!({"name": "goose", "count": 3, "verbose": true});
+        let mut params = recipe_params(Some(&params)).unwrap();
+        params.sort();
+        assert_eq!(
+            params,
+            vec![
+                ("count".to_string(), "3".to_string()),
+                ("name".to_string(), "goose".to_string()),
+                ("verbose".to_string(), "true".to_string()),
+            ]
+        );
+        assert!(recipe_params(Some(&json!(["a"]))).is_err());
+        assert!(recipe_params(None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_running_guard_rejects_concurrent_replies() {
+        let running = Arc::new(std::sync::Mutex::new(HashSet::new()));
+        let guard = RunningGuard::acquire(&running, "session").unwrap();
+        assert!(RunningGuard::acquire(&running, "session").is_err());
+        assert!(RunningGuard::acquire(&running, "other").is_ok());
+        drop(guard);
+        assert!(RunningGuard::acquire(&running, "session").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_unknown_tool() {
+        let router = GooseRouter::new();
+        let (tx, _rx) = mpsc::channel(1);
+        let result = router.call_tool("nope", json!({}), tx).await;
+        assert!(matches!(result, Err(ToolError::NotFound(_))));
+        assert_eq!(router.list_tools().len(), 3);
+    }
+}
@@ -2,8 +2,13 @@ pub mod bench;
 pub mod configure;
 pub mod info;
 pub mod mcp;
+pub mod mcp_gateway;
+pub mod mcp_serve;
+pub mod profile;
 pub mod project;
 pub mod recipe;
+pub mod recipe_library;
+pub mod recipe_test;
 pub mod schedule;
 pub mod session;
 pub mod update;
@@ -0,0 +1,81 @@
+use anyhow::{bail, Context, Result};
+use console::style;
+use goose::config::profiles::{validate_profile_name, PROFILE_ENV_VAR};
+use goose::config::{Config, ProfileManager};
+
+use crate::commands::configure::handle_configure;
+
+/// Make `profile` the profile for this process and any process it starts
+pub fn select_profile(profile: &str) -> Result<()> {
+    validate_profile_name(profile)?;
+    if !ProfileManager::default().exists(profile) {
+        bail!(
+            "Profile '{}' does not exist, create it with 'goose configure profile add {}'",
+            profile,
+            profile
+        );
+    }
+    std::env::set_var(PROFILE_ENV_VAR, profile);
+    Ok(())
+}
+
+/// Fail early on a GOOSE_PROFILE that can't be used, instead of falling back to the default
+/// profile behind the user's back
+pub fn check_profile_env() -> Result<()> {
+    let Ok(profile) = std::env::var(PROFILE_ENV_VAR) else {
+        return Ok(());
+    };
+    let profile = profile.trim();
+    if !profile.is_empty() {
+        select_profile(profile)
+            .with_context(|| format!("Invalid {} '{}'", PROFILE_ENV_VAR, profile))?;
+    }
+    Ok(())
+}
+
+pub async fn handle_profile_add(name: &str) -> Result<()> {
+    ProfileManager::default().add(name)?;
+
+    // Run the first time setup against the new profile
+    std::env::set_var(PROFILE_ENV_VAR, name);
+    let _ = handle_configure().await;
+
+    if Config::global().exists() {
+        println!(
+            "\n  {}: Use it with '{}' or make it the default with '{}'",
+            style("Tip").green().italic(),
+            style(format!("goose --profile {}", name)).cyan(),
+            style(format!("goose configure profile use {}", name)).cyan()
+        );
+    }
+    Ok(())
+}
+
+pub fn handle_profile_list() -> Result<()> {
+    let profiles = ProfileManager::default();
+    let active = profiles.active();
+    for name in profiles.list()? {
+        if name == active {
+            println!("* {}", style(name).green().bold());
+        } else {
+            println!("  {}", name);
+        }
+    }
+    Ok(())
+}
+
+pub fn handle_profile_use(name: &str) -> Result<()> {
+    ProfileManager::default().set_active(name)?;
+    println!("Using profile {}", style(name).green().bold());
+    if let Ok(env_profile) = std::env::var(PROFILE_ENV_VAR) {
+        if env_profile != name {
+            println!(
+                "  {}: {} is set to '{}' and takes precedence",
+                style("Note").yellow().italic(),
+                PROFILE_ENV_VAR,
+                env_profile
+            );
+        }
+    }
+    Ok(())
+}
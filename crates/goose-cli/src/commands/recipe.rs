@@ -1,8 +1,19 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use base64::Engine;
 use console::style;
+use goose::agents::sub_recipe_execution_tool::lib::{run_workflow, TaskContext, TaskResult};
+use goose::agents::SessionConfig;
+use goose::config::Config;
+use goose::message::Message;
+use goose::recipe::lockfile::{
+    lock_file_path, lock_recipe, write_lock_file, LockedSource, PublicRegistries,
+};
+use goose::recipe::{Recipe, RecipeStep, Settings};
+use goose::session::{self, Identifier, SessionMetadata};
+use serde_json::Value;
 
-use crate::recipes::recipe::load_recipe;
+use crate::recipes::recipe::{load_extended_recipe_content, load_recipe};
+use crate::recipes::search_recipe::retrieve_recipe_file;
 
 /// Validates a recipe file
 ///
@@ -18,6 +29,11 @@ pub fn handle_validate(recipe_name: &str) -> Result<()> {
     match load_recipe(recipe_name) {
         Ok(_) => {
             println!("{} recipe file is valid", style("✓").green().bold());
+            // Show what a recipe that extends others resolves to
+            if let Some(content) = load_extended_recipe_content(recipe_name)? {
+                println!("\n{}", style("Resolved recipe:").bold());
+                println!("{}", content.trim_end());
+            }
             Ok(())
         }
         Err(err) => {
@@ -61,6 +77,148 @@ pub fn handle_deeplink(recipe_name: &str) -> Result<String> {
     }
 }
 
+/// Resolves a recipe's extensions to exact versions and writes them, with the provider and
+/// model the recipe runs with, to the recipe's lock file
+pub async fn handle_lock(recipe_name: &str) -> Result<()> {
+    let recipe = load_recipe(recipe_name)?;
+    let lock_path = lock_file_path(&retrieve_recipe_file(recipe_name)?.file_path);
+    let config = Config::global();
+    let lock = lock_recipe(
+        &recipe,
+        config.get_param("GOOSE_PROVIDER").ok(),
+        config.get_param("GOOSE_MODEL").ok(),
+        &PublicRegistries::default(),
+    )
+    .await?;
+    write_lock_file(&lock_path, &lock)?;
+
+    println!(
+        "{} Locked {} to {}",
+        style("✓").green().bold(),
+        recipe.title,
+        lock_path.display()
+    );
+    if let (Some(provider), Some(model)) = (&lock.provider, &lock.model) {
+        println!("  {} {} {}", style("model").dim(), provider, model);
+    }
+    for extension in &lock.extensions {
+        let resolved = match &extension.source {
+            LockedSource::Npm { package, version } | LockedSource::Pypi { package, version } => {
+                format!("{} {}", package, version)
+            }
+            LockedSource::Binary { path, sha256, .. } => {
+                format!("{} sha256:{}", path.display(), &sha256[..12])
+            }
+            LockedSource::Remote { uri } => uri.clone(),
+            LockedSource::Builtin => format!("goose {}", lock.goose_version),
+        };
+        println!("  {} {}", style(&extension.name).dim(), resolved);
+    }
+    Ok(())
+}
+
+fn format_output(output: &Value) -> String {
+    match output {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_default(),
+    }
+}
+
+fn print_step_result(step: &RecipeStep, result: &TaskResult) {
+    let reason = result.error.as_deref().unwrap_or_default();
+    match result.status.as_str() {
+        "success" => println!("{} {}", style("✓").green().bold(), step.id),
+        "skipped" => println!("{} {} ({})", style("-").dim(), step.id, reason),
+        _ => println!("{} {}: {}", style("✗").red().bold(), step.id, reason),
+    }
+}
+
+/// Runs the steps of a workflow recipe in order, printing each step as it finishes and the
+/// output of the last step that produced one
+///
+/// The workflow gets its own session, which the sessions of its steps are linked to,
+/// unless `no_session` is set.
+pub async fn handle_run_workflow(
+    mut recipe: Recipe,
+    no_session: bool,
+    provider: Option<String>,
+    model: Option<String>,
+    scheduled_job_id: Option<String>,
+    quiet: bool,
+) -> Result<()> {
+    if provider.is_some() || model.is_some() {
+        let settings = recipe.settings.get_or_insert(Settings {
+            goose_provider: None,
+            goose_model: None,
+            temperature: None,
+        });
+        settings.goose_provider = provider.or(settings.goose_provider.take());
+        settings.goose_model = model.or(settings.goose_model.take());
+    }
+
+    let working_dir = std::env::current_dir()?;
+    let session_id = session::generate_session_id();
+    let session_file = session::get_path(Identifier::Name(session_id.clone()))?;
+    let mut messages = vec![Message::user().with_text(format!("Run workflow: {}", recipe.title))];
+    let parent_session = if no_session {
+        None
+    } else {
+        let metadata = SessionMetadata {
+            description: recipe.title.clone(),
+            schedule_id: scheduled_job_id.clone(),
+            message_count: messages.len(),
+            ..SessionMetadata::new(working_dir.clone())
+        };
+        session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+        Some(SessionConfig {
+            id: Identifier::Name(session_id),
+            working_dir,
+            schedule_id: scheduled_job_id,
+            execution_mode: None,
+            max_turns: None,
+        })
+    };
+    let context = TaskContext {
+        provider: None,
+        parent_session: parent_session.clone(),
+        working_dir: None,
+    };
+
+    let results = run_workflow(&recipe, context, |step, result| {
+        if !quiet {
+            print_step_result(step, result);
+        }
+    })
+    .await?;
+
+    if parent_session.is_some() {
+        for result in &results {
+            let outcome = match (&result.data, &result.error) {
+                (Some(output), _) => format_output(output),
+                (None, error) => error.clone().unwrap_or_default(),
+            };
+            messages.push(Message::assistant().with_text(format!(
+                "Step {} {}:\n{}",
+                result.task_id, result.status, outcome
+            )));
+        }
+        let mut metadata = session::read_metadata(&session_file)?;
+        metadata.message_count = messages.len();
+        session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+    }
+
+    if let Some(output) = results.iter().rev().find_map(|r| r.data.as_ref()) {
+        if !quiet {
+            println!();
+        }
+        println!("{}", format_output(output));
+    }
+    match results.iter().find(|r| r.status == "failed") {
+        Some(failed) => Err(anyhow!("Workflow step '{}' failed", failed.task_id)),
+        None => Ok(()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
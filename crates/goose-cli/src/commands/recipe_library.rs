@@ -0,0 +1,113 @@
+use anyhow::Result;
+use console::style;
+use goose::recipe::library::{InstalledSource, LibraryRecipe, RecipeLibrary, SourceLocation};
+
+fn print_recipe(recipe: &LibraryRecipe) {
+    let source = match &recipe.source_version {
+        Some(version) => format!("{} {}", recipe.source, version),
+        None => recipe.source.clone(),
+    };
+    println!(
+        "{} - {} {}",
+        style(&recipe.name).cyan().bold(),
+        recipe.title,
+        style(format!("(v{}, {})", recipe.version, source)).dim()
+    );
+    println!("    {}", recipe.description);
+    if !recipe.parameters.is_empty() {
+        println!(
+            "    {} {}",
+            style("Parameters:").dim(),
+            recipe.parameters.join(", ")
+        );
+    }
+}
+
+fn print_source(source: &InstalledSource) {
+    let location = match &source.location {
+        SourceLocation::Git { url } => url.clone(),
+        SourceLocation::Directory { path } => path.display().to_string(),
+    };
+    let version = source
+        .version()
+        .map(|version| format!(" @ {}", version))
+        .unwrap_or_default();
+    let pinned = if source.pinned.is_some() {
+        style(" (pinned)").yellow().to_string()
+    } else {
+        String::new()
+    };
+    println!(
+        "{} {}{}{}",
+        style(&source.name).cyan().bold(),
+        location,
+        version,
+        pinned
+    );
+}
+
+/// Lists the recipes in the library, or the sources installed into it
+pub fn handle_list(sources: bool) -> Result<()> {
+    let library = RecipeLibrary::default();
+    if sources {
+        let sources = library.sources()?;
+        if sources.is_empty() {
+            println!("No recipe sources installed. Add one with `goose recipe install`.");
+        }
+        sources.iter().for_each(print_source);
+        return Ok(());
+    }
+    let recipes = library.recipes()?;
+    if recipes.is_empty() {
+        println!(
+            "No recipes found. Install a source with `goose recipe install` or add directories to GOOSE_RECIPE_PATH."
+        );
+    }
+    recipes.iter().for_each(print_recipe);
+    Ok(())
+}
+
+/// Searches the library's recipes by title, description, activities and parameters
+pub fn handle_search(query: &str) -> Result<()> {
+    let recipes = RecipeLibrary::default().search(query)?;
+    if recipes.is_empty() {
+        println!("No recipes match '{}'", query);
+    }
+    recipes.iter().for_each(print_recipe);
+    Ok(())
+}
+
+/// Installs a directory or git repository of recipes into the library
+pub fn handle_install(source: &str, name: Option<&str>, version: Option<&str>) -> Result<()> {
+    let library = RecipeLibrary::default();
+    let installed = library.install(source, name, version)?;
+    let count = library
+        .recipes()?
+        .iter()
+        .filter(|recipe| recipe.source == installed.name)
+        .count();
+    print!("Installed ");
+    print_source(&installed);
+    println!("{} recipes found", count);
+    Ok(())
+}
+
+/// Removes a source from the library
+pub fn handle_remove(name: &str) -> Result<()> {
+    RecipeLibrary::default().remove(name)?;
+    println!("Removed {}", style(name).cyan().bold());
+    Ok(())
+}
+
+/// Upgrades git sources to their latest commit, or pins one to a version
+pub fn handle_upgrade(name: Option<&str>, version: Option<&str>) -> Result<()> {
+    let upgraded = RecipeLibrary::default().upgrade(name, version)?;
+    if upgraded.is_empty() {
+        println!("Recipe sources are up to date");
+    }
+    for source in &upgraded {
+        print!("Upgraded ");
+        print_source(source);
+    }
+    Ok(())
+}
@@ -0,0 +1,313 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Result};
+use console::style;
+use goose::agents::sub_recipe_execution_tool::lib::{run_recipe_conversation, TaskContext};
+use goose::recipe::testing::{check_expectations, RecipeTest};
+use goose::recipe::Recipe;
+use goose::session::{self, Identifier};
+use serde::Deserialize;
+
+use crate::recipes::recipe::{load_recipe, load_recipe_non_interactive};
+use crate::recipes::search_recipe::retrieve_recipe_file;
+
+/// Tests kept next to a recipe, in `<recipe>.test.yaml`
+#[derive(Deserialize)]
+struct TestFile {
+    tests: Vec<RecipeTest>,
+}
+
+enum Outcome {
+    Passed,
+    Failed(Vec<String>),
+    Error(String),
+}
+
+struct TestCase {
+    name: String,
+    outcome: Outcome,
+    duration: Duration,
+}
+
+fn sidecar_path(recipe_path: &Path) -> PathBuf {
+    recipe_path.with_extension("test.yaml")
+}
+
+// The recipe's own tests followed by those in its test file
+fn load_tests(recipe: &Recipe, recipe_path: &Path) -> Result<Vec<RecipeTest>> {
+    let mut tests = recipe.tests.clone().unwrap_or_default();
+    let sidecar = sidecar_path(recipe_path);
+    if sidecar.exists() {
+        let content = std::fs::read_to_string(&sidecar)?;
+        let file: TestFile = serde_yaml::from_str(&content)
+            .map_err(|e| anyhow!("Invalid test file {}: {}", sidecar.display(), e))?;
+        tests.extend(file.tests);
+    }
+    Ok(tests)
+}
+
+// Run the recipe with the test's values in a temporary working directory, which is where the
+// test's files are checked
+async fn run_test(recipe_path: &str, recipe_dir: &Path, test: &RecipeTest) -> Result<Outcome> {
+    let values = test
+        .values
+        .clone()
+        .unwrap_or_default()
+        .into_iter()
+        .collect();
+    let mut recipe = load_recipe_non_interactive(recipe_path, values)?;
+    if recipe.steps.is_some() {
+        return Err(anyhow!("Workflow recipes can't be tested yet"));
+    }
+    let prompt = recipe
+        .prompt
+        .clone()
+        .ok_or_else(|| anyhow!("Recipe '{}' has no prompt to test", recipe.title))?;
+    let provider = match &test.provider {
+        Some(provider) => {
+            // The test's provider answers in place of the one the recipe picks
+            recipe.settings = None;
+            Some(provider.provider(recipe_dir)?)
+        }
+        None => None,
+    };
+    let working_dir = tempfile::tempdir()?;
+    let context = TaskContext {
+        provider,
+        parent_session: None,
+        working_dir: Some(working_dir.path().to_path_buf()),
+    };
+
+    let session_id = session::generate_session_id();
+    let run = run_recipe_conversation(Some(&recipe), prompt, &context, session_id.clone()).await?;
+
+    let schema = recipe
+        .response
+        .as_ref()
+        .and_then(|r| r.json_schema.as_ref());
+    let mut failures = check_expectations(
+        &test.expect,
+        schema,
+        &run.output,
+        &run.messages,
+        working_dir.path(),
+    );
+    let session_file = session::get_path(Identifier::Name(session_id.clone()))?;
+    if failures.is_empty() {
+        let _ = std::fs::remove_file(session_file);
+        Ok(Outcome::Passed)
+    } else {
+        failures.push(format!("The run was kept as session {}", session_id));
+        Ok(Outcome::Failed(failures))
+    }
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn junit_report(suite: &str, cases: &[TestCase]) -> String {
+    let failures = cases
+        .iter()
+        .filter(|c| matches!(c.outcome, Outcome::Failed(_)))
+        .count();
+    let errors = cases
+        .iter()
+        .filter(|c| matches!(c.outcome, Outcome::Error(_)))
+        .count();
+    let time: f64 = cases.iter().map(|c| c.duration.as_secs_f64()).sum();
+    let suite = escape_xml(suite);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!(
+        "<testsuites tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+        cases.len(),
+        failures,
+        errors,
+        time
+    ));
+    xml.push_str(&format!(
+        "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\" time=\"{:.3}\">\n",
+        suite,
+        cases.len(),
+        failures,
+        errors,
+        time
+    ));
+    for case in cases {
+        let open = format!(
+            "    <testcase name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+            escape_xml(&case.name),
+            suite,
+            case.duration.as_secs_f64()
+        );
+        match &case.outcome {
+            Outcome::Passed => xml.push_str(&format!("{}/>\n", open)),
+            Outcome::Failed(reasons) => xml.push_str(&format!(
+                "{}>\n      <failure message=\"{}\">{}</failure>\n    </testcase>\n",
+                open,
+                escape_xml(&reasons[0]),
+                escape_xml(&reasons.join("\n"))
+            )),
+            Outcome::Error(error) => xml.push_str(&format!(
+                "{}>\n      <error message=\"{}\"/>\n    </testcase>\n",
+                open,
+                escape_xml(error)
+            )),
+        }
+    }
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+fn print_case(case: &TestCase) {
+    match &case.outcome {
+        Outcome::Passed => println!("{} {}", style("✓").green().bold(), case.name),
+        Outcome::Failed(reasons) => {
+            println!("{} {}", style("✗").red().bold(), case.name);
+            for reason in reasons {
+                println!("    {}", reason);
+            }
+        }
+        Outcome::Error(error) => {
+            println!("{} {}: {}", style("✗").red().bold(), case.name, error)
+        }
+    }
+}
+
+/// Runs the tests of a recipe, from its `tests` section and its `<recipe>.test.yaml` file
+///
+/// # Arguments
+///
+/// * `recipe_name` - Name or path of the recipe to test
+/// * `junit` - Where to write a JUnit XML report of the results
+/// * `auto` - Let tools run without confirmation instead of denying calls that need it
+///
+/// # Returns
+///
+/// An error if any test failed
+pub async fn handle_test(recipe_name: &str, junit: Option<PathBuf>, auto: bool) -> Result<()> {
+    let recipe_file = retrieve_recipe_file(recipe_name)?;
+    let recipe = load_recipe(recipe_name)?;
+    let tests = load_tests(&recipe, &recipe_file.file_path)?;
+    if tests.is_empty() {
+        return Err(anyhow!(
+            "No tests found in the recipe or in {}",
+            sidecar_path(&recipe_file.file_path).display()
+        ));
+    }
+    let recipe_path = recipe_file.file_path.to_string_lossy().into_owned();
+
+    // Nobody can confirm tool calls while tests run, so they are denied unless asked otherwise
+    if auto {
+        std::env::set_var("GOOSE_MODE", "auto");
+    }
+
+    let mut cases = Vec::new();
+    for test in &tests {
+        let start = Instant::now();
+        let outcome = run_test(&recipe_path, &recipe_file.parent_dir, test)
+            .await
+            .unwrap_or_else(|e| Outcome::Error(e.to_string()));
+        let case = TestCase {
+            name: test.name.clone(),
+            outcome,
+            duration: start.elapsed(),
+        };
+        print_case(&case);
+        cases.push(case);
+    }
+
+    let passed = cases
+        .iter()
+        .filter(|c| matches!(c.outcome, Outcome::Passed))
+        .count();
+    println!("\n{} passed, {} failed", passed, cases.len() - passed);
+    if let Some(path) = junit {
+        std::fs::write(&path, junit_report(&recipe.title, &cases))?;
+        println!("JUnit report written to {}", path.display());
+    }
+    if passed < cases.len() {
+        return Err(anyhow!("{} recipe tests failed", cases.len() - passed));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_junit_report() {
+        let cases = vec![
+            TestCase {
+                name: "finds the bug".to_string(),
+                outcome: Outcome::Passed,
+                duration: Duration::from_millis(1500),
+            },
+            TestCase {
+                name: "writes a <report>".to_string(),
+                outcome: Outcome::Failed(vec![
+                    "File report.md wasn't created".to_string(),
+                    "Tool developer__shell wasn't called".to_string(),
+                ]),
+                duration: Duration::from_millis(500),
+            },
+            TestCase {
+                name: "replays".to_string(),
+                outcome: Outcome::Error("Session file s.jsonl not found".to_string()),
+                duration: Duration::ZERO,
+            },
+        ];
+        let xml = junit_report("Triage & fix", &cases);
+        assert!(xml.contains(
+            "<testsuite name=\"Triage &amp; fix\" tests=\"3\" failures=\"1\" errors=\"1\" time=\"2.000\">"
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"finds the bug\" classname=\"Triage &amp; fix\" time=\"1.500\"/>"
+        ));
+        assert!(xml.contains("<testcase name=\"writes a &lt;report&gt;\""));
+        assert!(xml.contains(
+            "<failure message=\"File report.md wasn't created\">File report.md wasn't created\nTool developer__shell wasn't called</failure>"
+        ));
+        assert!(xml.contains("<error message=\"Session file s.jsonl not found\"/>"));
+    }
+
+    #[test]
+    fn test_load_tests_from_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let recipe_path = dir.path().join("triage.yaml");
+        std::fs::write(
+            &recipe_path,
+            r#"
+version: 1.0.0
+title: Triage
+description: Triages an issue
+prompt: Triage the issue
+tests:
+  - name: inline
+    provider:
+      script:
+        - text: It's a bug
+    expect:
+      output:
+        - contains: bug
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("triage.test.yaml"),
+            "tests:\n  - name: sidecar\n    expect:\n      tools_called: [developer__shell]\n",
+        )
+        .unwrap();
+
+        let recipe = load_recipe(recipe_path.to_str().unwrap()).unwrap();
+        let tests = load_tests(&recipe, &recipe_path).unwrap();
+        let names: Vec<_> = tests.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["inline", "sidecar"]);
+    }
+}
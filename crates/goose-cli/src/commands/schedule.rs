@@ -5,7 +5,11 @@ use goose::scheduler::{
     SchedulerError,
 };
 use goose::scheduler_factory::SchedulerFactory;
+use goose::scheduler_notifiers::{JobNotifier, NotifierTarget, NotifyWhen};
+use goose::scheduler_runs::{CatchUpPolicy, OverlapPolicy, RetryPolicy, RunPolicy};
+use goose::scheduler_triggers::{CompletionStatus, JobTrigger};
 use goose::temporal_scheduler::TemporalScheduler;
+use rand::{distributions::Alphanumeric, Rng};
 use std::path::Path;
 
 // Base64 decoding function - might be needed if recipe_source_arg can be base64
@@ -75,18 +79,139 @@ fn validate_cron_expression(cron: &str) -> Result<()> {
     Ok(())
 }
 
+/// Build the trigger for a job from the `schedule add` flags, if it runs on an event rather
+/// than a cron expression
+pub fn schedule_trigger(
+    on_file_change: Option<String>,
+    on_webhook: bool,
+    webhook_secret: Option<String>,
+    webhook_params: Vec<(String, String)>,
+    after: Option<String>,
+    after_status: &str,
+) -> Option<JobTrigger> {
+    if let Some(path) = on_file_change {
+        return Some(JobTrigger::FileChange {
+            path,
+            debounce_seconds: 2,
+        });
+    }
+    if on_webhook {
+        let secret = webhook_secret.unwrap_or_else(|| {
+            rand::thread_rng()
+                .sample_iter(&Alphanumeric)
+                .take(32)
+                .map(char::from)
+                .collect()
+        });
+        return Some(JobTrigger::Webhook {
+            secret,
+            params: webhook_params.into_iter().collect(),
+        });
+    }
+    after.map(|job_id| JobTrigger::After {
+        job_id,
+        status: match after_status {
+            "failure" => CompletionStatus::Failure,
+            "any" => CompletionStatus::Any,
+            _ => CompletionStatus::Success,
+        },
+    })
+}
+
+/// Build how a job's runs are retried, overlap and catch up from the `schedule add` flags
+pub fn schedule_policy(
+    max_attempts: Option<u32>,
+    retry_backoff: Option<u64>,
+    max_concurrent: u32,
+    overlap: &str,
+    catch_up: &str,
+) -> RunPolicy {
+    let retry = max_attempts.map(|max_attempts| {
+        let mut retry = RetryPolicy {
+            max_attempts,
+            ..RetryPolicy::default()
+        };
+        if let Some(backoff_seconds) = retry_backoff {
+            retry.backoff_seconds = backoff_seconds;
+        }
+        retry
+    });
+    RunPolicy {
+        retry,
+        max_concurrent,
+        overlap: match overlap {
+            "queue" => OverlapPolicy::Queue,
+            "cancel-previous" => OverlapPolicy::CancelPrevious,
+            _ => OverlapPolicy::Skip,
+        },
+        catch_up: match catch_up {
+            "run-once" => CatchUpPolicy::RunOnce,
+            "run-all" => CatchUpPolicy::RunAll,
+            _ => CatchUpPolicy::Skip,
+        },
+    }
+}
+
+/// Parse a `--notify` value: `desktop`, `webhook:URL`, `email:ADDRESS[,ADDRESS...]` or
+/// `file:PATH`
+pub fn parse_notifier(s: &str) -> Result<NotifierTarget, String> {
+    if s == "desktop" {
+        return Ok(NotifierTarget::Desktop);
+    }
+    match s.split_once(':') {
+        Some(("webhook", url)) if !url.is_empty() => Ok(NotifierTarget::Webhook {
+            url: url.to_string(),
+            headers: Default::default(),
+        }),
+        Some(("email", to)) if !to.is_empty() => Ok(NotifierTarget::Email {
+            to: to.split(',').map(|addr| addr.trim().to_string()).collect(),
+        }),
+        Some(("file", path)) if !path.is_empty() => Ok(NotifierTarget::File {
+            path: path.to_string(),
+        }),
+        _ => Err(format!(
+            "invalid notifier '{}', expected desktop, webhook:URL, email:ADDRESS or file:PATH",
+            s
+        )),
+    }
+}
+
+/// Build a job's notifiers from the `schedule add` flags
+pub fn schedule_notifiers(targets: Vec<NotifierTarget>, notify_on: &str) -> Vec<JobNotifier> {
+    let when = match notify_on {
+        "failure" => NotifyWhen::OnFailure,
+        _ => NotifyWhen::Always,
+    };
+    targets
+        .into_iter()
+        .map(|target| JobNotifier { target, when })
+        .collect()
+}
+
 pub async fn handle_schedule_add(
     id: String,
-    cron: String,
+    cron: Option<String>,
     recipe_source_arg: String, // This is expected to be a file path by the Scheduler
+    trigger: Option<JobTrigger>,
+    policy: RunPolicy,
+    notifiers: Vec<JobNotifier>,
 ) -> Result<()> {
-    println!(
-        "[CLI Debug] Scheduling job ID: {}, Cron: {}, Recipe Source Path: {}",
-        id, cron, recipe_source_arg
-    );
+    let cron = cron.unwrap_or_default();
+    match &trigger {
+        Some(trigger) => println!(
+            "[CLI Debug] Scheduling job ID: {}, Trigger: {}, Recipe Source Path: {}",
+            id, trigger, recipe_source_arg
+        ),
+        None => {
+            println!(
+                "[CLI Debug] Scheduling job ID: {}, Cron: {}, Recipe Source Path: {}",
+                id, cron, recipe_source_arg
+            );
 
-    // Validate cron expression and provide helpful feedback
-    validate_cron_expression(&cron)?;
+            // Validate cron expression and provide helpful feedback
+            validate_cron_expression(&cron)?;
+        }
+    }
 
     // The Scheduler's add_scheduled_job will handle copying the recipe from recipe_source_arg
     // to its internal storage and validating the path.
@@ -100,6 +225,9 @@ pub async fn handle_schedule_add(
         current_session_id: None,
         process_start_time: None,
         execution_mode: Some("background".to_string()), // Default to background for CLI
+        trigger: trigger.clone(),
+        policy,
+        notifiers,
     };
 
     let scheduler_storage_path =
@@ -124,6 +252,12 @@ pub async fn handle_schedule_add(
                 "Scheduled job '{}' added. Recipe expected at {:?}",
                 id, final_recipe_path
             );
+            if let Some(JobTrigger::Webhook { secret, .. }) = &trigger {
+                println!(
+                    "Send webhooks to POST /schedule/{}/webhook on goose-server with the unix time in the X-Goose-Timestamp header, signed in the X-Goose-Signature header with the HMAC-SHA256 of '<timestamp>.<body>' using the secret: {}",
+                    id, secret
+                );
+            }
             Ok(())
         }
         Err(e) => {
@@ -167,11 +301,16 @@ pub async fn handle_schedule_list() -> Result<()> {
                 "⏹️  IDLE"
             };
 
+            let runs = match &job.trigger {
+                Some(trigger) => format!("Runs: {}", trigger),
+                None => format!("Cron: {}", job.cron),
+            };
+
             println!(
-                "- ID: {}\n  Status: {}\n  Cron: {}\n  Recipe Source (in store): {}\n  Last Run: {}",
+                "- ID: {}\n  Status: {}\n  {}\n  Recipe Source (in store): {}\n  Last Run: {}",
                 job.id,
                 status,
-                job.cron,
+                runs,
                 job.source, // This source is now the path within scheduled_recipes_dir
                 job.last_run
                     .map_or_else(|| "Never".to_string(), |dt| dt.to_rfc3339())
@@ -236,6 +375,54 @@ pub async fn handle_schedule_sessions(id: String, limit: Option<u32>) -> Result<
     Ok(())
 }
 
+pub async fn handle_schedule_history(id: String, limit: Option<u32>) -> Result<()> {
+    let scheduler_storage_path =
+        get_default_scheduler_storage_path().context("Failed to get scheduler storage path")?;
+    let scheduler = SchedulerFactory::create(scheduler_storage_path)
+        .await
+        .context("Failed to initialize scheduler")?;
+
+    match scheduler
+        .run_history(&id, limit.unwrap_or(20) as usize)
+        .await
+    {
+        Ok(runs) => {
+            if runs.is_empty() {
+                println!("No runs found for schedule ID '{}'.", id);
+            } else {
+                println!("Runs for schedule ID '{}', newest first:", id);
+                for run in runs {
+                    let cost = run
+                        .cost
+                        .map_or_else(String::new, |cost| format!(", ${:.4}", cost));
+                    println!(
+                        "  - {}  {}  ({:?}, {} attempt(s), {:.1}s{})",
+                        run.started_at.to_rfc3339(),
+                        run.status,
+                        run.reason,
+                        run.attempts,
+                        run.duration_ms as f64 / 1000.0,
+                        cost
+                    );
+                    if let Some(session_id) = &run.session_id {
+                        println!("      Session: {}", session_id);
+                    }
+                    if let Some(error) = &run.error {
+                        println!("      Error: {}", error);
+                    }
+                }
+            }
+        }
+        Err(e) => match e {
+            SchedulerError::JobNotFound(job_id) => {
+                bail!("Error: Job with ID '{}' not found.", job_id);
+            }
+            _ => bail!("Failed to get history for schedule '{}': {:?}", id, e),
+        },
+    }
+    Ok(())
+}
+
 pub async fn handle_schedule_run_now(id: String) -> Result<()> {
     let scheduler_storage_path =
         get_default_scheduler_storage_path().context("Failed to get scheduler storage path")?;
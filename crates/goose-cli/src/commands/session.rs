@@ -2,6 +2,7 @@ use crate::session::message_to_markdown;
 use crate::utils::safe_truncate;
 use anyhow::{Context, Result};
 use cliclack::{confirm, multiselect, select};
+use goose::redaction::Redactor;
 use goose::session::info::{get_valid_sorted_sessions, SessionInfo, SortOrder};
 use goose::session::{self, Identifier};
 use regex::Regex;
@@ -194,6 +195,10 @@ pub fn handle_session_export(identifier: Identifier, output_path: Option<PathBuf
         }
     };
 
+    // Sessions saved before redaction was enabled, or with on_save turned off, may still hold
+    // secrets, so redact before sharing
+    let messages = Redactor::global().redact_messages(&messages);
+
     // Generate the markdown content using the export functionality
     let markdown = export_session_to_markdown(messages, &session_file_path, None);
 
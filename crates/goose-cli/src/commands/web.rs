@@ -623,6 +623,19 @@ async fn process_message_streaming(
                         // Log model change
                         tracing::info!("Model changed to {} in {} mode", model, mode);
                     }
+                    Ok(AgentEvent::HistoryReplaced(messages)) => {
+                        // The agent compacted the conversation, keep the session in sync
+                        *session_messages.lock().await = messages;
+                        let current_messages = session_messages.lock().await.clone();
+                        session::persist_messages(&session_file, &current_messages, None).await?;
+                    }
+                    Ok(AgentEvent::ExtensionRestarted(restart)) => {
+                        tracing::warn!(
+                            "Extension '{}' was restarted: {:?}",
+                            restart.name,
+                            restart.error
+                        );
+                    }
 
                     Err(e) => {
                         error!("Error in message stream: {}", e);
@@ -11,7 +11,7 @@ use tracing_subscriber::{
     Registry,
 };
 
-use goose::tracing::langfuse_layer;
+use goose::tracing::{langfuse_layer, otlp_layer};
 use goose_bench::bench_session::BenchAgentError;
 use goose_bench::error_capture::ErrorCaptureLayer;
 
@@ -55,6 +55,7 @@ fn get_log_directory_with_date(test_date: Option<String>) -> Result<PathBuf> {
 /// - File-based logging with JSON formatting (DEBUG level)
 /// - Console output for development (INFO level)
 /// - Optional Langfuse integration (DEBUG level)
+/// - Optional OpenTelemetry export over OTLP (INFO level)
 /// - Optional error capture layer for benchmarking
 pub fn setup_logging(
     name: Option<&str>,
@@ -145,6 +146,13 @@ fn setup_logging_internal(
                 layers.push(langfuse.with_filter(LevelFilter::DEBUG).boxed());
             }
 
+            // Add OpenTelemetry layer if OTLP export is configured
+            if !force {
+                if let Some(otlp) = otlp_layer::create_otlp_layer("goose") {
+                    layers.push(otlp);
+                }
+            }
+
             // Build the subscriber
             let subscriber = Registry::default().with(layers);
 
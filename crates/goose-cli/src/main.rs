@@ -3,5 +3,7 @@ use goose_cli::cli::cli;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    cli().await
+    let result = cli().await;
+    goose::tracing::shutdown_otlp();
+    result
 }
@@ -3,24 +3,31 @@ use std::path::PathBuf;
 use anyhow::{anyhow, Result};
 use goose::recipe::{Response, SubRecipe};
 
+use crate::recipes::recipe::{apply_recipe_lock, load_recipe_as_template};
 use crate::recipes::search_recipe::retrieve_recipe_file;
-use crate::{cli::InputConfig, recipes::recipe::load_recipe_as_template, session::SessionSettings};
+use crate::{cli::InputConfig, session::SessionSettings};
 
 #[allow(clippy::type_complexity)]
 pub fn extract_recipe_info_from_cli(
     recipe_name: String,
     params: Vec<(String, String)>,
     additional_sub_recipes: Vec<String>,
+    locked: bool,
 ) -> Result<(
     InputConfig,
     Option<SessionSettings>,
     Option<Vec<SubRecipe>>,
     Option<Response>,
 )> {
-    let recipe = load_recipe_as_template(&recipe_name, params).unwrap_or_else(|err| {
-        eprintln!("{}: {}", console::style("Error").red().bold(), err);
-        std::process::exit(1);
-    });
+    let recipe = load_recipe_as_template(&recipe_name, params)
+        .and_then(|mut recipe| {
+            apply_recipe_lock(&recipe_name, &mut recipe, locked)?;
+            Ok(recipe)
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("{}: {}", console::style("Error").red().bold(), err);
+            std::process::exit(1);
+        });
     let mut all_sub_recipes = recipe.sub_recipes.clone().unwrap_or_default();
     if !additional_sub_recipes.is_empty() {
         for sub_recipe_name in additional_sub_recipes {
@@ -90,7 +97,7 @@ mod tests {
         let recipe_name = recipe_path.to_str().unwrap().to_string();
 
         let (input_config, settings, sub_recipes, response) =
-            extract_recipe_info_from_cli(recipe_name, params, Vec::new()).unwrap();
+            extract_recipe_info_from_cli(recipe_name, params, Vec::new(), false).unwrap();
 
         assert_eq!(input_config.contents, Some("test_prompt".to_string()));
         assert_eq!(
@@ -146,7 +153,8 @@ mod tests {
         ];
 
         let (input_config, settings, sub_recipes, response) =
-            extract_recipe_info_from_cli(recipe_name, params, additional_sub_recipes).unwrap();
+            extract_recipe_info_from_cli(recipe_name, params, additional_sub_recipes, false)
+                .unwrap();
 
         assert_eq!(input_config.contents, Some("test_prompt".to_string()));
         assert_eq!(
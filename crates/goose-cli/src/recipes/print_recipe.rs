@@ -1,7 +1,8 @@
 use std::collections::HashMap;
 
 use console::style;
-use goose::recipe::Recipe;
+use goose::recipe::parameters::{is_secret, SECRET_MASK};
+use goose::recipe::{Recipe, RecipeParameter};
 
 use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
 
@@ -18,6 +19,7 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
             println!("{}", style("⚙️  Recipe Parameters:").bold());
             for param in params {
                 let default_display = match &param.default {
+                    Some(_) if is_secret(param) => format!(" (default: {})", SECRET_MASK),
                     Some(val) => format!(" (default: {})", val),
                     None => String::new(),
                 };
@@ -35,13 +37,20 @@ pub fn print_recipe_explanation(recipe: &Recipe) {
     }
 }
 
-pub fn print_parameters_with_values(params: HashMap<String, String>) {
+pub fn print_parameters_with_values(
+    params: HashMap<String, String>,
+    recipe_parameters: &[RecipeParameter],
+) {
     for (key, value) in params {
         let label = if key == BUILT_IN_RECIPE_DIR_PARAM {
             " (built-in)"
         } else {
             ""
         };
+        let secret = recipe_parameters
+            .iter()
+            .any(|param| param.key == key && is_secret(param));
+        let value = if secret { SECRET_MASK } else { &value };
         println!("   {}{}: {}", key, label, value);
     }
 }
@@ -49,13 +58,14 @@ pub fn print_parameters_with_values(params: HashMap<String, String>) {
 pub fn print_required_parameters_for_template(
     params_for_template: HashMap<String, String>,
     missing_params: Vec<String>,
+    recipe_parameters: &[RecipeParameter],
 ) {
     if !params_for_template.is_empty() {
         println!(
             "{}",
             style("📥 Parameters used to load this recipe:").bold()
         );
-        print_parameters_with_values(params_for_template)
+        print_parameters_with_values(params_for_template, recipe_parameters)
     }
     if !missing_params.is_empty() {
         println!(
@@ -2,13 +2,21 @@ use crate::recipes::print_recipe::{
     missing_parameters_command_line, print_parameters_with_values, print_recipe_explanation,
     print_required_parameters_for_template,
 };
-use crate::recipes::search_recipe::{retrieve_recipe_file, RecipeFile};
+use crate::recipes::search_recipe::{retrieve_base_recipe, retrieve_recipe_file, RecipeFile};
 use crate::recipes::template_recipe::{
     parse_recipe_content, render_recipe_content_with_params, render_recipe_for_preview,
 };
 use anyhow::Result;
 use console::style;
-use goose::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement};
+use goose::agents::sub_recipe_execution_tool::lib::validate_steps;
+use goose::recipe::extends::{extends_recipe, resolve_extends};
+use goose::recipe::lockfile::{apply_lock, lock_file_path, read_lock_file};
+use goose::recipe::parameters::{
+    check_secrets_not_in_conversation, is_secret, secret_from_keyring, typed_parameter_values,
+};
+use goose::recipe::{
+    Recipe, RecipeParameter, RecipeParameterInputType, RecipeParameterRequirement,
+};
 use std::collections::{HashMap, HashSet};
 
 pub const BUILT_IN_RECIPE_DIR_PARAM: &str = "recipe_dir";
@@ -18,27 +26,118 @@ pub fn load_recipe_content_as_template(
     recipe_name: &str,
     params: Vec<(String, String)>,
 ) -> Result<String> {
+    let (recipe_file_content, recipe_parameters, params_for_template, missing_params) =
+        resolve_recipe_parameters(recipe_name, &params, true)?;
+
+    if !missing_params.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Please provide the following parameters in the command line: {}",
+            missing_parameters_command_line(missing_params)
+        ));
+    }
+
+    render_with_typed_values(
+        &recipe_file_content,
+        &recipe_parameters,
+        &params_for_template,
+    )
+}
+
+/// Load and render a recipe without prompting for parameters or printing anything, for callers
+/// such as `goose mcp-serve` that don't own a terminal.
+pub fn load_recipe_non_interactive(
+    recipe_name: &str,
+    params: Vec<(String, String)>,
+) -> Result<Recipe> {
+    let (recipe_file_content, recipe_parameters, params_for_template, missing_params) =
+        resolve_recipe_parameters(recipe_name, &params, false)?;
+
+    if !missing_params.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Missing values for recipe parameters: {}",
+            missing_params.join(", ")
+        ));
+    }
+
+    let rendered_content = render_with_typed_values(
+        &recipe_file_content,
+        &recipe_parameters,
+        &params_for_template,
+    )?;
+    Recipe::from_content(&rendered_content)
+}
+
+// Check the values against their parameters' types and constraints and render the recipe with
+// the typed values, making sure no secret ends up in the conversation
+fn render_with_typed_values(
+    recipe_file_content: &str,
+    recipe_parameters: &[RecipeParameter],
+    params_for_template: &HashMap<String, String>,
+) -> Result<String> {
+    let values = typed_parameter_values(recipe_parameters, params_for_template)?;
+    let rendered_content = render_recipe_content_with_params(recipe_file_content, &values)?;
+    check_secrets_not_in_conversation(&Recipe::from_content(&rendered_content)?, &values)?;
+    Ok(rendered_content)
+}
+
+// Read the recipe file with any recipes it extends merged in
+fn retrieve_resolved_recipe_file(recipe_name: &str) -> Result<RecipeFile> {
+    let mut recipe_file = retrieve_recipe_file(recipe_name)?;
+    recipe_file.content = resolve_extends(
+        &recipe_file.content,
+        &recipe_file.parent_dir,
+        retrieve_base_recipe,
+    )?;
+    Ok(recipe_file)
+}
+
+/// The recipe file with the recipes it extends merged in, before its parameters are filled in,
+/// or `None` if it doesn't extend another recipe
+pub fn load_extended_recipe_content(recipe_name: &str) -> Result<Option<String>> {
+    let recipe_file = retrieve_recipe_file(recipe_name)?;
+    if !extends_recipe(&recipe_file.content) {
+        return Ok(None);
+    }
+    resolve_extends(
+        &recipe_file.content,
+        &recipe_file.parent_dir,
+        retrieve_base_recipe,
+    )
+    .map(Some)
+}
+
+fn resolve_recipe_parameters(
+    recipe_name: &str,
+    params: &[(String, String)],
+    enable_user_prompt: bool,
+) -> Result<(
+    String,
+    Vec<RecipeParameter>,
+    HashMap<String, String>,
+    Vec<String>,
+)> {
     let RecipeFile {
         content: recipe_file_content,
         parent_dir: recipe_parent_dir,
         ..
-    } = retrieve_recipe_file(recipe_name)?;
+    } = retrieve_resolved_recipe_file(recipe_name)?;
     let recipe_dir_str = recipe_parent_dir
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
     let recipe_parameters = validate_recipe_parameters(&recipe_file_content, recipe_dir_str)?;
 
-    let (params_for_template, missing_params) =
-        apply_values_to_parameters(&params, recipe_parameters, recipe_dir_str, true)?;
-
-    if !missing_params.is_empty() {
-        return Err(anyhow::anyhow!(
-            "Please provide the following parameters in the command line: {}",
-            missing_parameters_command_line(missing_params)
-        ));
-    }
-
-    render_recipe_content_with_params(&recipe_file_content, &params_for_template)
+    let (params_for_template, missing_params) = apply_values_to_parameters(
+        params,
+        recipe_parameters.clone(),
+        recipe_dir_str,
+        enable_user_prompt,
+    )?;
+    Ok((
+        recipe_file_content,
+        recipe_parameters.unwrap_or_default(),
+        params_for_template,
+        missing_params,
+    ))
 }
 
 fn validate_recipe_parameters(
@@ -67,18 +166,36 @@ pub fn load_recipe_as_template(recipe_name: &str, params: Vec<(String, String)>)
 
     if !params.is_empty() {
         println!("{}", style("Parameters used to load this recipe:").bold());
-        print_parameters_with_values(params.into_iter().collect());
+        print_parameters_with_values(
+            params.into_iter().collect(),
+            recipe.parameters.as_deref().unwrap_or_default(),
+        );
     }
     println!();
     Ok(recipe)
 }
 
+/// Whether the recipe is a workflow of `steps`, which `goose run` runs step by step instead
+/// of starting a session
+pub fn is_workflow_recipe(recipe_name: &str) -> Result<bool> {
+    let RecipeFile {
+        content: recipe_file_content,
+        parent_dir: recipe_parent_dir,
+        ..
+    } = retrieve_resolved_recipe_file(recipe_name)?;
+    let recipe_dir_str = recipe_parent_dir
+        .to_str()
+        .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
+    let (recipe, _) = parse_recipe_content(&recipe_file_content, recipe_dir_str.to_string())?;
+    Ok(recipe.steps.is_some())
+}
+
 pub fn load_recipe(recipe_name: &str) -> Result<Recipe> {
     let RecipeFile {
         content: recipe_file_content,
         parent_dir: recipe_parent_dir,
         ..
-    } = retrieve_recipe_file(recipe_name)?;
+    } = retrieve_resolved_recipe_file(recipe_name)?;
     let recipe_dir_str = recipe_parent_dir
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
@@ -94,10 +211,51 @@ pub fn load_recipe(recipe_name: &str) -> Result<Recipe> {
             validate_json_schema(json_schema)?;
         }
     }
+    if let Some(steps) = &recipe.steps {
+        validate_steps(steps)?;
+    }
 
     Ok(recipe)
 }
 
+/// Pin the recipe to its lock file, if it has one, and report how the recipe has drifted from
+/// it. Drift is a warning unless `locked` is set, which also requires the lock file to exist.
+pub fn apply_recipe_lock(recipe_name: &str, recipe: &mut Recipe, locked: bool) -> Result<()> {
+    let lock_path = lock_file_path(&retrieve_recipe_file(recipe_name)?.file_path);
+    if !lock_path.exists() {
+        if locked {
+            anyhow::bail!(
+                "Recipe has no lock file at {}, create one with `goose recipe lock {}`",
+                lock_path.display(),
+                recipe_name
+            );
+        }
+        return Ok(());
+    }
+    let drift = apply_lock(recipe, &read_lock_file(&lock_path)?);
+    if drift.is_empty() {
+        return Ok(());
+    }
+    if locked {
+        anyhow::bail!(
+            "Recipe has drifted from its lock file {}:\n  {}\nRun `goose recipe lock {}` to update it",
+            lock_path.display(),
+            drift.join("\n  "),
+            recipe_name
+        );
+    }
+    eprintln!(
+        "{} recipe has drifted from its lock file {}:",
+        style("Warning:").yellow().bold(),
+        lock_path.display()
+    );
+    for item in &drift {
+        eprintln!("  {}", item);
+    }
+    eprintln!();
+    Ok(())
+}
+
 pub fn explain_recipe_with_parameters(
     recipe_name: &str,
     params: Vec<(String, String)>,
@@ -106,7 +264,7 @@ pub fn explain_recipe_with_parameters(
         content: recipe_file_content,
         parent_dir: recipe_parent_dir,
         ..
-    } = retrieve_recipe_file(recipe_name)?;
+    } = retrieve_resolved_recipe_file(recipe_name)?;
     let recipe_dir_str = recipe_parent_dir
         .to_str()
         .ok_or_else(|| anyhow::anyhow!("Error getting recipe directory"))?;
@@ -120,7 +278,11 @@ pub fn explain_recipe_with_parameters(
         &params_for_template,
     )?;
     print_recipe_explanation(&recipe);
-    print_required_parameters_for_template(params_for_template, missing_params);
+    print_required_parameters_for_template(
+        params_for_template,
+        missing_params,
+        recipe.parameters.as_deref().unwrap_or_default(),
+    );
 
     Ok(())
 }
@@ -183,7 +345,9 @@ fn validate_optional_parameters(parameters: &Option<Vec<RecipeParameter>>) -> Re
         .unwrap_or(&vec![])
         .iter()
         .filter(|p| {
-            matches!(p.requirement, RecipeParameterRequirement::Optional) && p.default.is_none()
+            matches!(p.requirement, RecipeParameterRequirement::Optional)
+                && p.default.is_none()
+                && p.required_if.is_none()
         })
         .map(|p| p.key.clone())
         .collect();
@@ -209,14 +373,18 @@ fn apply_values_to_parameters(
     let mut missing_params: Vec<String> = Vec::new();
     for param in recipe_parameters.unwrap_or_default() {
         if !param_map.contains_key(&param.key) {
-            match (&param.default, &param.requirement) {
-                (Some(default), _) => param_map.insert(param.key.clone(), default.clone()),
-                (None, RecipeParameterRequirement::UserPrompt) if enable_user_prompt => {
-                    let input_value = cliclack::input(format!(
-                        "Please enter {} ({})",
-                        param.key, param.description
-                    ))
-                    .interact()?;
+            let secret = if is_secret(&param) {
+                secret_from_keyring(&param)
+            } else {
+                None
+            };
+            match (&param.default, secret, &param.requirement) {
+                (Some(default), _, _) => param_map.insert(param.key.clone(), default.clone()),
+                (None, Some(secret), _) => param_map.insert(param.key.clone(), secret),
+                // Whether it's needed depends on the other values
+                (None, None, _) if param.required_if.is_some() => None,
+                (None, None, RecipeParameterRequirement::UserPrompt) if enable_user_prompt => {
+                    let input_value = prompt_for_parameter(&param)?;
                     param_map.insert(param.key.clone(), input_value)
                 }
                 _ => {
@@ -229,6 +397,29 @@ fn apply_values_to_parameters(
     Ok((param_map, missing_params))
 }
 
+fn prompt_for_parameter(param: &RecipeParameter) -> Result<String> {
+    let prompt = format!("Please enter {} ({})", param.key, param.description);
+    let value = match (&param.input_type, &param.options) {
+        (RecipeParameterInputType::Secret, _) => cliclack::password(prompt).interact()?,
+        (RecipeParameterInputType::Select, Some(options)) => {
+            let mut select = cliclack::select(prompt);
+            for option in options {
+                select = select.item(option.clone(), option, "");
+            }
+            select.interact()?
+        }
+        (RecipeParameterInputType::Multiselect, Some(options)) => {
+            let mut multiselect = cliclack::multiselect(prompt);
+            for option in options {
+                multiselect = multiselect.item(option.clone(), option, "");
+            }
+            multiselect.interact()?.join(",")
+        }
+        _ => cliclack::input(prompt).interact()?,
+    };
+    Ok(value)
+}
+
 fn validate_json_schema(schema: &serde_json::Value) -> Result<()> {
     match jsonschema::validator_for(schema) {
         Ok(_) => Ok(()),
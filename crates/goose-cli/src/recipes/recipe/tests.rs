@@ -5,7 +5,7 @@ mod tests {
     use goose::recipe::{RecipeParameterInputType, RecipeParameterRequirement};
     use tempfile::TempDir;
 
-    use crate::recipes::recipe::load_recipe_as_template;
+    use crate::recipes::recipe::{load_recipe_as_template, load_recipe_non_interactive};
 
     fn setup_recipe_file(instructions_and_parameters: &str) -> (TempDir, PathBuf) {
         let recipe_content = format!(
@@ -311,5 +311,246 @@ mod tests {
                 "is_enabled"
             );
         }
+
+        #[test]
+        fn test_recipe_extends() {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let shared_dir = temp_dir.path().join("shared");
+            std::fs::create_dir(&shared_dir).unwrap();
+            let base_content = r#"
+version: 1.0.0
+title: Base
+description: Base recipe
+instructions: Use the team conventions
+settings:
+  goose_provider: openai
+  goose_model: gpt-4o
+parameters:
+  - key: team
+    input_type: string
+    requirement: optional
+    default: platform
+    description: the team
+"#;
+            std::fs::write(shared_dir.join("base.yaml"), base_content).unwrap();
+            let child_content = r#"
+extends: ./shared/base.yaml
+title: Child
+description: Child recipe
+prompt: "Summarize the {{ team }} team's open issues about {{ topic }}"
+settings:
+  temperature: 0.5
+parameters:
+  - key: topic
+    input_type: string
+    requirement: required
+    description: the topic
+"#;
+            let child_path = temp_dir.path().join("child.yaml");
+            std::fs::write(&child_path, child_content).unwrap();
+
+            let params = vec![("topic".to_string(), "billing".to_string())];
+            let recipe = load_recipe_as_template(child_path.to_str().unwrap(), params).unwrap();
+            assert_eq!(recipe.title, "Child");
+            assert_eq!(
+                recipe.prompt.as_deref(),
+                Some("Summarize the platform team's open issues about billing")
+            );
+            assert_eq!(
+                recipe.instructions.as_deref(),
+                Some("Use the team conventions")
+            );
+            let settings = recipe.settings.unwrap();
+            assert_eq!(settings.goose_model.as_deref(), Some("gpt-4o"));
+            assert_eq!(settings.temperature, Some(0.5));
+            assert_eq!(recipe.parameters.unwrap().len(), 2);
+        }
+    }
+
+    mod load_recipe_non_interactive_tests {
+        use super::*;
+
+        #[test]
+        fn test_load_recipe_non_interactive_success() {
+            let instructions_and_parameters = r#"
+                "instructions": "Test instructions with {{ my_name }}",
+                "parameters": [
+                    {
+                        "key": "my_name",
+                        "input_type": "string",
+                        "requirement": "user_prompt",
+                        "description": "A test parameter"
+                    }
+                ]"#;
+
+            let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+
+            let params = vec![("my_name".to_string(), "value".to_string())];
+            let recipe =
+                load_recipe_non_interactive(recipe_path.to_str().unwrap(), params).unwrap();
+
+            assert_eq!(recipe.instructions.unwrap(), "Test instructions with value");
+        }
+
+        #[test]
+        fn test_load_recipe_non_interactive_missing_user_prompt_parameter() {
+            let instructions_and_parameters = r#"
+                "instructions": "Test instructions with {{ my_name }}",
+                "parameters": [
+                    {
+                        "key": "my_name",
+                        "input_type": "string",
+                        "requirement": "user_prompt",
+                        "description": "A test parameter"
+                    }
+                ]"#;
+
+            let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+
+            let err =
+                load_recipe_non_interactive(recipe_path.to_str().unwrap(), Vec::new()).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Missing values for recipe parameters: my_name"
+            );
+        }
+
+        #[test]
+        fn test_load_recipe_non_interactive_typed_parameters() {
+            let instructions_and_parameters = r#"
+                "instructions": "Deploy {{ replicas }} replicas{% if mode == 'advanced' %} to depth {{ depth }}{% endif %}",
+                "parameters": [
+                    {
+                        "key": "replicas",
+                        "input_type": "number",
+                        "requirement": "required",
+                        "description": "How many replicas",
+                        "max": 5
+                    },
+                    {
+                        "key": "mode",
+                        "input_type": "select",
+                        "requirement": "required",
+                        "description": "How to deploy",
+                        "options": ["basic", "advanced"]
+                    },
+                    {
+                        "key": "depth",
+                        "input_type": "number",
+                        "requirement": "optional",
+                        "description": "How deep to look",
+                        "required_if": "mode == 'advanced'"
+                    }
+                ]"#;
+            let (_temp_dir, recipe_path) = setup_recipe_file(instructions_and_parameters);
+            let recipe_path = recipe_path.to_str().unwrap();
+            let params = |pairs: &[(&str, &str)]| {
+                pairs
+                    .iter()
+                    .map(|(k, v)| (k.to_string(), v.to_string()))
+                    .collect::<Vec<_>>()
+            };
+
+            let recipe = load_recipe_non_interactive(
+                recipe_path,
+                params(&[("replicas", "2"), ("mode", "basic")]),
+            )
+            .unwrap();
+            assert_eq!(recipe.instructions.unwrap(), "Deploy 2 replicas");
+
+            let err = load_recipe_non_interactive(
+                recipe_path,
+                params(&[("replicas", "2"), ("mode", "advanced")]),
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("'depth' is required when"));
+
+            let err = load_recipe_non_interactive(
+                recipe_path,
+                params(&[("replicas", "6"), ("mode", "basic")]),
+            )
+            .unwrap_err();
+            assert!(err.to_string().contains("'replicas' must be at most 5"));
+        }
+    }
+
+    mod apply_recipe_lock_tests {
+        use super::*;
+        use crate::recipes::recipe::apply_recipe_lock;
+        use goose::agents::ExtensionConfig;
+        use goose::recipe::lockfile::{
+            lock_file_path, write_lock_file, LockedExtension, LockedSource, RecipeLock,
+        };
+
+        fn locked_recipe() -> (TempDir, PathBuf) {
+            let (temp_dir, recipe_path) = setup_recipe_file(
+                r#""instructions": "Fetch the docs",
+                "extensions": [{
+                    "type": "stdio",
+                    "name": "fetch",
+                    "cmd": "uvx",
+                    "args": ["mcp-server-fetch"],
+                    "timeout": 300
+                }]"#,
+            );
+            let lock = RecipeLock {
+                goose_version: "1.0.0".to_string(),
+                locked_at: chrono::Utc::now(),
+                provider: Some("openai".to_string()),
+                model: Some("gpt-4o".to_string()),
+                extensions: vec![LockedExtension {
+                    name: "fetch".to_string(),
+                    source: LockedSource::Pypi {
+                        package: "mcp-server-fetch".to_string(),
+                        version: "2025.4.7".to_string(),
+                    },
+                }],
+            };
+            write_lock_file(&lock_file_path(&recipe_path), &lock).unwrap();
+            (temp_dir, recipe_path)
+        }
+
+        #[test]
+        fn test_apply_recipe_lock_pins_recipe() {
+            let (_temp_dir, recipe_path) = locked_recipe();
+            let recipe_name = recipe_path.to_str().unwrap();
+            let mut recipe = load_recipe_as_template(recipe_name, Vec::new()).unwrap();
+            apply_recipe_lock(recipe_name, &mut recipe, true).unwrap();
+
+            match &recipe.extensions.as_ref().unwrap()[0] {
+                ExtensionConfig::Stdio { args, .. } => {
+                    assert_eq!(args, &["mcp-server-fetch@2025.4.7"])
+                }
+                other => panic!("unexpected extension {:?}", other),
+            }
+            let settings = recipe.settings.unwrap();
+            assert_eq!(settings.goose_provider.as_deref(), Some("openai"));
+            assert_eq!(settings.goose_model.as_deref(), Some("gpt-4o"));
+        }
+
+        #[test]
+        fn test_apply_recipe_lock_drift() {
+            let (_temp_dir, recipe_path) = locked_recipe();
+            let recipe_name = recipe_path.to_str().unwrap();
+            let mut recipe = load_recipe_as_template(recipe_name, Vec::new()).unwrap();
+            recipe.extensions = Some(Vec::new());
+
+            apply_recipe_lock(recipe_name, &mut recipe.clone(), false).unwrap();
+            let err = apply_recipe_lock(recipe_name, &mut recipe, true).unwrap_err();
+            assert!(err
+                .to_string()
+                .contains("Extension 'fetch' is locked but the recipe no longer uses it"));
+        }
+
+        #[test]
+        fn test_apply_recipe_lock_requires_lock_file_when_locked() {
+            let (_temp_dir, recipe_path) = setup_recipe_file(r#""instructions": "Fetch the docs""#);
+            let recipe_name = recipe_path.to_str().unwrap();
+            let mut recipe = load_recipe_as_template(recipe_name, Vec::new()).unwrap();
+
+            apply_recipe_lock(recipe_name, &mut recipe, false).unwrap();
+            let err = apply_recipe_lock(recipe_name, &mut recipe, true).unwrap_err();
+            assert!(err.to_string().contains("has no lock file"));
+        }
     }
 }
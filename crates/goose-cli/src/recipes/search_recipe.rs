@@ -1,5 +1,7 @@
 use anyhow::{anyhow, Result};
 use goose::config::Config;
+use goose::recipe::extends::BaseRecipe;
+use goose::recipe::library::RecipeLibrary;
 use std::path::{Path, PathBuf};
 use std::{env, fs};
 
@@ -29,12 +31,32 @@ pub fn retrieve_recipe_file(recipe_name: &str) -> Result<RecipeFile> {
             recipe_name
         ));
     }
-    retrieve_recipe_from_local_path(recipe_name).or_else(|e| {
-        if let Some(recipe_repo_full_name) = configured_github_recipe_repo() {
-            retrieve_recipe_from_github(recipe_name, &recipe_repo_full_name)
-        } else {
-            Err(e)
-        }
+    retrieve_recipe_from_local_path(recipe_name)
+        .or_else(|e| retrieve_recipe_from_library(recipe_name).ok_or(e))
+        .or_else(|e| {
+            if let Some(recipe_repo_full_name) = configured_github_recipe_repo() {
+                retrieve_recipe_from_github(recipe_name, &recipe_repo_full_name)
+            } else {
+                Err(e)
+            }
+        })
+}
+
+/// Find the recipe that a recipe in `recipe_dir` extends. Paths are relative to the extending
+/// recipe, and names are looked up like any other recipe.
+pub fn retrieve_base_recipe(reference: &str, recipe_dir: &Path) -> Result<BaseRecipe> {
+    let reference = if is_file_path(reference) || is_file_name(reference) {
+        let path = convert_path_with_tilde_expansion(Path::new(reference));
+        recipe_dir.join(path).to_string_lossy().into_owned()
+    } else {
+        reference.to_string()
+    };
+    let RecipeFile {
+        content, file_path, ..
+    } = retrieve_recipe_file(&reference)?;
+    Ok(BaseRecipe {
+        content,
+        path: file_path,
     })
 }
 
@@ -92,6 +114,12 @@ fn retrieve_recipe_from_local_path(recipe_name: &str) -> Result<RecipeFile> {
     ))
 }
 
+// Installed recipe sources, and recipes in subdirectories of GOOSE_RECIPE_PATH
+fn retrieve_recipe_from_library(recipe_name: &str) -> Option<RecipeFile> {
+    let recipe = RecipeLibrary::default().find(recipe_name).ok()??;
+    read_recipe_file(recipe.path).ok()
+}
+
 fn configured_github_recipe_repo() -> Option<String> {
     let config = Config::global();
     match config.get_param(GOOSE_RECIPE_GITHUB_REPO_CONFIG_KEY) {
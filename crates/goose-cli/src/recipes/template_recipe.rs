@@ -4,22 +4,26 @@ use std::{
 };
 
 use anyhow::Result;
+use goose::recipe::template::{check_step_references, deferred_step_outputs, STEPS_TEMPLATE_VAR};
 use goose::recipe::Recipe;
 use minijinja::{Environment, UndefinedBehavior};
+use serde_json::Value;
 
 use crate::recipes::recipe::BUILT_IN_RECIPE_DIR_PARAM;
 
 const CURRENT_TEMPLATE_NAME: &str = "current_template";
 
+/// Render recipe content with typed parameter values, which must include the recipe directory
 pub fn render_recipe_content_with_params(
     content: &str,
-    params: &HashMap<String, String>,
+    params: &HashMap<String, Value>,
 ) -> Result<String> {
-    let env = add_template_in_env(
-        content,
-        params.get(BUILT_IN_RECIPE_DIR_PARAM).unwrap().clone(),
-        UndefinedBehavior::Strict,
-    )?;
+    let recipe_dir = params
+        .get(BUILT_IN_RECIPE_DIR_PARAM)
+        .and_then(Value::as_str)
+        .unwrap()
+        .to_string();
+    let env = add_template_in_env(content, recipe_dir, UndefinedBehavior::Strict)?;
     let template = env.get_template(CURRENT_TEMPLATE_NAME).unwrap();
     let rendered_content = template
         .render(params)
@@ -33,6 +37,8 @@ pub fn render_recipe_silent_when_variables_are_provided(
 ) -> Result<String> {
     let mut env = minijinja::Environment::new();
     env.set_undefined_behavior(UndefinedBehavior::Lenient);
+    env.add_global(STEPS_TEMPLATE_VAR, deferred_step_outputs());
+    check_step_references(content)?;
     let template = env.template_from_str(content)?;
     let rendered_content = template.render(params)?;
     Ok(rendered_content)
@@ -48,7 +54,13 @@ fn add_template_in_env(
     env.set_loader(move |name| {
         let path = Path::new(recipe_dir.as_str()).join(name);
         match std::fs::read_to_string(&path) {
-            Ok(content) => Ok(Some(content)),
+            Ok(content) => match check_step_references(&content) {
+                Ok(()) => Ok(Some(content)),
+                Err(e) => Err(minijinja::Error::new(
+                    minijinja::ErrorKind::InvalidOperation,
+                    e.to_string(),
+                )),
+            },
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
             Err(e) => Err(minijinja::Error::new(
                 minijinja::ErrorKind::InvalidOperation,
@@ -57,6 +69,9 @@ fn add_template_in_env(
             .with_source(e)),
         }
     });
+    // Workflow step outputs are only known at run time, so they are left in place
+    env.add_global(STEPS_TEMPLATE_VAR, deferred_step_outputs());
+    check_step_references(content)?;
 
     env.add_template(CURRENT_TEMPLATE_NAME, content)?;
     Ok(env)
@@ -74,6 +89,7 @@ fn get_env_with_template_variables(
     for (_, template) in state.env().templates() {
         template_variables.extend(template.undeclared_variables(true));
     }
+    template_variables.retain(|variable| !is_step_reference(variable));
     Ok((env, template_variables))
 }
 
@@ -110,6 +126,13 @@ pub fn render_recipe_for_preview(
     Recipe::from_content(&rendered_content)
 }
 
+fn is_step_reference(variable: &str) -> bool {
+    variable == STEPS_TEMPLATE_VAR
+        || variable
+            .strip_prefix(STEPS_TEMPLATE_VAR)
+            .is_some_and(|rest| rest.starts_with('.'))
+}
+
 fn preserve_vars(variables: &HashSet<String>) -> HashMap<String, String> {
     let mut context = HashMap::<String, String>::new();
     for template_var in variables {
@@ -123,8 +146,42 @@ mod tests {
     mod render_content_with_params_tests {
         use std::collections::HashMap;
 
+        use serde_json::{json, Value};
+
         use crate::recipes::template_recipe::render_recipe_content_with_params;
 
+        fn render(content: &str, params: &HashMap<String, String>) -> anyhow::Result<String> {
+            let values: HashMap<String, Value> = params
+                .iter()
+                .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+                .collect();
+            render_recipe_content_with_params(content, &values)
+        }
+
+        #[test]
+        fn test_render_content_with_typed_params() {
+            let content = "{% if verbose %}{{ count + 1 }} {{ labels | join('/') }}{% endif %}";
+            let params = HashMap::from([
+                ("recipe_dir".to_string(), json!("some_dir")),
+                ("verbose".to_string(), json!(true)),
+                ("count".to_string(), json!(2)),
+                ("labels".to_string(), json!(["bug", "ci"])),
+            ]);
+            let result = render_recipe_content_with_params(content, &params).unwrap();
+            assert_eq!(result, "3 bug/ci");
+        }
+
+        #[test]
+        fn test_render_content_keeps_step_outputs() {
+            let content = "Fix {{ name }}: {{ steps.triage.output.summary }}";
+            let params = HashMap::from([
+                ("recipe_dir".to_string(), "some_dir".to_string()),
+                ("name".to_string(), "the bug".to_string()),
+            ]);
+            let result = render(content, &params).unwrap();
+            assert_eq!(result, "Fix the bug: {{ steps.triage.output.summary }}");
+        }
+
         #[test]
         fn test_render_content_with_params() {
             // Test basic parameter substitution
@@ -133,7 +190,7 @@ mod tests {
                 ("recipe_dir".to_string(), "some_dir".to_string()),
                 ("name".to_string(), "World".to_string()),
             ]);
-            let result = render_recipe_content_with_params(content, &params).unwrap();
+            let result = render(content, &params).unwrap();
             assert_eq!(result, "Hello World!");
 
             // Test empty parameter substitution
@@ -142,7 +199,7 @@ mod tests {
                 ("recipe_dir".to_string(), "some_dir".to_string()),
                 ("empty".to_string(), "".to_string()),
             ]);
-            let result = render_recipe_content_with_params(content, &params).unwrap();
+            let result = render(content, &params).unwrap();
             assert_eq!(result, "Hello !");
 
             // Test multiple parameters
@@ -152,20 +209,20 @@ mod tests {
                 ("greeting".to_string(), "Hi".to_string()),
                 ("name".to_string(), "Alice".to_string()),
             ]);
-            let result = render_recipe_content_with_params(content, &params).unwrap();
+            let result = render(content, &params).unwrap();
             assert_eq!(result, "Hi Alice!");
 
             // Test missing parameter results in error
             let content = "Hello {{ missing }}!";
             let params = HashMap::from([("recipe_dir".to_string(), "some_dir".to_string())]);
-            let err = render_recipe_content_with_params(content, &params).unwrap_err();
+            let err = render(content, &params).unwrap_err();
             let error_msg = err.to_string();
             assert!(error_msg.contains("Failed to render the recipe"));
 
             // Test invalid template syntax results in error
             let content = "Hello {{ unclosed";
             let params = HashMap::from([("recipe_dir".to_string(), "some_dir".to_string())]);
-            let err = render_recipe_content_with_params(content, &params).unwrap_err();
+            let err = render(content, &params).unwrap_err();
             assert!(err.to_string().contains("unexpected end of input"));
         }
     }
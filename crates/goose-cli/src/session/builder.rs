@@ -62,6 +62,46 @@ pub struct SessionBuilderConfig {
     pub final_output_response: Option<Response>,
 }
 
+/// Ask before using the extensions and hooks a project's .goose/config.yaml adds, since they
+/// run commands on this machine. Until they are trusted they are left out.
+fn review_project_commands(interactive: bool) {
+    let config = Config::global();
+    let untrusted = config.untrusted_project_commands();
+    let Some(project_path) = config.project_path().filter(|_| !untrusted.is_empty()) else {
+        return;
+    };
+
+    let trust = interactive
+        && cliclack::confirm(format!(
+            "{} {} adds these extensions and hooks: {}. They will run commands on this machine. Do you trust them?",
+            style("WARNING:").yellow(),
+            style(project_path.display()).cyan(),
+            untrusted.join(", ")
+        ))
+        .initial_value(false)
+        .interact()
+        .unwrap_or(false);
+
+    if !trust {
+        eprintln!(
+            "{}",
+            style(format!(
+                "Skipping extensions and hooks from {}: {}. Start an interactive session here to review them.",
+                project_path.display(),
+                untrusted.join(", ")
+            ))
+            .yellow()
+        );
+        return;
+    }
+    if let Err(e) = config.trust_project_commands() {
+        output::render_error(&format!(
+            "Failed to save the trusted project settings: {}",
+            e
+        ));
+    }
+}
+
 /// Offers to help debug an extension failure by creating a minimal debugging session
 async fn offer_extension_debugging_help(
     extension_name: &str,
@@ -336,6 +376,7 @@ pub async fn build_session(session_config: SessionBuilderConfig) -> Session {
     // Setup extensions for the agent
     // Extensions need to be added after the session is created because we change directory when resuming a session
     // If we get extensions_override, only run those extensions and none other
+    review_project_commands(session_config.interactive);
     let extensions_to_run: Vec<_> = if let Some(extensions) = session_config.extensions_override {
         extensions.into_iter().collect()
     } else {
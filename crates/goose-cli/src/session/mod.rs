@@ -909,6 +909,7 @@ impl Session {
                                     "clear" => "clear",
                                     "truncate" => "truncate",
                                     "summarize" => "summarize",
+                                    "compact" => "compact",
                                     _ => {
                                         if interactive {
                                             // In interactive mode with no default, ask the user what to do
@@ -917,6 +918,7 @@ impl Session {
                                                 .item("clear", "Clear Session", "Removes all messages from Goose's memory")
                                                 .item("truncate", "Truncate Messages", "Removes old messages till context is within limits")
                                                 .item("summarize", "Summarize Session", "Summarize the session to reduce context length")
+                                                .item("compact", "Compact Session", "Elide old tool output and summarize older turns, keeping recent ones")
                                                 .interact()?
                                         } else {
                                             // In headless mode, default to summarize
@@ -959,6 +961,16 @@ impl Session {
                                         };
                                         Self::summarize_context_messages(&mut self.messages, &self.agent, message_suffix).await?;
                                     }
+                                    "compact" => {
+                                        let (compacted_messages, _) = self.agent.compact_context(&self.messages).await?;
+                                        let msg = if context_strategy == "compact" {
+                                            format!("Context maxed out - automatically compacted messages.\n{}\nGoose elided old tool output and summarized older turns.", "-".repeat(50))
+                                        } else {
+                                            format!("Context maxed out\n{}\nGoose elided old tool output and summarized older turns.", "-".repeat(50))
+                                        };
+                                        output::render_text(&msg, Some(Color::Yellow), true);
+                                        self.messages = compacted_messages;
+                                    }
                                     _ => {
                                         unreachable!()
                                     }
@@ -1112,6 +1124,41 @@ impl Session {
                                 eprintln!("Model changed to {} in {} mode", model, mode);
                             }
                         }
+                        Some(Ok(AgentEvent::HistoryReplaced(messages))) => {
+                            self.messages = messages;
+                            if let Some(session_file) = &self.session_file {
+                                session::persist_messages_with_schedule_id(
+                                    session_file,
+                                    &self.messages,
+                                    None,
+                                    self.scheduled_job_id.clone(),
+                                )
+                                .await?;
+                            }
+                            if self.debug {
+                                eprintln!("Compacted conversation to {} messages", self.messages.len());
+                            }
+                        }
+                        Some(Ok(AgentEvent::ExtensionRestarted(restart))) => {
+                            match restart.error {
+                                None => eprintln!(
+                                    "{}",
+                                    console::style(format!(
+                                        "Extension '{}' stopped responding and was restarted",
+                                        restart.name
+                                    ))
+                                    .yellow()
+                                ),
+                                Some(error) => eprintln!(
+                                    "{}",
+                                    console::style(format!(
+                                        "Extension '{}' stopped responding and could not be restarted: {}",
+                                        restart.name, error
+                                    ))
+                                    .red()
+                                ),
+                            }
+                        }
 
                         Some(Err(e)) => {
                             eprintln!("Error: {}", e);
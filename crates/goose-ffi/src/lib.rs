@@ -269,6 +269,12 @@ pub unsafe extern "C" fn goose_agent_send_message(
                 Ok(AgentEvent::ModelChange { .. }) => {
                     // Model change events are informational, just continue
                 }
+                Ok(AgentEvent::HistoryReplaced(_)) => {
+                    // Compacted history isn't part of the response text
+                }
+                Ok(AgentEvent::ExtensionRestarted(_)) => {
+                    // Extension restarts are informational, just continue
+                }
 
                 Err(e) => {
                     full_response.push_str(&format!("\nError in message stream: {}", e));
@@ -40,6 +40,8 @@ use mcp_core::role::Role;
 
 use self::editor_models::{create_editor_model, EditorModel};
 use self::shell::{expand_path, get_shell_config, is_absolute_path, normalize_line_endings};
+use crate::lsp::LspManager;
+use goose_sandbox::SandboxProfile;
 use indoc::indoc;
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
@@ -93,6 +95,28 @@ pub fn load_prompt_files() -> HashMap<String, Prompt> {
     prompts
 }
 
+/// Language server diagnostics are appended to text_editor results when
+/// GOOSE_EDITOR_DIAGNOSTICS is enabled
+fn create_lsp_manager() -> Option<Arc<LspManager>> {
+    if cfg!(test) {
+        return None;
+    }
+
+    let enabled = std::env::var("GOOSE_EDITOR_DIAGNOSTICS")
+        .map(|v| matches!(v.to_lowercase().as_str(), "1" | "true"))
+        .unwrap_or(false);
+    enabled.then(|| Arc::new(LspManager::from_env()))
+}
+
+/// Shell commands run in a sandbox when goose hands down a profile in GOOSE_SHELL_SANDBOX
+fn load_shell_sandbox() -> Option<SandboxProfile> {
+    SandboxProfile::from_env().unwrap_or_else(|e| {
+        // Fail closed, a profile we can't read still gets the default restrictions
+        tracing::error!("{}, falling back to the default shell sandbox", e);
+        Some(SandboxProfile::default())
+    })
+}
+
 pub struct DeveloperRouter {
     tools: Vec<Tool>,
     prompts: Arc<HashMap<String, Prompt>>,
@@ -100,6 +124,8 @@ pub struct DeveloperRouter {
     file_history: Arc<Mutex<HashMap<PathBuf, Vec<String>>>>,
     ignore_patterns: Arc<Gitignore>,
     editor_model: Option<EditorModel>,
+    lsp: Option<Arc<LspManager>>,
+    shell_sandbox: Option<SandboxProfile>,
 }
 
 impl Default for DeveloperRouter {
@@ -108,6 +134,61 @@ impl Default for DeveloperRouter {
     }
 }
 
+/// Load the `.gooseignore` patterns that apply in `cwd`: the global file in goose's config
+/// directory, then the local `.gooseignore` (or `.gitignore` as a fallback). Sensible defaults
+/// for secrets are used when neither exists.
+pub fn load_ignore_patterns(cwd: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(cwd);
+    let mut has_ignore_file = false;
+    // Initialize ignore patterns
+    // - macOS/Linux: ~/.config/goose/
+    // - Windows:     ~\AppData\Roaming\Block\goose\config\
+    let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .unwrap_or_else(|_| {
+            PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
+        });
+
+    // Create the directory if it doesn't exist
+    let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
+
+    // Read global ignores if they exist
+    if global_ignore_path.is_file() {
+        let _ = builder.add(global_ignore_path);
+        has_ignore_file = true;
+    }
+
+    // Check for local ignores in current directory
+    let local_ignore_path = cwd.join(".gooseignore");
+
+    // Read local ignores if they exist
+    if local_ignore_path.is_file() {
+        let _ = builder.add(local_ignore_path);
+        has_ignore_file = true;
+    } else {
+        // If no .gooseignore exists, check for .gitignore as fallback
+        let gitignore_path = cwd.join(".gitignore");
+        if gitignore_path.is_file() {
+            tracing::debug!(
+                "No .gooseignore found, using .gitignore as fallback for ignore patterns"
+            );
+            let _ = builder.add(gitignore_path);
+            has_ignore_file = true;
+        }
+    }
+
+    // Only use default patterns if no .gooseignore files were found
+    // AND no .gitignore was used as fallback
+    if !has_ignore_file {
+        // Add some sensible defaults
+        let _ = builder.add_line(None, "**/.env");
+        let _ = builder.add_line(None, "**/.env.*");
+        let _ = builder.add_line(None, "**/secrets.*");
+    }
+
+    builder.build().expect("Failed to build ignore patterns")
+}
+
 impl DeveloperRouter {
     pub fn new() -> Self {
         // TODO consider rust native search tools, we could use
@@ -430,55 +511,7 @@ impl DeveloperRouter {
             format!("{base_instructions}\n{hints}")
         };
 
-        let mut builder = GitignoreBuilder::new(cwd.clone());
-        let mut has_ignore_file = false;
-        // Initialize ignore patterns
-        // - macOS/Linux: ~/.config/goose/
-        // - Windows:     ~\AppData\Roaming\Block\goose\config\
-        let global_ignore_path = choose_app_strategy(crate::APP_STRATEGY.clone())
-            .map(|strategy| strategy.in_config_dir(".gooseignore"))
-            .unwrap_or_else(|_| {
-                PathBuf::from(shellexpand::tilde("~/.config/goose/.gooseignore").to_string())
-            });
-
-        // Create the directory if it doesn't exist
-        let _ = std::fs::create_dir_all(global_ignore_path.parent().unwrap());
-
-        // Read global ignores if they exist
-        if global_ignore_path.is_file() {
-            let _ = builder.add(global_ignore_path);
-            has_ignore_file = true;
-        }
-
-        // Check for local ignores in current directory
-        let local_ignore_path = cwd.join(".gooseignore");
-
-        // Read local ignores if they exist
-        if local_ignore_path.is_file() {
-            let _ = builder.add(local_ignore_path);
-            has_ignore_file = true;
-        } else {
-            // If no .gooseignore exists, check for .gitignore as fallback
-            let gitignore_path = cwd.join(".gitignore");
-            if gitignore_path.is_file() {
-                tracing::debug!(
-                    "No .gooseignore found, using .gitignore as fallback for ignore patterns"
-                );
-                let _ = builder.add(gitignore_path);
-                has_ignore_file = true;
-            }
-        }
-
-        // Only use default patterns if no .gooseignore files were found
-        // AND no .gitignore was used as fallback
-        if !has_ignore_file {
-            // Add some sensible defaults
-            let _ = builder.add_line(None, "**/.env");
-            let _ = builder.add_line(None, "**/.env.*");
-            let _ = builder.add_line(None, "**/secrets.*");
-        }
-
-        let ignore_patterns = builder.build().expect("Failed to build ignore patterns");
+        let ignore_patterns = load_ignore_patterns(&cwd);
 
         Self {
             tools: vec![
@@ -493,6 +526,8 @@ impl DeveloperRouter {
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
             editor_model,
+            lsp: create_lsp_manager(),
+            shell_sandbox: load_shell_sandbox(),
         }
     }
 
@@ -558,7 +593,13 @@ impl DeveloperRouter {
         let shell_config = get_shell_config();
 
         // Execute the command using platform-specific shell
-        let mut child = Command::new(&shell_config.executable)
+        let mut process = Command::new(&shell_config.executable);
+        if let Some(sandbox) = &self.shell_sandbox {
+            sandbox.apply(process.as_std_mut()).map_err(|e| {
+                ToolError::ExecutionError(format!("The shell sandbox could not be set up: {}", e))
+            })?;
+        }
+        let mut child = process
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .stdin(Stdio::null())
@@ -566,7 +607,13 @@ impl DeveloperRouter {
             .args(&shell_config.args)
             .arg(command)
             .spawn()
-            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+            .map_err(|e| match self.shell_sandbox {
+                Some(_) => ToolError::ExecutionError(format!(
+                    "The command could not be started in the shell sandbox: {}",
+                    e
+                )),
+                None => ToolError::ExecutionError(e.to_string()),
+            })?;
 
         let stdout = child.stdout.take().unwrap();
         let stderr = child.stderr.take().unwrap();
@@ -642,16 +689,26 @@ impl DeveloperRouter {
         });
 
         // Wait for the command to complete and get output
-        child
+        let status = child
             .wait()
             .await
             .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
 
-        let output_str = match output_task.await {
+        let mut output_str = match output_task.await {
             Ok(result) => result.map_err(|e| ToolError::ExecutionError(e.to_string()))?,
             Err(e) => return Err(ToolError::ExecutionError(e.to_string())),
         };
 
+        // Tell the model when a failure looks like the sandbox at work, so it doesn't retry
+        if let Some(explanation) = self
+            .shell_sandbox
+            .as_ref()
+            .filter(|_| !status.success())
+            .and_then(|sandbox| sandbox.explain_failure(&output_str))
+        {
+            output_str.push_str(&format!("\n{}", explanation));
+        }
+
         // Check the character count of the output
         const MAX_CHAR_COUNT: usize = 400_000; // 409600 chars = 400KB
         let char_count = output_str.chars().count();
@@ -695,7 +752,7 @@ impl DeveloperRouter {
             )));
         }
 
-        match command {
+        let mut result = match command {
             "view" => {
                 let view_range = params
                     .get("view_range")
@@ -758,6 +815,33 @@ impl DeveloperRouter {
                 "Unknown command '{}'",
                 command
             ))),
+        }?;
+
+        if command != "view" {
+            if let Some(diagnostics) = self.edit_diagnostics(&path).await {
+                result.push(diagnostics);
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Fresh language server diagnostics for a file that was just edited, when enabled
+    async fn edit_diagnostics(&self, path: &Path) -> Option<Content> {
+        let lsp = self.lsp.as_ref()?;
+        if !lsp.supports(path) {
+            return None;
+        }
+
+        match lsp.diagnostics(path).await {
+            Ok(diagnostics) => Some(
+                Content::text(format!("Diagnostics after this edit:\n{}", diagnostics))
+                    .with_audience(vec![Role::Assistant]),
+            ),
+            Err(e) => {
+                tracing::debug!("Skipping diagnostics for {}: {}", path.display(), e);
+                None
+            }
         }
     }
 
@@ -1500,6 +1584,8 @@ impl Clone for DeveloperRouter {
             file_history: Arc::clone(&self.file_history),
             ignore_patterns: Arc::clone(&self.ignore_patterns),
             editor_model: create_editor_model(), // Recreate the editor model since it's not Clone
+            lsp: self.lsp.clone(),
+            shell_sandbox: self.shell_sandbox.clone(),
         }
     }
 }
@@ -1600,6 +1686,40 @@ mod tests {
         temp_dir.close().unwrap();
     }
 
+    #[tokio::test]
+    #[serial]
+    #[cfg(target_os = "linux")]
+    async fn test_shell_sandbox_explains_refusal() {
+        let root = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let workspace = root.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+
+        let mut router = DeveloperRouter::new();
+        router.shell_sandbox = Some(SandboxProfile {
+            workspace: Some(workspace.clone()),
+            ..Default::default()
+        });
+
+        let outside = root.path().join("outside.txt");
+        let result = router
+            .call_tool(
+                "shell",
+                json!({"command": format!("echo no > {}", outside.display())}),
+                dummy_sender(),
+            )
+            .await;
+        let content = match result {
+            Ok(content) => content,
+            // Kernels without Landlock refuse to run sandboxed commands at all
+            Err(ToolError::ExecutionError(e)) if e.contains("Landlock") => return,
+            Err(e) => panic!("unexpected error: {e}"),
+        };
+
+        assert!(!outside.exists());
+        let text = content[0].as_text().unwrap();
+        assert!(text.contains("This command ran in a sandbox"), "{text}");
+    }
+
     #[tokio::test]
     #[serial]
     #[cfg(windows)]
@@ -1930,6 +2050,8 @@ mod tests {
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
             editor_model: None,
+            lsp: None,
+            shell_sandbox: None,
         };
 
         // Test basic file matching
@@ -1981,6 +2103,8 @@ mod tests {
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
             editor_model: None,
+            lsp: None,
+            shell_sandbox: None,
         };
 
         // Try to write to an ignored file
@@ -2041,6 +2165,8 @@ mod tests {
             file_history: Arc::new(Mutex::new(HashMap::new())),
             ignore_patterns: Arc::new(ignore_patterns),
             editor_model: None,
+            lsp: None,
+            shell_sandbox: None,
         };
 
         // Create an ignored file
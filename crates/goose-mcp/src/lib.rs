@@ -11,12 +11,14 @@ pub mod computercontroller;
 mod developer;
 pub mod google_drive;
 mod jetbrains;
+mod lsp;
 mod memory;
 mod tutorial;
 
 pub use computercontroller::ComputerControllerRouter;
-pub use developer::DeveloperRouter;
+pub use developer::{load_ignore_patterns, DeveloperRouter};
 pub use google_drive::GoogleDriveRouter;
 pub use jetbrains::JetBrainsRouter;
+pub use lsp::LspRouter;
 pub use memory::MemoryRouter;
 pub use tutorial::TutorialRouter;
@@ -0,0 +1,373 @@
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::{oneshot, Mutex, Notify, RwLock};
+use url::Url;
+
+use super::servers::LanguageServer;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+type PendingRequests = Arc<Mutex<HashMap<i64, oneshot::Sender<Result<Value, String>>>>>;
+
+/// Diagnostics most recently published by the server for a single document,
+/// along with a counter that is bumped on every publish so callers can wait
+/// for a fresh set after an edit.
+#[derive(Debug, Clone, Default)]
+struct PublishedDiagnostics {
+    generation: u64,
+    diagnostics: Vec<Value>,
+}
+
+/// A running language server speaking JSON-RPC over stdio.
+pub struct LspClient {
+    name: &'static str,
+    _child: Child,
+    stdin: Arc<Mutex<ChildStdin>>,
+    next_id: AtomicI64,
+    pending: PendingRequests,
+    diagnostics: Arc<RwLock<HashMap<String, PublishedDiagnostics>>>,
+    diagnostics_published: Arc<Notify>,
+    open_documents: Mutex<HashMap<String, i64>>,
+    exited: Arc<AtomicBool>,
+}
+
+impl LspClient {
+    /// Spawn the language server, run the `initialize` handshake and return a ready client.
+    pub async fn start(server: &LanguageServer, root: &Path) -> Result<Self> {
+        let mut child = Command::new(server.command)
+            .args(server.args)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("Failed to start language server '{}'", server.command))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| anyhow!("Language server stdin unavailable"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| anyhow!("Language server stdout unavailable"))?;
+
+        let client = Self {
+            name: server.name,
+            _child: child,
+            stdin: Arc::new(Mutex::new(stdin)),
+            next_id: AtomicI64::new(1),
+            pending: Arc::new(Mutex::new(HashMap::new())),
+            diagnostics: Arc::new(RwLock::new(HashMap::new())),
+            diagnostics_published: Arc::new(Notify::new()),
+            open_documents: Mutex::new(HashMap::new()),
+            exited: Arc::new(AtomicBool::new(false)),
+        };
+
+        tokio::spawn(read_loop(
+            BufReader::new(stdout),
+            Arc::clone(&client.stdin),
+            Arc::clone(&client.pending),
+            Arc::clone(&client.diagnostics),
+            Arc::clone(&client.diagnostics_published),
+            Arc::clone(&client.exited),
+        ));
+
+        let root_uri = file_uri(root)?;
+        client
+            .request(
+                "initialize",
+                json!({
+                    "processId": std::process::id(),
+                    "rootUri": root_uri,
+                    "workspaceFolders": [{
+                        "uri": root_uri,
+                        "name": root.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default(),
+                    }],
+                    "capabilities": {
+                        "textDocument": {
+                            "synchronization": { "didSave": true },
+                            "publishDiagnostics": { "relatedInformation": false },
+                            "hover": { "contentFormat": ["plaintext", "markdown"] },
+                            "definition": { "linkSupport": false },
+                            "references": {},
+                            "rename": { "prepareSupport": false },
+                        },
+                        "workspace": {
+                            "workspaceEdit": { "documentChanges": true },
+                            "workspaceFolders": true,
+                            "configuration": true,
+                        },
+                    },
+                }),
+            )
+            .await?;
+        client.notify("initialized", json!({})).await?;
+
+        Ok(client)
+    }
+
+    /// Whether the server is still running, which it isn't once its output has closed
+    pub fn is_running(&self) -> bool {
+        !self.exited.load(Ordering::SeqCst)
+    }
+
+    /// Send a request and wait for its response.
+    pub async fn request(&self, method: &str, params: Value) -> Result<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().await.insert(id, tx);
+
+        let message = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        if let Err(e) = write_message(&mut *self.stdin.lock().await, &message).await {
+            self.pending.lock().await.remove(&id);
+            return Err(e);
+        }
+
+        match tokio::time::timeout(REQUEST_TIMEOUT, rx).await {
+            Ok(Ok(Ok(result))) => Ok(result),
+            Ok(Ok(Err(error))) => Err(anyhow!("{} returned an error: {}", self.name, error)),
+            Ok(Err(_)) => Err(anyhow!("{} exited before responding", self.name)),
+            Err(_) => {
+                self.pending.lock().await.remove(&id);
+                Err(anyhow!("{} timed out waiting for '{}'", self.name, method))
+            }
+        }
+    }
+
+    /// Send a notification, which has no response.
+    pub async fn notify(&self, method: &str, params: Value) -> Result<()> {
+        let message = json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+        });
+        write_message(&mut *self.stdin.lock().await, &message).await
+    }
+
+    /// Make sure the server sees the current on-disk contents of `path`, opening it
+    /// on first use and sending a full-text change (followed by a save) afterwards.
+    pub async fn sync_document(&self, path: &Path, language_id: &str) -> Result<String> {
+        let uri = file_uri(path)?;
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let mut open_documents = self.open_documents.lock().await;
+        match open_documents.get_mut(&uri) {
+            None => {
+                open_documents.insert(uri.clone(), 1);
+                self.notify(
+                    "textDocument/didOpen",
+                    json!({
+                        "textDocument": {
+                            "uri": uri,
+                            "languageId": language_id,
+                            "version": 1,
+                            "text": text,
+                        }
+                    }),
+                )
+                .await?;
+            }
+            Some(version) => {
+                *version += 1;
+                self.notify(
+                    "textDocument/didChange",
+                    json!({
+                        "textDocument": { "uri": uri, "version": *version },
+                        "contentChanges": [{ "text": text }],
+                    }),
+                )
+                .await?;
+                self.notify(
+                    "textDocument/didSave",
+                    json!({ "textDocument": { "uri": uri } }),
+                )
+                .await?;
+            }
+        }
+
+        Ok(uri)
+    }
+
+    /// Sync the document and wait up to `wait` for the server to publish diagnostics for it.
+    ///
+    /// If nothing new arrives in time the last known diagnostics are returned, which
+    /// may be empty for servers that only report on save or on a full build.
+    pub async fn diagnostics(
+        &self,
+        path: &Path,
+        language_id: &str,
+        wait: Duration,
+    ) -> Result<Vec<Value>> {
+        let uri = file_uri(path)?;
+        let before = self.generation(&uri).await;
+        self.sync_document(path, language_id).await?;
+
+        let deadline = tokio::time::Instant::now() + wait;
+        loop {
+            let published = self.diagnostics_published.notified();
+            if self.generation(&uri).await > before {
+                break;
+            }
+            if tokio::time::timeout_at(deadline, published).await.is_err() {
+                break;
+            }
+        }
+
+        Ok(self
+            .diagnostics
+            .read()
+            .await
+            .get(&uri)
+            .map(|d| d.diagnostics.clone())
+            .unwrap_or_default())
+    }
+
+    async fn generation(&self, uri: &str) -> u64 {
+        self.diagnostics
+            .read()
+            .await
+            .get(uri)
+            .map(|d| d.generation)
+            .unwrap_or(0)
+    }
+}
+
+pub fn file_uri(path: &Path) -> Result<String> {
+    Url::from_file_path(path)
+        .map(|url| url.to_string())
+        .map_err(|_| anyhow!("Cannot convert {} to a file URI", path.display()))
+}
+
+async fn read_loop<R: AsyncRead + Unpin>(
+    mut reader: BufReader<R>,
+    stdin: Arc<Mutex<ChildStdin>>,
+    pending: PendingRequests,
+    diagnostics: Arc<RwLock<HashMap<String, PublishedDiagnostics>>>,
+    diagnostics_published: Arc<Notify>,
+    exited: Arc<AtomicBool>,
+) {
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                tracing::warn!("Failed to read language server message: {}", e);
+                break;
+            }
+        };
+
+        let method = message.get("method").and_then(|m| m.as_str());
+        let id = message.get("id").cloned();
+
+        match (method, id) {
+            // Response to one of our requests
+            (None, Some(id)) => {
+                let Some(id) = id.as_i64() else { continue };
+                if let Some(tx) = pending.lock().await.remove(&id) {
+                    let result = match message.get("error") {
+                        Some(error) => Err(error
+                            .get("message")
+                            .and_then(|m| m.as_str())
+                            .unwrap_or("unknown error")
+                            .to_string()),
+                        None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                    };
+                    let _ = tx.send(result);
+                }
+            }
+            // Request from the server, e.g. workspace/configuration or progress registration.
+            // We don't support any of these beyond acknowledging them.
+            (Some(method), Some(id)) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"]
+                            .as_array()
+                            .map(|items| items.len())
+                            .unwrap_or(0);
+                        Value::Array(vec![Value::Null; items])
+                    }
+                    _ => Value::Null,
+                };
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                if let Err(e) = write_message(&mut *stdin.lock().await, &response).await {
+                    tracing::warn!("Failed to respond to language server request: {}", e);
+                }
+            }
+            (Some("textDocument/publishDiagnostics"), None) => {
+                let params = &message["params"];
+                if let Some(uri) = params.get("uri").and_then(|u| u.as_str()) {
+                    let items = params
+                        .get("diagnostics")
+                        .and_then(|d| d.as_array())
+                        .cloned()
+                        .unwrap_or_default();
+                    let mut diagnostics = diagnostics.write().await;
+                    let entry = diagnostics.entry(uri.to_string()).or_default();
+                    entry.generation += 1;
+                    entry.diagnostics = items;
+                }
+                diagnostics_published.notify_waiters();
+            }
+            _ => {}
+        }
+    }
+
+    // Fail any requests still waiting so callers don't hang on a dead server
+    exited.store(true, Ordering::SeqCst);
+    pending.lock().await.clear();
+}
+
+/// Write a single message using the `Content-Length` framing from the LSP base protocol.
+pub async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Value) -> Result<()> {
+    let body = serde_json::to_vec(message)?;
+    writer
+        .write_all(format!("Content-Length: {}\r\n\r\n", body.len()).as_bytes())
+        .await?;
+    writer.write_all(&body).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Read a single framed message, returning `None` on a clean end of stream.
+pub async fn read_message<R: AsyncRead + Unpin>(
+    reader: &mut BufReader<R>,
+) -> Result<Option<Value>> {
+    let mut content_length: Option<usize> = None;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = Some(value.trim().parse()?);
+            }
+        }
+    }
+
+    let length = content_length.ok_or_else(|| anyhow!("Missing Content-Length header"))?;
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(serde_json::from_slice(&body)?))
+}
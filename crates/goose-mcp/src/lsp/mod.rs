@@ -0,0 +1,961 @@
+mod client;
+mod servers;
+
+use indoc::formatdoc;
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{mpsc, Mutex};
+use url::Url;
+
+use ignore::gitignore::Gitignore;
+use mcp_core::{
+    handler::{PromptError, ResourceError, ToolError},
+    prompt::Prompt,
+    protocol::{JsonRpcMessage, ServerCapabilities},
+    resource::Resource,
+    role::Role,
+    tool::{Tool, ToolAnnotations},
+    Content,
+};
+use mcp_server::router::CapabilitiesBuilder;
+use mcp_server::Router;
+
+use self::client::LspClient;
+use self::servers::{
+    detect_workspace_servers, find_project_root, language_id, server_for_path, LANGUAGE_SERVERS,
+};
+use crate::developer::load_ignore_patterns;
+
+/// How long to wait for a server to publish diagnostics after a file changes
+const DIAGNOSTICS_WAIT: Duration = Duration::from_secs(10);
+const MAX_LOCATIONS: usize = 100;
+
+/// Starts language servers on demand and keeps one running per (server, project root).
+pub struct LspManager {
+    workspace: PathBuf,
+    ignore_patterns: Gitignore,
+    clients: Mutex<HashMap<(&'static str, PathBuf), Arc<LspClient>>>,
+}
+
+impl LspManager {
+    pub fn new(workspace: PathBuf) -> Self {
+        Self {
+            ignore_patterns: load_ignore_patterns(&workspace),
+            workspace,
+            clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create a manager rooted at `GOOSE_WORKING_DIR`, or the current directory if unset
+    pub fn from_env() -> Self {
+        let workspace = std::env::var("GOOSE_WORKING_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| {
+                std::env::current_dir().expect("should have a current working dir")
+            });
+        Self::new(workspace)
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    pub fn is_ignored(&self, path: &Path) -> bool {
+        self.ignore_patterns.matched(path, false).is_ignore()
+    }
+
+    pub fn supports(&self, path: &Path) -> bool {
+        server_for_path(path).is_some_and(|server| which::which(server.command).is_ok())
+    }
+
+    async fn client_for(&self, path: &Path) -> Result<Arc<LspClient>, ToolError> {
+        let server = server_for_path(path).ok_or_else(|| {
+            ToolError::ExecutionError(format!(
+                "No language server is configured for {}",
+                path.display()
+            ))
+        })?;
+        if which::which(server.command).is_err() {
+            return Err(ToolError::ExecutionError(format!(
+                "The {} language server is not installed ('{}' was not found on the PATH)",
+                server.name, server.command
+            )));
+        }
+
+        let root = find_project_root(server, path, &self.workspace);
+        let mut clients = self.clients.lock().await;
+        // Forget servers that have exited, so they're started again when next needed
+        clients.retain(|(name, root), client| {
+            if !client.is_running() {
+                tracing::info!("{} for {} has exited", name, root.display());
+            }
+            client.is_running()
+        });
+        if let Some(client) = clients.get(&(server.name, root.clone())) {
+            return Ok(Arc::clone(client));
+        }
+
+        tracing::info!("Starting {} for {}", server.name, root.display());
+        let client = Arc::new(LspClient::start(server, &root).await.map_err(|e| {
+            ToolError::ExecutionError(format!("Failed to start {}: {}", server.name, e))
+        })?);
+        clients.insert((server.name, root), Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Fresh diagnostics for `path`, rendered one per line
+    pub async fn diagnostics(&self, path: &Path) -> Result<String, ToolError> {
+        let client = self.client_for(path).await?;
+        let diagnostics = client
+            .diagnostics(path, language_id(path), DIAGNOSTICS_WAIT)
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        Ok(format_diagnostics(path, &diagnostics))
+    }
+
+    async fn position_request(
+        &self,
+        method: &str,
+        path: &Path,
+        line: usize,
+        column: usize,
+        extra: Value,
+    ) -> Result<Value, ToolError> {
+        let client = self.client_for(path).await?;
+        let uri = client
+            .sync_document(path, language_id(path))
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))?;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| ToolError::ExecutionError(format!("Failed to read file: {}", e)))?;
+
+        let mut params = json!({
+            "textDocument": { "uri": uri },
+            "position": lsp_position(&text, line, column)?,
+        });
+        if let (Some(params), Value::Object(extra)) = (params.as_object_mut(), extra) {
+            params.extend(extra);
+        }
+
+        client
+            .request(method, params)
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))
+    }
+
+    pub async fn definition(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Result<String, ToolError> {
+        let result = self
+            .position_request("textDocument/definition", path, line, column, json!({}))
+            .await?;
+        Ok(format_locations(&result, "No definition found"))
+    }
+
+    pub async fn references(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Result<String, ToolError> {
+        let result = self
+            .position_request(
+                "textDocument/references",
+                path,
+                line,
+                column,
+                json!({ "context": { "includeDeclaration": true } }),
+            )
+            .await?;
+        Ok(format_locations(&result, "No references found"))
+    }
+
+    pub async fn hover(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+    ) -> Result<String, ToolError> {
+        let result = self
+            .position_request("textDocument/hover", path, line, column, json!({}))
+            .await?;
+        let text = hover_text(&result["contents"]);
+        Ok(if text.trim().is_empty() {
+            "No hover information available".to_string()
+        } else {
+            text
+        })
+    }
+
+    /// Rename the symbol at the position and apply the resulting edits to disk
+    pub async fn rename(
+        &self,
+        path: &Path,
+        line: usize,
+        column: usize,
+        new_name: &str,
+    ) -> Result<String, ToolError> {
+        if self.is_ignored(path) {
+            return Err(ToolError::ExecutionError(format!(
+                "Access to '{}' is restricted by .gooseignore",
+                path.display()
+            )));
+        }
+        let result = self
+            .position_request(
+                "textDocument/rename",
+                path,
+                line,
+                column,
+                json!({ "newName": new_name }),
+            )
+            .await?;
+
+        let edits = collect_workspace_edits(&result);
+        if edits.is_empty() {
+            return Ok("The language server returned no edits for this rename".to_string());
+        }
+        // Leave every file alone if the rename would touch any that goose may not access
+        if let Some((ignored, _)) = edits.iter().find(|(file, _)| self.is_ignored(file)) {
+            return Err(ToolError::ExecutionError(format!(
+                "The rename would edit '{}', which is restricted by .gooseignore",
+                ignored.display()
+            )));
+        }
+
+        let client = self.client_for(path).await?;
+        let mut summary = Vec::new();
+        for (file, file_edits) in &edits {
+            let text = std::fs::read_to_string(file).map_err(|e| {
+                ToolError::ExecutionError(format!("Failed to read {}: {}", file.display(), e))
+            })?;
+            let updated = apply_text_edits(&text, file_edits)?;
+            std::fs::write(file, updated).map_err(|e| {
+                ToolError::ExecutionError(format!("Failed to write {}: {}", file.display(), e))
+            })?;
+            // Keep the server's view in sync with what we just wrote
+            let _ = client.sync_document(file, language_id(file)).await;
+            summary.push(format!("{} ({} edits)", file.display(), file_edits.len()));
+        }
+
+        Ok(format!(
+            "Renamed to '{}' in {} files:\n{}",
+            new_name,
+            edits.len(),
+            summary.join("\n")
+        ))
+    }
+}
+
+/// Convert a 1-based line and column (in characters) into a zero-based LSP position,
+/// which counts UTF-16 code units.
+fn lsp_position(text: &str, line: usize, column: usize) -> Result<Value, ToolError> {
+    if line == 0 || column == 0 {
+        return Err(ToolError::InvalidParameters(
+            "'line' and 'column' are 1-indexed".to_string(),
+        ));
+    }
+    let line_text = text.lines().nth(line - 1).ok_or_else(|| {
+        ToolError::InvalidParameters(format!("Line {} is beyond the end of the file", line))
+    })?;
+    let character: usize = line_text
+        .chars()
+        .take(column - 1)
+        .map(char::len_utf16)
+        .sum();
+    Ok(json!({ "line": line - 1, "character": character }))
+}
+
+/// Byte offset into `text` for a zero-based LSP line and UTF-16 character
+fn byte_offset(text: &str, line: usize, character: usize) -> usize {
+    let mut offset = 0;
+    for (index, line_text) in text.split_inclusive('\n').enumerate() {
+        if index == line {
+            let mut units = 0;
+            for (byte, ch) in line_text.char_indices() {
+                if units >= character || ch == '\n' || ch == '\r' {
+                    return offset + byte;
+                }
+                units += ch.len_utf16();
+            }
+            return offset + line_text.trim_end_matches(['\r', '\n']).len();
+        }
+        offset += line_text.len();
+    }
+    text.len()
+}
+
+/// One-based character column for a zero-based UTF-16 character offset on a line
+fn display_column(line_text: &str, character: usize) -> usize {
+    let mut units = 0;
+    let mut column = 1;
+    for ch in line_text.chars() {
+        if units >= character {
+            break;
+        }
+        units += ch.len_utf16();
+        column += 1;
+    }
+    column
+}
+
+fn range_start(range: &Value) -> (usize, usize) {
+    let start = &range["start"];
+    (
+        start["line"].as_u64().unwrap_or(0) as usize,
+        start["character"].as_u64().unwrap_or(0) as usize,
+    )
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    Url::parse(uri).ok()?.to_file_path().ok()
+}
+
+fn format_diagnostics(path: &Path, diagnostics: &[Value]) -> String {
+    if diagnostics.is_empty() {
+        return format!("No diagnostics reported for {}", path.display());
+    }
+
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let lines: Vec<&str> = text.lines().collect();
+
+    let mut output = Vec::new();
+    for diagnostic in diagnostics {
+        let (line, character) = range_start(&diagnostic["range"]);
+        let column = lines
+            .get(line)
+            .map(|l| display_column(l, character))
+            .unwrap_or(character + 1);
+        let severity = match diagnostic["severity"].as_u64() {
+            Some(1) => "error",
+            Some(2) => "warning",
+            Some(3) => "info",
+            Some(4) => "hint",
+            _ => "diagnostic",
+        };
+        let message = diagnostic["message"].as_str().unwrap_or_default();
+        let source = diagnostic["source"]
+            .as_str()
+            .map(|s| format!(" [{}]", s))
+            .unwrap_or_default();
+        output.push(format!(
+            "{}:{}:{}: {}: {}{}",
+            path.display(),
+            line + 1,
+            column,
+            severity,
+            message,
+            source
+        ));
+    }
+    output.join("\n")
+}
+
+/// Render a `Location`, `Location[]` or `LocationLink[]` result as `path:line:column` lines
+/// with the source line for context.
+fn format_locations(result: &Value, empty_message: &str) -> String {
+    let locations: Vec<&Value> = match result {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![result],
+        _ => Vec::new(),
+    };
+    if locations.is_empty() {
+        return empty_message.to_string();
+    }
+
+    let mut file_cache: HashMap<PathBuf, Vec<String>> = HashMap::new();
+    let mut output = Vec::new();
+    for location in locations.iter().take(MAX_LOCATIONS) {
+        let uri = location["uri"]
+            .as_str()
+            .or_else(|| location["targetUri"].as_str())
+            .unwrap_or_default();
+        let range = if location.get("range").is_some() {
+            &location["range"]
+        } else {
+            &location["targetSelectionRange"]
+        };
+        let (line, character) = range_start(range);
+
+        let Some(path) = uri_to_path(uri) else {
+            output.push(format!("{}:{}:{}", uri, line + 1, character + 1));
+            continue;
+        };
+        let lines = file_cache.entry(path.clone()).or_insert_with(|| {
+            std::fs::read_to_string(&path)
+                .map(|text| text.lines().map(String::from).collect())
+                .unwrap_or_default()
+        });
+        let source_line = lines.get(line).map(String::as_str).unwrap_or_default();
+        output.push(format!(
+            "{}:{}:{}: {}",
+            path.display(),
+            line + 1,
+            display_column(source_line, character),
+            source_line.trim()
+        ));
+    }
+    if locations.len() > MAX_LOCATIONS {
+        output.push(format!("... and {} more", locations.len() - MAX_LOCATIONS));
+    }
+    output.join("\n")
+}
+
+/// Flatten the various shapes of `Hover.contents` into plain text
+fn hover_text(contents: &Value) -> String {
+    match contents {
+        Value::String(s) => s.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(hover_text)
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(object) => match (object.get("language"), object.get("value")) {
+            (Some(language), Some(value)) => format!(
+                "```{}\n{}\n```",
+                language.as_str().unwrap_or_default(),
+                value.as_str().unwrap_or_default()
+            ),
+            (None, Some(value)) => value.as_str().unwrap_or_default().to_string(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Group the text edits in a `WorkspaceEdit` by file, supporting both `changes`
+/// and `documentChanges`.
+fn collect_workspace_edits(edit: &Value) -> Vec<(PathBuf, Vec<Value>)> {
+    let mut by_file: Vec<(PathBuf, Vec<Value>)> = Vec::new();
+    let mut push = |uri: &str, edits: &[Value]| {
+        if let Some(path) = uri_to_path(uri) {
+            match by_file.iter_mut().find(|(p, _)| *p == path) {
+                Some((_, existing)) => existing.extend_from_slice(edits),
+                None => by_file.push((path, edits.to_vec())),
+            }
+        }
+    };
+
+    if let Some(changes) = edit["changes"].as_object() {
+        for (uri, edits) in changes {
+            push(uri, edits.as_array().map(Vec::as_slice).unwrap_or_default());
+        }
+    }
+    if let Some(document_changes) = edit["documentChanges"].as_array() {
+        for change in document_changes {
+            if let Some(uri) = change["textDocument"]["uri"].as_str() {
+                push(
+                    uri,
+                    change["edits"]
+                        .as_array()
+                        .map(Vec::as_slice)
+                        .unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    by_file
+}
+
+/// Apply LSP `TextEdit`s to a document. Edits are applied back to front so that
+/// earlier offsets stay valid.
+fn apply_text_edits(text: &str, edits: &[Value]) -> Result<String, ToolError> {
+    let mut ranges = Vec::with_capacity(edits.len());
+    for edit in edits {
+        let (start_line, start_character) = range_start(&edit["range"]);
+        let end = &edit["range"]["end"];
+        let end_line = end["line"].as_u64().unwrap_or(0) as usize;
+        let end_character = end["character"].as_u64().unwrap_or(0) as usize;
+        let start = byte_offset(text, start_line, start_character);
+        let end = byte_offset(text, end_line, end_character);
+        if end < start {
+            return Err(ToolError::ExecutionError(
+                "Language server returned an invalid edit range".to_string(),
+            ));
+        }
+        ranges.push((start, end, edit["newText"].as_str().unwrap_or_default()));
+    }
+    ranges.sort_by_key(|range| std::cmp::Reverse(range.0));
+
+    let mut updated = text.to_string();
+    for (start, end, new_text) in ranges {
+        updated.replace_range(start..end, new_text);
+    }
+    Ok(updated)
+}
+
+pub struct LspRouter {
+    tools: Vec<Tool>,
+    instructions: String,
+    manager: Arc<LspManager>,
+}
+
+impl Default for LspRouter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LspRouter {
+    pub fn new() -> Self {
+        let position_properties = json!({
+            "path": {
+                "type": "string",
+                "description": "Absolute path to the source file"
+            },
+            "line": {
+                "type": "integer",
+                "description": "1-indexed line number of the symbol"
+            },
+            "column": {
+                "type": "integer",
+                "description": "1-indexed column of any character within the symbol"
+            }
+        });
+
+        let diagnostics = Tool::new(
+            "diagnostics",
+            "Get compiler and linter diagnostics (errors, warnings) for a file from its language server. Use this after editing to check that the code is still valid without running a full build.",
+            json!({
+                "type": "object",
+                "required": ["path"],
+                "properties": {
+                    "path": {
+                        "type": "string",
+                        "description": "Absolute path to the source file"
+                    }
+                }
+            }),
+            Some(ToolAnnotations {
+                title: Some("Get diagnostics".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
+        let definition = Tool::new(
+            "definition",
+            "Find where the symbol at the given position is defined.",
+            json!({
+                "type": "object",
+                "required": ["path", "line", "column"],
+                "properties": position_properties.clone()
+            }),
+            Some(ToolAnnotations {
+                title: Some("Go to definition".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
+        let references = Tool::new(
+            "references",
+            "Find all references to the symbol at the given position, including its declaration.",
+            json!({
+                "type": "object",
+                "required": ["path", "line", "column"],
+                "properties": position_properties.clone()
+            }),
+            Some(ToolAnnotations {
+                title: Some("Find references".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
+        let hover = Tool::new(
+            "hover",
+            "Show the type signature and documentation of the symbol at the given position.",
+            json!({
+                "type": "object",
+                "required": ["path", "line", "column"],
+                "properties": position_properties.clone()
+            }),
+            Some(ToolAnnotations {
+                title: Some("Hover".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
+        let mut rename_properties = position_properties;
+        rename_properties["new_name"] = json!({
+            "type": "string",
+            "description": "The new name for the symbol"
+        });
+        let rename = Tool::new(
+            "rename",
+            "Rename the symbol at the given position across the project. The edits are applied to the files on disk.",
+            json!({
+                "type": "object",
+                "required": ["path", "line", "column", "new_name"],
+                "properties": rename_properties
+            }),
+            Some(ToolAnnotations {
+                title: Some("Rename symbol".to_string()),
+                read_only_hint: false,
+                destructive_hint: true,
+                idempotent_hint: false,
+                open_world_hint: false,
+            }),
+        );
+
+        let manager = Arc::new(LspManager::from_env());
+
+        let detected = detect_workspace_servers(manager.workspace());
+        let supported = LANGUAGE_SERVERS
+            .iter()
+            .map(|server| format!("{} ({})", server.name, server.extensions.join(", ")))
+            .collect::<Vec<_>>()
+            .join("\n- ");
+        let detected = if detected.is_empty() {
+            "None of the supported language servers were detected for this workspace. Tools will still start a server for a file if its binary is on the PATH.".to_string()
+        } else {
+            format!(
+                "Detected for this workspace: {}",
+                detected
+                    .iter()
+                    .map(|server| server.name)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        };
+
+        let instructions = formatdoc! {r#"
+            The lsp extension gives you access to language servers for code intelligence.
+            Servers are started on demand the first time a file of their language is used.
+
+            Supported servers:
+            - {supported}
+
+            {detected}
+
+            Prefer these tools over grep and full builds when you need to:
+            - check whether a file you edited still compiles (diagnostics)
+            - jump to a symbol's definition or list its usages (definition, references)
+            - read a symbol's type or documentation (hover)
+            - rename a symbol safely across files (rename)
+
+            Lines and columns are 1-indexed. Paths must be absolute.
+            "#,
+            supported = supported,
+            detected = detected,
+        };
+
+        Self {
+            tools: vec![diagnostics, definition, references, hover, rename],
+            instructions,
+            manager,
+        }
+    }
+
+    fn resolve_path(params: &Value) -> Result<PathBuf, ToolError> {
+        let path = params
+            .get("path")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'path' parameter".into()))?;
+        let path = PathBuf::from(shellexpand::tilde(path).into_owned());
+        if !path.is_absolute() {
+            return Err(ToolError::InvalidParameters(format!(
+                "The path {} is not an absolute path",
+                path.display()
+            )));
+        }
+        if !path.is_file() {
+            return Err(ToolError::InvalidParameters(format!(
+                "The path {} does not exist or is not a file",
+                path.display()
+            )));
+        }
+        Ok(path)
+    }
+
+    fn position(params: &Value) -> Result<(usize, usize), ToolError> {
+        let line = params
+            .get("line")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'line' parameter".to_string()))?;
+        let column = params
+            .get("column")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| {
+                ToolError::InvalidParameters("Missing 'column' parameter".to_string())
+            })?;
+        Ok((line as usize, column as usize))
+    }
+
+    async fn dispatch(&self, tool_name: &str, params: Value) -> Result<String, ToolError> {
+        let path = Self::resolve_path(&params)?;
+        match tool_name {
+            "diagnostics" => self.manager.diagnostics(&path).await,
+            "definition" => {
+                let (line, column) = Self::position(&params)?;
+                self.manager.definition(&path, line, column).await
+            }
+            "references" => {
+                let (line, column) = Self::position(&params)?;
+                self.manager.references(&path, line, column).await
+            }
+            "hover" => {
+                let (line, column) = Self::position(&params)?;
+                self.manager.hover(&path, line, column).await
+            }
+            "rename" => {
+                let (line, column) = Self::position(&params)?;
+                let new_name = params
+                    .get("new_name")
+                    .and_then(|v| v.as_str())
+                    .filter(|s| !s.is_empty())
+                    .ok_or_else(|| {
+                        ToolError::InvalidParameters("Missing 'new_name' parameter".to_string())
+                    })?;
+                self.manager.rename(&path, line, column, new_name).await
+            }
+            _ => Err(ToolError::NotFound(format!("Tool {} not found", tool_name))),
+        }
+    }
+}
+
+impl Router for LspRouter {
+    fn name(&self) -> String {
+        "lsp".to_string()
+    }
+
+    fn instructions(&self) -> String {
+        self.instructions.clone()
+    }
+
+    fn capabilities(&self) -> ServerCapabilities {
+        CapabilitiesBuilder::new().with_tools(false).build()
+    }
+
+    fn list_tools(&self) -> Vec<Tool> {
+        self.tools.clone()
+    }
+
+    fn call_tool(
+        &self,
+        tool_name: &str,
+        arguments: Value,
+        _notifier: mpsc::Sender<JsonRpcMessage>,
+    ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>> {
+        let this = self.clone();
+        let tool_name = tool_name.to_string();
+
+        Box::pin(async move {
+            let output = this.dispatch(&tool_name, arguments).await?;
+            Ok(vec![
+                Content::text(output).with_audience(vec![Role::Assistant])
+            ])
+        })
+    }
+
+    fn list_resources(&self) -> Vec<Resource> {
+        Vec::new()
+    }
+
+    fn read_resource(
+        &self,
+        _uri: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
+        Box::pin(async move { Ok("".to_string()) })
+    }
+
+    fn list_prompts(&self) -> Vec<Prompt> {
+        vec![]
+    }
+
+    fn get_prompt(
+        &self,
+        prompt_name: &str,
+    ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
+        let prompt_name = prompt_name.to_string();
+        Box::pin(async move {
+            Err(PromptError::NotFound(format!(
+                "Prompt {} not found",
+                prompt_name
+            )))
+        })
+    }
+}
+
+impl Clone for LspRouter {
+    fn clone(&self) -> Self {
+        Self {
+            tools: self.tools.clone(),
+            instructions: self.instructions.clone(),
+            manager: Arc::clone(&self.manager),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::client::{read_message, write_message};
+    use super::*;
+    use tokio::io::BufReader;
+
+    #[tokio::test]
+    async fn test_message_framing_roundtrip() {
+        let message =
+            json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"é": "ü"}});
+        let mut buffer = Vec::new();
+        write_message(&mut buffer, &message).await.unwrap();
+
+        let header = String::from_utf8_lossy(&buffer);
+        assert!(header.starts_with("Content-Length: "));
+
+        let mut reader = BufReader::new(buffer.as_slice());
+        let decoded = read_message(&mut reader).await.unwrap().unwrap();
+        assert_eq!(decoded, message);
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_server_for_path() {
+        assert_eq!(
+            server_for_path(Path::new("/tmp/src/main.rs")).map(|s| s.name),
+            Some("rust-analyzer")
+        );
+        assert_eq!(
+            server_for_path(Path::new("/tmp/app.tsx")).map(|s| s.name),
+            Some("typescript-language-server")
+        );
+        assert_eq!(
+            server_for_path(Path::new("/tmp/main.go")).map(|s| s.name),
+            Some("gopls")
+        );
+        assert!(server_for_path(Path::new("/tmp/README.md")).is_none());
+        assert_eq!(language_id(Path::new("/tmp/app.tsx")), "typescriptreact");
+    }
+
+    #[test]
+    fn test_find_project_root_prefers_outermost_marker() {
+        let workspace = tempfile::tempdir().unwrap();
+        let member = workspace.path().join("crates").join("member");
+        std::fs::create_dir_all(member.join("src")).unwrap();
+        std::fs::write(workspace.path().join("Cargo.toml"), "").unwrap();
+        std::fs::write(member.join("Cargo.toml"), "").unwrap();
+
+        let rust = server_for_path(Path::new("lib.rs")).unwrap();
+        let root = find_project_root(rust, &member.join("src").join("lib.rs"), workspace.path());
+        assert_eq!(root, workspace.path());
+    }
+
+    #[test]
+    fn test_find_project_root_stays_in_workspace() {
+        let outer = tempfile::tempdir().unwrap();
+        let workspace = outer.path().join("project");
+        std::fs::create_dir_all(workspace.join("src")).unwrap();
+        std::fs::write(outer.path().join("Cargo.toml"), "").unwrap();
+
+        let rust = server_for_path(Path::new("lib.rs")).unwrap();
+        let root = find_project_root(rust, &workspace.join("src").join("lib.rs"), &workspace);
+        assert_eq!(root, workspace);
+    }
+
+    #[tokio::test]
+    async fn test_rename_respects_ignore_patterns() {
+        let workspace = tempfile::tempdir().unwrap();
+        std::fs::write(workspace.path().join(".gooseignore"), "secret.rs\n").unwrap();
+        let secret = workspace.path().join("secret.rs");
+        std::fs::write(&secret, "fn main() {}\n").unwrap();
+
+        let manager = LspManager::new(workspace.path().to_path_buf());
+        assert!(manager.is_ignored(&secret));
+        let err = manager.rename(&secret, 1, 4, "start").await.unwrap_err();
+        assert!(err.to_string().contains(".gooseignore"));
+        assert_eq!(std::fs::read_to_string(&secret).unwrap(), "fn main() {}\n");
+    }
+
+    #[test]
+    fn test_lsp_position_counts_utf16() {
+        let text = "fn main() {}\nlet 😀x = 1;\n";
+        assert_eq!(
+            lsp_position(text, 2, 6).unwrap(),
+            json!({"line": 1, "character": 6})
+        );
+        assert!(lsp_position(text, 0, 1).is_err());
+        assert!(lsp_position(text, 10, 1).is_err());
+    }
+
+    #[test]
+    fn test_apply_text_edits() {
+        let text = "let foo = 1;\nprintln!(\"{}\", foo);\n";
+        let edits = vec![
+            json!({"range": {"start": {"line": 0, "character": 4}, "end": {"line": 0, "character": 7}}, "newText": "bar"}),
+            json!({"range": {"start": {"line": 1, "character": 15}, "end": {"line": 1, "character": 18}}, "newText": "bar"}),
+        ];
+        assert_eq!(
+            apply_text_edits(text, &edits).unwrap(),
+            "let bar = 1;\nprintln!(\"{}\", bar);\n"
+        );
+    }
+
+    #[test]
+    fn test_collect_workspace_edits() {
+        let edit = json!({
+            "changes": {
+                "file:///tmp/a.rs": [{"range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 1}}, "newText": "x"}]
+            },
+            "documentChanges": [{
+                "textDocument": {"uri": "file:///tmp/b.rs", "version": 1},
+                "edits": [{"range": {"start": {"line": 0, "character": 0}, "end": {"line": 0, "character": 1}}, "newText": "y"}]
+            }]
+        });
+        let edits = collect_workspace_edits(&edit);
+        assert_eq!(edits.len(), 2);
+        assert!(edits.iter().any(|(p, _)| p == Path::new("/tmp/a.rs")));
+        assert!(edits.iter().any(|(p, _)| p == Path::new("/tmp/b.rs")));
+    }
+
+    #[test]
+    fn test_hover_text_shapes() {
+        assert_eq!(
+            hover_text(&json!({"kind": "markdown", "value": "docs"})),
+            "docs"
+        );
+        assert_eq!(
+            hover_text(&json!([{"language": "rust", "value": "fn f()"}, "more"])),
+            "```rust\nfn f()\n```\n\nmore"
+        );
+    }
+
+    #[test]
+    fn test_format_diagnostics() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        std::fs::write(&path, "fn main() {\n    let x: u32 = \"a\";\n}\n").unwrap();
+        let diagnostics = vec![json!({
+            "range": {"start": {"line": 1, "character": 17}, "end": {"line": 1, "character": 20}},
+            "severity": 1,
+            "message": "mismatched types",
+            "source": "rustc"
+        })];
+        assert_eq!(
+            format_diagnostics(&path, &diagnostics),
+            format!("{}:2:18: error: mismatched types [rustc]", path.display())
+        );
+        assert!(format_diagnostics(&path, &[]).starts_with("No diagnostics"));
+    }
+}
@@ -0,0 +1,115 @@
+use std::path::{Path, PathBuf};
+
+/// A language server goose knows how to launch over stdio.
+#[derive(Debug)]
+pub struct LanguageServer {
+    pub name: &'static str,
+    pub command: &'static str,
+    pub args: &'static [&'static str],
+    /// File extensions handled by this server
+    pub extensions: &'static [&'static str],
+    /// Files whose presence marks the root of a project for this server
+    pub root_markers: &'static [&'static str],
+}
+
+pub const LANGUAGE_SERVERS: &[LanguageServer] = &[
+    LanguageServer {
+        name: "rust-analyzer",
+        command: "rust-analyzer",
+        args: &[],
+        extensions: &["rs"],
+        root_markers: &["Cargo.toml"],
+    },
+    LanguageServer {
+        name: "pyright",
+        command: "pyright-langserver",
+        args: &["--stdio"],
+        extensions: &["py", "pyi"],
+        root_markers: &[
+            "pyproject.toml",
+            "setup.py",
+            "setup.cfg",
+            "requirements.txt",
+            "pyrightconfig.json",
+        ],
+    },
+    LanguageServer {
+        name: "typescript-language-server",
+        command: "typescript-language-server",
+        args: &["--stdio"],
+        extensions: &["ts", "tsx", "js", "jsx", "mjs", "cjs"],
+        root_markers: &["tsconfig.json", "jsconfig.json", "package.json"],
+    },
+    LanguageServer {
+        name: "gopls",
+        command: "gopls",
+        args: &[],
+        extensions: &["go"],
+        root_markers: &["go.mod", "go.work"],
+    },
+];
+
+/// Find the language server responsible for a file based on its extension
+pub fn server_for_path(path: &Path) -> Option<&'static LanguageServer> {
+    let extension = path.extension()?.to_str()?;
+    LANGUAGE_SERVERS
+        .iter()
+        .find(|server| server.extensions.contains(&extension))
+}
+
+/// The LSP `languageId` for a file, falling back to the extension itself
+pub fn language_id(path: &Path) -> &str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("rs") => "rust",
+        Some("py") | Some("pyi") => "python",
+        Some("ts") => "typescript",
+        Some("tsx") => "typescriptreact",
+        Some("js") | Some("mjs") | Some("cjs") => "javascript",
+        Some("jsx") => "javascriptreact",
+        Some("go") => "go",
+        Some(other) => other,
+        None => "plaintext",
+    }
+}
+
+/// Walk up from `path` looking for one of the server's root markers, without leaving `workspace`.
+///
+/// Falls back to the workspace itself so single-file edits still get a server.
+pub fn find_project_root(server: &LanguageServer, path: &Path, workspace: &Path) -> PathBuf {
+    let start = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+
+    let mut best = None;
+    for dir in start
+        .ancestors()
+        .take_while(|dir| dir.starts_with(workspace))
+    {
+        if server
+            .root_markers
+            .iter()
+            .any(|marker| dir.join(marker).exists())
+        {
+            // Keep going so that e.g. a cargo workspace root wins over a member crate
+            best = Some(dir.to_path_buf());
+        }
+    }
+
+    best.unwrap_or_else(|| workspace.to_path_buf())
+}
+
+/// Servers whose root markers appear at the top of the workspace and whose binary is on the PATH
+pub fn detect_workspace_servers(workspace: &Path) -> Vec<&'static LanguageServer> {
+    LANGUAGE_SERVERS
+        .iter()
+        .filter(|server| {
+            server
+                .root_markers
+                .iter()
+                .any(|marker| workspace.join(marker).exists())
+        })
+        .filter(|server| which::which(server.command).is_ok())
+        .collect()
+}
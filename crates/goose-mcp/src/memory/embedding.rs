@@ -0,0 +1,263 @@
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Dimensions of the vectors produced by the lexical embedder
+const LEXICAL_DIMENSIONS: usize = 512;
+
+/// Weight of a character trigram relative to a whole word
+const TRIGRAM_WEIGHT: f32 = 0.5;
+
+const DEFAULT_REMOTE_MODEL: &str = "text-embedding-3-small";
+
+/// Turns memories and queries into vectors that can be compared with [`cosine_similarity`].
+#[derive(Debug, Clone)]
+pub enum Embedder {
+    /// Lexical fallback used when no embedding API is configured: hashed words and character
+    /// trigrams. It needs no model or network access, but only matches shared words and
+    /// spellings, not meaning, so "car" won't find a memory about an "automobile".
+    Lexical,
+    /// Any OpenAI-compatible `/v1/embeddings` endpoint
+    Remote {
+        host: String,
+        api_key: String,
+        model: String,
+    },
+}
+
+impl Embedder {
+    /// Pick the embedder from `GOOSE_MEMORY_EMBEDDING_HOST`, `GOOSE_MEMORY_EMBEDDING_API_KEY` and
+    /// `GOOSE_MEMORY_EMBEDDING_MODEL`, falling back to lexical matching when no host is configured.
+    pub fn from_env() -> Self {
+        // Never call out to an embedding API during tests
+        if cfg!(test) {
+            return Embedder::Lexical;
+        }
+
+        let host = std::env::var("GOOSE_MEMORY_EMBEDDING_HOST").unwrap_or_default();
+        if host.is_empty() || host == "local" || host == "lexical" {
+            return Embedder::Lexical;
+        }
+
+        Embedder::Remote {
+            host,
+            api_key: std::env::var("GOOSE_MEMORY_EMBEDDING_API_KEY").unwrap_or_default(),
+            model: std::env::var("GOOSE_MEMORY_EMBEDDING_MODEL")
+                .unwrap_or_else(|_| DEFAULT_REMOTE_MODEL.to_string()),
+        }
+    }
+
+    /// Identifies the embedding space, so vectors from different models are never compared
+    pub fn id(&self) -> String {
+        match self {
+            Embedder::Lexical => format!("lexical-{}", LEXICAL_DIMENSIONS),
+            Embedder::Remote { model, .. } => model.clone(),
+        }
+    }
+
+    /// Embed `texts`, reusing vectors cached under `cache_dir` for remote embedders
+    pub async fn embed(&self, texts: &[String], cache_dir: &Path) -> Result<Vec<Vec<f32>>, String> {
+        match self {
+            Embedder::Lexical => Ok(texts.iter().map(|text| embed_lexical(text)).collect()),
+            Embedder::Remote {
+                host,
+                api_key,
+                model,
+            } => {
+                let mut cache = EmbeddingCache::load(cache_dir, &self.id());
+                let missing: Vec<String> = texts
+                    .iter()
+                    .filter(|text| cache.get(text).is_none())
+                    .cloned()
+                    .collect();
+
+                if !missing.is_empty() {
+                    let vectors = embed_remote(host, api_key, model, &missing).await?;
+                    for (text, vector) in missing.iter().zip(vectors) {
+                        cache.insert(text, vector);
+                    }
+                    if let Err(e) = cache.save() {
+                        tracing::warn!("Failed to save memory embedding cache: {}", e);
+                    }
+                }
+
+                texts
+                    .iter()
+                    .map(|text| {
+                        cache
+                            .get(text)
+                            .cloned()
+                            .ok_or_else(|| "Embedding service returned too few vectors".to_string())
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+/// Hash words and their character trigrams into a fixed size, L2-normalized vector
+pub fn embed_lexical(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; LEXICAL_DIMENSIONS];
+
+    let lowercase = text.to_lowercase();
+    let words = lowercase
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty());
+
+    for word in words {
+        vector[bucket(word)] += 1.0;
+
+        let padded: Vec<char> = format!(" {} ", word).chars().collect();
+        for trigram in padded.windows(3) {
+            let trigram: String = trigram.iter().collect();
+            vector[bucket(&trigram)] += TRIGRAM_WEIGHT;
+        }
+    }
+
+    // Dampen repeated terms so one word can't dominate a long memory
+    for value in &mut vector {
+        if *value > 0.0 {
+            *value = 1.0 + value.ln();
+        }
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+fn bucket(feature: &str) -> usize {
+    (fnv1a(feature) % LEXICAL_DIMENSIONS as u64) as usize
+}
+
+/// Stable across builds and platforms, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[derive(Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+async fn embed_remote(
+    host: &str,
+    api_key: &str,
+    model: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, String> {
+    let url = format!("{}/v1/embeddings", host.trim_end_matches('/'));
+    let mut request = reqwest::Client::new()
+        .post(&url)
+        .json(&json!({ "input": texts, "model": model }));
+    if !api_key.is_empty() {
+        request = request.bearer_auth(api_key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach embedding service: {}", e))?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        return Err(format!("Embedding service returned {}: {}", status, body));
+    }
+
+    let response: EmbeddingResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse embedding response: {}", e))?;
+    Ok(response.data.into_iter().map(|d| d.embedding).collect())
+}
+
+/// Remote embeddings stored on disk, keyed by a hash of the embedded text
+struct EmbeddingCache {
+    path: PathBuf,
+    vectors: HashMap<String, Vec<f32>>,
+}
+
+impl EmbeddingCache {
+    fn load(cache_dir: &Path, embedder_id: &str) -> Self {
+        let file_name = embedder_id.replace(|c: char| !c.is_alphanumeric() && c != '-', "_");
+        let path = cache_dir.join(format!("{}.json", file_name));
+        let vectors = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self { path, vectors }
+    }
+
+    fn get(&self, text: &str) -> Option<&Vec<f32>> {
+        self.vectors.get(&format!("{:016x}", fnv1a(text)))
+    }
+
+    fn insert(&mut self, text: &str, vector: Vec<f32>) {
+        self.vectors.insert(format!("{:016x}", fnv1a(text)), vector);
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.vectors)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lexical_embedding_is_normalized_and_stable() {
+        let a = embed_lexical("We format Python code with black");
+        let b = embed_lexical("We format Python code with black");
+        assert_eq!(a.len(), LEXICAL_DIMENSIONS);
+        assert_eq!(a, b);
+        let norm = a.iter().map(|v| v * v).sum::<f32>().sqrt();
+        assert!((norm - 1.0).abs() < 1e-5);
+        assert!(embed_lexical("").iter().all(|v| *v == 0.0));
+    }
+
+    #[test]
+    fn test_lexical_embedding_ranks_related_text_higher() {
+        let query = embed_lexical("how do we format python files?");
+        let related = embed_lexical("Python formatting: we use black with a line length of 100");
+        let unrelated = embed_lexical("The staging database lives in us-east-1");
+        assert!(cosine_similarity(&query, &related) > cosine_similarity(&query, &unrelated));
+    }
+
+    #[test]
+    fn test_cosine_similarity_edge_cases() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert!((cosine_similarity(&[1.0, 2.0], &[2.0, 4.0]) - 1.0).abs() < 1e-6);
+    }
+}
@@ -1,4 +1,7 @@
+mod embedding;
+
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, SecondsFormat, Utc};
 use etcetera::{choose_app_strategy, AppStrategy};
 use indoc::formatdoc;
 use serde_json::{json, Value};
@@ -7,7 +10,7 @@ use std::{
     fs,
     future::Future,
     io::{self, Read, Write},
-    path::PathBuf,
+    path::{Path, PathBuf},
     pin::Pin,
 };
 use tokio::sync::mpsc;
@@ -23,6 +26,150 @@ use mcp_core::{
 use mcp_server::router::CapabilitiesBuilder;
 use mcp_server::Router;
 
+use embedding::{cosine_similarity, embed_lexical, Embedder};
+
+/// Most memories loaded into the instructions when the extension starts
+const DEFAULT_PROMPT_MEMORY_LIMIT: usize = 20;
+
+/// Number of results returned by `search_memories` when `k` is not given
+const DEFAULT_SEARCH_RESULTS: usize = 5;
+
+/// How much of each workspace file is used to judge which memories are relevant
+const WORKSPACE_CONTEXT_CHARS: usize = 2000;
+
+/// A single stored memory along with its metadata
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemoryEntry {
+    pub category: String,
+    pub data: String,
+    pub tags: Vec<String>,
+    pub created: Option<DateTime<Utc>>,
+    pub expires: Option<DateTime<Utc>>,
+    pub is_global: bool,
+}
+
+impl MemoryEntry {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn embedding_text(&self) -> String {
+        format!("{} {} {}", self.category, self.tags.join(" "), self.data)
+    }
+
+    fn describe(&self) -> String {
+        let mut details = Vec::new();
+        if !self.tags.is_empty() {
+            details.push(format!("tags: {}", self.tags.join(" ")));
+        }
+        if let Some(created) = self.created {
+            details.push(format!("saved {}", created.format("%Y-%m-%d")));
+        }
+        if let Some(expires) = self.expires {
+            details.push(format!("expires {}", expires.format("%Y-%m-%d")));
+        }
+
+        if details.is_empty() {
+            self.data.clone()
+        } else {
+            format!("{} ({})", self.data, details.join(", "))
+        }
+    }
+}
+
+/// Parse the entries of a category file.
+///
+/// Entries are separated by blank lines. Each may start with a `# tag1 tag2` line,
+/// followed by optional `@created <rfc3339>` and `@expires <rfc3339>` lines.
+fn parse_entries(category: &str, content: &str, is_global: bool) -> Vec<MemoryEntry> {
+    content
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut lines = block.lines().peekable();
+            let tags = match lines.peek() {
+                Some(line) if line.starts_with('#') => {
+                    let tags = line[1..].split_whitespace().map(String::from).collect();
+                    lines.next();
+                    tags
+                }
+                _ => Vec::new(),
+            };
+
+            let mut created = None;
+            let mut expires = None;
+            while let Some(line) = lines.peek() {
+                if let Some(value) = line.strip_prefix("@created ") {
+                    created = parse_timestamp(value);
+                } else if let Some(value) = line.strip_prefix("@expires ") {
+                    expires = parse_timestamp(value);
+                } else {
+                    break;
+                }
+                lines.next();
+            }
+
+            let data = lines.collect::<Vec<_>>().join("\n");
+            if data.trim().is_empty() {
+                return None;
+            }
+
+            Some(MemoryEntry {
+                category: category.to_string(),
+                data,
+                tags,
+                created,
+                expires,
+                is_global,
+            })
+        })
+        .collect()
+}
+
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value.trim())
+        .ok()
+        .map(|timestamp| timestamp.with_timezone(&Utc))
+}
+
+/// Text describing the workspace, used to pick the memories worth loading up front
+fn workspace_context(working_dir: &Path) -> String {
+    let mut context = working_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    for file in [".goosehints", "README.md"] {
+        if let Ok(content) = fs::read_to_string(working_dir.join(file)) {
+            context.push('\n');
+            context.extend(content.chars().take(WORKSPACE_CONTEXT_CHARS));
+        }
+    }
+    context
+}
+
+/// Keep at most `limit` memories, preferring the ones most similar to `context` and then the newest
+fn select_relevant(mut entries: Vec<MemoryEntry>, context: &str, limit: usize) -> Vec<MemoryEntry> {
+    if entries.len() <= limit {
+        return entries;
+    }
+
+    let context = embed_lexical(context);
+    let mut scored: Vec<(f32, MemoryEntry)> = entries
+        .drain(..)
+        .map(|entry| {
+            let score = cosine_similarity(&context, &embed_lexical(&entry.embedding_text()));
+            (score, entry)
+        })
+        .collect();
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .total_cmp(a_score)
+            .then_with(|| b.created.cmp(&a.created))
+    });
+    scored.truncate(limit);
+    scored.into_iter().map(|(_, entry)| entry).collect()
+}
+
 // MemoryRouter implementation
 #[derive(Clone)]
 pub struct MemoryRouter {
@@ -30,6 +177,7 @@ pub struct MemoryRouter {
     instructions: String,
     global_memory_dir: PathBuf,
     local_memory_dir: PathBuf,
+    embedder: Embedder,
 }
 
 impl Default for MemoryRouter {
@@ -42,14 +190,18 @@ impl MemoryRouter {
     pub fn new() -> Self {
         let remember_memory = Tool::new(
             "remember_memory",
-            "Stores a memory with optional tags in a specified category",
+            "Stores a memory with optional tags and expiry in a specified category",
             json!({
                 "type": "object",
                 "properties": {
                     "category": {"type": "string"},
                     "data": {"type": "string"},
                     "tags": {"type": "array", "items": {"type": "string"}},
-                    "is_global": {"type": "boolean"}
+                    "is_global": {"type": "boolean"},
+                    "expires_in_days": {
+                        "type": "integer",
+                        "description": "Forget the memory after this many days. Omit to keep it until removed."
+                    }
                 },
                 "required": ["category", "data", "is_global"]
             }),
@@ -82,6 +234,28 @@ impl MemoryRouter {
             }),
         );
 
+        let search_memories = Tool::new(
+            "search_memories",
+            "Finds the k memories most relevant to a query across categories",
+            json!({
+                "type": "object",
+                "properties": {
+                    "query": {"type": "string"},
+                    "k": {"type": "integer", "description": "Number of memories to return, defaults to 5"},
+                    "category": {"type": "string", "description": "Only search this category"},
+                    "is_global": {"type": "boolean", "description": "Only search global (true) or local (false) memories, searches both when omitted"}
+                },
+                "required": ["query"]
+            }),
+            Some(ToolAnnotations {
+                title: Some("Search Memories".to_string()),
+                read_only_hint: true,
+                destructive_hint: false,
+                idempotent_hint: true,
+                open_world_hint: false,
+            }),
+        );
+
         let remove_memory_category = Tool::new(
             "remove_memory_category",
             "Removes all memories within a specified category",
@@ -127,8 +301,8 @@ impl MemoryRouter {
              This extension allows storage and retrieval of categorized information with tagging support. It's designed to help
              manage important information across sessions in a systematic and organized manner.
              Capabilities:
-             1. Store information in categories with optional tags for context-based retrieval.
-             2. Search memories by content or specific tags to find relevant information.
+             1. Store information in categories with optional tags and an optional expiry for context-based retrieval.
+             2. Search memories by relevance with `search_memories` to find only the relevant information.
              3. List all available memory categories for easy navigation.
              4. Remove entire categories of memories when they are no longer needed.
              When to call memory tools:
@@ -191,6 +365,9 @@ impl MemoryRouter {
              Assistant: "I'll store this in the 'github' category. Any specific tags to add? Suggestions: #comments #gh"
              Retrieving Memories:
              To access stored information, utilize the memory retrieval protocols:
+             - **Search by Relevance** (preferred):
+               - Returns the memories closest to a query from any category.
+               - Use: `search_memories(query="code formatting", k=5)`
              - **Search by Category**:
                - Provides all memories within the specified context.
                - Use: `retrieve_memories(category="development", is_global=False)`
@@ -208,12 +385,12 @@ impl MemoryRouter {
             The Protocol is:
              1. Confirm what kind of information the user seeks by category or keyword.
              2. Suggest categories or relevant tags based on the user's request.
-             3. Use the retrieve function to access relevant memory entries.
+             3. Use search_memories, or the retrieve function for a whole category, to access relevant memory entries.
              4. Present a summary of findings, offering detailed exploration upon request.
              Example Interaction for Retrieving Information:
              User: "What configuration do we use for code formatting?"
              Assistant: "Let me check the 'development' category for any related memories. Searching using #formatting tag."
-             Assistant: *Executes retrieval: `retrieve_memories(category="development", is_global=False)`*
+             Assistant: *Executes search: `search_memories(query="code formatting", k=5)`*
              Assistant: "We have 'black' configured for code formatting, specific to this project. Would you like further
              details?"
              Memory Overview:
@@ -227,11 +404,10 @@ impl MemoryRouter {
             "#};
 
         // Check for .goose/memory in current directory
-        let local_memory_dir = std::env::var("GOOSE_WORKING_DIR")
+        let working_dir = std::env::var("GOOSE_WORKING_DIR")
             .map(PathBuf::from)
-            .unwrap_or_else(|_| std::env::current_dir().unwrap())
-            .join(".goose")
-            .join("memory");
+            .unwrap_or_else(|_| std::env::current_dir().unwrap());
+        let local_memory_dir = working_dir.join(".goose").join("memory");
 
         // choose_app_strategy().config_dir()
         // - macOS/Linux: ~/.config/goose/memory/
@@ -248,16 +424,28 @@ impl MemoryRouter {
             tools: vec![
                 remember_memory,
                 retrieve_memories,
+                search_memories,
                 remove_memory_category,
                 remove_specific_memory,
             ],
             instructions: instructions.clone(),
             global_memory_dir,
             local_memory_dir,
+            embedder: Embedder::from_env(),
         };
 
-        let retrieved_global_memories = memory_router.retrieve_all(true);
-        let retrieved_local_memories = memory_router.retrieve_all(false);
+        let mut saved_memories = memory_router.entries(true).unwrap_or_default();
+        saved_memories.extend(memory_router.entries(false).unwrap_or_default());
+        let saved_count = saved_memories.len();
+        let prompt_limit = std::env::var("GOOSE_MEMORY_PROMPT_LIMIT")
+            .ok()
+            .and_then(|limit| limit.parse().ok())
+            .unwrap_or(DEFAULT_PROMPT_MEMORY_LIMIT);
+        let relevant_memories = select_relevant(
+            saved_memories,
+            &workspace_context(&working_dir),
+            prompt_limit,
+        );
 
         let mut updated_instructions = instructions;
 
@@ -272,30 +460,39 @@ impl MemoryRouter {
         updated_instructions.push_str("\n\n");
         updated_instructions.push_str(&memories_follow_up_instructions);
 
-        if let Ok(global_memories) = retrieved_global_memories {
-            if !global_memories.is_empty() {
-                updated_instructions.push_str("\n\nGlobal Memories:\n");
-                for (category, memories) in global_memories {
-                    updated_instructions.push_str(&format!("\nCategory: {}\n", category));
-                    for memory in memories {
-                        updated_instructions.push_str(&format!("- {}\n", memory));
-                    }
+        for (heading, is_global) in [("Global Memories", true), ("Local Memories", false)] {
+            let mut by_category: Vec<(&str, Vec<&MemoryEntry>)> = Vec::new();
+            for memory in relevant_memories
+                .iter()
+                .filter(|m| m.is_global == is_global)
+            {
+                match by_category.iter_mut().find(|(c, _)| *c == memory.category) {
+                    Some((_, memories)) => memories.push(memory),
+                    None => by_category.push((&memory.category, vec![memory])),
                 }
             }
-        }
+            if by_category.is_empty() {
+                continue;
+            }
 
-        if let Ok(local_memories) = retrieved_local_memories {
-            if !local_memories.is_empty() {
-                updated_instructions.push_str("\n\nLocal Memories:\n");
-                for (category, memories) in local_memories {
-                    updated_instructions.push_str(&format!("\nCategory: {}\n", category));
-                    for memory in memories {
-                        updated_instructions.push_str(&format!("- {}\n", memory));
-                    }
+            updated_instructions.push_str(&format!("\n\n{}:\n", heading));
+            for (category, memories) in by_category {
+                updated_instructions.push_str(&format!("\nCategory: {}\n", category));
+                for memory in memories {
+                    updated_instructions.push_str(&format!("- {}\n", memory.describe()));
                 }
             }
         }
 
+        if saved_count > relevant_memories.len() {
+            updated_instructions.push_str(&format!(
+                "\n\nOnly the {} memories most relevant to this workspace are shown above, out of {} saved. \
+                Use search_memories to find the others when they could help.\n",
+                relevant_memories.len(),
+                saved_count
+            ));
+        }
+
         memory_router.set_instructions(updated_instructions);
 
         memory_router
@@ -331,7 +528,14 @@ impl MemoryRouter {
             for entry in fs::read_dir(base_dir)? {
                 let entry = entry?;
                 if entry.file_type()?.is_file() {
-                    let category = entry.file_name().to_string_lossy().replace(".txt", "");
+                    let Some(category) = entry
+                        .file_name()
+                        .to_string_lossy()
+                        .strip_suffix(".txt")
+                        .map(String::from)
+                    else {
+                        continue;
+                    };
                     let category_memories = self.retrieve(&category, is_global)?;
                     memories.insert(
                         category,
@@ -350,6 +554,7 @@ impl MemoryRouter {
         data: &str,
         tags: &[&str],
         is_global: bool,
+        expires: Option<DateTime<Utc>>,
     ) -> io::Result<()> {
         let memory_file_path = self.get_memory_file(category, is_global);
 
@@ -360,11 +565,103 @@ impl MemoryRouter {
         if !tags.is_empty() {
             writeln!(file, "# {}", tags.join(" "))?;
         }
+        writeln!(
+            file,
+            "@created {}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+        )?;
+        if let Some(expires) = expires {
+            writeln!(
+                file,
+                "@expires {}",
+                expires.to_rfc3339_opts(SecondsFormat::Secs, true)
+            )?;
+        }
         writeln!(file, "{}\n", data)?;
 
         Ok(())
     }
 
+    /// All memories in a category that have not expired
+    fn category_entries(&self, category: &str, is_global: bool) -> io::Result<Vec<MemoryEntry>> {
+        let memory_file_path = self.get_memory_file(category, is_global);
+        if !memory_file_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(memory_file_path)?;
+        let now = Utc::now();
+        Ok(parse_entries(category, &content, is_global)
+            .into_iter()
+            .filter(|entry| !entry.is_expired(now))
+            .collect())
+    }
+
+    /// All memories in the global or local scope that have not expired
+    pub fn entries(&self, is_global: bool) -> io::Result<Vec<MemoryEntry>> {
+        let base_dir = if is_global {
+            &self.global_memory_dir
+        } else {
+            &self.local_memory_dir
+        };
+        let mut entries = Vec::new();
+        if base_dir.exists() {
+            for entry in fs::read_dir(base_dir)? {
+                let entry = entry?;
+                if !entry.file_type()?.is_file() {
+                    continue;
+                }
+                if let Some(category) = entry.file_name().to_string_lossy().strip_suffix(".txt") {
+                    entries.extend(self.category_entries(category, is_global)?);
+                }
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Find the `k` memories most similar to `query`, optionally limited to one scope or category
+    pub async fn search(
+        &self,
+        query: &str,
+        k: usize,
+        is_global: Option<bool>,
+        category: Option<&str>,
+    ) -> io::Result<Vec<(f32, MemoryEntry)>> {
+        let mut candidates = Vec::new();
+        for scope in [true, false] {
+            if is_global.is_some_and(|is_global| is_global != scope) {
+                continue;
+            }
+            match category {
+                Some(category) => candidates.extend(self.category_entries(category, scope)?),
+                None => candidates.extend(self.entries(scope)?),
+            }
+        }
+        if candidates.is_empty() || k == 0 {
+            return Ok(Vec::new());
+        }
+
+        let mut texts = vec![query.to_string()];
+        texts.extend(candidates.iter().map(MemoryEntry::embedding_text));
+        let vectors = self
+            .embedder
+            .embed(&texts, &self.global_memory_dir.join(".cache"))
+            .await
+            .map_err(io::Error::other)?;
+        let (query_vector, memory_vectors) = vectors
+            .split_first()
+            .ok_or_else(|| io::Error::other("No embedding returned for the query"))?;
+
+        let mut results: Vec<(f32, MemoryEntry)> = memory_vectors
+            .iter()
+            .map(|vector| cosine_similarity(query_vector, vector))
+            .zip(candidates)
+            .collect();
+        results.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        results.truncate(k);
+        Ok(results)
+    }
+
     pub fn retrieve(
         &self,
         category: &str,
@@ -375,30 +672,17 @@ impl MemoryRouter {
             return Ok(HashMap::new());
         }
 
-        let mut file = fs::File::open(memory_file_path)?;
-        let mut content = String::new();
-        file.read_to_string(&mut content)?;
-
         let mut memories = HashMap::new();
-        for entry in content.split("\n\n") {
-            let mut lines = entry.lines();
-            if let Some(first_line) = lines.next() {
-                if let Some(stripped) = first_line.strip_prefix('#') {
-                    let tags = stripped
-                        .split_whitespace()
-                        .map(String::from)
-                        .collect::<Vec<_>>();
-                    memories.insert(tags.join(" "), lines.map(String::from).collect());
-                } else {
-                    let entry_data: Vec<String> = std::iter::once(first_line.to_string())
-                        .chain(lines.map(String::from))
-                        .collect();
-                    memories
-                        .entry("untagged".to_string())
-                        .or_insert_with(Vec::new)
-                        .extend(entry_data);
-                }
-            }
+        for entry in self.category_entries(category, is_global)? {
+            let key = if entry.tags.is_empty() {
+                "untagged".to_string()
+            } else {
+                entry.tags.join(" ")
+            };
+            memories
+                .entry(key)
+                .or_insert_with(Vec::new)
+                .extend(entry.data.lines().map(String::from));
         }
 
         Ok(memories)
@@ -460,9 +744,64 @@ impl MemoryRouter {
                         "Data must exist when remembering a memory",
                     )
                 })?;
-                self.remember("context", args.category, data, &args.tags, args.is_global)?;
+                let expires = match tool_call.arguments.get("expires_in_days") {
+                    None | Some(Value::Null) => None,
+                    Some(days) => {
+                        let days = days.as_i64().filter(|days| *days > 0).ok_or_else(|| {
+                            io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                "expires_in_days must be a positive integer",
+                            )
+                        })?;
+                        Some(Utc::now() + Duration::days(days))
+                    }
+                };
+                self.remember(
+                    "context",
+                    args.category,
+                    data,
+                    &args.tags,
+                    args.is_global,
+                    expires,
+                )?;
                 Ok(format!("Stored memory in category: {}", args.category))
             }
+            "search_memories" => {
+                let query = tool_call.arguments["query"]
+                    .as_str()
+                    .filter(|query| !query.is_empty())
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "Query must be a string")
+                    })?;
+                let k = tool_call.arguments["k"]
+                    .as_u64()
+                    .map(|k| k as usize)
+                    .unwrap_or(DEFAULT_SEARCH_RESULTS);
+                let is_global = tool_call.arguments["is_global"].as_bool();
+                let category = tool_call.arguments["category"]
+                    .as_str()
+                    .filter(|category| !category.is_empty() && *category != "*");
+
+                let results = self.search(query, k, is_global, category).await?;
+                if results.is_empty() {
+                    return Ok("No memories found".to_string());
+                }
+                Ok(results
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (score, memory))| {
+                        format!(
+                            "{}. [{}/{}] {} (relevance {:.2})",
+                            i + 1,
+                            if memory.is_global { "global" } else { "local" },
+                            memory.category,
+                            memory.describe(),
+                            score
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n"))
+            }
             "retrieve_memories" => {
                 let args = MemoryArgs::from_value(&tool_call.arguments)?;
                 let memories = if args.category == "*" {
@@ -617,3 +956,125 @@ impl<'a> MemoryArgs<'a> {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn test_router(dir: &TempDir) -> MemoryRouter {
+        let global_memory_dir = dir.path().join("global");
+        let local_memory_dir = dir.path().join("local");
+        fs::create_dir_all(&global_memory_dir).unwrap();
+        fs::create_dir_all(&local_memory_dir).unwrap();
+        MemoryRouter {
+            tools: Vec::new(),
+            instructions: String::new(),
+            global_memory_dir,
+            local_memory_dir,
+            embedder: Embedder::Lexical,
+        }
+    }
+
+    #[test]
+    fn test_parse_entries_with_and_without_metadata() {
+        let content = "# formatting tools\n@created 2025-01-02T03:04:05Z\n@expires 2025-02-01T00:00:00Z\nWe use black\n\nPlain legacy memory\n\n";
+        let entries = parse_entries("development", content, false);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].tags, vec!["formatting", "tools"]);
+        assert_eq!(entries[0].data, "We use black");
+        assert_eq!(entries[0].created, parse_timestamp("2025-01-02T03:04:05Z"));
+        assert!(entries[0].expires.is_some());
+        assert_eq!(entries[1].data, "Plain legacy memory");
+        assert!(entries[1].tags.is_empty());
+        assert!(entries[1].created.is_none());
+    }
+
+    #[test]
+    fn test_expired_memories_are_hidden() {
+        let dir = TempDir::new().unwrap();
+        let router = test_router(&dir);
+        let yesterday = Utc::now() - Duration::days(1);
+        let tomorrow = Utc::now() + Duration::days(1);
+
+        router
+            .remember("context", "notes", "stale", &[], false, Some(yesterday))
+            .unwrap();
+        router
+            .remember("context", "notes", "fresh", &["tag"], false, Some(tomorrow))
+            .unwrap();
+
+        let entries = router.entries(false).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].data, "fresh");
+        assert!(entries[0].created.is_some());
+
+        let retrieved = router.retrieve("notes", false).unwrap();
+        assert_eq!(retrieved.get("tag"), Some(&vec!["fresh".to_string()]));
+        assert!(!retrieved.contains_key("untagged"));
+    }
+
+    #[tokio::test]
+    async fn test_search_ranks_relevant_memories_first() {
+        let dir = TempDir::new().unwrap();
+        let router = test_router(&dir);
+        router
+            .remember(
+                "context",
+                "development",
+                "Python code is formatted with black",
+                &["formatting"],
+                false,
+                None,
+            )
+            .unwrap();
+        router
+            .remember(
+                "context",
+                "infra",
+                "The staging database runs in us-east-1",
+                &[],
+                true,
+                None,
+            )
+            .unwrap();
+
+        let results = router
+            .search("how is python formatted?", 1, None, None)
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].1.category, "development");
+
+        let global_only = router
+            .search("how is python formatted?", 5, Some(true), None)
+            .await
+            .unwrap();
+        assert_eq!(global_only.len(), 1);
+        assert_eq!(global_only[0].1.category, "infra");
+    }
+
+    #[test]
+    fn test_select_relevant_respects_limit() {
+        let entry = |category: &str, data: &str| MemoryEntry {
+            category: category.to_string(),
+            data: data.to_string(),
+            tags: Vec::new(),
+            created: None,
+            expires: None,
+            is_global: false,
+        };
+        let entries = vec![
+            entry("cooking", "Grandma's lasagne recipe uses ricotta"),
+            entry("rust", "Run cargo clippy before committing rust changes"),
+            entry("travel", "Passport expires next spring"),
+        ];
+
+        assert_eq!(select_relevant(entries.clone(), "anything", 5), entries);
+
+        let selected = select_relevant(entries, "goose rust cargo workspace", 1);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].category, "rust");
+    }
+}
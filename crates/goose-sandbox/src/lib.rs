@@ -0,0 +1,252 @@
+//! Optional OS-level sandbox for the processes goose starts on behalf of the model, such as
+//! stdio extensions and the developer shell.
+//!
+//! On Linux a sandboxed process sees the filesystem read-only except for its workspace, the
+//! temp directory and any extra writable paths (Landlock), can be cut off from the network
+//! (user and network namespaces), has syscalls that escape or inspect the sandbox refused
+//! (seccomp), and starts with a scrubbed environment. Other platforms refuse to start a
+//! process that asks for a sandbox rather than silently running it unrestricted.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[cfg(target_os = "linux")]
+mod linux;
+
+/// Environment variable the developer extension reads its shell sandbox profile from, as JSON
+pub const SHELL_SANDBOX_ENV: &str = "GOOSE_SHELL_SANDBOX";
+
+/// Environment variables a scrubbed process still receives
+const DEFAULT_KEEP_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "LC_CTYPE", "TERM", "TMPDIR",
+    "TZ",
+];
+
+/// Output fragments that point at the sandbox refusing an operation
+const REFUSAL_MARKERS: &[&str] = &[
+    "Permission denied",
+    "Operation not permitted",
+    "Read-only file system",
+];
+
+/// Output fragments that point at a missing network
+const NETWORK_MARKERS: &[&str] = &[
+    "Network is unreachable",
+    "Temporary failure in name resolution",
+    "Could not resolve host",
+    "Name or service not known",
+];
+
+#[derive(Error, Debug)]
+pub enum SandboxError {
+    #[error("Sandboxing is only supported on Linux")]
+    Unsupported,
+    #[error("Landlock is not available, it needs Linux 5.13 or newer with Landlock enabled: {0}")]
+    Landlock(String),
+    #[error("Failed to build the seccomp filter: {0}")]
+    Seccomp(String),
+    #[error("Invalid sandbox profile: {0}")]
+    InvalidProfile(String),
+}
+
+/// What a sandboxed process is allowed to do
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SandboxProfile {
+    /// Directory the process may write to, defaults to the current directory
+    pub workspace: Option<PathBuf>,
+    /// Additional paths the process may write to
+    pub writable_paths: Vec<PathBuf>,
+    /// Whether the process may use the network, including localhost
+    pub network: bool,
+    /// Start the process with only a minimal set of environment variables
+    pub scrub_env: bool,
+    /// Extra environment variables passed through when the environment is scrubbed
+    pub keep_env: Vec<String>,
+}
+
+impl Default for SandboxProfile {
+    fn default() -> Self {
+        Self {
+            workspace: None,
+            writable_paths: Vec::new(),
+            network: true,
+            scrub_env: true,
+            keep_env: Vec::new(),
+        }
+    }
+}
+
+impl SandboxProfile {
+    /// Read the shell sandbox profile handed down by goose, if any
+    pub fn from_env() -> Result<Option<Self>, SandboxError> {
+        match std::env::var(SHELL_SANDBOX_ENV) {
+            Ok(value) if !value.trim().is_empty() => serde_json::from_str(&value)
+                .map(Some)
+                .map_err(|e| SandboxError::InvalidProfile(e.to_string())),
+            _ => Ok(None),
+        }
+    }
+
+    /// The directory the process may write to
+    pub fn workspace(&self) -> PathBuf {
+        match &self.workspace {
+            Some(path) => expand_home(path),
+            None => std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        }
+    }
+
+    /// Every path the process may write to: the workspace, the temp directory and the
+    /// configured extras
+    pub fn writable_paths(&self) -> Vec<PathBuf> {
+        let mut paths = vec![
+            self.workspace(),
+            std::env::temp_dir(),
+            PathBuf::from("/dev/null"),
+        ];
+        paths.extend(self.writable_paths.iter().map(|p| expand_home(p)));
+        paths
+    }
+
+    /// Restrict a command so the process it spawns runs inside this sandbox
+    ///
+    /// This clears the command's environment when scrubbing is on, so set the variables the
+    /// process needs after calling it.
+    pub fn apply(&self, command: &mut Command) -> Result<(), SandboxError> {
+        if !cfg!(target_os = "linux") {
+            return Err(SandboxError::Unsupported);
+        }
+        #[cfg(target_os = "linux")]
+        linux::restrict(command, self)?;
+
+        if self.scrub_env {
+            let kept: Vec<(String, String)> = std::env::vars()
+                .filter(|(key, _)| {
+                    DEFAULT_KEEP_ENV.contains(&key.as_str()) || self.keep_env.contains(key)
+                })
+                .collect();
+            command.env_clear();
+            command.envs(kept);
+        }
+
+        Ok(())
+    }
+
+    /// Explain a failed command's output when it looks like the sandbox refused something,
+    /// so the model doesn't keep retrying an operation that can never succeed
+    pub fn explain_failure(&self, output: &str) -> Option<String> {
+        let refused = REFUSAL_MARKERS.iter().any(|m| output.contains(m));
+        let offline = !self.network && NETWORK_MARKERS.iter().any(|m| output.contains(m));
+        if !refused && !offline {
+            return None;
+        }
+
+        let writable = self
+            .writable_paths()
+            .iter()
+            .map(|p| p.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let network = if self.network {
+            ""
+        } else {
+            " and has no network access"
+        };
+        Some(format!(
+            "This command ran in a sandbox: it can only write to {}{}. \
+            The error above is most likely the sandbox refusing the operation. \
+            Don't retry it; if it is needed, ask the user to adjust the sandbox section of their goose config.",
+            writable, network
+        ))
+    }
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_defaults() {
+        let profile: SandboxProfile = serde_json::from_str("{}").unwrap();
+        assert_eq!(profile, SandboxProfile::default());
+        assert!(profile.network);
+        assert!(profile.scrub_env);
+
+        let profile: SandboxProfile =
+            serde_json::from_str(r#"{"network": false, "writable_paths": ["~/.cache"]}"#).unwrap();
+        assert!(!profile.network);
+        assert!(profile.scrub_env);
+        if let Some(home) = std::env::var_os("HOME") {
+            assert!(profile
+                .writable_paths()
+                .contains(&PathBuf::from(home).join(".cache")));
+        }
+    }
+
+    #[test]
+    fn test_explain_failure() {
+        let profile = SandboxProfile {
+            workspace: Some(PathBuf::from("/work")),
+            network: false,
+            ..Default::default()
+        };
+
+        let explanation = profile
+            .explain_failure("touch: cannot touch '/etc/x': Permission denied")
+            .unwrap();
+        assert!(explanation.contains("/work"));
+        assert!(explanation.contains("no network access"));
+        assert!(profile
+            .explain_failure("curl: (6) Could not resolve host: example.com")
+            .is_some());
+        assert!(profile.explain_failure("all good").is_none());
+
+        let online = SandboxProfile::default();
+        assert!(online
+            .explain_failure("curl: (6) Could not resolve host: example.com")
+            .is_none());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_sandbox_limits_writes_and_env() {
+        let root = tempfile::tempdir_in(env!("CARGO_MANIFEST_DIR")).unwrap();
+        let workspace = root.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        let profile = SandboxProfile {
+            workspace: Some(workspace.clone()),
+            ..Default::default()
+        };
+
+        let mut command = Command::new("sh");
+        command
+            .current_dir(&workspace)
+            .arg("-c")
+            .arg("echo ok > inside; echo no > ../outside; echo \"[$GOOSE_SANDBOX_TEST]\"");
+        command.env("GOOSE_SANDBOX_TEST", "leaked");
+        match profile.apply(&mut command) {
+            Ok(()) => {}
+            // Kernels without Landlock can't run the sandbox at all
+            Err(SandboxError::Landlock(_)) => return,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+
+        let output = command.output().unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(workspace.join("inside").exists());
+        assert!(!root.path().join("outside").exists());
+        assert!(profile.explain_failure(&stderr).is_some(), "{stderr}");
+        assert!(stdout.contains("[]"), "{stdout}");
+    }
+}
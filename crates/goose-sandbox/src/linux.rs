@@ -0,0 +1,159 @@
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::Command;
+
+use landlock::{
+    path_beneath_rules, Access, AccessFs, CompatLevel, Compatible, Ruleset, RulesetAttr,
+    RulesetCreated, RulesetCreatedAttr, RulesetError, ABI,
+};
+use seccompiler::{BpfProgram, SeccompAction, SeccompFilter};
+
+use super::{SandboxError, SandboxProfile};
+
+/// Syscalls a sandboxed process has no business making: they either escape the sandbox or
+/// reach into other processes and the kernel
+const DENIED_SYSCALLS: &[i64] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_chroot,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_kexec_load,
+    libc::SYS_reboot,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_acct,
+];
+
+/// Install the sandbox in the child between fork and exec
+///
+/// Everything that allocates or can fail in an interesting way happens here in the parent,
+/// the child only makes the syscalls that apply what was prepared.
+pub(crate) fn restrict(
+    command: &mut Command,
+    profile: &SandboxProfile,
+) -> Result<(), SandboxError> {
+    let ruleset = landlock_ruleset(&profile.writable_paths())
+        .map_err(|e| SandboxError::Landlock(e.to_string()))?;
+    let filter = seccomp_filter()?;
+    let namespace = (!profile.network).then(NetworkNamespace::new);
+
+    // SAFETY: the closure only makes syscalls on data prepared above, it doesn't touch locks
+    // or state shared with other threads of the parent
+    unsafe {
+        command.pre_exec(move || {
+            if let Some(namespace) = &namespace {
+                namespace.enter()?;
+            }
+            ruleset
+                .try_clone()?
+                .restrict_self()
+                .map_err(io::Error::other)?;
+            seccompiler::apply_filter(&filter).map_err(io::Error::other)?;
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// Read and execute everywhere, write only beneath the writable paths
+fn landlock_ruleset(writable: &[PathBuf]) -> Result<RulesetCreated, RulesetError> {
+    let abi = ABI::V3;
+    Ruleset::default()
+        // Refuse to run at all rather than unrestricted when the kernel has no Landlock
+        .set_compatibility(CompatLevel::HardRequirement)
+        .handle_access(AccessFs::from_all(ABI::V1))?
+        .set_compatibility(CompatLevel::BestEffort)
+        .handle_access(AccessFs::from_all(abi))?
+        .create()?
+        .add_rules(path_beneath_rules(["/"], AccessFs::from_read(abi)))?
+        .add_rules(path_beneath_rules(writable, AccessFs::from_all(abi)))
+}
+
+fn seccomp_filter() -> Result<BpfProgram, SandboxError> {
+    let arch = std::env::consts::ARCH
+        .try_into()
+        .map_err(|e: seccompiler::BackendError| SandboxError::Seccomp(e.to_string()))?;
+    let rules = DENIED_SYSCALLS
+        .iter()
+        .map(|syscall| (*syscall, Vec::new()))
+        .collect::<BTreeMap<_, _>>();
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        arch,
+    )
+    .map_err(|e| SandboxError::Seccomp(e.to_string()))?;
+    filter
+        .try_into()
+        .map_err(|e: seccompiler::BackendError| SandboxError::Seccomp(e.to_string()))
+}
+
+/// A fresh user and network namespace, which leaves the process with nothing but a downed
+/// loopback interface
+struct NetworkNamespace {
+    setgroups: CString,
+    uid_map_path: CString,
+    gid_map_path: CString,
+    uid_map: Vec<u8>,
+    gid_map: Vec<u8>,
+}
+
+impl NetworkNamespace {
+    fn new() -> Self {
+        // SAFETY: getuid and getgid can't fail
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Self {
+            setgroups: CString::new("/proc/self/setgroups").unwrap(),
+            uid_map_path: CString::new("/proc/self/uid_map").unwrap(),
+            gid_map_path: CString::new("/proc/self/gid_map").unwrap(),
+            // Keep the same ids inside the namespace so file ownership still looks right
+            uid_map: format!("{uid} {uid} 1").into_bytes(),
+            gid_map: format!("{gid} {gid} 1").into_bytes(),
+        }
+    }
+
+    fn enter(&self) -> io::Result<()> {
+        // SAFETY: unshare only affects the calling process
+        if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNET) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        write_file(&self.setgroups, b"deny")?;
+        write_file(&self.uid_map_path, &self.uid_map)?;
+        write_file(&self.gid_map_path, &self.gid_map)
+    }
+}
+
+fn write_file(path: &CString, contents: &[u8]) -> io::Result<()> {
+    // SAFETY: the path is a valid C string and the buffer outlives the call
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+        libc::close(fd);
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
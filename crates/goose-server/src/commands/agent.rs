@@ -40,6 +40,9 @@ pub async fn run() -> Result<()> {
         .join("schedules.json");
 
     let scheduler_instance = SchedulerFactory::create(schedule_file_path).await?;
+    if let Err(e) = scheduler_instance.catch_up().await {
+        tracing::warn!("Failed to catch up on missed scheduled runs: {}", e);
+    }
     app_state.set_scheduler(scheduler_instance.clone()).await;
 
     // NEW: Provide scheduler access to the agent
@@ -54,6 +57,34 @@ pub async fn run() -> Result<()> {
 
     let listener = tokio::net::TcpListener::bind(settings.socket_addr()).await?;
     info!("listening on {}", listener.local_addr()?);
-    axum::serve(listener, app).await?;
+    tokio::select! {
+        result = axum::serve(listener, app) => result?,
+        _ = shutdown_signal() => info!("shutting down"),
+    }
+    goose::tracing::shutdown_otlp();
     Ok(())
 }
+
+/// Resolves on Ctrl+C or, on unix, SIGTERM, so spans are flushed however goosed is stopped
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install signal handler")
+            .recv()
+            .await;
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
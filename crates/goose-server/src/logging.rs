@@ -9,7 +9,7 @@ use tracing_subscriber::{
 };
 
 use goose::config::APP_STRATEGY;
-use goose::tracing::langfuse_layer;
+use goose::tracing::{langfuse_layer, otlp_layer};
 
 /// Returns the directory where log files should be stored.
 /// Creates the directory structure if it doesn't exist.
@@ -40,6 +40,7 @@ fn get_log_directory() -> Result<PathBuf> {
 /// - File-based logging with JSON formatting (DEBUG level)
 /// - Console output for development (INFO level)
 /// - Optional Langfuse integration (DEBUG level)
+/// - Optional OpenTelemetry export over OTLP (INFO level)
 pub fn setup_logging(name: Option<&str>) -> Result<()> {
     // Set up file appender for goose module logs
     let log_dir = get_log_directory()?;
@@ -91,10 +92,11 @@ pub fn setup_logging(name: Option<&str>) -> Result<()> {
             .add_directive(LevelFilter::WARN.into())
     });
 
-    // Build the subscriber with required layers
+    // Build the subscriber with required layers, exporting over OTLP when configured
     let subscriber = Registry::default()
         .with(file_layer.with_filter(env_filter))
-        .with(console_layer.with_filter(LevelFilter::INFO));
+        .with(console_layer.with_filter(LevelFilter::INFO))
+        .with(otlp_layer::create_otlp_layer("goosed"));
 
     // Initialize with Langfuse if available
     if let Some(langfuse) = langfuse_layer::create_langfuse_observer() {
@@ -35,6 +35,9 @@ use utoipa::OpenApi;
         super::routes::config_management::read_all_config,
         super::routes::config_management::providers,
         super::routes::config_management::upsert_permissions,
+        super::routes::config_management::list_profiles,
+        super::routes::config_management::add_profile,
+        super::routes::config_management::use_profile,
         super::routes::agent::get_tools,
         super::routes::reply::confirm_permission,
         super::routes::context::manage_context,
@@ -49,7 +52,9 @@ use utoipa::OpenApi;
         super::routes::schedule::unpause_schedule,
         super::routes::schedule::kill_running_job,
         super::routes::schedule::inspect_running_job,
-        super::routes::schedule::sessions_handler
+        super::routes::schedule::sessions_handler,
+        super::routes::schedule::webhook_handler,
+        super::routes::schedule::history_handler
     ),
     components(schemas(
         super::routes::config_management::UpsertConfigQuery,
@@ -61,6 +66,8 @@ use utoipa::OpenApi;
         super::routes::config_management::ExtensionQuery,
         super::routes::config_management::ToolPermission,
         super::routes::config_management::UpsertPermissionsQuery,
+        super::routes::config_management::ProfilesResponse,
+        super::routes::config_management::ProfileQuery,
         super::routes::reply::PermissionConfirmationRequest,
         super::routes::context::ContextManageRequest,
         super::routes::context::ContextManageResponse,
@@ -102,6 +109,18 @@ use utoipa::OpenApi;
         super::routes::schedule::KillJobResponse,
         super::routes::schedule::InspectJobResponse,
         goose::scheduler::ScheduledJob,
+        goose::scheduler_triggers::JobTrigger,
+        goose::scheduler_triggers::CompletionStatus,
+        goose::scheduler_runs::RunPolicy,
+        goose::scheduler_runs::RetryPolicy,
+        goose::scheduler_runs::OverlapPolicy,
+        goose::scheduler_runs::CatchUpPolicy,
+        goose::scheduler_runs::JobRun,
+        goose::scheduler_runs::RunReason,
+        goose::scheduler_runs::RunStatus,
+        goose::scheduler_notifiers::JobNotifier,
+        goose::scheduler_notifiers::NotifierTarget,
+        goose::scheduler_notifiers::NotifyWhen,
         super::routes::schedule::RunNowResponse,
         super::routes::schedule::ListSchedulesResponse,
         super::routes::schedule::SessionsQuery,
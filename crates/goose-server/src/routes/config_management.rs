@@ -6,10 +6,9 @@ use axum::{
     routing::{delete, get, post},
     Json, Router,
 };
-use etcetera::{choose_app_strategy, AppStrategy};
-use goose::config::Config;
-use goose::config::APP_STRATEGY;
+use goose::config::profiles::validate_profile_name;
 use goose::config::{extensions::name_to_key, PermissionManager};
+use goose::config::{Config, ProfileManager};
 use goose::config::{ExtensionConfigManager, ExtensionEntry};
 use goose::model::ModelConfig;
 use goose::providers::base::ProviderMetadata;
@@ -22,7 +21,7 @@ use http::{HeaderMap, StatusCode};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use serde_yaml;
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
 use utoipa::ToSchema;
 
 #[derive(Serialize, ToSchema)]
@@ -80,6 +79,21 @@ pub struct UpsertPermissionsQuery {
     pub tool_permissions: Vec<ToolPermission>,
 }
 
+#[derive(Serialize, ToSchema)]
+pub struct ProfilesResponse {
+    /// All profiles, the default profile first
+    pub profiles: Vec<String>,
+    /// The profile this server is running with
+    pub current: String,
+    /// The profile new sessions start with unless GOOSE_PROFILE is set
+    pub selected: String,
+}
+
+#[derive(Deserialize, ToSchema)]
+pub struct ProfileQuery {
+    pub name: String,
+}
+
 #[utoipa::path(
     post,
     path = "/config/upsert",
@@ -280,7 +294,7 @@ pub async fn read_all_config(
     let config = Config::global();
 
     let values = config
-        .load_values()
+        .load_effective_values()
         .map_err(|_| StatusCode::UNPROCESSABLE_ENTITY)?;
 
     Ok(Json(ConfigResponse { config: values }))
@@ -512,11 +526,7 @@ pub async fn backup_config(
 ) -> Result<Json<String>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let config_dir = choose_app_strategy(APP_STRATEGY.clone())
-        .expect("goose requires a home dir")
-        .config_dir();
-
-    let config_path = config_dir.join("config.yaml");
+    let config_path = PathBuf::from(Config::global().path());
 
     if config_path.exists() {
         let file_name = config_path
@@ -587,11 +597,7 @@ pub async fn validate_config(
 ) -> Result<Json<String>, StatusCode> {
     verify_secret_key(&headers, &state)?;
 
-    let config_dir = choose_app_strategy(APP_STRATEGY.clone())
-        .expect("goose requires a home dir")
-        .config_dir();
-
-    let config_path = config_dir.join("config.yaml");
+    let config_path = PathBuf::from(Config::global().path());
 
     if !config_path.exists() {
         return Ok(Json("Config file does not exist".to_string()));
@@ -632,6 +638,94 @@ pub async fn get_current_model(
     })))
 }
 
+fn profiles_response() -> Result<ProfilesResponse, StatusCode> {
+    let manager = ProfileManager::default();
+    Ok(ProfilesResponse {
+        profiles: manager
+            .list()
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?,
+        current: Config::global().profile().to_string(),
+        selected: manager.selected(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    path = "/config/profiles",
+    responses(
+        (status = 200, description = "Profiles listed successfully", body = ProfilesResponse),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_profiles(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    Ok(Json(profiles_response()?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/config/profiles",
+    request_body = ProfileQuery,
+    responses(
+        (status = 200, description = "Profile created successfully", body = ProfilesResponse),
+        (status = 400, description = "Invalid profile name"),
+        (status = 409, description = "Profile already exists"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn add_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<ProfileQuery>,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    if validate_profile_name(&query.name).is_err() {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+    let manager = ProfileManager::default();
+    if manager.exists(&query.name) {
+        return Err(StatusCode::CONFLICT);
+    }
+    manager
+        .add(&query.name)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(profiles_response()?))
+}
+
+#[utoipa::path(
+    post,
+    path = "/config/profiles/use",
+    request_body = ProfileQuery,
+    responses(
+        (status = 200, description = "Profile selected, it applies once the server restarts", body = ProfilesResponse),
+        (status = 404, description = "Profile not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn use_profile(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<ProfileQuery>,
+) -> Result<Json<ProfilesResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let manager = ProfileManager::default();
+    if !manager.exists(&query.name) {
+        return Err(StatusCode::NOT_FOUND);
+    }
+    manager
+        .set_active(&query.name)
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(profiles_response()?))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/config", get(read_all_config))
@@ -649,12 +743,17 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/config/validate", get(validate_config))
         .route("/config/permissions", post(upsert_permissions))
         .route("/config/current-model", get(get_current_model))
+        .route("/config/profiles", get(list_profiles))
+        .route("/config/profiles", post(add_profile))
+        .route("/config/profiles/use", post(use_profile))
         .with_state(state)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use etcetera::{choose_app_strategy, AppStrategy};
+    use goose::config::APP_STRATEGY;
 
     #[tokio::test]
     async fn test_read_model_limits() {
@@ -17,7 +17,7 @@ use utoipa::ToSchema;
 pub struct ContextManageRequest {
     /// Collection of messages to be managed
     pub messages: Vec<Message>,
-    /// Operation to perform: "truncation" or "summarize"
+    /// Operation to perform: "truncation", "summarize" or "compact"
     pub manage_action: String,
 }
 
@@ -71,6 +71,11 @@ async fn manage_context(
             .summarize_context(&request.messages)
             .await
             .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    } else if request.manage_action == "compact" {
+        (processed_messages, token_counts) = agent
+            .compact_context(&request.messages)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     }
 
     Ok(Json(ContextManageResponse {
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::env;
 use std::path::Path;
 use std::sync::Arc;
@@ -5,8 +6,12 @@ use std::sync::OnceLock;
 
 use super::utils::verify_secret_key;
 use crate::state::AppState;
-use axum::{extract::State, routing::post, Json, Router};
-use goose::agents::{extension::Envs, ExtensionConfig};
+use axum::{
+    extract::State,
+    routing::{get, post},
+    Json, Router,
+};
+use goose::agents::{extension::Envs, extension_manager::ExtensionHealth, ExtensionConfig};
 use http::{HeaderMap, StatusCode};
 use serde::{Deserialize, Serialize};
 use tracing;
@@ -315,11 +320,26 @@ async fn remove_extension(
     }
 }
 
+/// Handler for reporting the health of every running extension
+async fn extension_health(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<HashMap<String, ExtensionHealth>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+
+    let agent = state
+        .get_agent()
+        .await
+        .map_err(|_| StatusCode::PRECONDITION_FAILED)?;
+    Ok(Json(agent.extension_health().await))
+}
+
 /// Registers the extension management routes with the Axum router.
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/extensions/add", post(add_extension))
         .route("/extensions/remove", post(remove_extension))
+        .route("/extensions/health", get(extension_health))
         .with_state(state)
 }
 
@@ -0,0 +1,80 @@
+use super::utils::verify_secret_key;
+use crate::state::AppState;
+use axum::{
+    extract::State,
+    http::{header, HeaderMap, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use std::sync::Arc;
+
+/// Set to "true" to serve /metrics without the secret key, for scrapers that can't send it
+pub const METRICS_PUBLIC_ENV_VAR: &str = "GOOSE_SERVER__METRICS_PUBLIC";
+
+fn metrics_public() -> bool {
+    std::env::var(METRICS_PUBLIC_ENV_VAR)
+        .map(|v| v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Prometheus metrics for replies, providers, tools, the scheduler and extensions
+async fn metrics(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    if !metrics_public() {
+        verify_secret_key(&headers, &state)?;
+    }
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        goose::metrics::render(),
+    ))
+}
+
+pub fn routes(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_requires_secret_key() {
+        std::env::remove_var(METRICS_PUBLIC_ENV_VAR);
+        let state = AppState::new(
+            Arc::new(goose::agents::Agent::default()),
+            "test".to_string(),
+        )
+        .await;
+        let app = routes(state);
+
+        let response = app
+            .clone()
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let response = app
+            .oneshot(
+                Request::get("/metrics")
+                    .header("X-Secret-Key", "test")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(String::from_utf8_lossy(&body).contains("goose_active_sessions"));
+    }
+}
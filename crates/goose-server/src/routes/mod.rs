@@ -5,6 +5,7 @@ pub mod config_management;
 pub mod context;
 pub mod extension;
 pub mod health;
+pub mod metrics;
 pub mod recipe;
 pub mod reply;
 pub mod schedule;
@@ -18,6 +19,7 @@ use axum::Router;
 pub fn configure(state: Arc<crate::state::AppState>) -> Router {
     Router::new()
         .merge(health::routes())
+        .merge(metrics::routes(state.clone()))
         .merge(reply::routes(state.clone()))
         .merge(agent::routes(state.clone()))
         .merge(audio::routes(state.clone()))
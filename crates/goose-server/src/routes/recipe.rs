@@ -1,10 +1,20 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
-use axum::{extract::State, http::StatusCode, routing::post, Json, Router};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    routing::{get, post},
+    Json, Router,
+};
 use goose::message::Message;
+use goose::recipe::library::{InstalledSource, LibraryRecipe, RecipeLibrary};
+use goose::recipe::parameters::{apply_parameter_defaults, is_secret, typed_parameter_values};
 use goose::recipe::Recipe;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+use crate::routes::utils::verify_secret_key;
 use crate::state::AppState;
 
 #[derive(Debug, Deserialize)]
@@ -84,8 +94,213 @@ async fn create_recipe(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RecipeParametersRequest {
+    recipe: Recipe,
+    #[serde(default)]
+    values: HashMap<String, Value>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecipeParametersResponse {
+    values: Option<HashMap<String, Value>>,
+    error: Option<String>,
+}
+
+fn parameter_values(request: RecipeParametersRequest) -> anyhow::Result<HashMap<String, Value>> {
+    // The app runs a recipe's prompt in a chat, which a workflow doesn't have
+    if request.recipe.steps.is_some() {
+        anyhow::bail!(
+            "'{}' is a workflow recipe. Run it with 'goose run --recipe' or on a schedule.",
+            request.recipe.title
+        );
+    }
+    // Secrets stay on the server, read from the keyring when the recipe runs. They're left out
+    // here so that checking them against the request's constraints can't reveal them.
+    let (secrets, parameters): (Vec<_>, Vec<_>) = request
+        .recipe
+        .parameters
+        .unwrap_or_default()
+        .into_iter()
+        .partition(is_secret);
+    let mut values: HashMap<String, String> = request
+        .values
+        .into_iter()
+        .filter(|(key, _)| !secrets.iter().any(|secret| &secret.key == key))
+        .map(|(key, value)| match value {
+            Value::String(text) => (key, text),
+            other => (key, other.to_string()),
+        })
+        .collect();
+    apply_parameter_defaults(&parameters, &mut values)?;
+    typed_parameter_values(&parameters, &values)
+}
+
+/// Check parameter values against the types and constraints of the recipe's parameters,
+/// returning the typed values to fill in the recipe with
+async fn validate_recipe_parameters(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<RecipeParametersRequest>,
+) -> Result<Json<RecipeParametersResponse>, (StatusCode, Json<RecipeParametersResponse>)> {
+    verify_secret_key(&headers, &state).map_err(|status| {
+        (
+            status,
+            Json(RecipeParametersResponse {
+                values: None,
+                error: None,
+            }),
+        )
+    })?;
+    match parameter_values(request) {
+        Ok(values) => Ok(Json(RecipeParametersResponse {
+            values: Some(values),
+            error: None,
+        })),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(RecipeParametersResponse {
+                values: None,
+                error: Some(e.to_string()),
+            }),
+        )),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecipeLibraryQuery {
+    #[serde(default)]
+    query: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecipeLibraryResponse {
+    recipes: Vec<LibraryRecipe>,
+    sources: Vec<InstalledSource>,
+}
+
+/// List the recipes in the recipe library, or those matching `query`, along with the sources
+/// installed into it
+async fn list_recipe_library(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(params): Query<RecipeLibraryQuery>,
+) -> Result<Json<RecipeLibraryResponse>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    let library = RecipeLibrary::default();
+    let recipes = match params.query.as_deref().filter(|q| !q.trim().is_empty()) {
+        Some(query) => library.search(query),
+        None => library.recipes(),
+    };
+    let listing = recipes.and_then(|recipes| {
+        Ok(RecipeLibraryResponse {
+            recipes,
+            sources: library.sources()?,
+        })
+    });
+    listing.map(Json).map_err(|e| {
+        tracing::error!("Failed to list the recipe library: {}", e);
+        StatusCode::INTERNAL_SERVER_ERROR
+    })
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/recipe/create", post(create_recipe))
+        .route("/recipe/parameters", post(validate_recipe_parameters))
+        .route("/recipe/library", get(list_recipe_library))
         .with_state(state)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request(values: Value) -> RecipeParametersRequest {
+        serde_json::from_value(json!({
+            "recipe": {
+                "version": "1.0.0",
+                "title": "Deploy",
+                "description": "Deploys a service",
+                "prompt": "Deploy to {{ environment }} with {{ replicas }} replicas",
+                "parameters": [
+                    {
+                        "key": "environment",
+                        "input_type": "select",
+                        "requirement": "required",
+                        "description": "where to deploy",
+                        "options": ["staging", "production"]
+                    },
+                    {
+                        "key": "replicas",
+                        "input_type": "number",
+                        "requirement": "optional",
+                        "description": "how many replicas",
+                        "default": "2",
+                        "max": 5
+                    },
+                    {
+                        "key": "token",
+                        "input_type": "secret",
+                        "requirement": "required",
+                        "description": "deploy token"
+                    }
+                ]
+            },
+            "values": values
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parameter_values() {
+        let values = parameter_values(request(
+            json!({"environment": "staging", "token": "s3cret"}),
+        ))
+        .unwrap();
+        assert_eq!(
+            values,
+            HashMap::from([
+                ("environment".to_string(), json!("staging")),
+                ("replicas".to_string(), json!(2)),
+            ])
+        );
+
+        let err = parameter_values(request(json!({
+            "environment": "qa",
+            "replicas": 9,
+            "token": "s3cret"
+        })))
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("'environment' must be one of: staging, production"));
+        assert!(err.contains("'replicas' must be at most 5"));
+    }
+
+    #[test]
+    fn test_secrets_are_not_checked() {
+        // Left for the keyring when the recipe runs, so neither a missing secret nor a
+        // constraint on one is checked here
+        let mut with_pattern = request(json!({"environment": "staging", "token": "s3cret"}));
+        let parameters = with_pattern.recipe.parameters.as_mut().unwrap();
+        parameters[2].pattern = Some("x+".to_string());
+        let values = parameter_values(with_pattern).unwrap();
+        assert!(!values.contains_key("token"));
+
+        let values = parameter_values(request(json!({"environment": "staging"}))).unwrap();
+        assert!(!values.contains_key("token"));
+    }
+
+    #[test]
+    fn test_workflow_recipes_are_rejected() {
+        let mut request = request(json!({"environment": "staging", "token": "s3cret"}));
+        request.recipe.prompt = None;
+        request.recipe.steps = Some(vec![serde_json::from_value(
+            json!({"id": "deploy", "prompt": "Deploy"}),
+        )
+        .unwrap()]);
+        let err = parameter_values(request).unwrap_err().to_string();
+        assert!(err.contains("workflow recipe"));
+    }
+}
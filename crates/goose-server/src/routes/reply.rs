@@ -93,6 +93,13 @@ enum MessageEvent {
         model: String,
         mode: String,
     },
+    HistoryReplaced {
+        messages: Vec<Message>,
+    },
+    ExtensionRestarted {
+        name: String,
+        error: Option<String>,
+    },
     Notification {
         request_id: String,
         message: JsonRpcMessage,
@@ -264,6 +271,32 @@ async fn handler(
                                 ).await;
                             }
                         }
+                        Ok(Some(Ok(AgentEvent::HistoryReplaced(messages)))) => {
+                            all_messages = messages.clone();
+                            if let Err(e) = stream_event(MessageEvent::HistoryReplaced { messages }, &tx).await {
+                                tracing::error!("Error sending compacted history through channel: {}", e);
+                                let _ = stream_event(
+                                    MessageEvent::Error {
+                                        error: e.to_string(),
+                                    },
+                                    &tx,
+                                ).await;
+                            }
+                        }
+                        Ok(Some(Ok(AgentEvent::ExtensionRestarted(restart)))) => {
+                            if let Err(e) = stream_event(MessageEvent::ExtensionRestarted {
+                                name: restart.name,
+                                error: restart.error,
+                            }, &tx).await {
+                                tracing::error!("Error sending extension restart through channel: {}", e);
+                                let _ = stream_event(
+                                    MessageEvent::Error {
+                                        error: e.to_string(),
+                                    },
+                                    &tx,
+                                ).await;
+                            }
+                        }
                         Ok(Some(Ok(AgentEvent::McpNotification((request_id, n))))) => {
                             if let Err(e) = stream_event(MessageEvent::Notification{
                                 request_id: request_id.clone(),
@@ -391,6 +424,16 @@ async fn ask_handler(
                 // Log model change for non-streaming
                 tracing::info!("Model changed to {} in {} mode", model, mode);
             }
+            Ok(AgentEvent::HistoryReplaced(messages)) => {
+                all_messages = messages;
+            }
+            Ok(AgentEvent::ExtensionRestarted(restart)) => {
+                tracing::warn!(
+                    "Extension '{}' was restarted: {:?}",
+                    restart.name,
+                    restart.error
+                );
+            }
             Ok(AgentEvent::McpNotification(n)) => {
                 // Handle notifications if needed
                 tracing::info!("Received notification: {:?}", n);
@@ -1,6 +1,7 @@
 use std::sync::Arc;
 
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
     routing::{delete, get, post, put},
@@ -13,6 +14,9 @@ use chrono::NaiveDateTime;
 use crate::routes::utils::verify_secret_key;
 use crate::state::AppState;
 use goose::scheduler::ScheduledJob;
+use goose::scheduler_notifiers::JobNotifier;
+use goose::scheduler_runs::{JobRun, RunPolicy};
+use goose::scheduler_triggers::JobTrigger;
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
 pub struct CreateScheduleRequest {
@@ -21,6 +25,12 @@ pub struct CreateScheduleRequest {
     cron: String,
     #[serde(default)]
     execution_mode: Option<String>, // "foreground" or "background"
+    #[serde(default)]
+    trigger: Option<JobTrigger>, // Runs the job on an event instead of the cron expression
+    #[serde(flatten)]
+    policy: RunPolicy, // Retries, overlapping runs and missed runs
+    #[serde(default)]
+    notifiers: Vec<JobNotifier>, // Who to tell when a run finishes
 }
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
@@ -54,7 +64,7 @@ pub struct RunNowResponse {
     session_id: String,
 }
 
-// Query parameters for the sessions endpoint
+// Query parameters for the sessions and history endpoints
 #[derive(Deserialize, utoipa::ToSchema, utoipa::IntoParams)]
 pub struct SessionsQuery {
     #[serde(default = "default_limit")]
@@ -127,6 +137,9 @@ async fn create_schedule(
         current_session_id: None,
         process_start_time: None,
         execution_mode: req.execution_mode.or(Some("background".to_string())), // Default to background
+        trigger: req.trigger,
+        policy: req.policy,
+        notifiers: req.notifiers,
     };
     scheduler
         .add_scheduled_job(job.clone())
@@ -141,7 +154,7 @@ async fn create_schedule(
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             }
         })?;
-    Ok(Json(job))
+    Ok(Json(job.without_secrets()))
 }
 
 #[utoipa::path(
@@ -169,6 +182,10 @@ async fn list_schedules(
         eprintln!("Error listing schedules: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })?;
+    let jobs = jobs
+        .into_iter()
+        .map(ScheduledJob::without_secrets)
+        .collect();
     Ok(Json(ListSchedulesResponse { jobs }))
 }
 
@@ -440,7 +457,7 @@ async fn update_schedule(
         .find(|job| job.id == id)
         .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(Json(updated_job))
+    Ok(Json(updated_job.without_secrets()))
 }
 
 #[utoipa::path(
@@ -529,6 +546,101 @@ pub async fn inspect_running_job(
     }
 }
 
+#[utoipa::path(
+    post,
+    path = "/schedule/{id}/webhook",
+    params(
+        ("id" = String, Path, description = "ID of the webhook-triggered schedule to run")
+    ),
+    request_body(content = String, description = "JSON payload, signed with the job's webhook secret", content_type = "application/json"),
+    responses(
+        (status = 202, description = "Scheduled job started"),
+        (status = 400, description = "Payload is not JSON"),
+        (status = 401, description = "Missing, invalid or expired signature, or no webhook-triggered job with this ID"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+pub async fn webhook_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    body: Bytes,
+) -> Result<StatusCode, StatusCode> {
+    // Authenticated by the HMAC signature of the timestamp and payload rather than the secret
+    // key, so other services can deliver webhooks
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .ok_or(StatusCode::UNAUTHORIZED)
+    };
+    let timestamp = header("X-Goose-Timestamp")?;
+    let signature = header("X-Goose-Signature")?;
+    let scheduler = state
+        .scheduler()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    scheduler
+        .trigger_webhook(&id, &body, timestamp, signature)
+        .await
+        .map_err(|e| {
+            eprintln!("Error running webhook for schedule '{}': {:?}", id, e);
+            // Unknown jobs look like bad signatures, so callers can't find out which jobs exist
+            match e {
+                goose::scheduler::SchedulerError::JobNotFound(_)
+                | goose::scheduler::SchedulerError::InvalidWebhookSignature(_) => {
+                    StatusCode::UNAUTHORIZED
+                }
+                goose::scheduler::SchedulerError::AnyhowError(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+#[utoipa::path(
+    get,
+    path = "/schedule/{id}/history",
+    params(
+        ("id" = String, Path, description = "ID of the schedule"),
+        SessionsQuery
+    ),
+    responses(
+        (status = 200, description = "The schedule's most recent runs, newest first", body = Vec<JobRun>),
+        (status = 404, description = "Scheduled job not found"),
+        (status = 500, description = "Internal server error")
+    ),
+    tag = "schedule"
+)]
+#[axum::debug_handler]
+pub async fn history_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<String>,
+    Query(query_params): Query<SessionsQuery>,
+) -> Result<Json<Vec<JobRun>>, StatusCode> {
+    verify_secret_key(&headers, &state)?;
+    let scheduler = state
+        .scheduler()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    let runs = scheduler
+        .run_history(&id, query_params.limit as usize)
+        .await
+        .map_err(|e| {
+            eprintln!("Error fetching history for schedule '{}': {:?}", id, e);
+            match e {
+                goose::scheduler::SchedulerError::JobNotFound(_) => StatusCode::NOT_FOUND,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            }
+        })?;
+    Ok(Json(runs))
+}
+
 pub fn routes(state: Arc<AppState>) -> Router {
     Router::new()
         .route("/schedule/create", post(create_schedule))
@@ -541,5 +653,7 @@ pub fn routes(state: Arc<AppState>) -> Router {
         .route("/schedule/{id}/kill", post(kill_running_job))
         .route("/schedule/{id}/inspect", get(inspect_running_job))
         .route("/schedule/{id}/sessions", get(sessions_handler)) // Corrected
+        .route("/schedule/{id}/webhook", post(webhook_handler))
+        .route("/schedule/{id}/history", get(history_handler))
         .with_state(state)
 }
@@ -1,20 +1,27 @@
 use std::collections::HashMap;
 use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
 use futures::stream::BoxStream;
 use futures::{stream, FutureExt, Stream, StreamExt, TryStreamExt};
 use mcp_core::protocol::JsonRpcMessage;
+use mcp_core::role::Role;
 
 use crate::agents::final_output_tool::{FINAL_OUTPUT_CONTINUATION_MESSAGE, FINAL_OUTPUT_TOOL_NAME};
+use crate::agents::sub_recipe_execution_tool::lib::TaskContext;
 use crate::agents::sub_recipe_execution_tool::sub_recipe_execute_task_tool::{
     self, SUB_RECIPE_EXECUTE_TASK_TOOL_NAME,
 };
 use crate::agents::sub_recipe_manager::SubRecipeManager;
 use crate::config::{Config, ExtensionConfigManager, PermissionManager};
-use crate::message::Message;
+use crate::hooks::{HookEvent, Hooks};
+use crate::message::{Message, ToolRequest};
+use crate::metrics;
 use crate::permission::permission_judge::check_tool_permissions;
 use crate::permission::PermissionConfirmation;
 use crate::providers::base::Provider;
@@ -22,13 +29,17 @@ use crate::providers::errors::ProviderError;
 use crate::recipe::{Author, Recipe, Response, Settings, SubRecipe};
 use crate::scheduler_trait::SchedulerTrait;
 use crate::tool_monitor::{ToolCall, ToolMonitor};
+use crate::tracing::gen_ai;
 use regex::Regex;
 use serde_json::Value;
 use tokio::sync::{mpsc, Mutex, RwLock};
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, instrument, warn, Instrument, Span};
 
+use crate::agents::code_index::{self, CodeIndex, SEMANTIC_SEARCH_TOOL_NAME};
 use crate::agents::extension::{ExtensionConfig, ExtensionError, ExtensionResult, ToolInfo};
-use crate::agents::extension_manager::{get_parameter_names, ExtensionManager};
+use crate::agents::extension_manager::{
+    get_parameter_names, ExtensionHealth, ExtensionManager, ExtensionRestart,
+};
 use crate::agents::platform_tools::{
     PLATFORM_LIST_RESOURCES_TOOL_NAME, PLATFORM_MANAGE_EXTENSIONS_TOOL_NAME,
     PLATFORM_MANAGE_SCHEDULE_TOOL_NAME, PLATFORM_READ_RESOURCE_TOOL_NAME,
@@ -76,13 +87,26 @@ pub struct Agent {
     pub(super) scheduler_service: Mutex<Option<Arc<dyn SchedulerTrait>>>,
     pub(super) subagent_manager: Mutex<Option<SubAgentManager>>,
     pub(super) mcp_notification_rx: Arc<Mutex<mpsc::Receiver<JsonRpcMessage>>>,
+    pub(super) session_started: AtomicBool,
+    pub(super) code_index: Mutex<Option<Arc<CodeIndex>>>,
+    /// The session of the reply in progress, which sub-recipe runs are linked to
+    pub(super) current_session: Mutex<Option<SessionConfig>>,
+    /// Hooks loaded for the session's working directory, reused until it changes
+    pub(super) hooks: Mutex<Option<Hooks>>,
 }
 
 #[derive(Clone, Debug)]
 pub enum AgentEvent {
     Message(Message),
     McpNotification((String, JsonRpcMessage)),
-    ModelChange { model: String, mode: String },
+    ModelChange {
+        model: String,
+        mode: String,
+    },
+    /// The conversation was compacted; these messages replace the history so far
+    HistoryReplaced(Vec<Message>),
+    /// An extension lost its connection and goose restarted it, or failed to
+    ExtensionRestarted(ExtensionRestart),
 }
 
 impl Default for Agent {
@@ -151,6 +175,10 @@ impl Agent {
             // Initialize with MCP notification support
             subagent_manager: Mutex::new(Some(SubAgentManager::new(mcp_tx))),
             mcp_notification_rx: Arc::new(Mutex::new(mcp_rx)),
+            session_started: AtomicBool::new(false),
+            code_index: Mutex::new(None),
+            current_session: Mutex::new(None),
+            hooks: Mutex::new(None),
         }
     }
 
@@ -220,17 +248,153 @@ impl Agent {
         self.extend_system_prompt(final_output_system_prompt).await;
     }
 
+    /// The output collected by the final output tool, once the agent has called it
+    pub async fn final_output(&self) -> Option<String> {
+        self.final_output_tool
+            .lock()
+            .await
+            .as_ref()
+            .and_then(|tool| tool.final_output.clone())
+    }
+
     pub async fn add_sub_recipes(&self, sub_recipes: Vec<SubRecipe>) {
         let mut sub_recipe_manager = self.sub_recipe_manager.lock().await;
         sub_recipe_manager.add_sub_recipe_tools(sub_recipes);
     }
 
-    /// Dispatch a single tool call to the appropriate client
-    #[instrument(skip(self, tool_call, request_id), fields(input, output))]
+    /// Dispatch a single tool call to the appropriate client, after its `PreToolUse` hooks
     pub async fn dispatch_tool_call(
         &self,
         tool_call: mcp_core::tool::ToolCall,
         request_id: String,
+    ) -> (String, Result<ToolCallResult, ToolError>) {
+        let hooks = self.hooks_for(&self.working_dir().await).await;
+        match self.apply_pre_tool_use_hooks(&hooks, tool_call).await {
+            Ok(tool_call) => self.dispatch_hooked_tool_call(tool_call, request_id).await,
+            Err(e) => (request_id, Err(e)),
+        }
+    }
+
+    /// Run the `PreToolUse` hooks for a tool call, returning the arguments they settled on
+    async fn apply_pre_tool_use_hooks(
+        &self,
+        hooks: &Hooks,
+        mut tool_call: mcp_core::tool::ToolCall,
+    ) -> Result<mcp_core::tool::ToolCall, ToolError> {
+        if !hooks.has(HookEvent::PreToolUse) {
+            return Ok(tool_call);
+        }
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &tool_call))
+            .await;
+        if let Some(reason) = outcome.block_reason {
+            return Err(ToolError::ExecutionError(format!(
+                "Tool call blocked by hook: {}",
+                reason
+            )));
+        }
+        if let Some(arguments) = outcome.updated_tool_input {
+            tool_call.arguments = arguments;
+        }
+        Ok(tool_call)
+    }
+
+    /// Run `PreToolUse` hooks over the requests before their permissions are checked, so the
+    /// arguments that get approved are the ones that run. Blocked requests are answered here.
+    async fn apply_pre_tool_use_hooks_to_requests(
+        &self,
+        hooks: &Hooks,
+        requests: Vec<ToolRequest>,
+        message_tool_response: &Mutex<Message>,
+    ) -> Vec<ToolRequest> {
+        let mut hooked = Vec::with_capacity(requests.len());
+        for request in requests {
+            let Ok(tool_call) = request.tool_call.clone() else {
+                hooked.push(request);
+                continue;
+            };
+            match self.apply_pre_tool_use_hooks(hooks, tool_call).await {
+                Ok(tool_call) => hooked.push(ToolRequest {
+                    id: request.id,
+                    tool_call: Ok(tool_call),
+                }),
+                Err(e) => {
+                    let mut response = message_tool_response.lock().await;
+                    *response = response.clone().with_tool_response(request.id, Err(e));
+                }
+            }
+        }
+        hooked
+    }
+
+    /// Dispatch a tool call whose `PreToolUse` hooks have already run
+    #[instrument(skip(self, tool_call, request_id), fields(input, output))]
+    pub(crate) async fn dispatch_hooked_tool_call(
+        &self,
+        tool_call: mcp_core::tool::ToolCall,
+        request_id: String,
+    ) -> (String, Result<ToolCallResult, ToolError>) {
+        let tool_name = tool_call.name.clone();
+        let span = gen_ai::tool_span(&tool_name, &request_id);
+        let start = Instant::now();
+        let (request_id, result) = self
+            .dispatch_tool_call_inner(tool_call, request_id)
+            .instrument(span.clone())
+            .await;
+        match result {
+            Ok(result) => {
+                let output = result.result;
+                let output = async move {
+                    let output = output.await;
+                    gen_ai::record_tool_call(
+                        &Span::current(),
+                        &tool_name,
+                        start.elapsed(),
+                        output.as_ref().err(),
+                    );
+                    output
+                }
+                .instrument(span);
+                (
+                    request_id,
+                    Ok(ToolCallResult {
+                        notification_stream: result.notification_stream,
+                        result: Box::new(Box::pin(output)),
+                    }),
+                )
+            }
+            Err(e) => {
+                gen_ai::record_tool_call(&span, &tool_name, start.elapsed(), Some(&e));
+                (request_id, Err(e))
+            }
+        }
+    }
+
+    /// The hooks for `working_dir`, loaded from disk only when the directory changes
+    async fn hooks_for(&self, working_dir: &Path) -> Hooks {
+        let mut cached = self.hooks.lock().await;
+        match cached.as_ref() {
+            Some(hooks) if hooks.cwd() == working_dir => hooks.clone(),
+            _ => {
+                let hooks = Hooks::load(working_dir);
+                *cached = Some(hooks.clone());
+                hooks
+            }
+        }
+    }
+
+    /// The working directory of the current session, or of the process without one
+    async fn working_dir(&self) -> PathBuf {
+        match self.current_session.lock().await.as_ref() {
+            Some(session) => session.working_dir.clone(),
+            None => std::env::current_dir().unwrap_or_default(),
+        }
+    }
+
+    async fn dispatch_tool_call_inner(
+        &self,
+        tool_call: mcp_core::tool::ToolCall,
+        request_id: String,
     ) -> (String, Result<ToolCallResult, ToolError>) {
         // Check if this tool call should be allowed based on repetition monitoring
         if let Some(monitor) = self.tool_monitor.lock().await.as_mut() {
@@ -294,7 +458,12 @@ impl Agent {
                 .dispatch_sub_recipe_tool_call(&tool_call.name, tool_call.arguments.clone())
                 .await
         } else if tool_call.name == SUB_RECIPE_EXECUTE_TASK_TOOL_NAME {
-            sub_recipe_execute_task_tool::run_tasks(tool_call.arguments.clone()).await
+            let context = TaskContext {
+                provider: self.provider().await.ok(),
+                parent_session: self.current_session.lock().await.clone(),
+                working_dir: None,
+            };
+            sub_recipe_execute_task_tool::run_tasks(tool_call.arguments.clone(), context).await
         } else if tool_call.name == PLATFORM_READ_RESOURCE_TOOL_NAME {
             // Check if the tool is read_resource and handle it separately
             ToolCallResult::from(
@@ -315,6 +484,8 @@ impl Agent {
                 self.handle_run_subagent_task(tool_call.arguments.clone())
                     .await,
             )
+        } else if tool_call.name == SEMANTIC_SEARCH_TOOL_NAME {
+            ToolCallResult::from(self.semantic_search(tool_call.arguments.clone()).await)
         } else if self.is_frontend_tool(&tool_call.name).await {
             // For frontend tools, return an error indicating we need frontend execution
             ToolCallResult::from(Err(ToolError::ExecutionError(
@@ -358,15 +529,24 @@ impl Agent {
             }
         };
 
+        let output = result
+            .result
+            .map(super::large_response_handler::process_tool_response);
+        let output: Box<dyn Future<Output = ToolResult<Vec<Content>>> + Send + Unpin> =
+            if hooks.has(HookEvent::PostToolUse) {
+                Box::new(Box::pin(async move {
+                    let output = output.await;
+                    hooks.run_post_tool_use(&tool_call, output).await
+                }))
+            } else {
+                Box::new(output)
+            };
+
         (
             request_id,
             Ok(ToolCallResult {
                 notification_stream: result.notification_stream,
-                result: Box::new(
-                    result
-                        .result
-                        .map(super::large_response_handler::process_tool_response),
-                ),
+                result: output,
             }),
         )
     }
@@ -481,6 +661,12 @@ impl Agent {
         (request_id, result)
     }
 
+    /// Start the local extensions added from now on in `dir`, for agents that don't work in
+    /// the current directory
+    pub async fn set_working_dir(&self, dir: PathBuf) {
+        self.extension_manager.write().await.set_working_dir(dir);
+    }
+
     pub async fn add_extension(&self, extension: ExtensionConfig) -> ExtensionResult<()> {
         match &extension {
             ExtensionConfig::Frontend {
@@ -571,6 +757,12 @@ impl Agent {
             }
         }
 
+        if extension_name.is_none() || extension_name.as_deref() == Some("developer") {
+            if self.code_index.lock().await.is_some() {
+                prefixed_tools.push(code_index::semantic_search_tool());
+            }
+        }
+
         if extension_name.is_none() {
             let sub_recipe_manager = self.sub_recipe_manager.lock().await;
             prefixed_tools.extend(sub_recipe_manager.sub_recipe_tools.values().cloned());
@@ -653,6 +845,11 @@ impl Agent {
             .expect("Failed to list extensions")
     }
 
+    /// Last known health of each extension, from the periodic health checks
+    pub async fn extension_health(&self) -> HashMap<String, ExtensionHealth> {
+        self.extension_manager.read().await.extension_health()
+    }
+
     /// Handle a confirmation response for a tool request
     pub async fn handle_confirmation(
         &self,
@@ -664,7 +861,17 @@ impl Agent {
         }
     }
 
-    #[instrument(skip(self, messages, session), fields(user_message))]
+    #[instrument(
+        skip(self, messages, session),
+        fields(
+            user_message,
+            otel.name = "invoke_agent goose",
+            gen_ai.operation.name = "invoke_agent",
+            gen_ai.agent.name = "goose",
+            gen_ai.conversation.id,
+            goose.schedule.id,
+        )
+    )]
     pub async fn reply(
         &self,
         messages: &[Message],
@@ -676,6 +883,60 @@ impl Agent {
         // Load settings from config
         let config = Config::global();
 
+        let working_dir = session
+            .as_ref()
+            .map(|s| s.working_dir.clone())
+            .unwrap_or_else(|| std::env::current_dir().unwrap_or_default());
+        *self.current_session.lock().await = session.clone();
+        let session_id = session.as_ref().map(|s| s.id.session_id());
+        if let Some(session_id) = &session_id {
+            reply_span.record("gen_ai.conversation.id", session_id.as_str());
+        }
+        if let Some(schedule_id) = session.as_ref().and_then(|s| s.schedule_id.as_deref()) {
+            reply_span.record("goose.schedule.id", schedule_id);
+        }
+        let hooks = self.hooks_for(&working_dir).await;
+
+        if code_index::is_enabled() {
+            self.start_code_index(&working_dir).await;
+        }
+
+        if !self.session_started.swap(true, Ordering::SeqCst) && hooks.has(HookEvent::SessionStart)
+        {
+            let outcome = hooks
+                .run(
+                    hooks
+                        .input(HookEvent::SessionStart)
+                        .with_session_id(session_id.clone()),
+                )
+                .await;
+            for context in outcome.additional_context {
+                self.extend_system_prompt(context).await;
+            }
+        }
+
+        if hooks.has(HookEvent::UserPromptSubmit) {
+            if let Some(last) = messages.last_mut().filter(|m| m.role == Role::User) {
+                let outcome = hooks
+                    .run(
+                        hooks
+                            .prompt_input(&last.as_concat_text())
+                            .with_session_id(session_id.clone()),
+                    )
+                    .await;
+                if let Some(reason) = outcome.block_reason {
+                    let message = Message::assistant()
+                        .with_text(format!("This prompt was blocked by a hook: {}", reason));
+                    return Ok(Box::pin(stream::once(async move {
+                        Ok::<_, anyhow::Error>(AgentEvent::Message(message))
+                    })));
+                }
+                for context in outcome.additional_context {
+                    *last = last.clone().with_text(context);
+                }
+            }
+        }
+
         // Setup tools and prompt
         let (mut tools, mut toolshim_tools, mut system_prompt) =
             self.prepare_tools_and_prompt().await?;
@@ -711,9 +972,10 @@ impl Agent {
             debug!("user_message" = &content);
         }
 
-        Ok(Box::pin(async_stream::try_stream! {
-            let _ = reply_span.enter();
+        let events = async_stream::try_stream! {
+            let _reply_metrics = metrics::reply_started();
             let mut turns_taken = 0u32;
+            let mut stop_hook_active = false;
             let max_turns = session
                 .as_ref()
                 .and_then(|s| s.max_turns)
@@ -730,6 +992,20 @@ impl Agent {
                     break;
                 }
 
+                match self.auto_compact(&messages).await {
+                    Ok(Some(compacted)) => {
+                        messages = compacted;
+                        yield AgentEvent::HistoryReplaced(messages.clone());
+                    }
+                    Ok(None) => {}
+                    Err(e) => warn!("Failed to compact conversation: {}", e),
+                }
+
+                let restarts = self.extension_manager.read().await.take_restarts();
+                for restart in restarts {
+                    yield AgentEvent::ExtensionRestarted(restart);
+                }
+
                 // Check for MCP notifications from subagents
                 let mcp_notifications = self.get_mcp_notifications().await;
                 for notification in mcp_notifications {
@@ -831,6 +1107,21 @@ impl Agent {
                                     yield AgentEvent::Message(message);
                                 }
                             }
+
+                            // A Stop hook can keep the agent going by blocking, with its reason as the next prompt
+                            if hooks.has(HookEvent::Stop) {
+                                let outcome = hooks
+                                    .run(hooks.stop_input(stop_hook_active).with_session_id(session_id.clone()))
+                                    .await;
+                                if let Some(reason) = outcome.block_reason {
+                                    stop_hook_active = true;
+                                    let message = Message::user().with_text(reason);
+                                    messages.push(response);
+                                    messages.push(message.clone());
+                                    yield AgentEvent::Message(message);
+                                    continue;
+                                }
+                            }
                             break;
                         }
 
@@ -865,6 +1156,9 @@ impl Agent {
                             // At this point, we have handled the frontend tool requests and know goose_mode != "chat"
                             // What remains is handling the remaining tool requests (enable extension,
                             // regular tool calls) in goose_mode == ["auto", "approve" or "smart_approve"]
+                            let remaining_requests = self
+                                .apply_pre_tool_use_hooks_to_requests(&hooks, remaining_requests, &message_tool_response)
+                                .await;
                             let mut permission_manager = PermissionManager::default();
                             let (permission_check_result, enable_extension_request_ids) = check_tool_permissions(
                                 &remaining_requests,
@@ -880,7 +1174,7 @@ impl Agent {
                             // Skip the confirmation for approved tools
                             for request in &permission_check_result.approved {
                                 if let Ok(tool_call) = request.tool_call.clone() {
-                                    let (req_id, tool_result) = self.dispatch_tool_call(tool_call, request.id.clone()).await;
+                                    let (req_id, tool_result) = self.dispatch_hooked_tool_call(tool_call, request.id.clone()).await;
 
                                     tool_futures.push((req_id, match tool_result {
                                         Ok(result) => tool_stream(
@@ -1012,7 +1306,14 @@ impl Agent {
                 // Yield control back to the scheduler to prevent blocking
                 tokio::task::yield_now().await;
             }
-        }))
+        };
+        let mut events: BoxStream<'_, anyhow::Result<AgentEvent>> = Box::pin(events);
+
+        // Run every turn inside the reply span, so provider calls and tool calls nest under it
+        Ok(Box::pin(stream::poll_fn(move |cx| {
+            let _enter = reply_span.enter();
+            events.poll_next_unpin(cx)
+        })))
     }
 
     /// Extend the system prompt with one line of additional instruction
@@ -1207,14 +1508,8 @@ impl Agent {
 
         messages.push(Message::user().with_text(recipe_prompt));
 
-        let (result, _usage) = self
-            .provider
-            .lock()
-            .await
-            .as_ref()
-            .unwrap()
-            .complete(&system_prompt, &messages, &tools)
-            .await?;
+        let (result, _usage) =
+            gen_ai::complete(provider.as_ref(), &system_prompt, &messages, &tools).await?;
 
         let content = result.as_concat_text();
 
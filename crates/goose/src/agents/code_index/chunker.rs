@@ -0,0 +1,243 @@
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+
+/// Chunks shorter than this are merged into the one that follows
+const MIN_CHUNK_LINES: usize = 5;
+/// Longer chunks are split so each stays focused and fits an embedding request
+const MAX_CHUNK_LINES: usize = 80;
+/// Paragraphs of prose are grouped until they reach roughly this many lines
+const PARAGRAPH_TARGET_LINES: usize = 40;
+
+/// Files with these extensions are split at symbol definitions
+const CODE_EXTENSIONS: &[&str] = &[
+    "rs", "py", "ts", "tsx", "js", "jsx", "mjs", "go", "java", "kt", "swift", "rb", "php", "cs",
+    "c", "h", "cc", "cpp", "hpp", "scala", "sh",
+];
+/// Files with these extensions are split into paragraphs
+const TEXT_EXTENSIONS: &[&str] = &["md", "mdx", "rst", "txt", "toml", "yaml", "yml"];
+
+/// A line that starts a top-level or member definition in one of the common languages
+static SYMBOL_START: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^\s{0,4}(?:(?:pub(?:\([^)]*\))?|export|default|async|unsafe|static|public|private|protected|abstract|final)\s+)*(?:fn|struct|enum|trait|impl|mod|class|def|function|func|interface|type|macro_rules!)\b",
+    )
+    .unwrap()
+});
+
+/// A contiguous range of lines from a file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Chunk {
+    /// 1-based, inclusive
+    pub start_line: usize,
+    /// 1-based, inclusive
+    pub end_line: usize,
+    /// The definition or first line the chunk starts with
+    pub symbol: String,
+    pub content: String,
+}
+
+/// Whether the indexer knows how to chunk this file
+pub fn is_indexable(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| CODE_EXTENSIONS.contains(&ext) || TEXT_EXTENSIONS.contains(&ext))
+}
+
+/// Split a file into chunks by symbol for source code and by paragraph for everything else
+pub fn chunk_file(path: &Path, content: &str) -> Vec<Chunk> {
+    let lines: Vec<&str> = content.lines().collect();
+    let is_code = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| CODE_EXTENSIONS.contains(&ext));
+
+    let ranges = if is_code {
+        symbol_ranges(&lines)
+    } else {
+        paragraph_ranges(&lines)
+    };
+
+    ranges
+        .into_iter()
+        .flat_map(|(start, end)| split_long(start, end))
+        .filter_map(|(start, end)| {
+            let body = &lines[start..end];
+            if body.iter().all(|line| line.trim().is_empty()) {
+                return None;
+            }
+            let symbol = body
+                .iter()
+                .find(|line| SYMBOL_START.is_match(line))
+                .or_else(|| body.iter().find(|line| !line.trim().is_empty()))
+                .map(|line| line.trim())
+                .unwrap_or_default()
+                .chars()
+                .take(100)
+                .collect();
+            Some(Chunk {
+                start_line: start + 1,
+                end_line: end,
+                symbol,
+                content: body.join("\n"),
+            })
+        })
+        .collect()
+}
+
+/// Ranges that each begin at a symbol definition, with short ranges folded into the next one
+fn symbol_ranges(lines: &[&str]) -> Vec<(usize, usize)> {
+    let mut starts: Vec<usize> = lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| SYMBOL_START.is_match(line))
+        .map(|(i, _)| leading_comments_start(lines, i))
+        .collect();
+    starts.dedup();
+    if starts.first() != Some(&0) {
+        starts.insert(0, 0);
+    }
+
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for &next in starts.iter().skip(1) {
+        if next - start >= MIN_CHUNK_LINES {
+            ranges.push((start, next));
+            start = next;
+        }
+    }
+    if start < lines.len() {
+        ranges.push((start, lines.len()));
+    }
+    ranges
+}
+
+/// Walk back from a definition over the doc comments, attributes and decorators attached to it
+fn leading_comments_start(lines: &[&str], definition: usize) -> usize {
+    let mut start = definition;
+    while start > 0 {
+        let previous = lines[start - 1].trim_start();
+        let is_preamble = ["//", "/*", "*", "#", "@"]
+            .iter()
+            .any(|prefix| previous.starts_with(prefix));
+        if !is_preamble {
+            break;
+        }
+        start -= 1;
+    }
+    start
+}
+
+/// Blank-line separated paragraphs grouped up to the target size
+fn paragraph_ranges(lines: &[&str]) -> Vec<(usize, usize)> {
+    let mut ranges = Vec::new();
+    let mut start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.trim().is_empty() && i - start >= PARAGRAPH_TARGET_LINES {
+            ranges.push((start, i));
+            start = i + 1;
+        }
+    }
+    if start < lines.len() {
+        ranges.push((start, lines.len()));
+    }
+    ranges
+}
+
+fn split_long(start: usize, end: usize) -> Vec<(usize, usize)> {
+    (start..end)
+        .step_by(MAX_CHUNK_LINES)
+        .map(|chunk_start| (chunk_start, (chunk_start + MAX_CHUNK_LINES).min(end)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rust_source() -> String {
+        let mut source = String::from("use std::fmt;\nuse std::io;\n\n");
+        for name in ["first", "second"] {
+            source.push_str(&format!(
+                "/// Does the {} thing\npub fn {}() {{\n",
+                name, name
+            ));
+            for i in 0..6 {
+                source.push_str(&format!("    let x{} = {};\n", i, i));
+            }
+            source.push_str("}\n\n");
+        }
+        source
+    }
+
+    #[test]
+    fn test_chunks_rust_by_function() {
+        let chunks = chunk_file(Path::new("src/lib.rs"), &rust_source());
+
+        let symbols: Vec<&str> = chunks.iter().map(|c| c.symbol.as_str()).collect();
+        assert_eq!(
+            symbols,
+            vec!["pub fn first() {", "pub fn second() {"],
+            "the short import block is folded into the first function"
+        );
+        assert_eq!(chunks[0].start_line, 1);
+        assert!(chunks[0].content.starts_with("use std::fmt;"));
+        assert_eq!(chunks[1].start_line, chunks[0].end_line + 1);
+        assert!(
+            chunks[1].content.starts_with("/// Does the second thing"),
+            "doc comments stay with the definition they describe"
+        );
+        assert_eq!(
+            chunks.last().unwrap().end_line,
+            rust_source().lines().count()
+        );
+    }
+
+    #[test]
+    fn test_python_methods_start_chunks() {
+        let mut source = String::from("class Greeter:\n");
+        for method in ["hello", "goodbye"] {
+            source.push_str(&format!("    def {}(self):\n", method));
+            for _ in 0..5 {
+                source.push_str("        print('hi')\n");
+            }
+        }
+        let chunks = chunk_file(Path::new("greeter.py"), &source);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].symbol, "class Greeter:");
+        assert_eq!(chunks[1].symbol, "def goodbye(self):");
+    }
+
+    #[test]
+    fn test_long_chunks_are_split() {
+        let source = (0..200)
+            .map(|i| format!("line {}", i))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let chunks = chunk_file(Path::new("notes.txt"), &source);
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks
+            .iter()
+            .all(|c| c.end_line - c.start_line < MAX_CHUNK_LINES));
+        assert_eq!(chunks[2].end_line, 200);
+    }
+
+    #[test]
+    fn test_markdown_paragraphs_are_grouped() {
+        let paragraph = "Some prose\nthat wraps\n\n";
+        let source = paragraph.repeat(30);
+        let chunks = chunk_file(Path::new("README.md"), &source);
+        assert!(chunks.len() > 1);
+        assert!(chunks
+            .iter()
+            .all(|c| c.end_line - c.start_line < MAX_CHUNK_LINES));
+    }
+
+    #[test]
+    fn test_is_indexable() {
+        assert!(is_indexable(Path::new("src/main.rs")));
+        assert!(is_indexable(Path::new("docs/guide.md")));
+        assert!(!is_indexable(Path::new("logo.png")));
+        assert!(!is_indexable(Path::new("Makefile")));
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::{Context, Result};
+use std::env;
+use std::sync::Arc;
+
+use crate::config::Config;
+use crate::model::ModelConfig;
+use crate::providers::{self, base::Provider};
+
+/// Dimensions of the vectors produced by the lexical embedder
+const LEXICAL_DIMENSIONS: usize = 768;
+
+/// Weight of an identifier's sub-words relative to the identifier itself
+const SUBWORD_WEIGHT: f32 = 0.5;
+
+/// Texts are sent to embedding providers in batches of this size
+const PROVIDER_BATCH_SIZE: usize = 64;
+
+/// Turns code chunks and queries into vectors
+pub enum CodeEmbedder {
+    /// An embedding model behind [`Provider::create_embeddings`]
+    Provider {
+        provider: Arc<dyn Provider>,
+        model: String,
+    },
+    /// Offline embedder based on hashed identifiers and their sub-words.
+    /// It needs no model or network access, at the cost of only capturing lexical similarity.
+    Lexical,
+}
+
+impl CodeEmbedder {
+    /// Pick the embedder the same way the vector tool router does: a dedicated
+    /// `GOOSE_EMBEDDING_MODEL_PROVIDER` if configured, otherwise the agent's provider.
+    /// Falls back to the lexical embedder when the provider can't embed, or when
+    /// `GOOSE_CODE_INDEX_EMBEDDING` is set to `lexical`.
+    pub fn from_config(provider: Arc<dyn Provider>) -> Result<Self> {
+        let choice = Config::global()
+            .get_param::<String>("GOOSE_CODE_INDEX_EMBEDDING")
+            .unwrap_or_default();
+        if choice.eq_ignore_ascii_case("lexical") {
+            return Ok(CodeEmbedder::Lexical);
+        }
+
+        let embedding_model = env::var("GOOSE_EMBEDDING_MODEL").ok();
+        let provider = if let Ok(provider_name) = env::var("GOOSE_EMBEDDING_MODEL_PROVIDER") {
+            let model = embedding_model
+                .clone()
+                .unwrap_or_else(|| "text-embedding-3-small".to_string());
+            providers::create(&provider_name, ModelConfig::new(model)).context(format!(
+                "Failed to create {} provider for code index embeddings",
+                provider_name
+            ))?
+        } else {
+            provider
+        };
+
+        if !provider.supports_embeddings() {
+            tracing::info!(
+                "Provider does not support embeddings, using the lexical embedder for the code index"
+            );
+            return Ok(CodeEmbedder::Lexical);
+        }
+
+        let model =
+            embedding_model.unwrap_or_else(|| provider.get_model_config().model_name.clone());
+        Ok(CodeEmbedder::Provider { provider, model })
+    }
+
+    /// Identifies the embedding space, so the index is rebuilt when the model changes
+    pub fn id(&self) -> String {
+        match self {
+            CodeEmbedder::Provider { model, .. } => model.clone(),
+            CodeEmbedder::Lexical => format!("lexical-{}", LEXICAL_DIMENSIONS),
+        }
+    }
+
+    pub async fn embed(&self, texts: Vec<String>) -> Result<Vec<Vec<f32>>> {
+        match self {
+            CodeEmbedder::Lexical => Ok(texts.iter().map(|text| embed_lexical(text)).collect()),
+            CodeEmbedder::Provider { provider, .. } => {
+                let mut vectors = Vec::with_capacity(texts.len());
+                for batch in texts.chunks(PROVIDER_BATCH_SIZE) {
+                    let embeddings = provider
+                        .create_embeddings(batch.to_vec())
+                        .await
+                        .map_err(|e| anyhow::anyhow!("Failed to create embeddings: {}", e))?;
+                    if embeddings.len() != batch.len() {
+                        return Err(anyhow::anyhow!(
+                            "Expected {} embeddings, got {}",
+                            batch.len(),
+                            embeddings.len()
+                        ));
+                    }
+                    vectors.extend(embeddings);
+                }
+                Ok(vectors)
+            }
+        }
+    }
+}
+
+/// Hash identifiers and the words inside them (`parseConfig`, `parse_config` -> `parse`, `config`)
+/// into a fixed size, L2-normalized vector
+pub fn embed_lexical(text: &str) -> Vec<f32> {
+    let mut vector = vec![0.0; LEXICAL_DIMENSIONS];
+
+    let identifiers = text
+        .split(|c: char| !c.is_alphanumeric() && c != '_')
+        .filter(|identifier| !identifier.is_empty());
+
+    for identifier in identifiers {
+        let words = split_identifier(identifier);
+        if words.len() > 1 {
+            vector[bucket(&identifier.to_lowercase())] += 1.0;
+        }
+        for word in words {
+            vector[bucket(&word)] += SUBWORD_WEIGHT;
+        }
+    }
+
+    // Dampen repeated terms so one identifier can't dominate a long chunk
+    for value in &mut vector {
+        if *value > 0.0 {
+            *value = 1.0 + value.ln();
+        }
+    }
+
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        for value in &mut vector {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+/// Split snake_case and camelCase identifiers into lowercase words
+fn split_identifier(identifier: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in identifier.split('_').filter(|part| !part.is_empty()) {
+        let mut word = String::new();
+        let mut previous_lowercase = false;
+        for c in part.chars() {
+            if c.is_uppercase() && previous_lowercase && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            previous_lowercase = c.is_lowercase() || c.is_ascii_digit();
+            word.extend(c.to_lowercase());
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+fn bucket(feature: &str) -> usize {
+    (fnv1a(feature) % LEXICAL_DIMENSIONS as u64) as usize
+}
+
+/// Stable across builds and platforms, unlike `DefaultHasher`
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+        // Lexical vectors are normalized
+        a.iter().zip(b).map(|(x, y)| x * y).sum()
+    }
+
+    #[test]
+    fn test_split_identifier() {
+        assert_eq!(split_identifier("parseConfig"), vec!["parse", "config"]);
+        assert_eq!(split_identifier("parse_config"), vec!["parse", "config"]);
+        assert_eq!(split_identifier("HTTPServer"), vec!["httpserver"]);
+        assert_eq!(split_identifier("load2Files"), vec!["load2", "files"]);
+        assert_eq!(split_identifier("__init__"), vec!["init"]);
+    }
+
+    #[test]
+    fn test_lexical_embedding_matches_across_naming_styles() {
+        let query = embed_lexical("where do we parse the config file");
+        let related = embed_lexical("fn parse_config_file(path: &Path) -> Result<Config>");
+        let unrelated = embed_lexical("fn render_button(label: &str) -> Html");
+        assert_eq!(query.len(), LEXICAL_DIMENSIONS);
+        assert!(cosine_similarity(&query, &related) > cosine_similarity(&query, &unrelated));
+
+        let camel = embed_lexical("function parseConfigFile(path) {}");
+        assert!(cosine_similarity(&query, &camel) > cosine_similarity(&query, &unrelated));
+    }
+}
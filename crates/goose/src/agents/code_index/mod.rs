@@ -0,0 +1,736 @@
+//! Opt-in semantic index of the working directory, stored in the same LanceDB database as
+//! the tool router's vectors and kept fresh by watching for changed files.
+
+mod chunker;
+mod embedder;
+mod store;
+
+use anyhow::{Context, Result};
+use etcetera::{choose_app_strategy, AppStrategy};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{Match, WalkBuilder};
+use indoc::indoc;
+use mcp_core::tool::{Tool, ToolAnnotations};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+use tokio::sync::{mpsc, Mutex};
+
+use crate::agents::tool_vectordb::ToolVectorDB;
+use crate::config::{Config, APP_STRATEGY};
+use chunker::Chunk;
+pub use embedder::CodeEmbedder;
+use store::{ChunkRecord, CodeVectorDB};
+
+pub const SEMANTIC_SEARCH_TOOL_NAME: &str = "developer__semantic_search";
+
+const DEFAULT_REFRESH_INTERVAL_SECS: u64 = 30;
+/// How long file changes settle before the changed files are re-embedded together
+const WATCH_DEBOUNCE: Duration = Duration::from_secs(2);
+pub const DEFAULT_SEARCH_RESULTS: usize = 8;
+/// Larger files are usually generated or vendored
+const MAX_FILE_BYTES: u64 = 512 * 1024;
+/// Stop listing files past this point so a huge tree can't stall the agent
+const MAX_FILES: usize = 20_000;
+/// Upper bound on the text sent to the embedder for a single chunk
+const MAX_EMBED_CHARS: usize = 6_000;
+/// Changed files are embedded together once they add up to this many chunks
+const FLUSH_CHUNKS: usize = 128;
+/// Build and dependency directories, skipped even when no `.gitignore` lists them
+const SKIPPED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "__pycache__",
+];
+
+/// The index is opt-in through `GOOSE_CODE_INDEX`
+pub fn is_enabled() -> bool {
+    Config::global()
+        .get_param::<bool>("GOOSE_CODE_INDEX")
+        .unwrap_or(false)
+}
+
+/// How often the background task looks for changed files when the workspace can't be watched,
+/// from `GOOSE_CODE_INDEX_INTERVAL`
+pub fn refresh_interval() -> Duration {
+    Duration::from_secs(
+        Config::global()
+            .get_param::<u64>("GOOSE_CODE_INDEX_INTERVAL")
+            .unwrap_or(DEFAULT_REFRESH_INTERVAL_SECS)
+            .max(1),
+    )
+}
+
+pub fn semantic_search_tool() -> Tool {
+    Tool::new(
+        SEMANTIC_SEARCH_TOOL_NAME.to_string(),
+        indoc! {r#"
+            Search the current workspace by meaning rather than exact text.
+            Returns the functions, types and documentation sections most related to the query,
+            with their file path and line range. Use this to find where a concept is implemented
+            when you don't know the exact names to grep for, then read or edit the files it points to.
+            Describe what the code does in the query, e.g. "retry with exponential backoff" or
+            "where the config file is parsed".
+        "#}
+        .to_string(),
+        json!({
+            "type": "object",
+            "required": ["query"],
+            "properties": {
+                "query": {"type": "string", "description": "What the code you are looking for does"},
+                "k": {"type": "integer", "description": "The number of results to return (defaults to 8)", "default": DEFAULT_SEARCH_RESULTS},
+                "path": {"type": "string", "description": "Only search files under this path, relative to the workspace root"}
+            }
+        }),
+        Some(ToolAnnotations {
+            title: Some("Semantic code search".to_string()),
+            read_only_hint: true,
+            destructive_hint: false,
+            idempotent_hint: true,
+            open_world_hint: false,
+        }),
+    )
+}
+
+/// What was last indexed, so a refresh only re-embeds files that changed
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    embedder: String,
+    files: HashMap<String, FileState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct FileState {
+    size: u64,
+    modified: u64,
+    hash: String,
+}
+
+struct PendingFile {
+    path: String,
+    state: FileState,
+    chunks: Vec<Chunk>,
+}
+
+enum FileCheck {
+    Unchanged,
+    Changed(PendingFile),
+    /// Deleted, too large or not text
+    Gone,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct RefreshStats {
+    pub indexed: usize,
+    pub removed: usize,
+    pub chunks: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub symbol: String,
+    pub content: String,
+}
+
+impl SearchHit {
+    pub fn to_markdown(&self) -> String {
+        let language = Path::new(&self.path)
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default();
+        format!(
+            "{}:{}-{} ({})\n```{}\n{}\n```",
+            self.path, self.start_line, self.end_line, self.symbol, language, self.content
+        )
+    }
+}
+
+pub struct CodeIndex {
+    root: PathBuf,
+    embedder: CodeEmbedder,
+    db: CodeVectorDB,
+    manifest_path: PathBuf,
+    /// Held for the whole of a refresh, so refreshes never overlap
+    manifest: Mutex<Manifest>,
+    ignore: Gitignore,
+}
+
+impl CodeIndex {
+    pub async fn open(root: PathBuf, embedder: CodeEmbedder) -> Result<Self> {
+        let table_name = table_name(&root);
+        let manifest_path = ToolVectorDB::get_db_path()?
+            .with_file_name("code_index")
+            .join(format!("{}.json", table_name));
+        let db = CodeVectorDB::new(table_name).await?;
+
+        let mut manifest: Manifest = tokio::fs::read_to_string(&manifest_path)
+            .await
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        // Vectors from different models can't be compared, so start over
+        if manifest.embedder != embedder.id() {
+            db.clear().await?;
+            manifest = Manifest {
+                embedder: embedder.id(),
+                files: HashMap::new(),
+            };
+        }
+
+        Ok(Self {
+            ignore: load_ignore(&root),
+            root,
+            embedder,
+            db,
+            manifest_path,
+            manifest: Mutex::new(manifest),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Refresh now, then re-embed files as the watcher reports changes to them until the index
+    /// is dropped. Falls back to a full refresh every `interval` when the workspace can't be
+    /// watched.
+    pub fn watch(self: &Arc<Self>, interval: Duration) {
+        let index = Arc::downgrade(self);
+        let root = self.root.clone();
+        tokio::spawn(async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let watcher = notify::recommended_watcher(
+                move |event: notify::Result<notify::Event>| match event {
+                    Ok(event)
+                        if event.kind.is_create()
+                            || event.kind.is_modify()
+                            || event.kind.is_remove() =>
+                    {
+                        for path in event.paths {
+                            let _ = tx.send(path);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Error watching code index files: {}", e),
+                },
+            )
+            .and_then(|mut watcher| {
+                watcher.watch(&root, RecursiveMode::Recursive)?;
+                Ok(watcher)
+            });
+
+            let Some(first) = index.upgrade() else {
+                return;
+            };
+            log_refresh(first.refresh().await);
+            drop(first);
+
+            // Keep the watcher alive for as long as the task runs
+            let _watcher = match watcher {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    tracing::warn!(
+                        "Can't watch {} for changes, checking every {}s instead: {}",
+                        root.display(),
+                        interval.as_secs(),
+                        e
+                    );
+                    loop {
+                        tokio::time::sleep(interval).await;
+                        let Some(index) = index.upgrade() else {
+                            return;
+                        };
+                        log_refresh(index.refresh().await);
+                    }
+                }
+            };
+
+            while let Some(changed) = rx.recv().await {
+                let mut changed = vec![changed];
+                // Let a burst of changes settle so they are embedded together
+                while let Ok(Some(path)) = tokio::time::timeout(WATCH_DEBOUNCE, rx.recv()).await {
+                    changed.push(path);
+                }
+                let Some(index) = index.upgrade() else {
+                    return;
+                };
+                log_refresh(index.update_files(&changed).await);
+            }
+        });
+    }
+
+    /// Re-embed or forget just the files in `changed`, as reported by the watcher
+    pub async fn update_files(&self, changed: &[PathBuf]) -> Result<RefreshStats> {
+        let mut manifest = self.manifest.lock().await;
+        let result = self.sync_files(&mut manifest, changed).await;
+        self.save_manifest(&manifest).await?;
+        result
+    }
+
+    async fn sync_files(
+        &self,
+        manifest: &mut Manifest,
+        changed: &[PathBuf],
+    ) -> Result<RefreshStats> {
+        let mut stats = RefreshStats::default();
+        let mut pending = Vec::new();
+        let mut removed = Vec::new();
+        let mut seen = HashSet::new();
+
+        for path in changed {
+            let Some(relative) = relative_path(&self.root, path) else {
+                continue;
+            };
+            if !seen.insert(relative.clone()) {
+                continue;
+            }
+            let indexable = chunker::is_indexable(Path::new(&relative))
+                && !relative.split('/').any(|component| component == ".git")
+                && !is_ignored(&self.ignore, &relative)
+                && !is_git_ignored(&self.root, &relative);
+            let check = match indexable {
+                true => self.check_file(manifest, &relative).await,
+                false => FileCheck::Gone,
+            };
+            match check {
+                FileCheck::Unchanged => {}
+                FileCheck::Changed(file) => pending.push(file),
+                FileCheck::Gone if manifest.files.contains_key(&relative) => removed.push(relative),
+                FileCheck::Gone => {}
+            }
+        }
+        self.flush(pending, manifest, &mut stats).await?;
+
+        if !removed.is_empty() {
+            self.db.remove_paths(&removed).await?;
+            for path in &removed {
+                manifest.files.remove(path);
+            }
+            stats.removed = removed.len();
+        }
+        Ok(stats)
+    }
+
+    /// Compare a file against what was last indexed, reading and chunking it when it changed
+    async fn check_file(&self, manifest: &mut Manifest, relative: &str) -> FileCheck {
+        let path = self.root.join(relative);
+        let Ok(metadata) = tokio::fs::metadata(&path).await else {
+            return FileCheck::Gone;
+        };
+        if !metadata.is_file() || metadata.len() > MAX_FILE_BYTES {
+            return FileCheck::Gone;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+
+        let previous = manifest.files.get(relative);
+        if previous.is_some_and(|p| p.size == metadata.len() && p.modified == modified) {
+            return FileCheck::Unchanged;
+        }
+
+        // Skips binary and non UTF-8 files
+        let Ok(content) = tokio::fs::read_to_string(&path).await else {
+            return FileCheck::Gone;
+        };
+
+        let state = FileState {
+            size: metadata.len(),
+            modified,
+            hash: blake3::hash(content.as_bytes()).to_hex().to_string(),
+        };
+        if previous.is_some_and(|p| p.hash == state.hash) {
+            manifest.files.insert(relative.to_string(), state);
+            return FileCheck::Unchanged;
+        }
+
+        FileCheck::Changed(PendingFile {
+            path: relative.to_string(),
+            state,
+            chunks: chunker::chunk_file(Path::new(relative), &content),
+        })
+    }
+
+    /// Re-embed files that were added or changed since the last refresh and forget deleted ones
+    pub async fn refresh(&self) -> Result<RefreshStats> {
+        let mut manifest = self.manifest.lock().await;
+        let result = self.sync(&mut manifest).await;
+        // Keep whatever progress was made, even if embedding failed part way through
+        self.save_manifest(&manifest).await?;
+        result
+    }
+
+    async fn sync(&self, manifest: &mut Manifest) -> Result<RefreshStats> {
+        let mut stats = RefreshStats::default();
+        let mut seen = HashSet::new();
+        let mut pending = Vec::new();
+        let mut pending_chunks = 0;
+
+        for relative in list_files(&self.root, &self.ignore).await {
+            match self.check_file(manifest, &relative).await {
+                FileCheck::Gone => continue,
+                FileCheck::Unchanged => {
+                    seen.insert(relative);
+                }
+                FileCheck::Changed(file) => {
+                    seen.insert(relative);
+                    pending_chunks += file.chunks.len();
+                    pending.push(file);
+                    if pending_chunks >= FLUSH_CHUNKS {
+                        self.flush(std::mem::take(&mut pending), manifest, &mut stats)
+                            .await?;
+                        pending_chunks = 0;
+                    }
+                }
+            }
+        }
+        self.flush(pending, manifest, &mut stats).await?;
+
+        let removed: Vec<String> = manifest
+            .files
+            .keys()
+            .filter(|path| !seen.contains(*path))
+            .cloned()
+            .collect();
+        if !removed.is_empty() {
+            self.db.remove_paths(&removed).await?;
+            for path in &removed {
+                manifest.files.remove(path);
+            }
+            stats.removed = removed.len();
+        }
+
+        Ok(stats)
+    }
+
+    async fn flush(
+        &self,
+        files: Vec<PendingFile>,
+        manifest: &mut Manifest,
+        stats: &mut RefreshStats,
+    ) -> Result<()> {
+        if files.is_empty() {
+            return Ok(());
+        }
+
+        let texts: Vec<String> = files
+            .iter()
+            .flat_map(|file| {
+                file.chunks
+                    .iter()
+                    .map(|chunk| embedding_text(&file.path, chunk))
+            })
+            .collect();
+        let mut vectors = self.embedder.embed(texts).await?.into_iter();
+
+        let mut records = Vec::new();
+        for file in &files {
+            for chunk in &file.chunks {
+                records.push(ChunkRecord {
+                    path: file.path.clone(),
+                    start_line: chunk.start_line as u32,
+                    end_line: chunk.end_line as u32,
+                    symbol: chunk.symbol.clone(),
+                    content: chunk.content.clone(),
+                    vector: vectors
+                        .next()
+                        .context("Embedder returned too few vectors")?,
+                });
+            }
+        }
+
+        let paths: Vec<String> = files.iter().map(|file| file.path.clone()).collect();
+        self.db.remove_paths(&paths).await?;
+        stats.chunks += records.len();
+        self.db.add_chunks(records).await?;
+
+        for file in files {
+            manifest.files.insert(file.path, file.state);
+            stats.indexed += 1;
+        }
+        Ok(())
+    }
+
+    async fn save_manifest(&self, manifest: &Manifest) -> Result<()> {
+        if let Some(parent) = self.manifest_path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create code index directory")?;
+        }
+        tokio::fs::write(&self.manifest_path, serde_json::to_string(manifest)?)
+            .await
+            .context("Failed to save code index manifest")
+    }
+
+    /// Find the chunks closest to `query`. The watcher keeps the index up to date, so this
+    /// doesn't look for changed files itself.
+    pub async fn search(
+        &self,
+        query: &str,
+        k: usize,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<SearchHit>> {
+        let query_vector = self
+            .embedder
+            .embed(vec![query.to_string()])
+            .await?
+            .into_iter()
+            .next()
+            .context("No embedding returned")?;
+
+        let path_prefix = path_prefix
+            .map(|prefix| prefix.trim_start_matches("./"))
+            .filter(|prefix| !prefix.is_empty());
+        let results = self.db.search(query_vector, k, path_prefix).await?;
+
+        Ok(results
+            .into_iter()
+            .map(|(record, _distance)| SearchHit {
+                path: record.path,
+                start_line: record.start_line,
+                end_line: record.end_line,
+                symbol: record.symbol,
+                content: record.content,
+            })
+            .collect())
+    }
+}
+
+fn log_refresh(result: Result<RefreshStats>) {
+    match result {
+        Ok(stats) if stats.indexed > 0 || stats.removed > 0 => {
+            tracing::debug!(
+                "Code index updated {} files ({} chunks), removed {}",
+                stats.indexed,
+                stats.chunks,
+                stats.removed
+            );
+        }
+        Ok(_) => {}
+        Err(e) => tracing::warn!("Failed to refresh code index: {}", e),
+    }
+}
+
+/// The patterns of files kept out of the index on top of `.gitignore`: the global
+/// `.gooseignore` in goose's config directory, then the workspace's `.gooseignore`
+fn load_ignore(root: &Path) -> Gitignore {
+    let mut builder = GitignoreBuilder::new(root);
+    let global = choose_app_strategy(APP_STRATEGY.clone())
+        .map(|strategy| strategy.in_config_dir(".gooseignore"))
+        .ok();
+    let files = global.into_iter().chain([root.join(".gooseignore")]);
+    for file in files.filter(|file| file.is_file()) {
+        if let Some(e) = builder.add(&file) {
+            tracing::warn!("Failed to read {}: {}", file.display(), e);
+        }
+    }
+    builder.build().unwrap_or_else(|e| {
+        tracing::warn!("Invalid ignore patterns for the code index: {}", e);
+        Gitignore::empty()
+    })
+}
+
+fn is_ignored(ignore: &Gitignore, relative: &str) -> bool {
+    ignore
+        .matched_path_or_any_parents(relative, false)
+        .is_ignore()
+}
+
+/// Whether the `.gitignore` files from `root` down to the file's directory ignore it, with
+/// deeper files taking precedence the way git applies them
+fn is_git_ignored(root: &Path, relative: &str) -> bool {
+    let path = root.join(relative);
+    let directories: Vec<&Path> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|dir| dir.starts_with(root))
+        .collect();
+
+    let mut ignored = false;
+    for directory in directories.into_iter().rev() {
+        let file = directory.join(".gitignore");
+        if !file.is_file() {
+            continue;
+        }
+        let (gitignore, _) = Gitignore::new(&file);
+        match gitignore.matched_path_or_any_parents(&path, false) {
+            Match::Ignore(_) => ignored = true,
+            Match::Whitelist(_) => ignored = false,
+            Match::None => {}
+        }
+    }
+    ignored
+}
+
+/// `path` relative to `root` with `/` separators, if it's inside it
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let components: Vec<_> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(components.join("/")).filter(|relative| !relative.is_empty())
+}
+
+/// One table per workspace, named after a hash of its path
+fn table_name(root: &Path) -> String {
+    let hash = blake3::hash(root.to_string_lossy().as_bytes()).to_hex();
+    format!("code_{}", &hash[..16])
+}
+
+/// Include the path so queries that mention a module or file name find its chunks
+fn embedding_text(path: &str, chunk: &Chunk) -> String {
+    format!("{}\n{}", path, chunk.content)
+        .chars()
+        .take(MAX_EMBED_CHARS)
+        .collect()
+}
+
+/// Files that can be indexed, relative to `root` and using `/` separators. Hidden files and
+/// anything a `.gitignore` at any depth ignores are skipped, along with files matching `ignore`.
+async fn list_files(root: &Path, ignore: &Gitignore) -> Vec<String> {
+    let walk_root = root.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || walk(&walk_root))
+        .await
+        .unwrap_or_default();
+
+    files
+        .into_iter()
+        .filter(|path| chunker::is_indexable(Path::new(path)) && !is_ignored(ignore, path))
+        .take(MAX_FILES)
+        .collect()
+}
+
+fn walk(root: &Path) -> Vec<String> {
+    WalkBuilder::new(root)
+        .hidden(true)
+        .require_git(false)
+        .filter_entry(|entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !(is_dir && SKIPPED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref()))
+        })
+        .build()
+        .flatten()
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .filter_map(|entry| relative_path(root, entry.path()))
+        .take(MAX_FILES)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_walk_skips_hidden_and_build_directories() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("src/nested")).unwrap();
+        fs::create_dir_all(dir.path().join("target/debug")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("src/nested/lib.rs"), "fn a() {}").unwrap();
+        fs::write(dir.path().join("target/debug/out.rs"), "fn b() {}").unwrap();
+        fs::write(dir.path().join(".git/config.toml"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "# Readme").unwrap();
+
+        let mut files = walk(dir.path());
+        files.sort();
+        assert_eq!(files, vec!["README.md", "src/nested/lib.rs"]);
+    }
+
+    #[test]
+    fn test_nested_gitignore_files_are_respected() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("web/generated")).unwrap();
+        fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        fs::write(dir.path().join("web/.gitignore"), "generated/\n!keep.log\n").unwrap();
+        fs::write(dir.path().join("web/generated/api.ts"), "").unwrap();
+        fs::write(dir.path().join("web/app.ts"), "").unwrap();
+        fs::write(dir.path().join("web/keep.log"), "").unwrap();
+        fs::write(dir.path().join("debug.log"), "").unwrap();
+
+        let mut files = walk(dir.path());
+        files.sort();
+        assert_eq!(files, vec!["web/app.ts", "web/keep.log"]);
+
+        assert!(is_git_ignored(dir.path(), "web/generated/api.ts"));
+        assert!(is_git_ignored(dir.path(), "debug.log"));
+        assert!(!is_git_ignored(dir.path(), "web/keep.log"));
+        assert!(!is_git_ignored(dir.path(), "web/app.ts"));
+    }
+
+    #[tokio::test]
+    async fn test_list_files_honours_gooseignore() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir_all(dir.path().join("generated")).unwrap();
+        fs::write(dir.path().join(".gooseignore"), "generated/\nsecrets.rs\n").unwrap();
+        fs::write(dir.path().join("generated/api.rs"), "fn a() {}").unwrap();
+        fs::write(dir.path().join("secrets.rs"), "fn b() {}").unwrap();
+        fs::write(dir.path().join("lib.rs"), "fn c() {}").unwrap();
+
+        let ignore = load_ignore(dir.path());
+        assert_eq!(list_files(dir.path(), &ignore).await, vec!["lib.rs"]);
+        assert!(is_ignored(&ignore, "generated/api.rs"));
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_refresh_is_incremental_and_searchable() -> Result<()> {
+        let workspace = TempDir::new()?;
+        let db_dir = TempDir::new()?;
+        std::env::set_var("GOOSE_VECTOR_DB_PATH", db_dir.path().join("tool_db"));
+
+        fs::write(
+            workspace.path().join("config.rs"),
+            "/// Read settings from disk\npub fn parse_config_file(path: &Path) -> Config {\n    todo!()\n}\n",
+        )?;
+        fs::write(
+            workspace.path().join("button.rs"),
+            "pub fn render_button(label: &str) -> Html {\n    todo!()\n}\n",
+        )?;
+
+        let index = CodeIndex::open(workspace.path().to_path_buf(), CodeEmbedder::Lexical).await?;
+        let stats = index.refresh().await?;
+        assert_eq!(stats.indexed, 2);
+
+        assert_eq!(index.refresh().await?, RefreshStats::default());
+
+        let hits = index
+            .search("where is the config file parsed", 1, None)
+            .await?;
+        assert_eq!(hits[0].path, "config.rs");
+        assert_eq!(
+            hits[0].symbol,
+            "pub fn parse_config_file(path: &Path) -> Config {"
+        );
+        assert!(hits[0].to_markdown().starts_with("config.rs:1-4 ("));
+
+        fs::remove_file(workspace.path().join("config.rs"))?;
+        let stats = index
+            .update_files(&[workspace.path().join("config.rs")])
+            .await?;
+        assert_eq!(stats.removed, 1);
+        let hits = index
+            .search("where is the config file parsed", 5, None)
+            .await?;
+        assert!(hits.iter().all(|hit| hit.path != "config.rs"));
+
+        std::env::remove_var("GOOSE_VECTOR_DB_PATH");
+        Ok(())
+    }
+}
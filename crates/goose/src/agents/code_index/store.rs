@@ -0,0 +1,335 @@
+use anyhow::{Context, Result};
+use arrow::array::{FixedSizeListBuilder, Float32Array, StringArray, UInt32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::agents::tool_vectordb::ToolVectorDB;
+
+#[derive(Debug, Clone)]
+pub struct ChunkRecord {
+    pub path: String,
+    pub start_line: u32,
+    pub end_line: u32,
+    pub symbol: String,
+    pub content: String,
+    pub vector: Vec<f32>,
+}
+
+/// Code chunks stored in the same LanceDB database as the [`ToolVectorDB`], one table per
+/// workspace and embedding model
+pub struct CodeVectorDB {
+    connection: Arc<RwLock<Connection>>,
+    table_name: String,
+}
+
+impl CodeVectorDB {
+    pub async fn new(table_name: String) -> Result<Self> {
+        let connection = ToolVectorDB::open_connection().await?;
+        Ok(Self {
+            connection: Arc::new(RwLock::new(connection)),
+            table_name,
+        })
+    }
+
+    fn schema(dimensions: i32) -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("path", DataType::Utf8, false),
+            Field::new("start_line", DataType::UInt32, false),
+            Field::new("end_line", DataType::UInt32, false),
+            Field::new("symbol", DataType::Utf8, false),
+            Field::new("content", DataType::Utf8, false),
+            Field::new(
+                "vector",
+                DataType::FixedSizeList(
+                    Arc::new(Field::new("item", DataType::Float32, true)),
+                    dimensions,
+                ),
+                false,
+            ),
+        ]))
+    }
+
+    async fn table_exists(&self) -> Result<bool> {
+        let connection = self.connection.read().await;
+        let table_names = connection
+            .table_names()
+            .execute()
+            .await
+            .context("Failed to list tables")?;
+        Ok(table_names.contains(&self.table_name))
+    }
+
+    /// Add chunks, creating the table on first use since its vector size depends on the embedder
+    pub async fn add_chunks(&self, chunks: Vec<ChunkRecord>) -> Result<()> {
+        let Some(first) = chunks.first() else {
+            return Ok(());
+        };
+        let dimensions = first.vector.len() as i32;
+        let schema = Self::schema(dimensions);
+
+        let mut vectors_builder =
+            FixedSizeListBuilder::new(arrow::array::Float32Builder::new(), dimensions);
+        for chunk in &chunks {
+            if chunk.vector.len() as i32 != dimensions {
+                return Err(anyhow::anyhow!(
+                    "Embedding for {} has {} dimensions, expected {}",
+                    chunk.path,
+                    chunk.vector.len(),
+                    dimensions
+                ));
+            }
+            vectors_builder.values().append_slice(&chunk.vector);
+            vectors_builder.append(true);
+        }
+
+        let batch = arrow::record_batch::RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from_iter_values(
+                    chunks.iter().map(|c| c.path.as_str()),
+                )),
+                Arc::new(UInt32Array::from_iter_values(
+                    chunks.iter().map(|c| c.start_line),
+                )),
+                Arc::new(UInt32Array::from_iter_values(
+                    chunks.iter().map(|c| c.end_line),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    chunks.iter().map(|c| c.symbol.as_str()),
+                )),
+                Arc::new(StringArray::from_iter_values(
+                    chunks.iter().map(|c| c.content.as_str()),
+                )),
+                Arc::new(vectors_builder.finish()),
+            ],
+        )
+        .context("Failed to create record batch")?;
+
+        let reader = arrow::record_batch::RecordBatchIterator::new(
+            vec![Ok(batch)].into_iter(),
+            schema.clone(),
+        );
+
+        if self.table_exists().await? {
+            let connection = self.connection.read().await;
+            let table = connection
+                .open_table(&self.table_name)
+                .execute()
+                .await
+                .context("Failed to open code index table")?;
+            table
+                .add(Box::new(reader))
+                .execute()
+                .await
+                .context("Failed to add chunks to code index")?;
+        } else {
+            let connection = self.connection.write().await;
+            connection
+                .create_table(&self.table_name, Box::new(reader))
+                .execute()
+                .await
+                .map_err(|e| {
+                    anyhow::anyhow!(
+                        "Failed to create code index table '{}': {}",
+                        self.table_name,
+                        e
+                    )
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove every chunk that came from one of `paths`
+    pub async fn remove_paths(&self, paths: &[String]) -> Result<()> {
+        if paths.is_empty() || !self.table_exists().await? {
+            return Ok(());
+        }
+
+        let connection = self.connection.read().await;
+        let table = connection
+            .open_table(&self.table_name)
+            .execute()
+            .await
+            .context("Failed to open code index table")?;
+
+        let paths: Vec<String> = paths
+            .iter()
+            .map(|path| format!("'{}'", escape_literal(path)))
+            .collect();
+        table
+            .delete(&format!("path IN ({})", paths.join(", ")))
+            .await
+            .context("Failed to delete chunks")?;
+
+        Ok(())
+    }
+
+    /// Drop the whole table, used when the index has to be rebuilt from scratch
+    pub async fn clear(&self) -> Result<()> {
+        if !self.table_exists().await? {
+            return Ok(());
+        }
+
+        let connection = self.connection.write().await;
+        connection
+            .drop_table(&self.table_name)
+            .await
+            .context("Failed to drop code index table")?;
+        Ok(())
+    }
+
+    /// Nearest chunks to `query_vector`, optionally restricted to paths under `path_prefix`,
+    /// paired with their distance
+    pub async fn search(
+        &self,
+        query_vector: Vec<f32>,
+        k: usize,
+        path_prefix: Option<&str>,
+    ) -> Result<Vec<(ChunkRecord, f32)>> {
+        if !self.table_exists().await? {
+            return Ok(vec![]);
+        }
+
+        let connection = self.connection.read().await;
+        let table = connection
+            .open_table(&self.table_name)
+            .execute()
+            .await
+            .context("Failed to open code index table")?;
+
+        let mut search = table
+            .vector_search(query_vector)
+            .context("Failed to create vector search")?
+            .limit(k);
+        if let Some(prefix) = path_prefix {
+            search = search.only_if(format!("path LIKE '{}%'", escape_literal(prefix)));
+        }
+
+        let results = search
+            .execute()
+            .await
+            .context("Failed to execute vector search")?;
+
+        let batches: Vec<_> = results.try_collect().await?;
+
+        let mut chunks = Vec::new();
+        for batch in batches {
+            let strings = |name: &str| -> Result<StringArray> {
+                batch
+                    .column_by_name(name)
+                    .with_context(|| format!("Missing {} column", name))?
+                    .as_any()
+                    .downcast_ref::<StringArray>()
+                    .cloned()
+                    .with_context(|| format!("Invalid {} column type", name))
+            };
+            let numbers = |name: &str| -> Result<UInt32Array> {
+                batch
+                    .column_by_name(name)
+                    .with_context(|| format!("Missing {} column", name))?
+                    .as_any()
+                    .downcast_ref::<UInt32Array>()
+                    .cloned()
+                    .with_context(|| format!("Invalid {} column type", name))
+            };
+
+            let paths = strings("path")?;
+            let start_lines = numbers("start_line")?;
+            let end_lines = numbers("end_line")?;
+            let symbols = strings("symbol")?;
+            let contents = strings("content")?;
+            let distances = batch
+                .column_by_name("_distance")
+                .context("Missing _distance column")?
+                .as_any()
+                .downcast_ref::<Float32Array>()
+                .context("Invalid _distance column type")?;
+
+            for i in 0..batch.num_rows() {
+                chunks.push((
+                    ChunkRecord {
+                        path: paths.value(i).to_string(),
+                        start_line: start_lines.value(i),
+                        end_line: end_lines.value(i),
+                        symbol: symbols.value(i).to_string(),
+                        content: contents.value(i).to_string(),
+                        vector: vec![], // We don't need to return the vector
+                    },
+                    distances.value(i),
+                ));
+            }
+        }
+        Ok(chunks)
+    }
+}
+
+/// Escape a value for use inside a single-quoted SQL literal
+fn escape_literal(value: &str) -> String {
+    value.replace('\'', "''")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(path: &str, symbol: &str, vector: Vec<f32>) -> ChunkRecord {
+        ChunkRecord {
+            path: path.to_string(),
+            start_line: 1,
+            end_line: 10,
+            symbol: symbol.to_string(),
+            content: format!("{} {{}}", symbol),
+            vector,
+        }
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_code_vectordb_operations() -> Result<()> {
+        let db = CodeVectorDB::new("test_code_vectordb_operations".to_string()).await?;
+        db.clear().await?;
+        assert!(db.search(vec![1.0, 0.0, 0.0], 5, None).await?.is_empty());
+
+        db.add_chunks(vec![
+            chunk("src/parser.rs", "fn parse", vec![1.0, 0.0, 0.0]),
+            chunk("src/it's.rs", "fn quoted", vec![0.9, 0.1, 0.0]),
+            chunk("docs/guide.md", "# Guide", vec![0.0, 1.0, 0.0]),
+        ])
+        .await?;
+
+        let results = db.search(vec![1.0, 0.0, 0.0], 1, None).await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.symbol, "fn parse");
+
+        let results = db.search(vec![1.0, 0.0, 0.0], 5, Some("docs/")).await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.path, "docs/guide.md");
+
+        db.remove_paths(&["src/it's.rs".to_string()]).await?;
+        let results = db.search(vec![1.0, 0.0, 0.0], 5, Some("src/")).await?;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0.path, "src/parser.rs");
+
+        db.clear().await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    #[serial_test::serial]
+    async fn test_rejects_mixed_dimensions() -> Result<()> {
+        let db = CodeVectorDB::new("test_code_vectordb_dimensions".to_string()).await?;
+        let result = db
+            .add_chunks(vec![
+                chunk("a.rs", "fn a", vec![1.0, 0.0]),
+                chunk("b.rs", "fn b", vec![1.0]),
+            ])
+            .await;
+        assert!(result.is_err());
+        Ok(())
+    }
+}
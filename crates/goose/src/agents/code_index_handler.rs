@@ -0,0 +1,89 @@
+use mcp_core::{Content, ToolError};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::agents::code_index::{self, CodeEmbedder, CodeIndex};
+use crate::agents::Agent;
+
+impl Agent {
+    /// Open the code index for `working_dir` and start keeping it fresh in the background.
+    /// Only indexes workspaces the developer extension is working in, and does nothing if the
+    /// index for this directory is already running.
+    pub(super) async fn start_code_index(&self, working_dir: &Path) {
+        let has_developer = self
+            .extension_manager
+            .read()
+            .await
+            .list_extensions()
+            .await
+            .is_ok_and(|extensions| extensions.iter().any(|name| name == "developer"));
+        if !has_developer {
+            return;
+        }
+
+        let mut current = self.code_index.lock().await;
+        if current
+            .as_ref()
+            .is_some_and(|index| index.root() == working_dir)
+        {
+            return;
+        }
+
+        let provider = match self.provider().await {
+            Ok(provider) => provider,
+            Err(_) => return,
+        };
+        let index = match CodeEmbedder::from_config(provider) {
+            Ok(embedder) => CodeIndex::open(working_dir.to_path_buf(), embedder).await,
+            Err(e) => Err(e),
+        };
+        match index {
+            Ok(index) => {
+                let index = Arc::new(index);
+                index.watch(code_index::refresh_interval());
+                *current = Some(index);
+            }
+            Err(e) => warn!("Failed to open code index: {}", e),
+        }
+    }
+
+    pub(super) async fn semantic_search(
+        &self,
+        arguments: Value,
+    ) -> Result<Vec<Content>, ToolError> {
+        let index = self.code_index.lock().await.clone().ok_or_else(|| {
+            ToolError::ExecutionError("The code index is not running".to_string())
+        })?;
+
+        let query = arguments
+            .get("query")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| ToolError::InvalidParameters("Missing 'query' parameter".to_string()))?;
+        let k = arguments
+            .get("k")
+            .and_then(|v| v.as_u64())
+            .map(|k| k as usize)
+            .unwrap_or(code_index::DEFAULT_SEARCH_RESULTS);
+        let path = arguments.get("path").and_then(|v| v.as_str());
+
+        let hits = index
+            .search(query, k, path)
+            .await
+            .map_err(|e| ToolError::ExecutionError(format!("Semantic search failed: {}", e)))?;
+
+        if hits.is_empty() {
+            return Ok(vec![Content::text(
+                "No matching code found. The index may still be building, try again shortly or fall back to text search.",
+            )]);
+        }
+
+        let results = hits
+            .iter()
+            .map(|hit| hit.to_markdown())
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        Ok(vec![Content::text(results)])
+    }
+}
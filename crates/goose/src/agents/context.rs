@@ -1,14 +1,27 @@
 use anyhow::Ok;
 
+use crate::config::Config;
 use crate::message::Message;
 use crate::token_counter::create_async_token_counter;
 
+use crate::context_mgmt::compact::{ToolOutputCompaction, DEFAULT_KEEP_RECENT_TURNS};
 use crate::context_mgmt::summarize::summarize_messages_async;
 use crate::context_mgmt::truncate::{truncate_messages, OldestFirstTruncation};
 use crate::context_mgmt::{estimate_target_context_limit, get_messages_token_counts_async};
 
 use super::super::agents::Agent;
 
+/// Fraction of the target context limit at which the conversation is compacted proactively.
+/// Off unless `GOOSE_AUTO_COMPACT_THRESHOLD` is set, so history is only summarized when asked.
+const DEFAULT_AUTO_COMPACT_THRESHOLD: f64 = 0.0;
+
+fn compaction_strategy() -> ToolOutputCompaction {
+    let keep_recent_turns = Config::global()
+        .get_param("GOOSE_COMPACT_KEEP_TURNS")
+        .unwrap_or(DEFAULT_KEEP_RECENT_TURNS);
+    ToolOutputCompaction::new(keep_recent_turns)
+}
+
 impl Agent {
     /// Public API to truncate oldest messages so that the conversation's token count is within the allowed context limit.
     pub async fn truncate_context(
@@ -84,4 +97,65 @@ impl Agent {
 
         Ok((new_messages, new_token_counts))
     }
+
+    /// Public API to compact the conversation by eliding old tool output and summarizing older turns,
+    /// keeping the most recent turns verbatim.
+    pub async fn compact_context(
+        &self,
+        messages: &[Message],
+    ) -> Result<(Vec<Message>, Vec<usize>), anyhow::Error> {
+        let provider = self.provider().await?;
+        let token_counter = create_async_token_counter()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+        let target_context_limit = estimate_target_context_limit(provider.clone());
+
+        compaction_strategy()
+            .compact(provider, messages, &token_counter, target_context_limit)
+            .await
+    }
+
+    /// Compact the conversation once it grows past `GOOSE_AUTO_COMPACT_THRESHOLD` of the target
+    /// context limit, rather than waiting for the provider to reject it.
+    ///
+    /// Returns `None` when the conversation is below the threshold or could not be made smaller.
+    pub(super) async fn auto_compact(
+        &self,
+        messages: &[Message],
+    ) -> Result<Option<Vec<Message>>, anyhow::Error> {
+        let threshold = Config::global()
+            .get_param::<f64>("GOOSE_AUTO_COMPACT_THRESHOLD")
+            .unwrap_or(DEFAULT_AUTO_COMPACT_THRESHOLD);
+        if threshold <= 0.0 {
+            return Ok(None);
+        }
+
+        let provider = self.provider().await?;
+        let compact_limit =
+            (estimate_target_context_limit(provider.clone()) as f64 * threshold.min(1.0)) as usize;
+        let token_counter = create_async_token_counter()
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to create token counter: {}", e))?;
+        let total_tokens: usize = get_messages_token_counts_async(&token_counter, messages)
+            .iter()
+            .sum();
+        if compact_limit == 0 || total_tokens <= compact_limit {
+            return Ok(None);
+        }
+
+        let (compacted, token_counts) = compaction_strategy()
+            .compact(provider, messages, &token_counter, compact_limit)
+            .await?;
+        let compacted_tokens: usize = token_counts.iter().sum();
+        if compacted_tokens >= total_tokens {
+            return Ok(None);
+        }
+
+        tracing::info!(
+            "Compacted conversation from {} to {} tokens",
+            total_tokens,
+            compacted_tokens
+        );
+        Ok(Some(compacted))
+    }
 }
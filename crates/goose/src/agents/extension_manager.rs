@@ -2,22 +2,30 @@ use anyhow::Result;
 use chrono::{DateTime, TimeZone, Utc};
 use futures::stream::{FuturesUnordered, StreamExt};
 use futures::{future, FutureExt};
-use mcp_core::protocol::GetPromptResult;
+use mcp_core::protocol::{GetPromptResult, InitializeResult};
+use serde::Serialize;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::time::Duration;
 use tokio::sync::Mutex;
-use tokio::task;
+use tokio::task::{self, AbortHandle};
+use tokio::time::MissedTickBehavior;
 use tokio_stream::wrappers::ReceiverStream;
 use tracing::{error, warn};
 
 use super::extension::{ExtensionConfig, ExtensionError, ExtensionInfo, ExtensionResult, ToolInfo};
 use super::tool_execution::ToolCallResult;
 use crate::agents::extension::Envs;
-use crate::config::{Config, ExtensionConfigManager};
+use crate::config::{Config, ExtensionConfigManager, SandboxConfig};
+use crate::metrics;
 use crate::prompt_template;
-use mcp_client::client::{ClientCapabilities, ClientInfo, McpClient, McpClientTrait};
+use goose_sandbox::SHELL_SANDBOX_ENV;
+use mcp_client::client::{
+    ClientCapabilities, ClientInfo, Error as ClientError, McpClient, McpClientTrait,
+};
 use mcp_client::transport::{SseTransport, StdioTransport, StreamableHttpTransport, Transport};
 use mcp_core::{prompt::Prompt, Content, Tool, ToolCall, ToolError};
 use serde_json::Value;
@@ -28,12 +36,64 @@ static DEFAULT_TIMESTAMP: LazyLock<DateTime<Utc>> =
     LazyLock::new(|| Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap());
 
 type McpClientBox = Arc<Mutex<Box<dyn McpClientTrait>>>;
+type HealthMap = Arc<std::sync::Mutex<HashMap<String, ExtensionHealth>>>;
+type RestartLog = Arc<std::sync::Mutex<Vec<ExtensionRestart>>>;
+
+/// Seconds between pings of each extension, overridden by GOOSE_EXTENSION_HEALTH_CHECK_INTERVAL
+/// (0 disables health checks)
+const DEFAULT_HEALTH_CHECK_INTERVAL_SECS: u64 = 30;
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+const RESTART_ATTEMPTS: u32 = 3;
+const RESTART_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// How long an extension that could not be restarted is left alone before trying again
+const FAILED_RETRY_INTERVAL: Duration = Duration::from_secs(60);
 
 /// Manages Goose extensions / MCP clients and their interactions
 pub struct ExtensionManager {
     clients: HashMap<String, McpClientBox>,
     instructions: HashMap<String, String>,
     resource_capable_extensions: HashSet<String>,
+    configs: HashMap<String, ExtensionConfig>,
+    health: HealthMap,
+    restarts: RestartLog,
+    health_checks: HashMap<String, AbortHandle>,
+    working_dir: Option<PathBuf>,
+}
+
+/// Connection state of an extension, as last seen by its health check
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "state", content = "error", rename_all = "snake_case")]
+pub enum ExtensionStatus {
+    Healthy,
+    Restarting,
+    Failed(String),
+}
+
+/// Health of a running extension, kept up to date by periodic pings
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExtensionHealth {
+    pub status: ExtensionStatus,
+    pub last_checked: DateTime<Utc>,
+    /// How many times the extension was restarted after losing its connection
+    pub restarts: u32,
+}
+
+impl ExtensionHealth {
+    fn healthy() -> Self {
+        Self {
+            status: ExtensionStatus::Healthy,
+            last_checked: Utc::now(),
+            restarts: 0,
+        }
+    }
+}
+
+/// Reported when an extension lost its connection and goose tried to restart it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtensionRestart {
+    pub name: String,
+    /// Why the restart failed, or None if the extension is running again
+    pub error: Option<String>,
 }
 
 /// A flattened representation of a resource used by the agent to prepare inference
@@ -91,12 +151,361 @@ pub fn get_parameter_names(tool: &Tool) -> Vec<String> {
         .unwrap_or_default()
 }
 
+/// Merge environment variables from direct envs and keychain-stored env_keys
+async fn merge_environments(
+    envs: &Envs,
+    env_keys: &[String],
+    ext_name: &str,
+) -> Result<HashMap<String, String>, ExtensionError> {
+    let mut all_envs = envs.get_env();
+    let config_instance = Config::global();
+
+    for key in env_keys {
+        // If the Envs payload already contains the key, prefer that value
+        // over looking into the keychain/secret store
+        if all_envs.contains_key(key) {
+            continue;
+        }
+
+        match config_instance.get(key, true) {
+            Ok(value) => {
+                if value.is_null() {
+                    warn!(
+                        key = %key,
+                        ext_name = %ext_name,
+                        "Secret key not found in config (returned null)."
+                    );
+                    continue;
+                }
+
+                // Try to get string value
+                if let Some(str_val) = value.as_str() {
+                    all_envs.insert(key.clone(), str_val.to_string());
+                } else {
+                    warn!(
+                        key = %key,
+                        ext_name = %ext_name,
+                        value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
+                        "Secret value is not a string; skipping."
+                    );
+                }
+            }
+            Err(e) => {
+                error!(
+                    key = %key,
+                    ext_name = %ext_name,
+                    error = %e,
+                    "Failed to fetch secret from config."
+                );
+                return Err(ExtensionError::SetupError(format!(
+                    "Failed to fetch secret '{}' from config: {}",
+                    key, e
+                )));
+            }
+        }
+    }
+
+    Ok(all_envs)
+}
+
+/// Start the MCP server described by `config`, connect to it and run the initialize handshake.
+/// Local servers start in `working_dir`, or the current directory when it's None.
+async fn connect_extension(
+    config: &ExtensionConfig,
+    working_dir: Option<&Path>,
+) -> ExtensionResult<(Box<dyn McpClientTrait>, InitializeResult)> {
+    let sanitized_name = normalize(config.key().to_string());
+    let sandbox = SandboxConfig::load()
+        .map_err(|e| ExtensionError::SetupError(format!("Invalid sandbox configuration: {}", e)))?;
+
+    let mut client: Box<dyn McpClientTrait> = match config {
+        ExtensionConfig::Sse {
+            uri,
+            envs,
+            env_keys,
+            timeout,
+            ..
+        } => {
+            let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+            let transport = SseTransport::new(uri, all_envs);
+            let handle = transport.start().await?;
+            Box::new(
+                McpClient::connect(
+                    handle,
+                    Duration::from_secs(
+                        timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                    ),
+                )
+                .await?,
+            )
+        }
+        ExtensionConfig::StreamableHttp {
+            uri,
+            envs,
+            env_keys,
+            headers,
+            timeout,
+            ..
+        } => {
+            let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+            let transport = StreamableHttpTransport::with_headers(uri, all_envs, headers.clone());
+            let handle = transport.start().await?;
+            Box::new(
+                McpClient::connect(
+                    handle,
+                    Duration::from_secs(
+                        timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                    ),
+                )
+                .await?,
+            )
+        }
+        ExtensionConfig::Stdio {
+            cmd,
+            args,
+            envs,
+            env_keys,
+            timeout,
+            ..
+        } => {
+            let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
+            let mut transport = StdioTransport::new(cmd, args.to_vec(), all_envs);
+            if let Some(profile) = sandbox.extension(&config.name()) {
+                transport = transport.with_sandbox(profile.clone());
+            }
+            if let Some(dir) = working_dir {
+                transport = transport.with_working_dir(dir.to_path_buf());
+            }
+            let handle = transport.start().await?;
+            Box::new(
+                McpClient::connect(
+                    handle,
+                    Duration::from_secs(
+                        timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                    ),
+                )
+                .await?,
+            )
+        }
+        ExtensionConfig::Builtin {
+            name,
+            display_name: _,
+            timeout,
+            bundled: _,
+        } => {
+            let cmd = std::env::current_exe()
+                .expect("should find the current executable")
+                .to_str()
+                .expect("should resolve executable to string path")
+                .to_string();
+            // Builtins run the shell tool, so they get its sandbox profile
+            let mut envs = HashMap::new();
+            if let Some(shell) = &sandbox.shell {
+                let profile = serde_json::to_string(shell)
+                    .map_err(|e| ExtensionError::SetupError(e.to_string()))?;
+                envs.insert(SHELL_SANDBOX_ENV.to_string(), profile);
+            }
+            let mut transport =
+                StdioTransport::new(&cmd, vec!["mcp".to_string(), name.clone()], envs);
+            if let Some(dir) = working_dir {
+                transport = transport.with_working_dir(dir.to_path_buf());
+            }
+            let handle = transport.start().await?;
+            Box::new(
+                McpClient::connect(
+                    handle,
+                    Duration::from_secs(
+                        timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
+                    ),
+                )
+                .await?,
+            )
+        }
+        _ => unreachable!(),
+    };
+
+    // Initialize the client with default capabilities
+    let info = ClientInfo {
+        name: "goose".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+    };
+    let capabilities = ClientCapabilities::default();
+
+    let init_result = client
+        .initialize(info, capabilities)
+        .await
+        .map_err(|e| ExtensionError::Initialization(Box::new(config.clone()), e))?;
+
+    Ok((client, init_result))
+}
+
+fn health_check_interval() -> Option<Duration> {
+    let secs = Config::global()
+        .get_param::<u64>("GOOSE_EXTENSION_HEALTH_CHECK_INTERVAL")
+        .unwrap_or(DEFAULT_HEALTH_CHECK_INTERVAL_SECS);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn set_status(health: &HealthMap, name: &str, status: ExtensionStatus) {
+    let mut health = health.lock().unwrap();
+    let entry = health
+        .entry(name.to_string())
+        .or_insert_with(ExtensionHealth::healthy);
+    entry.status = status;
+    entry.last_checked = Utc::now();
+}
+
+/// Whether the server still answers. Servers that don't implement ping still answer with an
+/// error, which is good enough.
+async fn ping(client: &dyn McpClientTrait) -> bool {
+    matches!(
+        tokio::time::timeout(PING_TIMEOUT, client.ping()).await,
+        Ok(Ok(())) | Ok(Err(ClientError::RpcError { .. }))
+    )
+}
+
+/// The error of an extension whose last restart failed less than FAILED_RETRY_INTERVAL ago.
+/// Trying again right away would most likely fail too, after several seconds of backoff.
+fn recently_failed(health: &HealthMap, name: &str) -> Option<String> {
+    let health = health.lock().unwrap();
+    let entry = health.get(name)?;
+    match &entry.status {
+        ExtensionStatus::Failed(error)
+            if (Utc::now() - entry.last_checked)
+                .to_std()
+                .ok()
+                .is_none_or(|elapsed| elapsed < FAILED_RETRY_INTERVAL) =>
+        {
+            Some(error.clone())
+        }
+        _ => None,
+    }
+}
+
+async fn reconnect(
+    config: &ExtensionConfig,
+    working_dir: Option<&Path>,
+) -> ExtensionResult<Box<dyn McpClientTrait>> {
+    connect_extension(config, working_dir)
+        .await
+        .map(|(client, _)| client)
+}
+
+/// Reconnect an extension whose connection was lost, retrying with exponential backoff. The new
+/// client replaces the old one behind the lock, so callers holding the handle pick it up.
+/// Returns None when there is nothing new to report, i.e. a restart that keeps failing after
+/// its failure was already reported.
+async fn restart_client<F, Fut>(
+    name: &str,
+    client: &mut Box<dyn McpClientTrait>,
+    health: &HealthMap,
+    initial_backoff: Duration,
+    connect: F,
+) -> Option<ExtensionRestart>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = ExtensionResult<Box<dyn McpClientTrait>>>,
+{
+    let already_failed = matches!(
+        health.lock().unwrap().get(name).map(|h| &h.status),
+        Some(ExtensionStatus::Failed(_))
+    );
+    set_status(health, name, ExtensionStatus::Restarting);
+
+    let mut backoff = initial_backoff;
+    let mut last_error = String::new();
+    for attempt in 1..=RESTART_ATTEMPTS {
+        match connect().await {
+            Ok(new_client) => {
+                *client = new_client;
+                set_status(health, name, ExtensionStatus::Healthy);
+                if let Some(entry) = health.lock().unwrap().get_mut(name) {
+                    entry.restarts += 1;
+                }
+                tracing::info!(extension = name, attempt, "Restarted extension");
+                metrics::record_extension_restart(name, true);
+                return Some(ExtensionRestart {
+                    name: name.to_string(),
+                    error: None,
+                });
+            }
+            Err(e) => {
+                warn!(extension = name, attempt, error = %e, "Failed to restart extension");
+                last_error = e.to_string();
+                if attempt < RESTART_ATTEMPTS {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+
+    set_status(health, name, ExtensionStatus::Failed(last_error.clone()));
+    metrics::record_extension_restart(name, false);
+    (!already_failed).then(|| ExtensionRestart {
+        name: name.to_string(),
+        error: Some(last_error),
+    })
+}
+
+/// Ping an extension periodically and restart it when it stops answering
+fn spawn_health_check(
+    name: String,
+    client: &McpClientBox,
+    config: ExtensionConfig,
+    working_dir: Option<PathBuf>,
+    health: HealthMap,
+    restarts: RestartLog,
+    interval: Duration,
+) -> AbortHandle {
+    let client = Arc::downgrade(client);
+    task::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            let Some(client) = client.upgrade() else {
+                break;
+            };
+            if recently_failed(&health, &name).is_some() {
+                continue;
+            }
+            // A client that is busy serving a call is evidently alive
+            let Ok(mut guard) = client.try_lock() else {
+                continue;
+            };
+            if !guard.is_closed() && ping(&**guard).await {
+                set_status(&health, &name, ExtensionStatus::Healthy);
+                continue;
+            }
+            let restart =
+                restart_client(&name, &mut guard, &health, RESTART_INITIAL_BACKOFF, || {
+                    reconnect(&config, working_dir.as_deref())
+                })
+                .await;
+            if let Some(restart) = restart {
+                restarts.lock().unwrap().push(restart);
+            }
+        }
+    })
+    .abort_handle()
+}
+
 impl Default for ExtensionManager {
     fn default() -> Self {
         Self::new()
     }
 }
 
+impl Drop for ExtensionManager {
+    fn drop(&mut self) {
+        for health_check in self.health_checks.values() {
+            health_check.abort();
+        }
+    }
+}
+
 impl ExtensionManager {
     /// Create a new ExtensionManager instance
     pub fn new() -> Self {
@@ -104,9 +513,19 @@ impl ExtensionManager {
             clients: HashMap::new(),
             instructions: HashMap::new(),
             resource_capable_extensions: HashSet::new(),
+            configs: HashMap::new(),
+            health: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            restarts: Arc::new(std::sync::Mutex::new(Vec::new())),
+            health_checks: HashMap::new(),
+            working_dir: None,
         }
     }
 
+    /// Start the local extensions added from now on in `dir` instead of the current directory
+    pub fn set_working_dir(&mut self, dir: PathBuf) {
+        self.working_dir = Some(dir);
+    }
+
     pub fn supports_resources(&self) -> bool {
         !self.resource_capable_extensions.is_empty()
     }
@@ -117,168 +536,7 @@ impl ExtensionManager {
         let config_name = config.key().to_string();
         let sanitized_name = normalize(config_name.clone());
 
-        /// Helper function to merge environment variables from direct envs and keychain-stored env_keys
-        async fn merge_environments(
-            envs: &Envs,
-            env_keys: &[String],
-            ext_name: &str,
-        ) -> Result<HashMap<String, String>, ExtensionError> {
-            let mut all_envs = envs.get_env();
-            let config_instance = Config::global();
-
-            for key in env_keys {
-                // If the Envs payload already contains the key, prefer that value
-                // over looking into the keychain/secret store
-                if all_envs.contains_key(key) {
-                    continue;
-                }
-
-                match config_instance.get(key, true) {
-                    Ok(value) => {
-                        if value.is_null() {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                "Secret key not found in config (returned null)."
-                            );
-                            continue;
-                        }
-
-                        // Try to get string value
-                        if let Some(str_val) = value.as_str() {
-                            all_envs.insert(key.clone(), str_val.to_string());
-                        } else {
-                            warn!(
-                                key = %key,
-                                ext_name = %ext_name,
-                                value_type = %value.get("type").and_then(|t| t.as_str()).unwrap_or("unknown"),
-                                "Secret value is not a string; skipping."
-                            );
-                        }
-                    }
-                    Err(e) => {
-                        error!(
-                            key = %key,
-                            ext_name = %ext_name,
-                            error = %e,
-                            "Failed to fetch secret from config."
-                        );
-                        return Err(ExtensionError::SetupError(format!(
-                            "Failed to fetch secret '{}' from config: {}",
-                            key, e
-                        )));
-                    }
-                }
-            }
-
-            Ok(all_envs)
-        }
-
-        let mut client: Box<dyn McpClientTrait> = match &config {
-            ExtensionConfig::Sse {
-                uri,
-                envs,
-                env_keys,
-                timeout,
-                ..
-            } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
-                let transport = SseTransport::new(uri, all_envs);
-                let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
-            }
-            ExtensionConfig::StreamableHttp {
-                uri,
-                envs,
-                env_keys,
-                headers,
-                timeout,
-                ..
-            } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
-                let transport =
-                    StreamableHttpTransport::with_headers(uri, all_envs, headers.clone());
-                let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
-            }
-            ExtensionConfig::Stdio {
-                cmd,
-                args,
-                envs,
-                env_keys,
-                timeout,
-                ..
-            } => {
-                let all_envs = merge_environments(envs, env_keys, &sanitized_name).await?;
-                let transport = StdioTransport::new(cmd, args.to_vec(), all_envs);
-                let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
-            }
-            ExtensionConfig::Builtin {
-                name,
-                display_name: _,
-                timeout,
-                bundled: _,
-            } => {
-                let cmd = std::env::current_exe()
-                    .expect("should find the current executable")
-                    .to_str()
-                    .expect("should resolve executable to string path")
-                    .to_string();
-                let transport = StdioTransport::new(
-                    &cmd,
-                    vec!["mcp".to_string(), name.clone()],
-                    HashMap::new(),
-                );
-                let handle = transport.start().await?;
-                Box::new(
-                    McpClient::connect(
-                        handle,
-                        Duration::from_secs(
-                            timeout.unwrap_or(crate::config::DEFAULT_EXTENSION_TIMEOUT),
-                        ),
-                    )
-                    .await?,
-                )
-            }
-            _ => unreachable!(),
-        };
-
-        // Initialize the client with default capabilities
-        let info = ClientInfo {
-            name: "goose".to_string(),
-            version: env!("CARGO_PKG_VERSION").to_string(),
-        };
-        let capabilities = ClientCapabilities::default();
-
-        let init_result = client
-            .initialize(info, capabilities)
-            .await
-            .map_err(|e| ExtensionError::Initialization(Box::new(config.clone()), e))?;
+        let (client, init_result) = connect_extension(&config, self.working_dir.as_deref()).await?;
 
         if let Some(instructions) = init_result.instructions {
             self.instructions
@@ -290,8 +548,30 @@ impl ExtensionManager {
                 .insert(sanitized_name.clone());
         }
 
-        self.clients
-            .insert(sanitized_name.clone(), Arc::new(Mutex::new(client)));
+        let client = Arc::new(Mutex::new(client));
+        if let Some(interval) = health_check_interval() {
+            let health_check = spawn_health_check(
+                sanitized_name.clone(),
+                &client,
+                config.clone(),
+                self.working_dir.clone(),
+                self.health.clone(),
+                self.restarts.clone(),
+                interval,
+            );
+            if let Some(previous) = self
+                .health_checks
+                .insert(sanitized_name.clone(), health_check)
+            {
+                previous.abort();
+            }
+        }
+        self.health
+            .lock()
+            .unwrap()
+            .insert(sanitized_name.clone(), ExtensionHealth::healthy());
+        self.configs.insert(sanitized_name.clone(), config);
+        self.clients.insert(sanitized_name.clone(), client);
 
         Ok(())
     }
@@ -315,9 +595,65 @@ impl ExtensionManager {
         self.clients.remove(&sanitized_name);
         self.instructions.remove(&sanitized_name);
         self.resource_capable_extensions.remove(&sanitized_name);
+        self.configs.remove(&sanitized_name);
+        self.health.lock().unwrap().remove(&sanitized_name);
+        if let Some(health_check) = self.health_checks.remove(&sanitized_name) {
+            health_check.abort();
+        }
         Ok(())
     }
 
+    /// Last known health of each extension
+    pub fn extension_health(&self) -> HashMap<String, ExtensionHealth> {
+        self.health.lock().unwrap().clone()
+    }
+
+    /// Restarts that happened since the last call, so they can be reported to the user
+    pub fn take_restarts(&self) -> Vec<ExtensionRestart> {
+        std::mem::take(&mut *self.restarts.lock().unwrap())
+    }
+
+    /// Restart an extension right away if its connection was lost, instead of waiting for the
+    /// next health check. Extensions that just failed to restart are not retried on every call.
+    async fn ensure_running(&self, name: &str, client: &McpClientBox) -> Result<(), String> {
+        if let Some(error) = recently_failed(&self.health, name) {
+            return Err(format!(
+                "Extension '{}' has stopped and could not be restarted: {}",
+                name, error
+            ));
+        }
+
+        let mut guard = client.lock().await;
+        if !guard.is_closed() {
+            return Ok(());
+        }
+        let config = self
+            .configs
+            .get(name)
+            .ok_or_else(|| format!("Extension '{}' has stopped", name))?;
+
+        let restart = restart_client(
+            name,
+            &mut guard,
+            &self.health,
+            RESTART_INITIAL_BACKOFF,
+            || reconnect(config, self.working_dir.as_deref()),
+        )
+        .await;
+        if let Some(restart) = restart {
+            self.restarts.lock().unwrap().push(restart);
+        }
+
+        if guard.is_closed() {
+            Err(format!(
+                "Extension '{}' has stopped and could not be restarted",
+                name
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn suggest_disable_extensions_prompt(&self) -> Value {
         let enabled_extensions_count = self.clients.len();
 
@@ -354,21 +690,41 @@ impl ExtensionManager {
         Ok(self.clients.keys().cloned().collect())
     }
 
+    /// The configs of the running extensions
+    pub fn extension_configs(&self) -> Vec<ExtensionConfig> {
+        self.configs.values().cloned().collect()
+    }
+
     /// Get all tools from all clients with proper prefixing
     pub async fn get_prefixed_tools(
         &self,
         extension_name: Option<String>,
     ) -> ExtensionResult<Vec<Tool>> {
         // Filter clients based on the provided extension_name or include all if None
-        let filtered_clients = self.clients.iter().filter(|(name, _)| {
-            if let Some(ref name_filter) = extension_name {
-                *name == name_filter
-            } else {
-                true
+        let candidates: Vec<_> = self
+            .clients
+            .iter()
+            .filter(|(name, _)| extension_name.as_ref().is_none_or(|filter| *name == filter))
+            .collect();
+
+        // Restart stopped extensions concurrently, so one slow restart doesn't hold up the rest,
+        // and leave out the ones that can't be brought back rather than failing
+        let checks = future::join_all(
+            candidates
+                .iter()
+                .map(|(name, client)| self.ensure_running(name, client)),
+        )
+        .await;
+        let mut filtered_clients = Vec::new();
+        for ((name, client), check) in candidates.into_iter().zip(checks) {
+            if let Err(e) = check {
+                warn!("{}", e);
+                continue;
             }
-        });
+            filtered_clients.push((name, client));
+        }
 
-        let client_futures = filtered_clients.map(|(name, client)| {
+        let client_futures = filtered_clients.into_iter().map(|(name, client)| {
             let name = name.clone();
             let client = client.clone();
 
@@ -653,6 +1009,10 @@ impl ExtensionManager {
             .ok_or_else(|| ToolError::NotFound(tool_call.name.clone()))?
             .to_string();
 
+        self.ensure_running(client_name, &client)
+            .await
+            .map_err(ToolError::ExecutionError)?;
+
         let arguments = tool_call.arguments.clone();
         let client = client.clone();
         let notifications_receiver = client.lock().await.subscribe().await;
@@ -836,7 +1196,10 @@ mod tests {
     use serde_json::json;
     use tokio::sync::mpsc;
 
-    struct MockClient {}
+    #[derive(Default)]
+    struct MockClient {
+        closed: bool,
+    }
 
     #[async_trait::async_trait]
     impl McpClientTrait for MockClient {
@@ -891,6 +1254,14 @@ mod tests {
         async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage> {
             mpsc::channel(1).1
         }
+
+        async fn ping(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn is_closed(&self) -> bool {
+            self.closed
+        }
     }
 
     #[test]
@@ -900,22 +1271,22 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         // Test basic case
@@ -948,17 +1319,17 @@ mod tests {
         // Add some mock clients
         extension_manager.clients.insert(
             normalize("test_client".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("__cli__ent__".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         extension_manager.clients.insert(
             normalize("client 🚀".to_string()),
-            Arc::new(Mutex::new(Box::new(MockClient {}))),
+            Arc::new(Mutex::new(Box::new(MockClient::default()))),
         );
 
         // verify a normal tool call
@@ -1038,4 +1409,108 @@ mod tests {
             panic!("Expected ToolError::NotFound");
         }
     }
+
+    fn closed_client() -> Box<dyn McpClientTrait> {
+        Box::new(MockClient { closed: true })
+    }
+
+    #[tokio::test]
+    async fn test_restart_client_replaces_closed_client() {
+        let health: HealthMap = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let mut client = closed_client();
+
+        let restart = restart_client("test", &mut client, &health, Duration::ZERO, || async {
+            Ok(Box::new(MockClient::default()) as Box<dyn McpClientTrait>)
+        })
+        .await;
+
+        assert_eq!(
+            restart,
+            Some(ExtensionRestart {
+                name: "test".to_string(),
+                error: None
+            })
+        );
+        assert!(!client.is_closed());
+        let health = health.lock().unwrap()["test"].clone();
+        assert_eq!(health.status, ExtensionStatus::Healthy);
+        assert_eq!(health.restarts, 1);
+    }
+
+    #[tokio::test]
+    async fn test_restart_client_reports_failure_once() {
+        let health: HealthMap = Arc::new(std::sync::Mutex::new(HashMap::new()));
+        let mut client = closed_client();
+        let failing = || async {
+            Err::<Box<dyn McpClientTrait>, _>(ExtensionError::SetupError("boom".to_string()))
+        };
+
+        let restart = restart_client("test", &mut client, &health, Duration::ZERO, failing).await;
+        assert_eq!(
+            restart.unwrap().error.unwrap(),
+            "Error during extension setup: boom"
+        );
+        assert!(client.is_closed());
+        assert!(matches!(
+            health.lock().unwrap()["test"].status,
+            ExtensionStatus::Failed(_)
+        ));
+
+        // Repeated failures are not reported again
+        let restart = restart_client("test", &mut client, &health, Duration::ZERO, failing).await;
+        assert!(restart.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_to_stopped_extension_without_config_fails() {
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager
+            .clients
+            .insert("test".to_string(), Arc::new(Mutex::new(closed_client())));
+
+        let result = extension_manager
+            .dispatch_tool_call(ToolCall {
+                name: "test__tool".to_string(),
+                arguments: json!({}),
+            })
+            .await;
+        let err = result.err().expect("dispatch should fail");
+        let tool_err = err.downcast_ref::<ToolError>().expect("Expected ToolError");
+        assert!(matches!(tool_err, ToolError::ExecutionError(_)));
+
+        // Stopped extensions are left out of the tool list instead of failing it
+        let tools = extension_manager.get_prefixed_tools(None).await.unwrap();
+        assert!(tools.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_recently_failed_extension_is_not_restarted_on_every_call() {
+        let mut extension_manager = ExtensionManager::new();
+        extension_manager
+            .clients
+            .insert("test".to_string(), Arc::new(Mutex::new(closed_client())));
+        extension_manager.configs.insert(
+            "test".to_string(),
+            ExtensionConfig::stdio("test", "goose-test-missing-command", "", 10u64),
+        );
+        set_status(
+            &extension_manager.health,
+            "test",
+            ExtensionStatus::Failed("boom".to_string()),
+        );
+        let failed_at = extension_manager.health.lock().unwrap()["test"].last_checked;
+
+        let client = extension_manager.clients["test"].clone();
+        let err = extension_manager
+            .ensure_running("test", &client)
+            .await
+            .unwrap_err();
+        assert!(err.contains("boom"));
+
+        // No restart was attempted, so the failure is left as it was
+        let health = extension_manager.health.lock().unwrap()["test"].clone();
+        assert_eq!(health.status, ExtensionStatus::Failed("boom".to_string()));
+        assert_eq!(health.last_checked, failed_at);
+        assert!(extension_manager.take_restarts().is_empty());
+    }
 }
@@ -1,4 +1,6 @@
 mod agent;
+mod code_index;
+mod code_index_handler;
 mod context;
 pub mod extension;
 pub mod extension_manager;
@@ -138,6 +138,12 @@ impl PromptManager {
 
         let mut system_prompt_extras = self.system_prompt_extras.clone();
         let config = Config::global();
+        // Instructions a project commits in its .goose/config.yaml (or the user sets globally)
+        if let Ok(instructions) = config.get_param::<String>("GOOSE_INSTRUCTIONS") {
+            if !instructions.trim().is_empty() {
+                system_prompt_extras.push(instructions);
+            }
+        }
         let goose_mode = config.get_param("GOOSE_MODE").unwrap_or("auto".to_string());
         if goose_mode == "chat" {
             system_prompt_extras.push(
@@ -5,6 +5,7 @@ use mcp_core::tool::{Tool, ToolAnnotations};
 use serde_json::{json, Map, Value};
 
 use crate::agents::sub_recipe_execution_tool::lib::Task;
+use crate::recipe::parameters::parameter_schema;
 use crate::recipe::{Recipe, RecipeParameter, RecipeParameterRequirement, SubRecipe};
 
 pub const SUB_RECIPE_TASK_TOOL_NAME_PREFIX: &str = "subrecipe__create_task";
@@ -49,13 +50,11 @@ fn get_input_schema(sub_recipe: &SubRecipe) -> Result<Value> {
             if sub_recipe_params_map.contains_key(&param.key) {
                 continue;
             }
-            properties.insert(
-                param.key.clone(),
-                json!({
-                    "type": param.input_type.to_string(),
-                    "description": param.description.clone(),
-                }),
-            );
+            // Secrets come from the keyring rather than the conversation
+            let Some(schema) = parameter_schema(&param) else {
+                continue;
+            };
+            properties.insert(param.key.clone(), schema);
             if !matches!(param.requirement, RecipeParameterRequirement::Optional) {
                 required.push(param.key);
             }
@@ -151,5 +151,54 @@ mod tests {
             assert_eq!(result["required"].as_array().unwrap().len(), 1);
             assert_eq!(result["required"][0], "key1");
         }
+
+        #[test]
+        fn test_get_input_schema_with_typed_parameters() {
+            let sub_recipe_file_content = r#"
+version: 1.0.0
+title: Deploy
+description: Deploys a service
+prompt: Deploy {{ service }}
+parameters:
+  - key: environment
+    input_type: select
+    requirement: required
+    description: where to deploy
+    options: [staging, production]
+  - key: replicas
+    input_type: number
+    requirement: required
+    description: how many replicas
+    min: 1
+    max: 5
+  - key: token
+    input_type: secret
+    requirement: required
+    description: deploy token
+"#;
+            let temp_dir = tempfile::tempdir().unwrap();
+            let temp_file = temp_dir.path().join("deploy.yaml");
+            std::fs::write(&temp_file, sub_recipe_file_content).unwrap();
+            let sub_recipe = SubRecipe {
+                name: "deploy".to_string(),
+                path: temp_file.to_string_lossy().to_string(),
+                values: None,
+            };
+
+            let result = get_input_schema(&sub_recipe).unwrap();
+
+            let properties = result["properties"].as_object().unwrap();
+            assert_eq!(properties.len(), 2);
+            assert_eq!(
+                properties["environment"]["enum"],
+                serde_json::json!(["staging", "production"])
+            );
+            assert_eq!(properties["replicas"]["minimum"], 1);
+            assert_eq!(properties["replicas"]["maximum"], 5);
+            assert_eq!(
+                result["required"],
+                serde_json::json!(["environment", "replicas"])
+            );
+        }
     }
 }
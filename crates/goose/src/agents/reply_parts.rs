@@ -11,7 +11,9 @@ use crate::providers::toolshim::{
     augment_message_with_tool_calls, convert_tool_messages_to_text,
     modify_system_prompt_for_tool_json, OllamaInterpreter,
 };
+use crate::redaction::Redactor;
 use crate::session;
+use crate::tracing::gen_ai;
 use mcp_core::tool::Tool;
 
 use super::super::agents::Agent;
@@ -122,11 +124,21 @@ impl Agent {
         } else {
             messages.to_vec()
         };
+        let redactor = Redactor::global();
+        let messages_for_provider = if redactor.before_provider() {
+            redactor.redact_messages(&messages_for_provider)
+        } else {
+            messages_for_provider
+        };
 
         // Call the provider to get a response
-        let (mut response, usage) = provider
-            .complete(system_prompt, &messages_for_provider, tools)
-            .await?;
+        let (mut response, usage) = gen_ai::complete(
+            provider.as_ref(),
+            system_prompt,
+            &messages_for_provider,
+            tools,
+        )
+        .await?;
 
         // Store the model information in the global store
         crate::providers::base::set_current_model(&usage.model);
@@ -15,6 +15,7 @@ use crate::agents::tool_vectordb::ToolVectorDB;
 use crate::message::Message;
 use crate::model::ModelConfig;
 use crate::providers::{self, base::Provider};
+use crate::tracing::gen_ai;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum RouterToolSelectionStrategy {
@@ -278,11 +279,12 @@ impl RouterToolSelector for LLMToolSelector {
                 tools, query
             );
             let system_message = Message::user().with_text("You are a tool selection assistant. Your task is to find the most relevant tools based on the user's query.");
-            let response = self
-                .llm_provider
-                .complete(&prompt, &[system_message], &[])
-                .await
-                .map_err(|e| ToolError::ExecutionError(format!("Failed to search tools: {}", e)))?;
+            let response =
+                gen_ai::complete(self.llm_provider.as_ref(), &prompt, &[system_message], &[])
+                    .await
+                    .map_err(|e| {
+                        ToolError::ExecutionError(format!("Failed to search tools: {}", e))
+                    })?;
 
             // Extract just the message content from the response
             let (message, _usage) = response;
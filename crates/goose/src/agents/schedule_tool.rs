@@ -150,6 +150,9 @@ impl Agent {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some(execution_mode.to_string()),
+            trigger: None,
+            policy: crate::scheduler_runs::RunPolicy::default(),
+            notifiers: Vec::new(),
         };
 
         match scheduler.add_scheduled_job(job).await {
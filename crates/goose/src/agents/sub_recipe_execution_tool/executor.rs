@@ -4,14 +4,18 @@ use tokio::sync::mpsc;
 use tokio::time::Instant;
 
 use crate::agents::sub_recipe_execution_tool::lib::{
-    Config, ExecutionResponse, ExecutionStats, Task, TaskResult,
+    Config, ExecutionResponse, ExecutionStats, Task, TaskContext, TaskResult,
 };
 use crate::agents::sub_recipe_execution_tool::tasks::process_task;
 use crate::agents::sub_recipe_execution_tool::workers::{run_scaler, spawn_worker, SharedState};
 
-pub async fn execute_single_task(task: &Task, config: Config) -> ExecutionResponse {
+pub async fn execute_single_task(
+    task: &Task,
+    config: Config,
+    context: TaskContext,
+) -> ExecutionResponse {
     let start_time = Instant::now();
-    let result = process_task(task, config.timeout_seconds).await;
+    let result = process_task(task, config.timeout_seconds, &context).await;
 
     let execution_time = start_time.elapsed().as_millis();
     let completed = if result.status == "success" { 1 } else { 0 };
@@ -30,7 +34,11 @@ pub async fn execute_single_task(task: &Task, config: Config) -> ExecutionRespon
 }
 
 // Main parallel execution function
-pub async fn parallel_execute(tasks: Vec<Task>, config: Config) -> ExecutionResponse {
+pub async fn parallel_execute(
+    tasks: Vec<Task>,
+    config: Config,
+    context: TaskContext,
+) -> ExecutionResponse {
     let start_time = Instant::now();
     let task_count = tasks.len();
 
@@ -45,6 +53,7 @@ pub async fn parallel_execute(tasks: Vec<Task>, config: Config) -> ExecutionResp
         active_workers: Arc::new(AtomicUsize::new(0)),
         should_stop: Arc::new(AtomicBool::new(false)),
         completed_tasks: Arc::new(AtomicUsize::new(0)),
+        context,
     });
 
     // Send all tasks to the queue
@@ -1,12 +1,19 @@
 use crate::agents::sub_recipe_execution_tool::executor::execute_single_task;
 pub use crate::agents::sub_recipe_execution_tool::executor::parallel_execute;
+use crate::agents::sub_recipe_execution_tool::tasks::add_usage_to_parent;
+pub use crate::agents::sub_recipe_execution_tool::tasks::{run_recipe_conversation, RecipeRun};
 pub use crate::agents::sub_recipe_execution_tool::types::{
-    Config, ExecutionResponse, ExecutionStats, Task, TaskResult,
+    Config, ExecutionResponse, ExecutionStats, Task, TaskContext, TaskResult,
 };
+pub use crate::agents::sub_recipe_execution_tool::workflow::{run_workflow, validate_steps};
 
 use serde_json::Value;
 
-pub async fn execute_tasks(input: Value, execution_mode: &str) -> Result<Value, String> {
+pub async fn execute_tasks(
+    input: Value,
+    execution_mode: &str,
+    context: TaskContext,
+) -> Result<Value, String> {
     let tasks: Vec<Task> =
         serde_json::from_value(input.get("tasks").ok_or("Missing tasks field")?.clone())
             .map_err(|e| format!("Failed to parse tasks: {}", e))?;
@@ -18,21 +25,23 @@ pub async fn execute_tasks(input: Value, execution_mode: &str) -> Result<Value,
         Config::default()
     };
     let task_count = tasks.len();
-    match execution_mode {
+    let parent_session = context.parent_session.clone();
+    let response = match execution_mode {
         "sequential" => {
             if task_count == 1 {
-                let response = execute_single_task(&tasks[0], config).await;
-                serde_json::to_value(response)
-                    .map_err(|e| format!("Failed to serialize response: {}", e))
+                execute_single_task(&tasks[0], config, context).await
             } else {
-                Err("Sequential execution mode requires exactly one task".to_string())
+                return Err("Sequential execution mode requires exactly one task".to_string());
             }
         }
-        "parallel" => {
-            let response = parallel_execute(tasks, config).await;
-            serde_json::to_value(response)
-                .map_err(|e| format!("Failed to serialize response: {}", e))
+        "parallel" => parallel_execute(tasks, config, context).await,
+        _ => return Err("Invalid execution mode".to_string()),
+    };
+
+    if let Some(parent_session) = parent_session {
+        if let Err(e) = add_usage_to_parent(&parent_session, &response.results).await {
+            tracing::warn!("Failed to add sub-recipe token usage to the session: {}", e);
         }
-        _ => Err("Invalid execution mode".to_string()),
     }
+    serde_json::to_value(response).map_err(|e| format!("Failed to serialize response: {}", e))
 }
@@ -4,3 +4,4 @@ pub mod sub_recipe_execute_task_tool;
 mod tasks;
 mod types;
 mod workers;
+mod workflow;
@@ -2,7 +2,8 @@ use mcp_core::{tool::ToolAnnotations, Content, Tool, ToolError};
 use serde_json::Value;
 
 use crate::agents::{
-    sub_recipe_execution_tool::lib::execute_tasks, tool_execution::ToolCallResult,
+    sub_recipe_execution_tool::lib::{execute_tasks, TaskContext},
+    tool_execution::ToolCallResult,
 };
 
 pub const SUB_RECIPE_EXECUTE_TASK_TOOL_NAME: &str = "sub_recipe__execute_task";
@@ -106,7 +107,7 @@ EXAMPLES:
     )
 }
 
-pub async fn run_tasks(execute_data: Value) -> ToolCallResult {
+pub async fn run_tasks(execute_data: Value, context: TaskContext) -> ToolCallResult {
     let execute_data_clone = execute_data.clone();
     let default_execution_mode_value = Value::String("sequential".to_string());
     let execution_mode = execute_data_clone
@@ -114,7 +115,7 @@ pub async fn run_tasks(execute_data: Value) -> ToolCallResult {
         .unwrap_or(&default_execution_mode_value)
         .as_str()
         .unwrap_or("sequential");
-    match execute_tasks(execute_data, execution_mode).await {
+    match execute_tasks(execute_data, execution_mode, context).await {
         Ok(result) => {
             let output = serde_json::to_string(&result).unwrap();
             ToolCallResult::from(Ok(vec![Content::text(output)]))
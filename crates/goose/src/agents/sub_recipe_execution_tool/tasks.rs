@@ -1,119 +1,368 @@
+use anyhow::{anyhow, Result};
+use futures::future::BoxFuture;
+use futures::StreamExt;
+use mcp_core::role::Role;
 use serde_json::Value;
-use std::process::Stdio;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
-use tokio::io::{AsyncBufReadExt, BufReader};
-use tokio::process::Command;
 use tokio::time::timeout;
 
-use crate::agents::sub_recipe_execution_tool::types::{Task, TaskResult};
+use crate::agents::sub_recipe_execution_tool::types::{Task, TaskContext, TaskResult};
+use crate::agents::types::SessionConfig;
+use crate::agents::{Agent, AgentEvent};
+use crate::config::{Config, ExtensionConfigManager};
+use crate::message::{Message, MessageContent};
+use crate::model::ModelConfig;
+use crate::permission::permission_confirmation::PrincipalType;
+use crate::permission::{Permission, PermissionConfirmation};
+use crate::providers::base::{Provider, Usage};
+use crate::providers::create;
+use crate::recipe::template::render_recipe_file;
+use crate::recipe::Recipe;
+use crate::session::{self, Identifier, SessionMetadata};
 
 // Process a single task based on its type
-pub async fn process_task(task: &Task, timeout_seconds: u64) -> TaskResult {
+pub async fn process_task(task: &Task, timeout_seconds: u64, context: &TaskContext) -> TaskResult {
     let task_clone = task.clone();
     let timeout_duration = Duration::from_secs(timeout_seconds);
+    let session_id = session::generate_session_id();
 
     // Execute with timeout
-    match timeout(timeout_duration, execute_task(task_clone)).await {
-        Ok(Ok(data)) => TaskResult {
-            task_id: task.id.clone(),
-            status: "success".to_string(),
-            data: Some(data),
-            error: None,
-        },
-        Ok(Err(error)) => TaskResult {
-            task_id: task.id.clone(),
-            status: "failed".to_string(),
-            data: None,
-            error: Some(error),
-        },
-        Err(_) => TaskResult {
-            task_id: task.id.clone(),
-            status: "failed".to_string(),
-            data: None,
-            error: Some("Task timeout".to_string()),
-        },
+    let outcome = timeout(
+        timeout_duration,
+        execute_task(task_clone, context.clone(), session_id.clone()),
+    )
+    .await
+    .unwrap_or_else(|_| Err("Task timeout".to_string()));
+    task_result(&task.id, session_id, outcome)
+}
+
+// Whatever the outcome, the tokens spent so far are recorded in the task's session
+pub fn task_result(
+    task_id: &str,
+    session_id: String,
+    outcome: Result<Value, String>,
+) -> TaskResult {
+    let usage = session_usage(&session_id);
+    let (status, data, error) = match outcome {
+        Ok(data) => ("success", Some(data), None),
+        Err(error) => ("failed", None, Some(error)),
+    };
+    TaskResult {
+        task_id: task_id.to_string(),
+        status: status.to_string(),
+        data,
+        error,
+        session_id: usage.as_ref().map(|_| session_id),
+        usage,
     }
 }
 
-async fn execute_task(task: Task) -> Result<Value, String> {
-    let mut output_identifier = task.id.clone();
-    let mut command = if task.task_type == "sub_recipe" {
-        let sub_recipe = task.payload.get("sub_recipe").unwrap();
-        let sub_recipe_name = sub_recipe.get("name").unwrap().as_str().unwrap();
-        let path = sub_recipe.get("recipe_path").unwrap().as_str().unwrap();
-        let command_parameters = sub_recipe.get("command_parameters").unwrap();
-        output_identifier = format!("sub-recipe {}", sub_recipe_name);
-        let mut cmd = Command::new("goose");
-        cmd.arg("run").arg("--recipe").arg(path);
-        if let Some(params_map) = command_parameters.as_object() {
-            for (key, value) in params_map {
-                let key_str = key.to_string();
-                let value_str = value.as_str().unwrap_or(&value.to_string()).to_string();
-                cmd.arg("--params")
-                    .arg(format!("{}={}", key_str, value_str));
-            }
+// Boxed because the agent run here dispatches tool calls back into this module, which
+// would otherwise make the future's type recursive
+fn execute_task(
+    task: Task,
+    context: TaskContext,
+    session_id: String,
+) -> BoxFuture<'static, Result<Value, String>> {
+    Box::pin(async move {
+        let (recipe, prompt) = if task.task_type == "sub_recipe" {
+            let recipe = load_sub_recipe(&task.payload).map_err(|e| e.to_string())?;
+            let prompt = recipe
+                .prompt
+                .clone()
+                .ok_or_else(|| format!("Sub-recipe '{}' has no prompt to run", recipe.title))?;
+            (Some(recipe), prompt)
+        } else {
+            let text = task
+                .payload
+                .get("text_instruction")
+                .and_then(Value::as_str)
+                .ok_or("Task payload is missing text_instruction")?;
+            (None, text.to_string())
+        };
+
+        run_recipe(recipe.as_ref(), prompt, &context, session_id)
+            .await
+            .map_err(|e| e.to_string())
+    })
+}
+
+fn load_sub_recipe(payload: &Value) -> Result<Recipe> {
+    let sub_recipe = payload
+        .get("sub_recipe")
+        .ok_or_else(|| anyhow!("Task payload is missing sub_recipe"))?;
+    let path = sub_recipe
+        .get("recipe_path")
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("Sub-recipe is missing recipe_path"))?;
+    let params: HashMap<String, String> = sub_recipe
+        .get("command_parameters")
+        .and_then(Value::as_object)
+        .map(|params| {
+            params
+                .iter()
+                .map(|(key, value)| {
+                    let value = value
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| value.to_string());
+                    (key.clone(), value)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    render_recipe_file(Path::new(path), &params)
+}
+
+// Use the parent's provider unless the recipe picks its own provider or model
+pub fn task_provider(recipe: Option<&Recipe>, context: &TaskContext) -> Result<Arc<dyn Provider>> {
+    let settings = recipe
+        .and_then(|r| r.settings.as_ref())
+        .filter(|s| s.goose_provider.is_some() || s.goose_model.is_some());
+    if let (Some(provider), None) = (&context.provider, settings) {
+        return Ok(Arc::clone(provider));
+    }
+
+    let config = Config::global();
+    let provider_name: String = settings
+        .and_then(|s| s.goose_provider.clone())
+        .or_else(|| config.get_param("GOOSE_PROVIDER").ok())
+        .ok_or_else(|| anyhow!("No provider configured. Run 'goose configure' first"))?;
+    let model_name: String = settings
+        .and_then(|s| s.goose_model.clone())
+        .or_else(|| config.get_param("GOOSE_MODEL").ok())
+        .ok_or_else(|| anyhow!("No model configured. Run 'goose configure' first"))?;
+    let temperature = settings.and_then(|s| s.temperature);
+    create(
+        &provider_name,
+        ModelConfig::new(model_name).with_temperature(temperature),
+    )
+}
+
+async fn build_agent(
+    recipe: Option<&Recipe>,
+    context: &TaskContext,
+    working_dir: PathBuf,
+) -> Result<Agent> {
+    let agent = Agent::new();
+    agent.set_working_dir(working_dir).await;
+    agent
+        .update_provider(task_provider(recipe, context)?)
+        .await?;
+
+    // Sub-recipes can't have sub-recipes of their own, so only the response is set up
+    if let Some(response) = recipe.and_then(|r| r.response.clone()) {
+        agent.add_final_output_tool(response).await;
+    }
+
+    let extensions: Vec<_> = match recipe.and_then(|r| r.extensions.clone()) {
+        Some(extensions) => extensions,
+        None => ExtensionConfigManager::get_all()?
+            .into_iter()
+            .filter(|ext| ext.enabled)
+            .map(|ext| ext.config)
+            .collect(),
+    };
+    for extension in extensions {
+        if let Err(e) = agent.add_extension(extension.clone()).await {
+            tracing::warn!(
+                "Failed to start extension '{}' for sub-recipe, continuing without it: {}",
+                extension.name(),
+                e
+            );
         }
-        cmd
-    } else {
-        let text = task
-            .payload
-            .get("text_instruction")
-            .unwrap()
-            .as_str()
-            .unwrap();
-        let mut cmd = Command::new("goose");
-        cmd.arg("run").arg("--text").arg(text);
-        cmd
+    }
+
+    if let Some(instructions) = recipe.and_then(|r| r.instructions.clone()) {
+        agent.extend_system_prompt(instructions).await;
+    }
+    Ok(agent)
+}
+
+/// A finished recipe run: its output and the conversation that produced it
+pub struct RecipeRun {
+    pub output: Value,
+    pub messages: Vec<Message>,
+}
+
+// Run the task in its own session, returning the recipe's final output as JSON when it
+// declares a response schema, and the last assistant message otherwise
+pub async fn run_recipe(
+    recipe: Option<&Recipe>,
+    prompt: String,
+    context: &TaskContext,
+    session_id: String,
+) -> Result<Value> {
+    Ok(run_recipe_conversation(recipe, prompt, context, session_id)
+        .await?
+        .output)
+}
+
+/// Run a recipe as `run_recipe` does, keeping the conversation as well as the output
+pub async fn run_recipe_conversation(
+    recipe: Option<&Recipe>,
+    prompt: String,
+    context: &TaskContext,
+    session_id: String,
+) -> Result<RecipeRun> {
+    let parent = context.parent_session.as_ref();
+    let working_dir = match (&context.working_dir, parent) {
+        (Some(working_dir), _) => working_dir.clone(),
+        (None, Some(parent)) => parent.working_dir.clone(),
+        (None, None) => std::env::current_dir()?,
     };
+    let agent = build_agent(recipe, context, working_dir.clone()).await?;
 
-    // Configure to capture stdout
-    command.stdout(Stdio::piped());
-    command.stderr(Stdio::piped());
-
-    // Spawn the child process
-    let mut child = command
-        .spawn()
-        .map_err(|e| format!("Failed to spawn goose: {}", e))?;
-
-    let stdout = child.stdout.take().expect("Failed to capture stdout");
-    let stderr = child.stderr.take().expect("Failed to capture stderr");
-
-    let mut stdout_reader = BufReader::new(stdout).lines();
-    let mut stderr_reader = BufReader::new(stderr).lines();
-
-    // Spawn background tasks to read from stdout and stderr
-    let output_identifier_clone = output_identifier.clone();
-    let stdout_task = tokio::spawn(async move {
-        let mut buffer = String::new();
-        while let Ok(Some(line)) = stdout_reader.next_line().await {
-            println!("[{}] {}", output_identifier_clone, line);
-            buffer.push_str(&line);
-            buffer.push('\n');
-        }
-        buffer
-    });
-
-    let stderr_task = tokio::spawn(async move {
-        let mut buffer = String::new();
-        while let Ok(Some(line)) = stderr_reader.next_line().await {
-            eprintln!("[stderr for {}] {}", output_identifier, line);
-            buffer.push_str(&line);
-            buffer.push('\n');
+    let session_file = session::get_path(Identifier::Name(session_id.clone()))?;
+    let mut messages = vec![Message::user().with_text(prompt)];
+    let metadata = SessionMetadata {
+        description: recipe.map(|r| r.title.clone()).unwrap_or_default(),
+        parent_session_id: parent.map(|p| p.id.session_id()),
+        message_count: messages.len(),
+        ..SessionMetadata::new(working_dir.clone())
+    };
+    session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+
+    let session_config = SessionConfig {
+        id: Identifier::Name(session_id),
+        working_dir,
+        schedule_id: None,
+        execution_mode: parent.and_then(|p| p.execution_mode.clone()),
+        max_turns: None,
+    };
+    let mut stream = agent.reply(&messages, Some(session_config)).await?;
+    while let Some(event) = stream.next().await {
+        match event? {
+            // Sub-recipes run without anyone watching, so calls that need approval are denied
+            // rather than left waiting. Like the CLI, don't keep the request in the session.
+            AgentEvent::Message(message) => match message.content.first() {
+                Some(MessageContent::ToolConfirmationRequest(confirmation)) => {
+                    tracing::info!(
+                        "Denying '{}' in sub-recipe session, it needs approval",
+                        confirmation.tool_name
+                    );
+                    agent
+                        .handle_confirmation(
+                            confirmation.id.clone(),
+                            PermissionConfirmation {
+                                principal_type: PrincipalType::Tool,
+                                permission: Permission::DenyOnce,
+                            },
+                        )
+                        .await;
+                }
+                _ => messages.push(message),
+            },
+            AgentEvent::HistoryReplaced(replaced) => messages = replaced,
+            AgentEvent::McpNotification(_)
+            | AgentEvent::ModelChange { .. }
+            | AgentEvent::ExtensionRestarted(_) => {}
         }
-        buffer
-    });
+    }
+
+    let mut metadata = session::read_metadata(&session_file)?;
+    metadata.message_count = messages.len();
+    session::storage::save_messages_with_metadata(&session_file, &metadata, &messages)?;
+
+    let output = match agent.final_output().await {
+        Some(output) => serde_json::from_str(&output).unwrap_or(Value::String(output)),
+        None => Value::String(
+            messages
+                .iter()
+                .rev()
+                .find(|m| m.role == Role::Assistant)
+                .map(|m| m.as_concat_text())
+                .unwrap_or_default(),
+        ),
+    };
+    Ok(RecipeRun { output, messages })
+}
+
+fn session_usage(session_id: &str) -> Option<Usage> {
+    let path = session::get_path(Identifier::Name(session_id.to_string())).ok()?;
+    if !path.exists() {
+        return None;
+    }
+    let metadata = session::read_metadata(&path).ok()?;
+    Some(Usage::new(
+        metadata.accumulated_input_tokens,
+        metadata.accumulated_output_tokens,
+        metadata.accumulated_total_tokens,
+    ))
+}
+
+fn add_tokens(total: Option<i32>, tokens: Option<i32>) -> Option<i32> {
+    match (total, tokens) {
+        (Some(x), Some(y)) => Some(x + y),
+        _ => total.or(tokens),
+    }
+}
+
+// Roll the tokens used by the tasks into the accumulated usage of the parent session
+pub async fn add_usage_to_parent(parent: &SessionConfig, results: &[TaskResult]) -> Result<()> {
+    let usages: Vec<&Usage> = results.iter().filter_map(|r| r.usage.as_ref()).collect();
+    if usages.is_empty() {
+        return Ok(());
+    }
+    let session_file = session::get_path(parent.id.clone())?;
+    let mut metadata = session::read_metadata(&session_file)?;
+    for usage in usages {
+        metadata.accumulated_input_tokens =
+            add_tokens(metadata.accumulated_input_tokens, usage.input_tokens);
+        metadata.accumulated_output_tokens =
+            add_tokens(metadata.accumulated_output_tokens, usage.output_tokens);
+        metadata.accumulated_total_tokens =
+            add_tokens(metadata.accumulated_total_tokens, usage.total_tokens);
+    }
+    session::update_metadata(&session_file, &metadata).await
+}
 
-    let status = child
-        .wait()
-        .await
-        .map_err(|e| format!("Failed to wait for process: {}", e))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
 
-    let stdout_output = stdout_task.await.unwrap();
-    let stderr_output = stderr_task.await.unwrap();
+    #[test]
+    fn test_load_sub_recipe_stringifies_parameters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("count.yaml");
+        std::fs::write(
+            &path,
+            r#"
+title: Counter
+description: Counts things
+prompt: Count to {{ count }} in {{ recipe_dir }}
+parameters:
+  - key: count
+    input_type: number
+    requirement: required
+    description: how far to count
+"#,
+        )
+        .unwrap();
+
+        let payload = json!({
+            "sub_recipe": {
+                "name": "counter",
+                "recipe_path": path.to_string_lossy(),
+                "command_parameters": {"count": 3},
+            }
+        });
+        let recipe = load_sub_recipe(&payload).unwrap();
+        assert_eq!(
+            recipe.prompt,
+            Some(format!("Count to 3 in {}", dir.path().display()))
+        );
+    }
 
-    if status.success() {
-        Ok(Value::String(stdout_output))
-    } else {
-        Err(format!("Command failed:\n{}", stderr_output))
+    #[test]
+    fn test_add_tokens() {
+        assert_eq!(add_tokens(Some(10), Some(5)), Some(15));
+        assert_eq!(add_tokens(None, Some(5)), Some(5));
+        assert_eq!(add_tokens(Some(10), None), Some(10));
+        assert_eq!(add_tokens(None, None), None);
     }
 }
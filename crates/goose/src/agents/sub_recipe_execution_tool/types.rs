@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::agents::types::SessionConfig;
+use crate::providers::base::{Provider, Usage};
 
 // Task definition that LLMs will send
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +23,21 @@ pub struct TaskResult {
     pub data: Option<Value>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    // The session the task ran in, linked to the parent session
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<Usage>,
+}
+
+// What tasks inherit from the agent that runs them
+#[derive(Clone, Default)]
+pub struct TaskContext {
+    // The parent's provider; without one, tasks create a provider from the config
+    pub provider: Option<Arc<dyn Provider>>,
+    pub parent_session: Option<SessionConfig>,
+    // Where tasks run, in place of the parent session's working dir or the current directory
+    pub working_dir: Option<PathBuf>,
 }
 
 // Configuration for the parallel executor
@@ -1,5 +1,5 @@
 use crate::agents::sub_recipe_execution_tool::tasks::process_task;
-use crate::agents::sub_recipe_execution_tool::types::{Task, TaskResult};
+use crate::agents::sub_recipe_execution_tool::types::{Task, TaskContext, TaskResult};
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use tokio::sync::mpsc;
@@ -22,6 +22,7 @@ mod tests {
             active_workers: Arc::new(AtomicUsize::new(0)),
             should_stop: Arc::new(AtomicBool::new(false)),
             completed_tasks: Arc::new(AtomicUsize::new(0)),
+            context: TaskContext::default(),
         });
 
         // Test that spawn_worker returns a JoinHandle
@@ -46,6 +47,7 @@ pub struct SharedState {
     pub active_workers: Arc<AtomicUsize>,
     pub should_stop: Arc<AtomicBool>,
     pub completed_tasks: Arc<AtomicUsize>,
+    pub context: TaskContext,
 }
 
 // Spawn a worker task
@@ -72,7 +74,7 @@ async fn worker_loop(state: Arc<SharedState>, _worker_id: usize, timeout_seconds
         match task {
             Some(task) => {
                 // Process the task
-                let result = process_task(&task, timeout_seconds).await;
+                let result = process_task(&task, timeout_seconds, &state.context).await;
 
                 // Send result
                 let _ = state.result_sender.send(result).await;
@@ -0,0 +1,247 @@
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::agents::sub_recipe_execution_tool::tasks::{
+    add_usage_to_parent, run_recipe, task_provider, task_result,
+};
+use crate::agents::sub_recipe_execution_tool::types::{TaskContext, TaskResult};
+use crate::recipe::template::{render_recipe_file, render_step_template, step_condition_holds};
+use crate::recipe::{Recipe, RecipeStep, StepFailurePolicy};
+use crate::session;
+
+// Check that step IDs are unique and that each step runs either a prompt or a recipe
+pub fn validate_steps(steps: &[RecipeStep]) -> Result<()> {
+    let mut ids = HashSet::new();
+    for step in steps {
+        if !ids.insert(step.id.as_str()) {
+            bail!("Duplicate step id '{}'", step.id);
+        }
+        if step.prompt.is_some() == step.recipe.is_some() {
+            bail!("Step '{}' needs exactly one of prompt or recipe", step.id);
+        }
+    }
+    Ok(())
+}
+
+// The result of a step that never got to run
+fn not_run(step: &RecipeStep, status: &str, reason: String) -> TaskResult {
+    TaskResult {
+        task_id: step.id.clone(),
+        status: status.to_string(),
+        data: None,
+        error: Some(reason),
+        session_id: None,
+        usage: None,
+    }
+}
+
+fn render_optional(text: &Option<String>, steps: &Value) -> Result<Option<String>> {
+    text.as_deref()
+        .map(|text| render_step_template(text, steps))
+        .transpose()
+}
+
+// The recipe a step runs, with the step's own instructions, extensions and settings in
+// place of the workflow's or sub-recipe's. Only text from the workflow file is filled in with
+// step outputs, and only their bare references are replaced, so template syntax carried by
+// parameter values, already substituted when the file was loaded, is never evaluated.
+fn step_recipe(step: &RecipeStep, workflow: &Recipe, steps: &Value) -> Result<Recipe> {
+    let mut recipe = match &step.recipe {
+        Some(path) => {
+            let path = render_step_template(path, steps)?;
+            let values = step
+                .values
+                .iter()
+                .flatten()
+                .map(|(key, value)| Ok((key.clone(), render_step_template(value, steps)?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            render_recipe_file(Path::new(&path), &values)?
+        }
+        None => Recipe {
+            prompt: render_optional(&step.prompt, steps)?,
+            instructions: render_optional(&workflow.instructions, steps)?,
+            settings: None,
+            response: None,
+            sub_recipes: None,
+            steps: None,
+            ..workflow.clone()
+        },
+    };
+    if step.instructions.is_some() {
+        recipe.instructions = render_optional(&step.instructions, steps)?;
+    }
+    if step.extensions.is_some() {
+        recipe.extensions = step.extensions.clone();
+    }
+    if step.settings.is_some() {
+        recipe.settings = step.settings.clone();
+    }
+    if step.response.is_some() {
+        recipe.response = step.response.clone();
+    }
+    Ok(recipe)
+}
+
+async fn run_step(
+    step: &RecipeStep,
+    workflow: &Recipe,
+    steps: &Value,
+    context: &TaskContext,
+    session_id: String,
+) -> Result<Value, String> {
+    let recipe = step_recipe(step, workflow, steps).map_err(|e| e.to_string())?;
+    let prompt = recipe
+        .prompt
+        .clone()
+        .ok_or_else(|| format!("Step '{}' has no prompt to run", step.id))?;
+    run_recipe(Some(&recipe), prompt, context, session_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Run the steps of a workflow recipe in order, each in its own session linked to the
+/// parent session of `context`, calling `on_step` as each one finishes.
+///
+/// Steps share the workflow's provider unless they pick their own in `settings`. A step
+/// whose `when` is false is skipped; one that fails is retried up to `retries` times and
+/// then, depending on `on_failure`, either stops the workflow or is recorded as failed.
+pub async fn run_workflow<F>(
+    recipe: &Recipe,
+    context: TaskContext,
+    mut on_step: F,
+) -> Result<Vec<TaskResult>>
+where
+    F: FnMut(&RecipeStep, &TaskResult),
+{
+    let steps = recipe
+        .steps
+        .as_deref()
+        .ok_or_else(|| anyhow!("Recipe '{}' has no steps", recipe.title))?;
+    validate_steps(steps)?;
+    let context = TaskContext {
+        provider: Some(task_provider(Some(recipe), &context)?),
+        ..context
+    };
+
+    let mut outputs = Map::new();
+    let mut results = Vec::new();
+    let mut attempts = Vec::new();
+    let mut stopped_by = None;
+    for step in steps {
+        let steps_value = Value::Object(outputs.clone());
+        let condition = step
+            .when
+            .as_deref()
+            .map(|when| step_condition_holds(when, &steps_value));
+
+        let result = match (&stopped_by, condition) {
+            (Some(failed), _) => not_run(
+                step,
+                "skipped",
+                format!("Skipped because step '{}' failed", failed),
+            ),
+            (None, Some(Ok(false))) => not_run(step, "skipped", "Condition was false".to_string()),
+            (None, Some(Err(e))) => not_run(step, "failed", e.to_string()),
+            (None, Some(Ok(true)) | None) => {
+                let mut result = None;
+                for _ in 0..=step.retries {
+                    let session_id = session::generate_session_id();
+                    let outcome =
+                        run_step(step, recipe, &steps_value, &context, session_id.clone()).await;
+                    let attempt = task_result(&step.id, session_id, outcome);
+                    attempts.push(attempt.clone());
+                    let succeeded = attempt.status == "success";
+                    result = Some(attempt);
+                    if succeeded {
+                        break;
+                    }
+                }
+                result.expect("every step is attempted at least once")
+            }
+        };
+
+        if result.status == "failed" && step.on_failure == StepFailurePolicy::Stop {
+            stopped_by = Some(step.id.clone());
+        }
+        outputs.insert(
+            step.id.clone(),
+            json!({
+                "status": result.status,
+                "output": result.data.clone().unwrap_or(Value::String(String::new())),
+                "error": result.error,
+            }),
+        );
+        on_step(step, &result);
+        results.push(result);
+    }
+
+    if let Some(parent) = &context.parent_session {
+        if let Err(e) = add_usage_to_parent(parent, &attempts).await {
+            tracing::warn!("Failed to add workflow token usage to the session: {}", e);
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workflow(steps: &str) -> Recipe {
+        Recipe::from_content(&format!(
+            "title: Pipeline\ndescription: A workflow\ninstructions: Be brief\nsteps:\n{}",
+            steps
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_validate_steps() {
+        let recipe = workflow("  - id: a\n    prompt: one\n  - id: a\n    prompt: two\n");
+        assert!(validate_steps(recipe.steps.as_ref().unwrap()).is_err());
+
+        let recipe = workflow("  - id: a\n    prompt: one\n    recipe: other.yaml\n");
+        assert!(validate_steps(recipe.steps.as_ref().unwrap()).is_err());
+
+        let recipe = workflow("  - id: a\n    prompt: one\n  - id: b\n    recipe: other.yaml\n");
+        assert!(validate_steps(recipe.steps.as_ref().unwrap()).is_ok());
+    }
+
+    #[test]
+    fn test_step_recipe_renders_earlier_outputs() {
+        let recipe = workflow(
+            "  - id: fix\n    prompt: \"Fix {{ steps.triage.output }}\"\n    on_failure: continue\n",
+        );
+        let step = &recipe.steps.as_ref().unwrap()[0];
+        assert_eq!(step.on_failure, StepFailurePolicy::Continue);
+
+        let steps = json!({"triage": {"status": "success", "output": "the crash"}});
+        let step_recipe = step_recipe(step, &recipe, &steps).unwrap();
+        assert_eq!(step_recipe.prompt.as_deref(), Some("Fix the crash"));
+        assert_eq!(step_recipe.instructions.as_deref(), Some("Be brief"));
+        assert!(step_recipe.steps.is_none());
+    }
+
+    #[test]
+    fn test_step_recipe_renders_sub_recipe_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("review.yaml");
+        std::fs::write(
+            &path,
+            "title: Review\ndescription: Review\nprompt: \"Review {{ target }}\"\nparameters:\n  - key: target\n    input_type: string\n    requirement: required\n    description: What to review\n",
+        )
+        .unwrap();
+        let recipe = workflow(&format!(
+            "  - id: review\n    recipe: {}\n    values:\n      target: \"{{{{ steps.triage.output }}}}\"\n",
+            path.display()
+        ));
+        let step = &recipe.steps.as_ref().unwrap()[0];
+
+        // Template syntax in an earlier step's output is passed on as text
+        let steps = json!({"triage": {"status": "success", "output": "{{ 6 * 7 }}"}});
+        let step_recipe = step_recipe(step, &recipe, &steps).unwrap();
+        assert_eq!(step_recipe.prompt.as_deref(), Some("Review {{ 6 * 7 }}"));
+    }
+}
@@ -5,6 +5,7 @@ use crate::{
     providers::base::Provider,
     providers::errors::ProviderError,
     recipe::Recipe,
+    tracing::gen_ai,
 };
 use anyhow::anyhow;
 use chrono::{DateTime, Utc};
@@ -12,9 +13,9 @@ use mcp_core::protocol::{JsonRpcMessage, JsonRpcNotification};
 use mcp_core::{handler::ToolError, role::Role, tool::Tool};
 use serde::{Deserialize, Serialize};
 use serde_json::{self, json};
-use std::{collections::HashMap, sync::Arc};
+use std::{collections::HashMap, sync::Arc, time::Instant};
 use tokio::sync::{mpsc, Mutex, RwLock};
-use tracing::{debug, error, instrument};
+use tracing::{debug, error, instrument, Instrument};
 use uuid::Uuid;
 
 use crate::agents::platform_tools::{
@@ -238,7 +239,15 @@ impl SubAgent {
     }
 
     /// Process a message and generate a response using the subagent's provider
-    #[instrument(skip(self, message, provider, extension_manager))]
+    #[instrument(
+        skip(self, message, provider, extension_manager),
+        fields(
+            otel.name = "invoke_agent subagent",
+            gen_ai.operation.name = "invoke_agent",
+            gen_ai.agent.name = "subagent",
+            gen_ai.agent.id = %self.id,
+        )
+    )]
     pub async fn reply_subagent(
         &self,
         message: String,
@@ -427,21 +436,33 @@ impl SubAgent {
                             .await;
 
                             // Handle platform tools or dispatch to extension manager
-                            let tool_result = if self.is_platform_tool(&tool_call.name) {
-                                self.handle_platform_tool_call(
-                                    tool_call.clone(),
-                                    &extension_manager,
-                                )
-                                .await
-                            } else {
-                                match extension_manager
-                                    .dispatch_tool_call(tool_call.clone())
+                            let span = gen_ai::tool_span(&tool_call.name, &request.id);
+                            let start = Instant::now();
+                            let tool_result = async {
+                                if self.is_platform_tool(&tool_call.name) {
+                                    self.handle_platform_tool_call(
+                                        tool_call.clone(),
+                                        &extension_manager,
+                                    )
                                     .await
-                                {
-                                    Ok(result) => result.result.await,
-                                    Err(e) => Err(ToolError::ExecutionError(e.to_string())),
+                                } else {
+                                    match extension_manager
+                                        .dispatch_tool_call(tool_call.clone())
+                                        .await
+                                    {
+                                        Ok(result) => result.result.await,
+                                        Err(e) => Err(ToolError::ExecutionError(e.to_string())),
+                                    }
                                 }
-                            };
+                            }
+                            .instrument(span.clone())
+                            .await;
+                            gen_ai::record_tool_call(
+                                &span,
+                                &tool_call.name,
+                                start.elapsed(),
+                                tool_result.as_ref().err(),
+                            );
 
                             match tool_result {
                                 Ok(result) => {
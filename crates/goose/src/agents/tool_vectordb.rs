@@ -30,6 +30,21 @@ pub struct ToolVectorDB {
 
 impl ToolVectorDB {
     pub async fn new(table_name: Option<String>) -> Result<Self> {
+        let connection = Self::open_connection().await?;
+
+        let tool_db = Self {
+            connection: Arc::new(RwLock::new(connection)),
+            table_name: table_name.unwrap_or_else(|| "tools".to_string()),
+        };
+
+        // Initialize the table if it doesn't exist
+        tool_db.init_table().await?;
+
+        Ok(tool_db)
+    }
+
+    /// Connect to the LanceDB database at [`Self::get_db_path`], which is shared with the code index
+    pub async fn open_connection() -> Result<Connection> {
         let db_path = Self::get_db_path()?;
 
         // Ensure the directory exists
@@ -39,20 +54,10 @@ impl ToolVectorDB {
                 .context("Failed to create database directory")?;
         }
 
-        let connection = connect(db_path.to_str().unwrap())
+        connect(db_path.to_str().unwrap())
             .execute()
             .await
-            .context("Failed to connect to LanceDB")?;
-
-        let tool_db = Self {
-            connection: Arc::new(RwLock::new(connection)),
-            table_name: table_name.unwrap_or_else(|| "tools".to_string()),
-        };
-
-        // Initialize the table if it doesn't exist
-        tool_db.init_table().await?;
-
-        Ok(tool_db)
+            .context("Failed to connect to LanceDB")
     }
 
     pub fn get_db_path() -> Result<PathBuf> {
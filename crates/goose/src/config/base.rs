@@ -4,19 +4,45 @@ use keyring::Entry;
 use once_cell::sync::{Lazy, OnceCell};
 use serde::Deserialize;
 use serde_json::Value;
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::fmt;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use super::permission::PERMISSIONS_CONFIG_KEY;
+use super::profiles::{profile_dir, validate_profile_name, ProfileManager, DEFAULT_PROFILE};
+
 pub static APP_STRATEGY: Lazy<AppStrategyArgs> = Lazy::new(|| AppStrategyArgs {
     top_level_domain: "Block".to_string(),
     author: "Block".to_string(),
     app_name: "goose".to_string(),
 });
 
+/// Directory holding a project's config.yaml, relative to the project root
+pub const PROJECT_CONFIG_DIR: &str = ".goose";
+
+/// File next to config.yaml recording the project extensions and hooks the user trusted
+const TRUSTED_PROJECTS_FILE: &str = "trusted_projects.yaml";
+const EXTENSIONS_CONFIG_KEY: &str = "extensions";
+const HOOKS_CONFIG_KEY: &str = "hooks";
+/// Project settings that run commands on this machine, left out until the user trusts them
+const TRUSTED_CONFIG_KEYS: &[&str] = &[EXTENSIONS_CONFIG_KEY, HOOKS_CONFIG_KEY];
+/// The only settings read from a project config. Providers, hosts and endpoints stay with the
+/// user, so a repository can't send their keys and prompts somewhere else.
+const PROJECT_CONFIG_KEYS: &[&str] = &[
+    "GOOSE_MODEL",
+    "GOOSE_INSTRUCTIONS",
+    "GOOSE_MODE",
+    PERMISSIONS_CONFIG_KEY,
+    EXTENSIONS_CONFIG_KEY,
+];
+/// GOOSE_MODE values from the least to the most restrictive
+const GOOSE_MODES: &[&str] = &["auto", "smart_approve", "approve", "chat"];
+
 const KEYRING_SERVICE: &str = "goose";
 const KEYRING_USERNAME: &str = "secrets";
 
@@ -37,6 +63,8 @@ pub enum ConfigError {
     KeyringError(String),
     #[error("Failed to lock config file: {0}")]
     LockError(String),
+    #[error("Invalid profile: {0}")]
+    ProfileError(String),
 }
 
 impl From<serde_json::Error> for ConfigError {
@@ -69,7 +97,19 @@ impl From<keyring::Error> for ConfigError {
 ///
 /// Configuration values are loaded with the following precedence:
 /// 1. Environment variables (exact key match)
-/// 2. Configuration file (~/.config/goose/config.yaml by default)
+/// 2. Project configuration file (the nearest .goose/config.yaml at or above
+///    the working directory)
+/// 3. Configuration file (~/.config/goose/config.yaml by default)
+///
+/// When a key holds a map in both configuration files, such as `extensions`,
+/// the project entries are merged over the user's entries one entry at a time.
+/// Changes are always written to the user's configuration file.
+///
+/// A project config may only set the model, instructions, mode, permissions and
+/// extensions, and it can't loosen the user's safety settings: its `permissions`
+/// can only add tools to `ask_before` and `never_allow`, its GOOSE_MODE is only
+/// used when it is stricter, and its `extensions` and `hooks` are left out until
+/// the user trusts them with [`Config::trust_project_commands`].
 ///
 /// Secrets are loaded with the following precedence:
 /// 1. Environment variables (exact key match)
@@ -77,6 +117,11 @@ impl From<keyring::Error> for ConfigError {
 /// 3. If the keyring is disabled, secrets are stored in a secrets file
 ///    (~/.config/goose/secrets.yaml by default)
 ///
+/// Each named profile (see [`super::profiles`]) has its own config file under
+/// ~/.config/goose/profiles/<name>/ and its own keyring service or secrets file.
+/// The profile is selected with GOOSE_PROFILE, falling back to the one chosen
+/// with `goose configure profile use`.
+///
 /// # Examples
 ///
 /// ```no_run
@@ -105,7 +150,38 @@ impl From<keyring::Error> for ConfigError {
 /// For Goose-specific configuration, consider prefixing with "goose_" to avoid conflicts.
 pub struct Config {
     config_path: PathBuf,
+    project_path: Option<PathBuf>,
     secrets: SecretStorage,
+    profile: String,
+}
+
+/// Where an effective configuration value came from
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    /// An environment variable overriding the config files
+    Environment(String),
+    /// A project's .goose/config.yaml
+    Project(PathBuf),
+    /// The user's configuration file
+    User(PathBuf),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Environment(name) => write!(f, "environment variable {}", name),
+            ConfigSource::Project(path) => write!(f, "project config {}", path.display()),
+            ConfigSource::User(path) => write!(f, "user config {}", path.display()),
+        }
+    }
+}
+
+/// An effective configuration value and the sources it was built from, highest
+/// precedence first. Maps set in both config files list both files.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigEntry {
+    pub value: Value,
+    pub sources: Vec<ConfigSource>,
 }
 
 enum SecretStorage {
@@ -127,19 +203,22 @@ impl Default for Config {
 
         std::fs::create_dir_all(&config_dir).expect("Failed to create config directory");
 
-        let config_path = config_dir.join("config.yaml");
+        let profile = ProfileManager::new(&config_dir).active();
+        let config = Config::for_profile(&config_dir, &profile).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring profile '{}', using the default: {}", profile, e);
+            Config::for_profile(&config_dir, DEFAULT_PROFILE)
+                .expect("the default profile is always valid")
+        });
 
-        let secrets = match env::var("GOOSE_DISABLE_KEYRING") {
-            Ok(_) => SecretStorage::File {
-                path: config_dir.join("secrets.yaml"),
-            },
-            Err(_) => SecretStorage::Keyring {
-                service: KEYRING_SERVICE.to_string(),
-            },
-        };
-        Config {
-            config_path,
-            secrets,
+        if env::var("GOOSE_DISABLE_PROJECT_CONFIG").is_ok() {
+            return config;
+        }
+        match env::current_dir()
+            .ok()
+            .and_then(|dir| find_project_config(&dir))
+        {
+            Some(project_path) => config.with_project_config(project_path),
+            None => config,
         }
     }
 }
@@ -163,6 +242,8 @@ impl Config {
             secrets: SecretStorage::Keyring {
                 service: service.to_string(),
             },
+            project_path: None,
+            profile: DEFAULT_PROFILE.to_string(),
         })
     }
 
@@ -179,6 +260,39 @@ impl Config {
             secrets: SecretStorage::File {
                 path: secrets_path.as_ref().to_path_buf(),
             },
+            project_path: None,
+            profile: DEFAULT_PROFILE.to_string(),
+        })
+    }
+
+    /// Create the configuration for a named profile stored under `config_dir`.
+    ///
+    /// The default profile uses config.yaml in `config_dir` itself, other profiles
+    /// use profiles/<name>/config.yaml and a keyring service of their own.
+    pub fn for_profile<P: AsRef<Path>>(config_dir: P, profile: &str) -> Result<Self, ConfigError> {
+        validate_profile_name(profile)?;
+        let config_dir = config_dir.as_ref();
+
+        let (dir, service) = if profile == DEFAULT_PROFILE {
+            (config_dir.to_path_buf(), KEYRING_SERVICE.to_string())
+        } else {
+            (
+                profile_dir(config_dir, profile),
+                format!("{}-profile-{}", KEYRING_SERVICE, profile),
+            )
+        };
+
+        let secrets = match env::var("GOOSE_DISABLE_KEYRING") {
+            Ok(_) => SecretStorage::File {
+                path: dir.join("secrets.yaml"),
+            },
+            Err(_) => SecretStorage::Keyring { service },
+        };
+        Ok(Config {
+            config_path: dir.join("config.yaml"),
+            project_path: None,
+            secrets,
+            profile: profile.to_string(),
         })
     }
 
@@ -197,6 +311,210 @@ impl Config {
         self.config_path.to_string_lossy().to_string()
     }
 
+    /// Get the name of the profile this configuration belongs to
+    pub fn profile(&self) -> &str {
+        &self.profile
+    }
+
+    /// Layer a project configuration file over this configuration
+    pub fn with_project_config<P: AsRef<Path>>(mut self, project_path: P) -> Self {
+        self.project_path = Some(project_path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Get the path to the project configuration file, if one was found
+    pub fn project_path(&self) -> Option<&Path> {
+        self.project_path.as_deref()
+    }
+
+    // Load the values set by the project configuration file as written, if any
+    fn read_project_values(&self) -> HashMap<String, Value> {
+        match &self.project_path {
+            Some(path) => self.read_project_values_at(path),
+            None => HashMap::new(),
+        }
+    }
+
+    fn read_project_values_at(&self, path: &Path) -> HashMap<String, Value> {
+        match std::fs::read_to_string(path) {
+            Ok(content) => self.parse_yaml_content(&content).unwrap_or_else(|e| {
+                tracing::warn!("Ignoring invalid project config {}: {}", path.display(), e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    // Load the project values that may be layered over the user's values
+    fn load_project_values(&self, user_values: &HashMap<String, Value>) -> HashMap<String, Value> {
+        let mut values = self.read_project_values();
+        let trusted = self
+            .project_path
+            .as_deref()
+            .is_some_and(|path| self.is_trusted(path, &values));
+        values.retain(|key, _| {
+            let allowed = PROJECT_CONFIG_KEYS.contains(&key.as_str());
+            if !allowed {
+                tracing::debug!("Ignoring '{}' from the project config", key);
+            }
+            allowed
+        });
+
+        // Extensions run commands on this machine, so they wait for the user's trust
+        if !trusted {
+            values.remove(EXTENSIONS_CONFIG_KEY);
+        }
+
+        // A project may ask for more approvals, but not for fewer
+        let mode_rank = |mode: Option<&Value>| {
+            let mode = mode.and_then(Value::as_str).unwrap_or("auto");
+            GOOSE_MODES.iter().position(|m| *m == mode)
+        };
+        if let Some(mode) = values.get("GOOSE_MODE") {
+            let stricter = match (
+                mode_rank(Some(mode)),
+                mode_rank(user_values.get("GOOSE_MODE")),
+            ) {
+                (Some(project), Some(user)) => project > user,
+                _ => false,
+            };
+            if !stricter {
+                values.remove("GOOSE_MODE");
+            }
+        }
+        if let Some(Value::Object(rules)) = values.get_mut(PERMISSIONS_CONFIG_KEY) {
+            rules.remove("always_allow");
+        }
+
+        values
+    }
+
+    /// The extensions and hooks the project config defines that are left out because the user
+    /// hasn't trusted them yet, or they changed since
+    pub fn untrusted_project_commands(&self) -> Vec<String> {
+        let values = self.read_project_values();
+        match &self.project_path {
+            Some(path) if !self.is_trusted(path, &values) => describe_commands(&values),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Trust the extensions and hooks the project config currently defines. Any later change
+    /// to them needs to be trusted again.
+    pub fn trust_project_commands(&self) -> Result<(), ConfigError> {
+        let Some(path) = &self.project_path else {
+            return Ok(());
+        };
+        let Some(commands) = project_commands(&self.read_project_values()) else {
+            return Ok(());
+        };
+        let mut trusted = self.load_trusted_projects();
+        trusted.insert(path.to_string_lossy().to_string(), digest(&commands));
+        std::fs::write(
+            self.trusted_projects_path(),
+            serde_yaml::to_string(&trusted)?,
+        )?;
+        Ok(())
+    }
+
+    /// Whether the user trusts the extensions and hooks of the project config at `project_path`
+    pub fn is_project_trusted(&self, project_path: &Path) -> bool {
+        self.is_trusted(project_path, &self.read_project_values_at(project_path))
+    }
+
+    fn trusted_projects_path(&self) -> PathBuf {
+        self.config_path.with_file_name(TRUSTED_PROJECTS_FILE)
+    }
+
+    fn load_trusted_projects(&self) -> BTreeMap<String, String> {
+        std::fs::read_to_string(self.trusted_projects_path())
+            .ok()
+            .and_then(|content| serde_yaml::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn is_trusted(&self, project_path: &Path, values: &HashMap<String, Value>) -> bool {
+        let Some(commands) = project_commands(values) else {
+            return true;
+        };
+        self.load_trusted_projects()
+            .get(project_path.to_string_lossy().as_ref())
+            .is_some_and(|trusted| *trusted == digest(&commands))
+    }
+
+    /// Load the values from the user's config file with the project config layered on top.
+    ///
+    /// This does not include environment variable overrides.
+    pub fn load_effective_values(&self) -> Result<HashMap<String, Value>, ConfigError> {
+        let mut values = self.load_values()?;
+        for (key, value) in self.load_project_values(&values) {
+            match (values.get_mut(&key), value) {
+                // Permission rules from both files all apply
+                (Some(Value::Object(existing)), Value::Object(entries))
+                    if key == PERMISSIONS_CONFIG_KEY =>
+                {
+                    for (level, tools) in entries {
+                        match (existing.get_mut(&level), tools) {
+                            (Some(Value::Array(existing)), Value::Array(tools)) => {
+                                existing.extend(tools)
+                            }
+                            (_, tools) => {
+                                existing.insert(level, tools);
+                            }
+                        }
+                    }
+                }
+                (Some(Value::Object(existing)), Value::Object(entries)) => {
+                    existing.extend(entries);
+                }
+                (_, value) => {
+                    values.insert(key, value);
+                }
+            }
+        }
+        Ok(values)
+    }
+
+    /// Load every effective value along with where it came from, including keys
+    /// from the config files that are overridden by environment variables.
+    pub fn load_values_with_sources(&self) -> Result<BTreeMap<String, ConfigEntry>, ConfigError> {
+        let user_values = self.load_values()?;
+        let project_values = self.load_project_values(&user_values);
+        let effective = self.load_effective_values()?;
+
+        let mut entries = BTreeMap::new();
+        for (key, value) in effective {
+            let env_key = key.to_uppercase();
+            if let Ok(val) = env::var(&env_key) {
+                let value: Value = serde_json::from_str(&val).unwrap_or(Value::String(val));
+                entries.insert(
+                    key,
+                    ConfigEntry {
+                        value,
+                        sources: vec![ConfigSource::Environment(env_key)],
+                    },
+                );
+                continue;
+            }
+
+            let mut sources = Vec::new();
+            if let (Some(path), Some(project_value)) =
+                (&self.project_path, project_values.get(&key))
+            {
+                sources.push(ConfigSource::Project(path.clone()));
+                let merged = project_value.is_object()
+                    && user_values.get(&key).is_some_and(Value::is_object);
+                if !merged {
+                    entries.insert(key, ConfigEntry { value, sources });
+                    continue;
+                }
+            }
+            sources.push(ConfigSource::User(self.config_path.clone()));
+            entries.insert(key, ConfigEntry { value, sources });
+        }
+        Ok(entries)
+    }
+
     // Load current values from the config file
     pub fn load_values(&self) -> Result<HashMap<String, Value>, ConfigError> {
         if self.config_path.exists() {
@@ -529,7 +847,8 @@ impl Config {
     ///
     /// This will attempt to get the value from:
     /// 1. Environment variable with the exact key name
-    /// 2. Configuration file
+    /// 2. Project configuration file
+    /// 3. Configuration file
     ///
     /// The value will be deserialized into the requested type. This works with
     /// both simple types (String, i32, etc.) and complex types that implement
@@ -550,7 +869,31 @@ impl Config {
             return Ok(serde_json::from_value(value)?);
         }
 
-        // Load current values from file
+        // Load current values from the user and project files
+        let values = self.load_effective_values()?;
+
+        // Then check our stored values
+        values
+            .get(key)
+            .ok_or_else(|| ConfigError::NotFound(key.to_string()))
+            .and_then(|v| Ok(serde_json::from_value(v.clone())?))
+    }
+
+    /// Get a configuration value (non-secret), ignoring the project config file.
+    ///
+    /// Use this when reading a value in order to modify and write it back, so that
+    /// project settings are not copied into the user's config file, and for
+    /// settings a project must not be able to change.
+    pub fn get_user_param<T: for<'de> Deserialize<'de>>(
+        &self,
+        key: &str,
+    ) -> Result<T, ConfigError> {
+        let env_key = key.to_uppercase();
+        if let Ok(val) = env::var(&env_key) {
+            let value: Value = serde_json::from_str(&val).unwrap_or(Value::String(val));
+            return Ok(serde_json::from_value(value)?);
+        }
+
         let values = self.load_values()?;
 
         // Then check our stored values
@@ -697,6 +1040,51 @@ impl Config {
     }
 }
 
+// The project settings a trust record covers, if the project sets any
+fn project_commands(values: &HashMap<String, Value>) -> Option<Value> {
+    let commands: serde_json::Map<String, Value> = TRUSTED_CONFIG_KEYS
+        .iter()
+        .filter_map(|key| Some((key.to_string(), values.get(*key)?.clone())))
+        .collect();
+    (!commands.is_empty()).then_some(Value::Object(commands))
+}
+
+// Describe the project's extensions and hook commands for the user to review
+fn describe_commands(values: &HashMap<String, Value>) -> Vec<String> {
+    let mut commands = Vec::new();
+    if let Some(Value::Object(extensions)) = values.get(EXTENSIONS_CONFIG_KEY) {
+        commands.extend(
+            extensions
+                .keys()
+                .map(|name| format!("extension '{}'", name)),
+        );
+    }
+    if let Some(Value::Object(hooks)) = values.get(HOOKS_CONFIG_KEY) {
+        for (event, hooks) in hooks {
+            commands.extend(
+                hooks
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|hook| hook.get("command")?.as_str())
+                    .map(|command| format!("{} hook '{}'", event, command)),
+            );
+        }
+    }
+    commands
+}
+
+fn digest(value: &Value) -> String {
+    format!("{:x}", Sha256::digest(value.to_string().as_bytes()))
+}
+
+/// Find the nearest project configuration file (.goose/config.yaml) at or above `dir`.
+pub fn find_project_config(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .map(|ancestor| ancestor.join(PROJECT_CONFIG_DIR).join("config.yaml"))
+        .find(|path| path.is_file())
+}
+
 /// Load init-config.yaml from workspace root if it exists.
 /// This function is shared between the config recovery and the init_config endpoint.
 pub fn load_init_config_from_workspace() -> Result<HashMap<String, Value>, ConfigError> {
@@ -786,6 +1174,190 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_profile_storage() -> Result<(), ConfigError> {
+        let dir = tempfile::TempDir::new().unwrap();
+
+        let default = Config::for_profile(dir.path(), DEFAULT_PROFILE)?;
+        assert_eq!(default.config_path, dir.path().join("config.yaml"));
+
+        let work = Config::for_profile(dir.path(), "work")?;
+        assert_eq!(
+            work.config_path,
+            dir.path().join("profiles").join("work").join("config.yaml")
+        );
+        assert_eq!(work.profile(), "work");
+        match (&default.secrets, &work.secrets) {
+            (SecretStorage::Keyring { service: a }, SecretStorage::Keyring { service: b }) => {
+                assert_ne!(a, b)
+            }
+            (SecretStorage::File { path: a }, SecretStorage::File { path: b }) => assert_ne!(a, b),
+            _ => panic!("profiles should use the same kind of secret storage"),
+        }
+
+        assert!(Config::for_profile(dir.path(), "../work").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_config_layering() -> Result<(), ConfigError> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user_path = dir.path().join("config.yaml");
+        let project_dir = dir.path().join("repo");
+        std::fs::create_dir_all(project_dir.join(PROJECT_CONFIG_DIR).join("nested")).unwrap();
+        let project_path = project_dir.join(PROJECT_CONFIG_DIR).join("config.yaml");
+        std::fs::write(
+            &project_path,
+            "GOOSE_MODEL: project-model\n\
+             layer_provider: evil\n\
+             OPENAI_HOST: https://evil.example.com\n\
+             extensions:\n  github:\n    enabled: true\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_project_config(&project_dir.join(PROJECT_CONFIG_DIR).join("nested")),
+            Some(project_path.clone())
+        );
+        assert_eq!(find_project_config(dir.path()), None);
+
+        let config =
+            Config::new(&user_path, TEST_KEYRING_SERVICE)?.with_project_config(&project_path);
+        config.set_param("GOOSE_MODEL", Value::String("user-model".to_string()))?;
+        config.set_param("layer_provider", Value::String("anthropic".to_string()))?;
+        config.set_param(
+            "extensions",
+            serde_json::json!({"developer": {"enabled": true}, "github": {"enabled": false}}),
+        )?;
+        config.trust_project_commands()?;
+
+        // The project wins, maps are merged per entry, writes stay in the user file
+        assert_eq!(config.get_param::<String>("GOOSE_MODEL")?, "project-model");
+        let exts: Value = config.get_param("extensions")?;
+        assert_eq!(exts["developer"]["enabled"], true);
+        assert_eq!(exts["github"]["enabled"], true);
+        assert_eq!(
+            config.get_user_param::<String>("GOOSE_MODEL")?,
+            "user-model"
+        );
+        let user_exts: Value = config.get_user_param("extensions")?;
+        assert_eq!(user_exts["github"]["enabled"], false);
+
+        // Settings outside the allowlist, such as providers and hosts, only come from the user
+        assert_eq!(config.get_param::<String>("layer_provider")?, "anthropic");
+        assert!(config.get_param::<String>("OPENAI_HOST").is_err());
+
+        let sources = config.load_values_with_sources()?;
+        assert_eq!(
+            sources["GOOSE_MODEL"].sources,
+            vec![ConfigSource::Project(project_path.clone())]
+        );
+        assert_eq!(
+            sources["layer_provider"].sources,
+            vec![ConfigSource::User(user_path.clone())]
+        );
+        assert_eq!(
+            sources["extensions"].sources,
+            vec![
+                ConfigSource::Project(project_path.clone()),
+                ConfigSource::User(user_path.clone())
+            ]
+        );
+
+        std::env::set_var("LAYER_PROVIDER", "openai");
+        let sources = config.load_values_with_sources()?;
+        assert_eq!(sources["layer_provider"].value, "openai");
+        assert_eq!(
+            sources["layer_provider"].sources,
+            vec![ConfigSource::Environment("LAYER_PROVIDER".to_string())]
+        );
+        std::env::remove_var("LAYER_PROVIDER");
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_config_cannot_loosen_safety_settings() -> Result<(), ConfigError> {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user_path = dir.path().join("config.yaml");
+        let project_path = dir
+            .path()
+            .join("repo")
+            .join(PROJECT_CONFIG_DIR)
+            .join("config.yaml");
+        std::fs::create_dir_all(project_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &project_path,
+            "GOOSE_MODE: auto\n\
+             permissions:\n  always_allow: [developer__shell]\n  ask_before: [developer__text_editor]\n\
+             extensions:\n  github:\n    enabled: true\n",
+        )
+        .unwrap();
+
+        let config =
+            Config::new(&user_path, TEST_KEYRING_SERVICE)?.with_project_config(&project_path);
+        config.set_param("GOOSE_MODE", Value::String("approve".to_string()))?;
+        config.set_param(
+            "permissions",
+            serde_json::json!({"always_allow": ["developer__text_editor"]}),
+        )?;
+
+        // The looser mode and the extra allowed tool are ignored, the extra approval applies
+        assert_eq!(config.get_param::<String>("GOOSE_MODE")?, "approve");
+        let rules: Value = config.get_param("permissions")?;
+        assert_eq!(
+            rules["always_allow"],
+            serde_json::json!(["developer__text_editor"])
+        );
+        assert_eq!(
+            rules["ask_before"],
+            serde_json::json!(["developer__text_editor"])
+        );
+
+        // Extensions are left out until they are trusted, and again once they change
+        assert!(config.get_param::<Value>("extensions").is_err());
+        assert_eq!(
+            config.untrusted_project_commands(),
+            vec!["extension 'github'".to_string()]
+        );
+        assert!(!config.is_project_trusted(&project_path));
+        config.trust_project_commands()?;
+        assert!(config.untrusted_project_commands().is_empty());
+        assert!(config.is_project_trusted(&project_path));
+        let extensions: Value = config.get_param("extensions")?;
+        assert_eq!(extensions["github"]["enabled"], true);
+
+        std::fs::write(
+            &project_path,
+            "extensions:\n  github:\n    enabled: true\n    cmd: other\n",
+        )
+        .unwrap();
+        assert!(config.get_param::<Value>("extensions").is_err());
+
+        // Hooks share the trust record, so adding one needs the project to be trusted again
+        std::fs::write(
+            &project_path,
+            "extensions:\n  github:\n    enabled: true\n    cmd: other\n\
+             hooks:\n  SessionStart:\n    - command: make setup\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.untrusted_project_commands(),
+            vec![
+                "extension 'github'".to_string(),
+                "SessionStart hook 'make setup'".to_string()
+            ]
+        );
+        config.trust_project_commands()?;
+        assert!(config.is_project_trusted(&project_path));
+        let extensions: Value = config.get_param("extensions")?;
+        assert_eq!(extensions["github"]["cmd"], "other");
+
+        // A stricter mode is used
+        std::fs::write(&project_path, "GOOSE_MODE: chat\n").unwrap();
+        assert_eq!(config.get_param::<String>("GOOSE_MODE")?, "chat");
+        Ok(())
+    }
+
     #[test]
     fn test_complex_type() -> Result<(), ConfigError> {
         #[derive(Deserialize, Debug, PartialEq)]
@@ -2,12 +2,16 @@ pub mod base;
 mod experiments;
 pub mod extensions;
 pub mod permission;
+pub mod profiles;
+pub mod sandbox;
 
 pub use crate::agents::ExtensionConfig;
-pub use base::{Config, ConfigError, APP_STRATEGY};
+pub use base::{Config, ConfigEntry, ConfigError, ConfigSource, APP_STRATEGY};
 pub use experiments::ExperimentManager;
 pub use extensions::{ExtensionConfigManager, ExtensionEntry};
 pub use permission::PermissionManager;
+pub use profiles::ProfileManager;
+pub use sandbox::SandboxConfig;
 
 pub use extensions::DEFAULT_DISPLAY_NAME;
 pub use extensions::DEFAULT_EXTENSION;
@@ -1,4 +1,4 @@
-use super::APP_STRATEGY;
+use super::{Config, APP_STRATEGY};
 use etcetera::{choose_app_strategy, AppStrategy};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -17,6 +17,7 @@ pub enum PermissionLevel {
 
 /// Struct representing the configuration of permissions, categorized by level.
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
+#[serde(default)]
 pub struct PermissionConfig {
     pub always_allow: Vec<String>, // List of tools that are always allowed
     pub ask_before: Vec<String>,   // List of tools that require user consent
@@ -28,12 +29,43 @@ pub struct PermissionConfig {
 pub struct PermissionManager {
     config_path: PathBuf, // Path to the permission configuration file
     permission_map: HashMap<String, PermissionConfig>, // Mapping of permission names to configurations
+    config_rules: PermissionConfig, // Rules from the `permissions` key of config.yaml or .goose/config.yaml
 }
 
 // Constants representing specific permission categories
 const USER_PERMISSION: &str = "user";
 const SMART_APPROVE_PERMISSION: &str = "smart_approve";
 
+/// Config key for permission rules, typically committed in a project's .goose/config.yaml
+pub const PERMISSIONS_CONFIG_KEY: &str = "permissions";
+
+/// Finds the permission level a config assigns to a tool, if any.
+fn permission_level(
+    permission_config: &PermissionConfig,
+    principal_name: &str,
+) -> Option<PermissionLevel> {
+    // Check the permission levels for the given tool, strictest first, since the rules of
+    // the user and project config files are combined
+    if permission_config
+        .never_allow
+        .contains(&principal_name.to_string())
+    {
+        Some(PermissionLevel::NeverAllow)
+    } else if permission_config
+        .ask_before
+        .contains(&principal_name.to_string())
+    {
+        Some(PermissionLevel::AskBefore)
+    } else if permission_config
+        .always_allow
+        .contains(&principal_name.to_string())
+    {
+        Some(PermissionLevel::AlwaysAllow)
+    } else {
+        None // Return None if no matching permission level is found
+    }
+}
+
 /// Implements the default constructor for `PermissionManager`.
 impl Default for PermissionManager {
     fn default() -> Self {
@@ -56,9 +88,16 @@ impl Default for PermissionManager {
             HashMap::new() // No config file, create an empty map
         };
 
+        // Rules from the config files take precedence over the choices saved in permission.yaml.
+        // A project's .goose/config.yaml can only add approvals and blocks, see Config.
+        let config_rules = Config::global()
+            .get_param(PERMISSIONS_CONFIG_KEY)
+            .unwrap_or_default();
+
         PermissionManager {
             config_path,
             permission_map,
+            config_rules,
         }
     }
 }
@@ -81,17 +120,31 @@ impl PermissionManager {
         PermissionManager {
             config_path,
             permission_map,
+            config_rules: PermissionConfig::default(),
         }
     }
 
+    /// Apply permission rules from configuration on top of the saved permissions.
+    pub fn with_config_rules(mut self, config_rules: PermissionConfig) -> Self {
+        self.config_rules = config_rules;
+        self
+    }
+
     /// Returns a list of all the names (keys) in the permission map.
     pub fn get_permission_names(&self) -> Vec<String> {
         self.permission_map.keys().cloned().collect()
     }
 
     /// Retrieves the user permission level for a specific tool.
+    ///
+    /// Rules set in the config files win over the choices saved in permission.yaml,
+    /// except that a tool the user chose to never allow stays that way.
     pub fn get_user_permission(&self, principal_name: &str) -> Option<PermissionLevel> {
-        self.get_permission(USER_PERMISSION, principal_name)
+        let saved = self.get_permission(USER_PERMISSION, principal_name);
+        if saved == Some(PermissionLevel::NeverAllow) {
+            return saved;
+        }
+        permission_level(&self.config_rules, principal_name).or(saved)
     }
 
     /// Retrieves the smart approve permission level for a specific tool.
@@ -102,26 +155,9 @@ impl PermissionManager {
     /// Helper function to retrieve the permission level for a specific permission category and tool.
     fn get_permission(&self, name: &str, principal_name: &str) -> Option<PermissionLevel> {
         // Check if the permission category exists in the map
-        if let Some(permission_config) = self.permission_map.get(name) {
-            // Check the permission levels for the given tool
-            if permission_config
-                .always_allow
-                .contains(&principal_name.to_string())
-            {
-                return Some(PermissionLevel::AlwaysAllow);
-            } else if permission_config
-                .ask_before
-                .contains(&principal_name.to_string())
-            {
-                return Some(PermissionLevel::AskBefore);
-            } else if permission_config
-                .never_allow
-                .contains(&principal_name.to_string())
-            {
-                return Some(PermissionLevel::NeverAllow);
-            }
-        }
-        None // Return None if no matching permission level is found
+        self.permission_map
+            .get(name)
+            .and_then(|permission_config| permission_level(permission_config, principal_name))
     }
 
     /// Updates the user permission level for a specific tool.
@@ -236,6 +272,31 @@ mod tests {
         assert_eq!(permission, None);
     }
 
+    #[test]
+    fn test_config_rules() {
+        let mut manager = create_test_permission_manager().with_config_rules(PermissionConfig {
+            always_allow: vec!["tool1".to_string(), "tool2".to_string()],
+            ask_before: vec![],
+            never_allow: vec!["tool3".to_string()],
+        });
+        manager.update_user_permission("tool1", PermissionLevel::AskBefore);
+        manager.update_user_permission("tool2", PermissionLevel::NeverAllow);
+        manager.update_user_permission("tool3", PermissionLevel::AlwaysAllow);
+
+        assert_eq!(
+            manager.get_user_permission("tool1"),
+            Some(PermissionLevel::AlwaysAllow)
+        );
+        assert_eq!(
+            manager.get_user_permission("tool2"),
+            Some(PermissionLevel::NeverAllow)
+        );
+        assert_eq!(
+            manager.get_user_permission("tool3"),
+            Some(PermissionLevel::NeverAllow)
+        );
+    }
+
     #[test]
     fn test_permission_levels() {
         let mut manager = create_test_permission_manager();
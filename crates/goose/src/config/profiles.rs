@@ -0,0 +1,181 @@
+use super::base::{Config, ConfigError};
+use super::APP_STRATEGY;
+use etcetera::{choose_app_strategy, AppStrategy};
+use std::env;
+use std::path::{Path, PathBuf};
+
+/// Environment variable selecting the profile for this process
+pub const PROFILE_ENV_VAR: &str = "GOOSE_PROFILE";
+
+/// The profile backed by the top level config.yaml and the "goose" keyring service
+pub const DEFAULT_PROFILE: &str = "default";
+
+const PROFILES_DIR: &str = "profiles";
+const ACTIVE_PROFILE_FILE: &str = "active_profile";
+
+/// Profile names end up in paths and keyring service names, so keep them simple
+pub fn validate_profile_name(name: &str) -> Result<(), ConfigError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(ConfigError::ProfileError(format!(
+            "'{}' is not a valid profile name, use letters, digits, '-' and '_'",
+            name
+        )))
+    }
+}
+
+/// Directory holding the config and secrets of a named profile
+pub fn profile_dir(config_dir: &Path, name: &str) -> PathBuf {
+    config_dir.join(PROFILES_DIR).join(name)
+}
+
+/// ProfileManager lists, creates and selects the named configuration profiles.
+///
+/// The default profile is the config.yaml at the root of the config directory, every
+/// other profile lives in its own directory under profiles/.
+pub struct ProfileManager {
+    config_dir: PathBuf,
+}
+
+impl Default for ProfileManager {
+    fn default() -> Self {
+        let config_dir = choose_app_strategy(APP_STRATEGY.clone())
+            .expect("goose requires a home dir")
+            .config_dir();
+        ProfileManager { config_dir }
+    }
+}
+
+impl ProfileManager {
+    pub fn new<P: AsRef<Path>>(config_dir: P) -> Self {
+        ProfileManager {
+            config_dir: config_dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// The profile to use: GOOSE_PROFILE if set, then the one chosen with `use`, then default
+    pub fn active(&self) -> String {
+        if let Ok(profile) = env::var(PROFILE_ENV_VAR) {
+            if !profile.trim().is_empty() {
+                return profile.trim().to_string();
+            }
+        }
+        self.selected()
+    }
+
+    /// The profile chosen with `use`, ignoring GOOSE_PROFILE
+    pub fn selected(&self) -> String {
+        std::fs::read_to_string(self.config_dir.join(ACTIVE_PROFILE_FILE))
+            .ok()
+            .map(|content| content.trim().to_string())
+            .filter(|name| validate_profile_name(name).is_ok())
+            .unwrap_or_else(|| DEFAULT_PROFILE.to_string())
+    }
+
+    /// All profiles, with the default profile first
+    pub fn list(&self) -> Result<Vec<String>, ConfigError> {
+        let mut profiles = Vec::new();
+        let dir = self.config_dir.join(PROFILES_DIR);
+        if dir.exists() {
+            for entry in std::fs::read_dir(dir)? {
+                let entry = entry?;
+                let name = entry.file_name().to_string_lossy().to_string();
+                if entry.file_type()?.is_dir()
+                    && name != DEFAULT_PROFILE
+                    && validate_profile_name(&name).is_ok()
+                {
+                    profiles.push(name);
+                }
+            }
+        }
+        profiles.sort();
+        profiles.insert(0, DEFAULT_PROFILE.to_string());
+        Ok(profiles)
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE
+            || (validate_profile_name(name).is_ok() && profile_dir(&self.config_dir, name).is_dir())
+    }
+
+    /// Create an empty profile, returning its configuration
+    pub fn add(&self, name: &str) -> Result<Config, ConfigError> {
+        validate_profile_name(name)?;
+        if self.exists(name) {
+            return Err(ConfigError::ProfileError(format!(
+                "profile '{}' already exists",
+                name
+            )));
+        }
+        std::fs::create_dir_all(profile_dir(&self.config_dir, name))
+            .map_err(|e| ConfigError::DirectoryError(e.to_string()))?;
+        self.config(name)
+    }
+
+    /// Make `name` the profile used when GOOSE_PROFILE is not set
+    pub fn set_active(&self, name: &str) -> Result<(), ConfigError> {
+        if !self.exists(name) {
+            return Err(ConfigError::ProfileError(format!(
+                "profile '{}' does not exist",
+                name
+            )));
+        }
+        std::fs::create_dir_all(&self.config_dir)
+            .map_err(|e| ConfigError::DirectoryError(e.to_string()))?;
+        std::fs::write(self.config_dir.join(ACTIVE_PROFILE_FILE), name)?;
+        Ok(())
+    }
+
+    /// The configuration of a profile
+    pub fn config(&self, name: &str) -> Result<Config, ConfigError> {
+        Config::for_profile(&self.config_dir, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::Value;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    #[test]
+    #[serial]
+    fn test_profiles() -> Result<(), ConfigError> {
+        env::remove_var(PROFILE_ENV_VAR);
+        let dir = TempDir::new().unwrap();
+        let profiles = ProfileManager::new(dir.path());
+
+        assert_eq!(profiles.list()?, vec![DEFAULT_PROFILE]);
+        assert_eq!(profiles.active(), DEFAULT_PROFILE);
+
+        let work = profiles.add("work")?;
+        assert!(profiles.add("work").is_err());
+        assert!(profiles.add("../escape").is_err());
+        assert!(profiles.set_active("personal").is_err());
+        assert_eq!(profiles.list()?, vec![DEFAULT_PROFILE, "work"]);
+
+        // Each profile has its own settings
+        work.set_param("profile_test_model", Value::String("dbrx".to_string()))?;
+        let default = profiles.config(DEFAULT_PROFILE)?;
+        assert!(default.get_param::<String>("profile_test_model").is_err());
+        assert_eq!(work.get_param::<String>("profile_test_model")?, "dbrx");
+
+        profiles.set_active("work")?;
+        assert_eq!(profiles.active(), "work");
+        assert_eq!(Config::for_profile(dir.path(), "work")?.profile(), "work");
+
+        env::set_var(PROFILE_ENV_VAR, DEFAULT_PROFILE);
+        assert_eq!(profiles.active(), DEFAULT_PROFILE);
+        assert_eq!(profiles.selected(), "work");
+
+        env::remove_var(PROFILE_ENV_VAR);
+        Ok(())
+    }
+}
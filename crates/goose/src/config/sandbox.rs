@@ -0,0 +1,78 @@
+use super::base::{Config, ConfigError};
+use super::extensions::name_to_key;
+use goose_sandbox::SandboxProfile;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Config key holding the sandbox profiles
+pub const SANDBOX_CONFIG_KEY: &str = "GOOSE_SANDBOX";
+
+/// Sandbox profiles for the processes goose starts, read from the `GOOSE_SANDBOX` section of
+/// config.yaml. Anything without a profile runs unsandboxed.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct SandboxConfig {
+    /// Profile for commands run by the developer shell tool
+    pub shell: Option<SandboxProfile>,
+    /// Profiles for stdio extensions, keyed by extension name
+    pub extensions: HashMap<String, SandboxProfile>,
+}
+
+impl SandboxConfig {
+    pub fn load() -> Result<Self, ConfigError> {
+        Self::from_config(Config::global())
+    }
+
+    /// Read the profiles from the user's config only, so a project can't loosen them
+    pub fn from_config(config: &Config) -> Result<Self, ConfigError> {
+        match config.get_user_param(SANDBOX_CONFIG_KEY) {
+            Ok(sandbox) => Ok(sandbox),
+            Err(ConfigError::NotFound(_)) => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// The profile for an extension, looked up by name or by its config key
+    pub fn extension(&self, name: &str) -> Option<&SandboxProfile> {
+        self.extensions
+            .get(name)
+            .or_else(|| self.extensions.get(&name_to_key(name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::NamedTempFile;
+
+    const TEST_KEYRING_SERVICE: &str = "goose-test-sandbox";
+
+    #[test]
+    fn test_sandbox_config() -> Result<(), ConfigError> {
+        let temp_file = NamedTempFile::new().unwrap();
+        let config = Config::new(temp_file.path(), TEST_KEYRING_SERVICE)?;
+        assert_eq!(
+            SandboxConfig::from_config(&config)?,
+            SandboxConfig::default()
+        );
+
+        config.set_param(
+            SANDBOX_CONFIG_KEY,
+            json!({
+                "shell": {"network": false},
+                "extensions": {"github": {"writable_paths": ["/cache"]}}
+            }),
+        )?;
+        let sandbox = SandboxConfig::from_config(&config)?;
+        assert!(!sandbox.shell.as_ref().unwrap().network);
+        let github = sandbox.extension("GitHub").unwrap();
+        assert!(github.network);
+        assert_eq!(
+            github.writable_paths,
+            vec![std::path::PathBuf::from("/cache")]
+        );
+        assert!(sandbox.extension("slack").is_none());
+        Ok(())
+    }
+}
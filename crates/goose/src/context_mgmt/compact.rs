@@ -0,0 +1,413 @@
+use super::common::get_messages_token_counts_async;
+use super::summarize::summarize_messages_async;
+use super::truncate::{truncate_messages, OldestFirstTruncation, TruncationStrategy};
+use crate::message::{Message, MessageContent};
+use crate::providers::base::Provider;
+use crate::token_counter::AsyncTokenCounter;
+use anyhow::Result;
+use mcp_core::{Content, ResourceContents, Role, ToolCall};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tracing::debug;
+
+/// Number of most recent turns kept verbatim when nothing else is configured
+pub const DEFAULT_KEEP_RECENT_TURNS: usize = 5;
+
+/// Tool outputs at or below this size are cheaper to keep than to replace with a stub
+const MIN_ELIDED_OUTPUT_CHARS: usize = 256;
+
+/// Longest rendering of a tool call's arguments kept in an elided stub
+const MAX_STUB_ARGS_CHARS: usize = 120;
+
+/// Compaction strategy aimed at conversations dominated by stale tool output.
+///
+/// A turn is one model response together with the user message or tool results that answer it.
+/// The last `keep_recent_turns` turns are always kept verbatim; everything before them is compacted
+/// in stages, stopping as soon as the conversation fits:
+/// 1. the bodies of old tool responses are replaced with a one-line stub naming the tool and its arguments
+/// 2. the older turns are summarized
+/// 3. the oldest messages outside the recent turns are dropped
+pub struct ToolOutputCompaction {
+    pub keep_recent_turns: usize,
+}
+
+impl Default for ToolOutputCompaction {
+    fn default() -> Self {
+        Self::new(DEFAULT_KEEP_RECENT_TURNS)
+    }
+}
+
+impl ToolOutputCompaction {
+    pub fn new(keep_recent_turns: usize) -> Self {
+        Self { keep_recent_turns }
+    }
+
+    /// Index of the first message belonging to the recent turns.
+    ///
+    /// Turns start at a model response so a tool request and its response are never split apart.
+    pub fn recent_turns_start(&self, messages: &[Message]) -> usize {
+        if self.keep_recent_turns == 0 {
+            return messages.len();
+        }
+
+        messages
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, message)| message.role == Role::Assistant)
+            .nth(self.keep_recent_turns - 1)
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Replace the output of every tool response in `messages` with a stub naming the call that produced it.
+    ///
+    /// Tool requests and the request/response pairing are left untouched so the result is still a
+    /// valid conversation for every provider. Errors and short outputs are kept as is.
+    pub fn elide_tool_outputs(&self, messages: &[Message]) -> Vec<Message> {
+        let tool_calls: HashMap<&str, &ToolCall> = messages
+            .iter()
+            .flat_map(|message| message.content.iter())
+            .filter_map(|content| match content {
+                MessageContent::ToolRequest(request) => request
+                    .tool_call
+                    .as_ref()
+                    .ok()
+                    .map(|call| (request.id.as_str(), call)),
+                _ => None,
+            })
+            .collect();
+
+        messages
+            .iter()
+            .map(|message| {
+                let mut message = message.clone();
+                for content in &mut message.content {
+                    let MessageContent::ToolResponse(response) = content else {
+                        continue;
+                    };
+                    let Ok(output) = &mut response.tool_result else {
+                        continue;
+                    };
+                    if output.iter().map(content_size).sum::<usize>() <= MIN_ELIDED_OUTPUT_CHARS {
+                        continue;
+                    }
+                    let stub = elided_stub(tool_calls.get(response.id.as_str()).copied());
+                    *output = vec![Content::text(stub)];
+                }
+                message
+            })
+            .collect()
+    }
+
+    /// Compact `messages` until they fit within `context_limit`, returning the new messages and their token counts.
+    pub async fn compact(
+        &self,
+        provider: Arc<dyn Provider>,
+        messages: &[Message],
+        token_counter: &AsyncTokenCounter,
+        context_limit: usize,
+    ) -> Result<(Vec<Message>, Vec<usize>)> {
+        let split = self.recent_turns_start(messages);
+        let recent = &messages[split..];
+        let recent_counts = get_messages_token_counts_async(token_counter, recent);
+        let recent_tokens: usize = recent_counts.iter().sum();
+
+        // Stage 1: elide old tool output
+        let mut older = self.elide_tool_outputs(&messages[..split]);
+        let mut older_counts = get_messages_token_counts_async(token_counter, &older);
+        let older_tokens: usize = older_counts.iter().sum();
+        debug!(
+            "Compaction: {} older tokens after eliding tool output, {} recent tokens",
+            older_tokens, recent_tokens
+        );
+
+        // Stage 2: summarize the older turns
+        if older_tokens + recent_tokens > context_limit
+            && older.len() > 1
+            && recent_tokens < context_limit
+        {
+            (older, older_counts) = summarize_messages_async(
+                provider,
+                &older,
+                token_counter,
+                context_limit - recent_tokens,
+            )
+            .await?;
+            debug!(
+                "Compaction: summarized older turns into {} tokens",
+                older_counts.iter().sum::<usize>()
+            );
+        }
+
+        let mut compacted = older;
+        compacted.extend_from_slice(recent);
+        let mut token_counts = older_counts;
+        token_counts.extend(recent_counts);
+
+        if token_counts.iter().sum::<usize>() <= context_limit {
+            return Ok((compacted, token_counts));
+        }
+
+        // Stage 3: drop whole messages, oldest first
+        truncate_messages(&compacted, &token_counts, context_limit, self)
+    }
+}
+
+impl TruncationStrategy for ToolOutputCompaction {
+    /// Removes the oldest messages before the recent turns, only reaching into the
+    /// recent turns when they alone do not fit.
+    fn determine_indices_to_remove(
+        &self,
+        messages: &[Message],
+        token_counts: &[usize],
+        context_limit: usize,
+    ) -> Result<HashSet<usize>> {
+        let split = self.recent_turns_start(messages);
+        let recent_tokens: usize = token_counts[split..].iter().sum();
+
+        if recent_tokens <= context_limit {
+            OldestFirstTruncation.determine_indices_to_remove(
+                &messages[..split],
+                &token_counts[..split],
+                context_limit - recent_tokens,
+            )
+        } else {
+            OldestFirstTruncation.determine_indices_to_remove(messages, token_counts, context_limit)
+        }
+    }
+}
+
+/// Rough size of a piece of tool output in characters
+fn content_size(content: &Content) -> usize {
+    match content {
+        Content::Text(text) => text.text.len(),
+        Content::Image(image) => image.data.len(),
+        Content::Resource(resource) => match &resource.resource {
+            ResourceContents::TextResourceContents { text, .. } => text.len(),
+            ResourceContents::BlobResourceContents { blob, .. } => blob.len(),
+        },
+    }
+}
+
+fn elided_stub(tool_call: Option<&ToolCall>) -> String {
+    let Some(tool_call) = tool_call else {
+        return "[Tool output elided to save context]".to_string();
+    };
+
+    let mut args = tool_call.arguments.to_string();
+    if args.chars().count() > MAX_STUB_ARGS_CHARS {
+        args = args.chars().take(MAX_STUB_ARGS_CHARS).collect::<String>() + "...";
+    }
+    format!(
+        "[Output of {}({}) elided to save context]",
+        tool_call.name, args
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelConfig;
+    use crate::providers::base::{ProviderMetadata, ProviderUsage, Usage};
+    use crate::providers::errors::ProviderError;
+    use crate::token_counter::create_async_token_counter;
+    use mcp_core::tool::Tool;
+    use serde_json::json;
+
+    struct MockProvider;
+
+    #[async_trait::async_trait]
+    impl Provider for MockProvider {
+        fn metadata() -> ProviderMetadata {
+            ProviderMetadata::empty()
+        }
+
+        fn get_model_config(&self) -> ModelConfig {
+            ModelConfig::new("test-model".to_string())
+        }
+
+        async fn complete(
+            &self,
+            _system: &str,
+            _messages: &[Message],
+            _tools: &[Tool],
+        ) -> Result<(Message, ProviderUsage), ProviderError> {
+            Ok((
+                Message::assistant().with_text("Summarized content"),
+                ProviderUsage::new("mock".to_string(), Usage::default()),
+            ))
+        }
+    }
+
+    fn tool_round(id: &str, command: &str, output: &str) -> Vec<Message> {
+        vec![
+            Message::assistant().with_tool_request(
+                id,
+                Ok(ToolCall::new(
+                    "developer__shell",
+                    json!({ "command": command }),
+                )),
+            ),
+            Message::user().with_tool_response(id, Ok(vec![Content::text(output)])),
+        ]
+    }
+
+    fn conversation() -> Vec<Message> {
+        let mut messages = vec![Message::user().with_text("Look around the repo")];
+        for i in 0..4 {
+            messages.extend(tool_round(
+                &format!("call{}", i),
+                &format!("cat file{}.txt", i),
+                &format!("line {}\n", i).repeat(200),
+            ));
+        }
+        messages.push(Message::assistant().with_text("Done looking around"));
+        messages
+    }
+
+    fn tool_output(message: &Message) -> Option<String> {
+        message.content.iter().find_map(|content| match content {
+            MessageContent::ToolResponse(response) => response
+                .tool_result
+                .as_ref()
+                .ok()
+                .and_then(|output| output.first())
+                .and_then(|c| c.as_text())
+                .map(str::to_string),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn test_recent_turns_start() {
+        let messages = conversation();
+        // Assistant messages sit at 1, 3, 5, 7 and 9
+        assert_eq!(
+            ToolOutputCompaction::new(1).recent_turns_start(&messages),
+            9
+        );
+        assert_eq!(
+            ToolOutputCompaction::new(2).recent_turns_start(&messages),
+            7
+        );
+        assert_eq!(
+            ToolOutputCompaction::new(10).recent_turns_start(&messages),
+            0
+        );
+        assert_eq!(
+            ToolOutputCompaction::new(0).recent_turns_start(&messages),
+            messages.len()
+        );
+    }
+
+    #[test]
+    fn test_elide_tool_outputs_keeps_pairs_and_names_call() {
+        let mut messages = tool_round("call0", "cat big.txt", &"x".repeat(1000));
+        messages.extend(tool_round("call1", "pwd", "/tmp"));
+
+        let elided = ToolOutputCompaction::default().elide_tool_outputs(&messages);
+
+        assert_eq!(elided.len(), messages.len());
+        assert_eq!(elided[0], messages[0]);
+        assert_eq!(
+            tool_output(&elided[1]).unwrap(),
+            "[Output of developer__shell({\"command\":\"cat big.txt\"}) elided to save context]"
+        );
+        assert_eq!(elided[1].get_tool_ids(), messages[1].get_tool_ids());
+        // Short outputs are not worth replacing
+        assert_eq!(tool_output(&elided[3]).unwrap(), "/tmp");
+    }
+
+    #[test]
+    fn test_elided_stub_truncates_arguments() {
+        let call = ToolCall::new("developer__shell", json!({ "command": "a".repeat(500) }));
+        let stub = elided_stub(Some(&call));
+        assert!(stub.len() < 200);
+        assert!(stub.contains("...) elided"));
+        assert_eq!(
+            elided_stub(None),
+            "[Tool output elided to save context]".to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compact_elides_before_summarizing() -> Result<()> {
+        let token_counter = create_async_token_counter().await.unwrap();
+        let messages = conversation();
+        let original: usize = get_messages_token_counts_async(&token_counter, &messages)
+            .iter()
+            .sum();
+
+        let strategy = ToolOutputCompaction::new(2);
+        let elided: usize = get_messages_token_counts_async(
+            &token_counter,
+            &strategy.elide_tool_outputs(&messages[..7]),
+        )
+        .iter()
+        .sum::<usize>()
+            + get_messages_token_counts_async(&token_counter, &messages[7..])
+                .iter()
+                .sum::<usize>();
+        assert!(elided < original);
+
+        let (compacted, token_counts) = strategy
+            .compact(Arc::new(MockProvider), &messages, &token_counter, elided)
+            .await?;
+
+        // Nothing was summarized, the older tool outputs were only stubbed out
+        assert_eq!(compacted.len(), messages.len());
+        assert_eq!(token_counts.iter().sum::<usize>(), elided);
+        assert!(tool_output(&compacted[2])
+            .unwrap()
+            .starts_with("[Output of"));
+        // The recent turns are untouched
+        assert_eq!(&compacted[7..], &messages[7..]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compact_summarizes_older_turns() -> Result<()> {
+        let token_counter = create_async_token_counter().await.unwrap();
+        let messages = conversation();
+        let strategy = ToolOutputCompaction::new(2);
+        let recent: usize = get_messages_token_counts_async(&token_counter, &messages[7..])
+            .iter()
+            .sum();
+        let elided_older: usize = get_messages_token_counts_async(
+            &token_counter,
+            &strategy.elide_tool_outputs(&messages[..7]),
+        )
+        .iter()
+        .sum();
+
+        // Eliding alone is not enough to fit
+        let (compacted, _) = strategy
+            .compact(
+                Arc::new(MockProvider),
+                &messages,
+                &token_counter,
+                recent + elided_older - 1,
+            )
+            .await?;
+
+        assert_eq!(compacted[0].role, Role::User);
+        assert_eq!(compacted[0].as_concat_text(), "Summarized content");
+        assert!(compacted.len() < messages.len());
+        assert!(compacted.ends_with(&messages[7..]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation_prefers_messages_before_recent_turns() -> Result<()> {
+        let messages = conversation();
+        let token_counts = vec![10; messages.len()];
+        let strategy = ToolOutputCompaction::new(2);
+
+        let indices = strategy.determine_indices_to_remove(&messages, &token_counts, 80)?;
+
+        assert!(indices.iter().all(|&i| i < 7));
+        assert!(indices.contains(&0));
+        Ok(())
+    }
+}
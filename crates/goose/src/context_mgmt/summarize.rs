@@ -2,6 +2,7 @@ use super::common::{get_messages_token_counts, get_messages_token_counts_async};
 use crate::message::{Message, MessageContent};
 use crate::providers::base::Provider;
 use crate::token_counter::{AsyncTokenCounter, TokenCounter};
+use crate::tracing::gen_ai;
 use anyhow::Result;
 use mcp_core::Role;
 use std::sync::Arc;
@@ -32,10 +33,14 @@ async fn summarize_combined_messages(
     let summarization_request = vec![Message::user().with_text(&request_text)];
 
     // Send the request to the provider and fetch the response.
-    let mut response = provider
-        .complete(SUMMARY_PROMPT, &summarization_request, &[])
-        .await?
-        .0;
+    let mut response = gen_ai::complete(
+        provider.as_ref(),
+        SUMMARY_PROMPT,
+        &summarization_request,
+        &[],
+    )
+    .await?
+    .0;
     // Set role to user as it will be used in following conversation as user content.
     response.role = Role::User;
 
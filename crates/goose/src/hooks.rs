@@ -0,0 +1,576 @@
+//! User-configurable lifecycle hooks.
+//!
+//! Hooks are shell commands configured under the `hooks` key of the global
+//! `config.yaml` or a project's `.goose/config.yaml`:
+//!
+//! ```yaml
+//! hooks:
+//!   PreToolUse:
+//!     - matcher: "developer__shell"
+//!       command: "./scripts/check-command.sh"
+//!   PostToolUse:
+//!     - matcher: "developer__text_editor"
+//!       command: "cargo fmt"
+//!   SessionStart:
+//!     - command: "git status --short"
+//! ```
+//!
+//! Each hook receives a JSON description of the event on stdin. A hook can block
+//! the event by exiting with status 2 (stderr is used as the reason), or by
+//! printing a JSON object on stdout:
+//!
+//! ```json
+//! {"decision": "block", "reason": "...", "tool_input": {...}, "additional_context": "..."}
+//! ```
+//!
+//! Plain text printed by `SessionStart` and `UserPromptSubmit` hooks is added as context.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use mcp_core::{tool::ToolCall, Content, ToolResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::config::base::find_project_config;
+use crate::config::Config;
+
+const DEFAULT_HOOK_TIMEOUT_SECS: u64 = 60;
+/// Exit status a hook uses to block the event it was run for
+const BLOCKING_EXIT_CODE: i32 = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HookEvent {
+    PreToolUse,
+    PostToolUse,
+    UserPromptSubmit,
+    SessionStart,
+    Stop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookCommand {
+    /// Regex matched against the prefixed tool name for tool events; matches every tool when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matcher: Option<String>,
+    pub command: String,
+    /// Timeout in seconds, defaults to 60
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+pub type HooksConfig = HashMap<HookEvent, Vec<HookCommand>>;
+
+/// The JSON document written to a hook's stdin
+#[derive(Debug, Clone, Serialize)]
+pub struct HookInput {
+    pub hook_event_name: HookEvent,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    pub cwd: PathBuf,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_input: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_response: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_hook_active: Option<bool>,
+}
+
+impl HookInput {
+    fn new(event: HookEvent, cwd: &Path) -> Self {
+        Self {
+            hook_event_name: event,
+            session_id: None,
+            cwd: cwd.to_path_buf(),
+            tool_name: None,
+            tool_input: None,
+            tool_response: None,
+            prompt: None,
+            stop_hook_active: None,
+        }
+    }
+
+    pub fn with_session_id(mut self, session_id: Option<String>) -> Self {
+        self.session_id = session_id;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum HookDecision {
+    Approve,
+    Block,
+}
+
+/// The optional JSON document a hook prints on stdout
+#[derive(Debug, Default, Deserialize)]
+struct HookOutput {
+    #[serde(default)]
+    decision: Option<HookDecision>,
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    tool_input: Option<Value>,
+    #[serde(default)]
+    additional_context: Option<String>,
+}
+
+/// The combined result of running every hook registered for an event
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HookOutcome {
+    /// Set when a hook blocked the event
+    pub block_reason: Option<String>,
+    /// Replacement tool arguments from a `PreToolUse` hook
+    pub updated_tool_input: Option<Value>,
+    /// Extra context to pass on to the model
+    pub additional_context: Vec<String>,
+}
+
+/// The hooks that apply in a working directory
+#[derive(Debug, Clone)]
+pub struct Hooks {
+    config: Arc<HooksConfig>,
+    cwd: PathBuf,
+}
+
+impl Hooks {
+    pub fn new(config: HooksConfig, cwd: PathBuf) -> Self {
+        Self {
+            config: Arc::new(config),
+            cwd,
+        }
+    }
+
+    /// Load hooks from the global config followed by the project's `.goose/config.yaml`.
+    ///
+    /// Project hooks run after global hooks for the same event.
+    pub fn load(cwd: &Path) -> Self {
+        let mut config: HooksConfig = Config::global().get_user_param("hooks").unwrap_or_default();
+
+        for (event, commands) in load_project_hooks(Config::global(), cwd) {
+            config.entry(event).or_default().extend(commands);
+        }
+
+        Self::new(config, cwd.to_path_buf())
+    }
+
+    pub fn cwd(&self) -> &Path {
+        &self.cwd
+    }
+
+    pub fn has(&self, event: HookEvent) -> bool {
+        self.config
+            .get(&event)
+            .is_some_and(|commands| !commands.is_empty())
+    }
+
+    pub fn input(&self, event: HookEvent) -> HookInput {
+        HookInput::new(event, &self.cwd)
+    }
+
+    pub fn tool_input(&self, event: HookEvent, tool_call: &ToolCall) -> HookInput {
+        let mut input = self.input(event);
+        input.tool_name = Some(tool_call.name.clone());
+        input.tool_input = Some(tool_call.arguments.clone());
+        input
+    }
+
+    pub fn prompt_input(&self, prompt: &str) -> HookInput {
+        let mut input = self.input(HookEvent::UserPromptSubmit);
+        input.prompt = Some(prompt.to_string());
+        input
+    }
+
+    pub fn stop_input(&self, stop_hook_active: bool) -> HookInput {
+        let mut input = self.input(HookEvent::Stop);
+        input.stop_hook_active = Some(stop_hook_active);
+        input
+    }
+
+    /// Run the hooks registered for the input's event in order, stopping at the first that blocks.
+    ///
+    /// Hooks that fail to run, time out or exit with an unexpected status are logged and skipped.
+    pub async fn run(&self, mut input: HookInput) -> HookOutcome {
+        let mut outcome = HookOutcome::default();
+        let Some(commands) = self.config.get(&input.hook_event_name) else {
+            return outcome;
+        };
+
+        for hook in commands {
+            if !hook_matches(hook, input.tool_name.as_deref()) {
+                continue;
+            }
+
+            let (status, stdout, stderr) = match run_hook_command(hook, &input, &self.cwd).await {
+                Ok(output) => output,
+                Err(e) => {
+                    tracing::warn!("Hook '{}' failed to run: {}", hook.command, e);
+                    continue;
+                }
+            };
+
+            if status == Some(BLOCKING_EXIT_CODE) {
+                let reason = stderr.trim();
+                outcome.block_reason = Some(if reason.is_empty() {
+                    format!("Blocked by hook '{}'", hook.command)
+                } else {
+                    reason.to_string()
+                });
+                return outcome;
+            }
+            if status != Some(0) {
+                tracing::warn!(
+                    "Hook '{}' exited with status {:?}: {}",
+                    hook.command,
+                    status,
+                    stderr.trim()
+                );
+                continue;
+            }
+
+            let stdout = stdout.trim();
+            if stdout.is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<HookOutput>(stdout) {
+                Ok(output) => {
+                    if let Some(context) = output.additional_context {
+                        outcome.additional_context.push(context);
+                    }
+                    if output.decision == Some(HookDecision::Block) {
+                        outcome.block_reason = Some(
+                            output
+                                .reason
+                                .unwrap_or_else(|| format!("Blocked by hook '{}'", hook.command)),
+                        );
+                        return outcome;
+                    }
+                    if let Some(tool_input) = output.tool_input {
+                        if input.hook_event_name == HookEvent::PreToolUse {
+                            // Later hooks see the modified arguments
+                            input.tool_input = Some(tool_input.clone());
+                            outcome.updated_tool_input = Some(tool_input);
+                        }
+                    }
+                }
+                Err(_) if accepts_plain_text_context(input.hook_event_name) => {
+                    outcome.additional_context.push(stdout.to_string());
+                }
+                Err(e) => {
+                    tracing::debug!(
+                        "Ignoring non-JSON output from hook '{}': {}",
+                        hook.command,
+                        e
+                    );
+                }
+            }
+        }
+
+        outcome
+    }
+
+    /// Run `PostToolUse` hooks for a finished tool call, appending any feedback to its result
+    pub async fn run_post_tool_use(
+        &self,
+        tool_call: &ToolCall,
+        result: ToolResult<Vec<Content>>,
+    ) -> ToolResult<Vec<Content>> {
+        let mut input = self.tool_input(HookEvent::PostToolUse, tool_call);
+        input.tool_response = Some(match &result {
+            Ok(contents) => json!(contents
+                .iter()
+                .filter_map(|c| c.as_text())
+                .collect::<Vec<_>>()),
+            Err(e) => json!({ "error": e.to_string() }),
+        });
+
+        let outcome = self.run(input).await;
+        let mut feedback: Vec<String> = outcome.additional_context;
+        if let Some(reason) = outcome.block_reason {
+            feedback.push(reason);
+        }
+
+        match result {
+            Ok(mut contents) if !feedback.is_empty() => {
+                contents.extend(
+                    feedback
+                        .into_iter()
+                        .map(|text| Content::text(format!("Hook feedback: {}", text))),
+                );
+                Ok(contents)
+            }
+            other => other,
+        }
+    }
+}
+
+fn load_project_hooks(config: &Config, cwd: &Path) -> HooksConfig {
+    #[derive(Deserialize)]
+    struct ProjectConfig {
+        #[serde(default)]
+        hooks: HooksConfig,
+    }
+
+    let Some(path) = find_project_config(cwd) else {
+        return HooksConfig::new();
+    };
+    let Ok(content) = std::fs::read_to_string(&path) else {
+        return HooksConfig::new();
+    };
+    let hooks = match serde_yaml::from_str::<ProjectConfig>(&content) {
+        Ok(project) => project.hooks,
+        Err(e) => {
+            tracing::warn!("Failed to parse hooks from {}: {}", path.display(), e);
+            return HooksConfig::new();
+        }
+    };
+
+    // Project hooks run commands from the repository, so they wait for the user's trust
+    if !hooks.is_empty() && !config.is_project_trusted(&path) {
+        tracing::warn!(
+            "Skipping hooks from {} until the project is trusted",
+            path.display()
+        );
+        return HooksConfig::new();
+    }
+    hooks
+}
+
+fn accepts_plain_text_context(event: HookEvent) -> bool {
+    matches!(event, HookEvent::SessionStart | HookEvent::UserPromptSubmit)
+}
+
+fn hook_matches(hook: &HookCommand, tool_name: Option<&str>) -> bool {
+    let (Some(matcher), Some(tool_name)) = (hook.matcher.as_deref(), tool_name) else {
+        return true;
+    };
+    if matcher.is_empty() || matcher == "*" {
+        return true;
+    }
+    match Regex::new(&format!("^(?:{})$", matcher)) {
+        Ok(re) => re.is_match(tool_name),
+        Err(e) => {
+            tracing::warn!("Invalid hook matcher '{}': {}", matcher, e);
+            false
+        }
+    }
+}
+
+async fn run_hook_command(
+    hook: &HookCommand,
+    input: &HookInput,
+    cwd: &Path,
+) -> Result<(Option<i32>, String, String)> {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C").arg(&hook.command);
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(&hook.command);
+        command
+    };
+
+    let mut child = command
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let payload = serde_json::to_vec(input)?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Hooks are free to ignore their input, so a closed pipe is not an error
+        let _ = stdin.write_all(&payload).await;
+    }
+
+    let timeout = Duration::from_secs(hook.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT_SECS));
+    let output = tokio::time::timeout(timeout, child.wait_with_output())
+        .await
+        .map_err(|_| anyhow!("timed out after {}s", timeout.as_secs()))??;
+
+    Ok((
+        output.status.code(),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn make_hooks(event: HookEvent, commands: Vec<HookCommand>) -> (Hooks, TempDir) {
+        let dir = TempDir::new().unwrap();
+        let hooks = Hooks::new(HashMap::from([(event, commands)]), dir.path().to_path_buf());
+        (hooks, dir)
+    }
+
+    fn command(matcher: Option<&str>, command: &str) -> HookCommand {
+        HookCommand {
+            matcher: matcher.map(String::from),
+            command: command.to_string(),
+            timeout: None,
+        }
+    }
+
+    fn shell_call(cmd: &str) -> ToolCall {
+        ToolCall::new("developer__shell", json!({ "command": cmd }))
+    }
+
+    #[tokio::test]
+    async fn test_exit_code_two_blocks_with_stderr() {
+        let (hooks, _dir) = make_hooks(
+            HookEvent::PreToolUse,
+            vec![command(
+                Some("developer__shell"),
+                "grep -q 'rm -rf' && echo 'rm -rf is not allowed' >&2 && exit 2; exit 0",
+            )],
+        );
+
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &shell_call("rm -rf /")))
+            .await;
+        assert_eq!(
+            outcome.block_reason.as_deref(),
+            Some("rm -rf is not allowed")
+        );
+
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &shell_call("ls")))
+            .await;
+        assert_eq!(outcome, HookOutcome::default());
+    }
+
+    #[tokio::test]
+    async fn test_matcher_filters_tools() {
+        let (hooks, _dir) = make_hooks(
+            HookEvent::PreToolUse,
+            vec![command(Some("developer__text_editor"), "exit 2")],
+        );
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &shell_call("ls")))
+            .await;
+        assert!(outcome.block_reason.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_json_output_modifies_tool_input() {
+        let (hooks, _dir) = make_hooks(
+            HookEvent::PreToolUse,
+            vec![command(
+                None,
+                r#"echo '{"tool_input": {"command": "ls -la"}, "additional_context": "rewritten"}'"#,
+            )],
+        );
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &shell_call("ls")))
+            .await;
+        assert_eq!(
+            outcome.updated_tool_input,
+            Some(json!({ "command": "ls -la" }))
+        );
+        assert_eq!(outcome.additional_context, vec!["rewritten".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_plain_text_is_context_for_session_start_only() {
+        let (hooks, _dir) = make_hooks(HookEvent::SessionStart, vec![command(None, "echo hello")]);
+        let outcome = hooks.run(hooks.input(HookEvent::SessionStart)).await;
+        assert_eq!(outcome.additional_context, vec!["hello".to_string()]);
+
+        let (hooks, _dir) = make_hooks(HookEvent::Stop, vec![command(None, "echo hello")]);
+        let outcome = hooks.run(hooks.stop_input(false)).await;
+        assert!(outcome.additional_context.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_hook_receives_event_on_stdin() {
+        let (hooks, dir) = make_hooks(
+            HookEvent::UserPromptSubmit,
+            vec![command(None, "cat > event.json")],
+        );
+        hooks.run(hooks.prompt_input("fix the bug")).await;
+
+        let event: Value =
+            serde_json::from_str(&std::fs::read_to_string(dir.path().join("event.json")).unwrap())
+                .unwrap();
+        assert_eq!(event["hook_event_name"], "UserPromptSubmit");
+        assert_eq!(event["prompt"], "fix the bug");
+    }
+
+    #[tokio::test]
+    async fn test_failing_and_slow_hooks_are_skipped() {
+        let (hooks, _dir) = make_hooks(
+            HookEvent::PreToolUse,
+            vec![
+                command(None, "exit 1"),
+                HookCommand {
+                    matcher: None,
+                    command: "sleep 5".to_string(),
+                    timeout: Some(1),
+                },
+            ],
+        );
+        let outcome = hooks
+            .run(hooks.tool_input(HookEvent::PreToolUse, &shell_call("ls")))
+            .await;
+        assert_eq!(outcome, HookOutcome::default());
+    }
+
+    #[tokio::test]
+    async fn test_post_tool_use_appends_feedback() {
+        let (hooks, _dir) = make_hooks(
+            HookEvent::PostToolUse,
+            vec![command(None, "echo 'formatting failed' >&2; exit 2")],
+        );
+        let result = hooks
+            .run_post_tool_use(&shell_call("ls"), Ok(vec![Content::text("file.txt")]))
+            .await
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result[1].as_text(),
+            Some("Hook feedback: formatting failed")
+        );
+    }
+
+    #[test]
+    fn test_project_hooks_wait_for_trust() {
+        let dir = TempDir::new().unwrap();
+        let project_path = dir.path().join(".goose").join("config.yaml");
+        std::fs::create_dir_all(project_path.parent().unwrap()).unwrap();
+        std::fs::write(
+            &project_path,
+            "hooks:\n  Stop:\n    - command: notify-send done\n",
+        )
+        .unwrap();
+        let config = Config::new_with_file_secrets(
+            dir.path().join("config.yaml"),
+            dir.path().join("secrets.yaml"),
+        )
+        .unwrap()
+        .with_project_config(&project_path);
+
+        assert!(load_project_hooks(&config, dir.path()).is_empty());
+
+        config.trust_project_commands().unwrap();
+        let hooks = load_project_hooks(&config, dir.path());
+        assert_eq!(hooks[&HookEvent::Stop][0].command, "notify-send done");
+    }
+}
@@ -1,15 +1,21 @@
 pub mod agents;
 pub mod config;
 pub mod context_mgmt;
+pub mod hooks;
 pub mod message;
+pub mod metrics;
 pub mod model;
 pub mod permission;
 pub mod prompt_template;
 pub mod providers;
 pub mod recipe;
+pub mod redaction;
 pub mod scheduler;
 pub mod scheduler_factory;
+pub mod scheduler_notifiers;
+pub mod scheduler_runs;
 pub mod scheduler_trait;
+pub mod scheduler_triggers;
 pub mod session;
 pub mod temporal_scheduler;
 pub mod token_counter;
@@ -0,0 +1,254 @@
+//! Prometheus metrics for replies, provider calls, tools, the scheduler and extensions.
+//!
+//! Metrics are always collected in process; goosed serves them at `/metrics` with [`render`].
+
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry,
+    TextEncoder,
+};
+
+/// Buckets in seconds, from quick tool calls up to long agent turns
+const DURATION_BUCKETS: [f64; 12] = [
+    0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 120.0, 300.0,
+];
+
+struct Metrics {
+    registry: Registry,
+    replies: IntCounter,
+    reply_duration: Histogram,
+    active_sessions: IntGauge,
+    provider_requests: IntCounterVec,
+    provider_duration: HistogramVec,
+    provider_errors: IntCounterVec,
+    tokens: IntCounterVec,
+    tool_calls: IntCounterVec,
+    tool_duration: HistogramVec,
+    scheduler_runs: IntCounterVec,
+    scheduler_duration: Histogram,
+    extension_restarts: IntCounterVec,
+}
+
+fn histogram_opts(name: &str, help: &str) -> HistogramOpts {
+    HistogramOpts::new(name, help).buckets(DURATION_BUCKETS.to_vec())
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new();
+        let metrics = Metrics {
+            replies: IntCounter::new("goose_replies_total", "Replies started by the agent")?,
+            reply_duration: Histogram::with_opts(histogram_opts(
+                "goose_reply_duration_seconds",
+                "Time from the start of a reply until its last message",
+            ))?,
+            active_sessions: IntGauge::new(
+                "goose_active_sessions",
+                "Sessions with a reply in progress",
+            )?,
+            provider_requests: IntCounterVec::new(
+                Opts::new(
+                    "goose_provider_requests_total",
+                    "Requests sent to providers",
+                ),
+                &["provider", "model"],
+            )?,
+            provider_duration: HistogramVec::new(
+                histogram_opts(
+                    "goose_provider_request_duration_seconds",
+                    "Latency of provider requests",
+                ),
+                &["provider", "model"],
+            )?,
+            provider_errors: IntCounterVec::new(
+                Opts::new("goose_provider_errors_total", "Failed provider requests"),
+                &["provider", "model", "error_type"],
+            )?,
+            tokens: IntCounterVec::new(
+                Opts::new("goose_tokens_total", "Tokens used by provider requests"),
+                &["provider", "model", "type"],
+            )?,
+            tool_calls: IntCounterVec::new(
+                Opts::new(
+                    "goose_tool_calls_total",
+                    "Tool calls by extension and outcome",
+                ),
+                &["extension", "outcome"],
+            )?,
+            tool_duration: HistogramVec::new(
+                histogram_opts("goose_tool_call_duration_seconds", "Duration of tool calls"),
+                &["extension"],
+            )?,
+            scheduler_runs: IntCounterVec::new(
+                Opts::new(
+                    "goose_scheduler_job_runs_total",
+                    "Scheduled job runs by outcome",
+                ),
+                &["outcome"],
+            )?,
+            scheduler_duration: Histogram::with_opts(histogram_opts(
+                "goose_scheduler_job_duration_seconds",
+                "Duration of scheduled job runs",
+            ))?,
+            extension_restarts: IntCounterVec::new(
+                Opts::new(
+                    "goose_extension_restarts_total",
+                    "Extension restarts by extension and outcome",
+                ),
+                &["extension", "outcome"],
+            )?,
+            registry,
+        };
+
+        let registry = &metrics.registry;
+        registry.register(Box::new(metrics.replies.clone()))?;
+        registry.register(Box::new(metrics.reply_duration.clone()))?;
+        registry.register(Box::new(metrics.active_sessions.clone()))?;
+        registry.register(Box::new(metrics.provider_requests.clone()))?;
+        registry.register(Box::new(metrics.provider_duration.clone()))?;
+        registry.register(Box::new(metrics.provider_errors.clone()))?;
+        registry.register(Box::new(metrics.tokens.clone()))?;
+        registry.register(Box::new(metrics.tool_calls.clone()))?;
+        registry.register(Box::new(metrics.tool_duration.clone()))?;
+        registry.register(Box::new(metrics.scheduler_runs.clone()))?;
+        registry.register(Box::new(metrics.scheduler_duration.clone()))?;
+        registry.register(Box::new(metrics.extension_restarts.clone()))?;
+        Ok(metrics)
+    }
+}
+
+static METRICS: Lazy<Metrics> = Lazy::new(|| Metrics::new().expect("metric definitions are valid"));
+
+fn outcome(success: bool) -> &'static str {
+    if success {
+        "success"
+    } else {
+        "failure"
+    }
+}
+
+/// Tracks a reply in progress, recording its duration when dropped
+pub struct ReplyGuard {
+    start: Instant,
+}
+
+/// Count a new reply and mark its session as active until the guard is dropped
+pub fn reply_started() -> ReplyGuard {
+    METRICS.replies.inc();
+    METRICS.active_sessions.inc();
+    ReplyGuard {
+        start: Instant::now(),
+    }
+}
+
+impl Drop for ReplyGuard {
+    fn drop(&mut self) {
+        METRICS.active_sessions.dec();
+        METRICS
+            .reply_duration
+            .observe(self.start.elapsed().as_secs_f64());
+    }
+}
+
+pub fn record_provider_request(
+    provider: &str,
+    model: &str,
+    duration: Duration,
+    error_type: Option<&str>,
+) {
+    METRICS
+        .provider_requests
+        .with_label_values(&[provider, model])
+        .inc();
+    METRICS
+        .provider_duration
+        .with_label_values(&[provider, model])
+        .observe(duration.as_secs_f64());
+    if let Some(error_type) = error_type {
+        METRICS
+            .provider_errors
+            .with_label_values(&[provider, model, error_type])
+            .inc();
+    }
+}
+
+pub fn record_tokens(provider: &str, model: &str, token_type: &str, tokens: u64) {
+    METRICS
+        .tokens
+        .with_label_values(&[provider, model, token_type])
+        .inc_by(tokens);
+}
+
+/// Record a finished tool call, with `error_type` set when it failed
+pub fn record_tool_call(extension: &str, duration: Duration, error_type: Option<&str>) {
+    METRICS
+        .tool_calls
+        .with_label_values(&[extension, error_type.unwrap_or("success")])
+        .inc();
+    METRICS
+        .tool_duration
+        .with_label_values(&[extension])
+        .observe(duration.as_secs_f64());
+}
+
+pub fn record_scheduler_job(success: bool, duration: Duration) {
+    METRICS
+        .scheduler_runs
+        .with_label_values(&[outcome(success)])
+        .inc();
+    METRICS.scheduler_duration.observe(duration.as_secs_f64());
+}
+
+pub fn record_extension_restart(extension: &str, success: bool) {
+    METRICS
+        .extension_restarts
+        .with_label_values(&[extension, outcome(success)])
+        .inc();
+}
+
+/// All metrics in the Prometheus text exposition format
+pub fn render() -> String {
+    TextEncoder::new()
+        .encode_to_string(&METRICS.registry.gather())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        // Other tests record metrics too, so only check labels unique to this test
+        let reply = reply_started();
+        record_provider_request(
+            "metrics-test",
+            "claude",
+            Duration::from_millis(120),
+            Some("rate_limit_exceeded"),
+        );
+        record_tokens("metrics-test", "claude", "input", 42);
+        record_tool_call("metrics-test", Duration::from_millis(5), Some("not_found"));
+        record_scheduler_job(false, Duration::from_secs(1));
+        record_extension_restart("metrics-test", true);
+        drop(reply);
+
+        let output = render();
+        assert!(output.contains(
+            r#"goose_provider_errors_total{error_type="rate_limit_exceeded",model="claude",provider="metrics-test"} 1"#
+        ));
+        assert!(output.contains(
+            r#"goose_tokens_total{model="claude",provider="metrics-test",type="input"} 42"#
+        ));
+        assert!(output
+            .contains(r#"goose_tool_calls_total{extension="metrics-test",outcome="not_found"} 1"#));
+        assert!(output.contains(
+            r#"goose_extension_restarts_total{extension="metrics-test",outcome="success"} 1"#
+        ));
+        assert!(output.contains(r#"goose_scheduler_job_runs_total{outcome="failure"}"#));
+        assert!(output.contains("goose_active_sessions"));
+        assert!(output.contains("goose_reply_duration_seconds_count"));
+    }
+}
@@ -3,6 +3,7 @@ use crate::config::permission::PermissionLevel;
 use crate::config::PermissionManager;
 use crate::message::{Message, MessageContent, ToolRequest};
 use crate::providers::base::Provider;
+use crate::tracing::gen_ai;
 use chrono::Utc;
 use indoc::indoc;
 use mcp_core::tool::ToolAnnotations;
@@ -136,13 +137,13 @@ pub async fn detect_read_only_tools(
     let tool = create_read_only_tool();
     let check_messages = create_check_messages(tool_requests);
 
-    let res = provider
-        .complete(
-            "You are a good analyst and can detect operations whether they have read-only operations.",
-            &check_messages,
-            &[tool.clone()],
-        )
-        .await;
+    let res = gen_ai::complete(
+        provider.as_ref(),
+        "You are a good analyst and can detect operations whether they have read-only operations.",
+        &check_messages,
+        &[tool.clone()],
+    )
+    .await;
 
     // Process the response and return an empty vector if the response is invalid
     if let Ok((message, _usage)) = res {
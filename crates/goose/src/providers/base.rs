@@ -214,11 +214,26 @@ pub trait LeadWorkerProviderTrait {
 
     /// Get the currently active model name
     fn get_active_model(&self) -> String;
+
+    /// Get the name of the provider, lead or worker, that serves `model`
+    fn get_provider_name(&self, model: &str) -> String;
+}
+
+/// Object-safe access to [`Provider::metadata`], implemented for every provider
+pub trait ProviderDescriptor {
+    /// Get the metadata for this provider's type
+    fn get_metadata(&self) -> ProviderMetadata;
+}
+
+impl<T: Provider> ProviderDescriptor for T {
+    fn get_metadata(&self) -> ProviderMetadata {
+        T::metadata()
+    }
 }
 
 /// Base trait for AI providers (OpenAI, Anthropic, etc)
 #[async_trait]
-pub trait Provider: Send + Sync {
+pub trait Provider: ProviderDescriptor + Send + Sync {
     /// Get the metadata for this provider type
     fn metadata() -> ProviderMetadata
     where
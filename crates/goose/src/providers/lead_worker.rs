@@ -3,7 +3,9 @@ use async_trait::async_trait;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use super::base::{LeadWorkerProviderTrait, Provider, ProviderMetadata, ProviderUsage};
+use super::base::{
+    LeadWorkerProviderTrait, Provider, ProviderDescriptor, ProviderMetadata, ProviderUsage,
+};
 use super::errors::ProviderError;
 use crate::message::{Message, MessageContent};
 use crate::model::ModelConfig;
@@ -301,6 +303,15 @@ impl LeadWorkerProviderTrait for LeadWorkerProvider {
             self.lead_provider.get_model_config().model_name
         })
     }
+
+    /// Get the name of the provider, lead or worker, that serves `model`
+    fn get_provider_name(&self, model: &str) -> String {
+        if model == self.worker_provider.get_model_config().model_name {
+            self.worker_provider.get_metadata().name
+        } else {
+            self.lead_provider.get_metadata().name
+        }
+    }
 }
 
 #[async_trait]
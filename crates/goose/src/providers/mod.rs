@@ -22,6 +22,7 @@ pub mod openai;
 pub mod openrouter;
 pub mod pricing;
 pub mod sagemaker_tgi;
+pub mod scripted;
 pub mod snowflake;
 pub mod toolshim;
 pub mod utils;
@@ -0,0 +1,95 @@
+//! A provider that answers with prepared messages instead of calling a model, so recipes can
+//! be tested without a live provider.
+
+use std::collections::VecDeque;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use mcp_core::role::Role;
+use mcp_core::tool::Tool;
+
+use super::base::{Provider, ProviderMetadata, ProviderUsage, Usage};
+use super::errors::ProviderError;
+use crate::message::Message;
+use crate::model::ModelConfig;
+use crate::session::storage::read_messages_with_truncation;
+
+pub const SCRIPTED_MODEL: &str = "scripted";
+
+pub struct ScriptedProvider {
+    model_config: ModelConfig,
+    responses: Mutex<VecDeque<Message>>,
+}
+
+impl ScriptedProvider {
+    /// Answer each request with the next of `responses`, failing once they run out
+    pub fn new(responses: Vec<Message>) -> Self {
+        Self {
+            model_config: ModelConfig::new(SCRIPTED_MODEL.to_string()),
+            responses: Mutex::new(responses.into()),
+        }
+    }
+
+    /// Replay the assistant messages of a recorded session file, in order
+    pub fn from_session_file(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(anyhow!("Session file {} not found", path.display()));
+        }
+        let responses = read_messages_with_truncation(path, None)?
+            .into_iter()
+            .filter(|message| message.role == Role::Assistant)
+            .collect();
+        Ok(Self::new(responses))
+    }
+}
+
+#[async_trait]
+impl Provider for ScriptedProvider {
+    fn metadata() -> ProviderMetadata {
+        ProviderMetadata::empty()
+    }
+
+    fn get_model_config(&self) -> ModelConfig {
+        self.model_config.clone()
+    }
+
+    async fn complete(
+        &self,
+        _system: &str,
+        _messages: &[Message],
+        _tools: &[Tool],
+    ) -> Result<(Message, ProviderUsage), ProviderError> {
+        let message = self
+            .responses
+            .lock()
+            .expect("scripted responses lock is never poisoned")
+            .pop_front()
+            .ok_or_else(|| {
+                ProviderError::ExecutionError("The script has no responses left".to_string())
+            })?;
+        Ok((
+            message,
+            ProviderUsage::new(SCRIPTED_MODEL.to_string(), Usage::default()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_scripted_provider_answers_in_order() {
+        let provider = ScriptedProvider::new(vec![
+            Message::assistant().with_text("first"),
+            Message::assistant().with_text("second"),
+        ]);
+        for expected in ["first", "second"] {
+            let (message, _) = provider.complete("", &[], &[]).await.unwrap();
+            assert_eq!(message.as_concat_text(), expected);
+        }
+        assert!(provider.complete("", &[], &[]).await.is_err());
+    }
+}
@@ -0,0 +1,489 @@
+//! A recipe can build on another with `extends`, taking everything it doesn't set itself from
+//! the recipe it extends. Files are merged before their templates are rendered, so the merged
+//! recipe declares the parameters of both.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+
+use super::library::RecipeLibrary;
+use super::template::BUILT_IN_RECIPE_DIR_PARAM;
+
+pub const EXTENDS_FIELD: &str = "extends";
+
+const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "json"];
+
+static RECIPE_DIR_VAR: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(&format!(
+        r"\{{\{{-?\s*{}\s*-?\}}\}}",
+        BUILT_IN_RECIPE_DIR_PARAM
+    ))
+    .unwrap()
+});
+
+/// How a recipe's instructions combine with those of the recipe it extends
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstructionsMerge {
+    /// The extending recipe's instructions follow the base recipe's
+    #[default]
+    Append,
+    /// The extending recipe's instructions are used on their own
+    Replace,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Extends {
+    Recipe(String),
+    Options {
+        recipe: String,
+        #[serde(default)]
+        instructions: InstructionsMerge,
+    },
+}
+
+/// A recipe file that another recipe extends
+pub struct BaseRecipe {
+    pub content: String,
+    pub path: PathBuf,
+}
+
+// Recipes can be nested under a top-level `recipe` key, as `Recipe::from_content` accepts
+fn recipe_mapping(value: Value) -> Option<Mapping> {
+    match value {
+        Value::Mapping(mut mapping) => match mapping.remove("recipe") {
+            Some(Value::Mapping(nested)) => Some(nested),
+            Some(other) => {
+                mapping.insert(Value::from("recipe"), other);
+                Some(mapping)
+            }
+            None => Some(mapping),
+        },
+        _ => None,
+    }
+}
+
+fn parse_recipe(content: &str) -> Option<Mapping> {
+    serde_yaml::from_str(content).ok().and_then(recipe_mapping)
+}
+
+// Templates with control structures aren't YAML until they're rendered, so check the raw
+// lines too rather than ignore an `extends` that can't be merged
+fn mentions_extends(content: &str) -> bool {
+    content.lines().any(|line| {
+        let line = line.trim_start().trim_start_matches('"');
+        line.starts_with(&format!("{}:", EXTENDS_FIELD))
+            || line.starts_with(&format!("{}\":", EXTENDS_FIELD))
+    })
+}
+
+/// Whether the recipe content extends another recipe
+pub fn extends_recipe(content: &str) -> bool {
+    match parse_recipe(content) {
+        Some(mapping) => mapping.contains_key(EXTENDS_FIELD),
+        None => mentions_extends(content),
+    }
+}
+
+/// Load the recipe `reference` names: a file relative to `recipe_dir`, or the name of a recipe
+/// in the current directory or the recipe library, as `goose run --recipe` finds it
+pub fn load_base_recipe_file(reference: &str, recipe_dir: &Path) -> Result<BaseRecipe> {
+    if is_file_reference(reference) {
+        return read_base_recipe(&recipe_dir.join(expand_tilde(reference)));
+    }
+    for ext in RECIPE_FILE_EXTENSIONS {
+        let path = PathBuf::from(format!("{}.{}", reference, ext));
+        if path.is_file() {
+            return read_base_recipe(&path);
+        }
+    }
+    match RecipeLibrary::default().find(reference)? {
+        Some(recipe) => read_base_recipe(&recipe.path),
+        None => bail!(
+            "No {}.yaml or {}.json in the current directory or the recipe library",
+            reference,
+            reference
+        ),
+    }
+}
+
+fn is_file_reference(reference: &str) -> bool {
+    reference.contains('/')
+        || reference.contains('\\')
+        || reference.starts_with('~')
+        || reference.starts_with('.')
+        || Path::new(reference).extension().is_some()
+}
+
+fn expand_tilde(reference: &str) -> PathBuf {
+    match (reference.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(reference),
+    }
+}
+
+fn read_base_recipe(path: &Path) -> Result<BaseRecipe> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read recipe file {}: {}", path.display(), e))?;
+    Ok(BaseRecipe {
+        path: path.canonicalize().unwrap_or_else(|_| path.to_path_buf()),
+        content,
+    })
+}
+
+/// Merge the recipes that `content` extends into it, returning the merged recipe file, or the
+/// content as it is when it doesn't extend anything
+///
+/// `load` finds the recipe an `extends` refers to, given the directory of the recipe that
+/// refers to it.
+pub fn resolve_extends<F>(content: &str, recipe_dir: &Path, mut load: F) -> Result<String>
+where
+    F: FnMut(&str, &Path) -> Result<BaseRecipe>,
+{
+    let Some(mapping) = parse_recipe(content) else {
+        if mentions_extends(content) {
+            bail!("A recipe that uses 'extends' must be valid YAML or JSON before templating");
+        }
+        return Ok(content.to_string());
+    };
+    if !mapping.contains_key(EXTENDS_FIELD) {
+        return Ok(content.to_string());
+    }
+    let merged = resolve_mapping(mapping, recipe_dir, &mut load, &mut HashSet::new())?;
+    Ok(serde_yaml::to_string(&merged)?)
+}
+
+fn resolve_mapping<F>(
+    mut mapping: Mapping,
+    recipe_dir: &Path,
+    load: &mut F,
+    visited: &mut HashSet<PathBuf>,
+) -> Result<Mapping>
+where
+    F: FnMut(&str, &Path) -> Result<BaseRecipe>,
+{
+    let Some(extends) = mapping.remove(EXTENDS_FIELD) else {
+        return Ok(mapping);
+    };
+    let (reference, instructions) = match serde_yaml::from_value(extends) {
+        Ok(Extends::Recipe(recipe)) => (recipe, InstructionsMerge::default()),
+        Ok(Extends::Options {
+            recipe,
+            instructions,
+        }) => (recipe, instructions),
+        Err(_) => bail!(
+            "'extends' must be a recipe, or an object with 'recipe' and optionally 'instructions' set to append or replace"
+        ),
+    };
+
+    let base = load(&reference, recipe_dir)
+        .map_err(|e| anyhow!("Failed to load the recipe '{}' to extend: {}", reference, e))?;
+    if !visited.insert(base.path.clone()) {
+        bail!("Recipe {} extends itself", base.path.display());
+    }
+    let base_mapping = parse_recipe(&base.content).ok_or_else(|| {
+        anyhow!(
+            "Recipe {} is extended, so it must be valid YAML or JSON before templating",
+            base.path.display()
+        )
+    })?;
+    let base_dir = base.path.parent().unwrap_or(recipe_dir).to_path_buf();
+    let base_mapping = resolve_mapping(base_mapping, &base_dir, load, visited)?;
+    let base_mapping = rebase_mapping(base_mapping, &base_dir);
+    Ok(merge_recipes(base_mapping, mapping, instructions))
+}
+
+// The merged recipe is rendered with the extending recipe's directory as `recipe_dir`, so
+// point the base recipe's own `recipe_dir` and relative sub-recipe paths at its directory
+fn rebase_paths(value: Value, base_dir: &Path) -> Value {
+    match value {
+        Value::String(text) => {
+            let dir = base_dir.to_string_lossy();
+            Value::String(
+                RECIPE_DIR_VAR
+                    .replace_all(&text, regex::NoExpand(&dir))
+                    .into_owned(),
+            )
+        }
+        Value::Sequence(items) => Value::Sequence(
+            items
+                .into_iter()
+                .map(|item| rebase_paths(item, base_dir))
+                .collect(),
+        ),
+        Value::Mapping(mapping) => Value::Mapping(rebase_mapping(mapping, base_dir)),
+        other => other,
+    }
+}
+
+fn rebase_mapping(mapping: Mapping, base_dir: &Path) -> Mapping {
+    mapping
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match (key.as_str(), value) {
+                (Some("sub_recipes"), Value::Sequence(sub_recipes)) => Value::Sequence(
+                    sub_recipes
+                        .into_iter()
+                        .map(|sub_recipe| rebase_sub_recipe(sub_recipe, base_dir))
+                        .collect(),
+                ),
+                (_, value) => rebase_paths(value, base_dir),
+            };
+            (key, value)
+        })
+        .collect()
+}
+
+fn rebase_sub_recipe(sub_recipe: Value, base_dir: &Path) -> Value {
+    let mut sub_recipe = rebase_paths(sub_recipe, base_dir);
+    if let Some(Value::String(path)) = sub_recipe.get_mut("path") {
+        if !path.contains("{{") && Path::new(path.as_str()).is_relative() {
+            *path = base_dir
+                .join(expand_tilde(path))
+                .to_string_lossy()
+                .into_owned();
+        }
+    }
+    sub_recipe
+}
+
+/// Merge a recipe over the one it extends: extensions and sub-recipes are merged by name,
+/// parameters by key, settings and author field by field, and instructions as `instructions`
+/// says. Anything else the recipe sets replaces the base recipe's.
+fn merge_recipes(base: Mapping, recipe: Mapping, instructions: InstructionsMerge) -> Mapping {
+    let mut merged = base;
+    for (key, value) in recipe {
+        let value = match (key.as_str(), merged.get(&key).cloned()) {
+            (Some("extensions" | "sub_recipes"), Some(base)) => merge_by_key(base, value, "name"),
+            (Some("parameters"), Some(base)) => merge_by_key(base, value, "key"),
+            (Some("settings" | "author"), Some(base)) => merge_fields(base, value),
+            (Some("instructions"), Some(Value::String(base)))
+                if instructions == InstructionsMerge::Append =>
+            {
+                match value {
+                    Value::String(value) => Value::String(format!("{}\n\n{}", base, value)),
+                    other => other,
+                }
+            }
+            _ => value,
+        };
+        merged.insert(key, value);
+    }
+    merged
+}
+
+// Items with the same key as a base item replace it in place; the rest are added at the end
+fn merge_by_key(base: Value, items: Value, key: &str) -> Value {
+    let (Value::Sequence(mut merged), Value::Sequence(items)) = (base, items.clone()) else {
+        return items;
+    };
+    for item in items {
+        let existing = item.get(key).and_then(|id| {
+            merged
+                .iter()
+                .position(|base_item| base_item.get(key) == Some(id))
+        });
+        match existing {
+            Some(index) => merged[index] = item,
+            None => merged.push(item),
+        }
+    }
+    Value::Sequence(merged)
+}
+
+fn merge_fields(base: Value, value: Value) -> Value {
+    match (base, value) {
+        (Value::Mapping(mut merged), Value::Mapping(fields)) => {
+            for (key, value) in fields {
+                let value = match merged.get(&key).cloned() {
+                    Some(base) => merge_fields(base, value),
+                    None => value,
+                };
+                merged.insert(key, value);
+            }
+            Value::Mapping(merged)
+        }
+        (_, value) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::recipe::Recipe;
+    use std::collections::HashMap;
+
+    const BASE: &str = r#"
+version: 1.0.0
+title: Base
+description: Shared setup
+author:
+  contact: platform-team
+instructions: Follow the team style guide
+settings:
+  goose_provider: anthropic
+  goose_model: claude-sonnet-4
+extensions:
+  - type: builtin
+    name: developer
+    timeout: 300
+  - type: builtin
+    name: memory
+parameters:
+  - key: repo
+    input_type: string
+    requirement: required
+    description: the repository
+"#;
+
+    fn loader(
+        files: HashMap<&'static str, &'static str>,
+    ) -> impl FnMut(&str, &Path) -> Result<BaseRecipe> {
+        move |reference, recipe_dir| {
+            let content = files
+                .get(reference)
+                .ok_or_else(|| anyhow!("no recipe {}", reference))?;
+            Ok(BaseRecipe {
+                content: content.to_string(),
+                path: recipe_dir.join(reference),
+            })
+        }
+    }
+
+    #[test]
+    fn test_resolve_extends_merges_fields() {
+        let content = r#"
+extends: ./base.yaml
+title: Review
+description: Reviews a pull request
+instructions: Review {{ repo }}
+settings:
+  temperature: 0.2
+extensions:
+  - type: builtin
+    name: developer
+    timeout: 600
+  - type: builtin
+    name: computercontroller
+parameters:
+  - key: repo
+    input_type: string
+    requirement: optional
+    default: goose
+    description: the repository
+"#;
+        let files = HashMap::from([("./base.yaml", BASE)]);
+        let resolved = resolve_extends(content, Path::new("/recipes"), loader(files)).unwrap();
+        let recipe = Recipe::from_content(&resolved).unwrap();
+
+        assert_eq!(recipe.title, "Review");
+        assert_eq!(
+            recipe.author.unwrap().contact.as_deref(),
+            Some("platform-team")
+        );
+        assert_eq!(
+            recipe.instructions.as_deref(),
+            Some("Follow the team style guide\n\nReview {{ repo }}")
+        );
+        let settings = recipe.settings.unwrap();
+        assert_eq!(settings.goose_provider.as_deref(), Some("anthropic"));
+        assert_eq!(settings.temperature, Some(0.2));
+        let extensions: Vec<_> = recipe
+            .extensions
+            .unwrap()
+            .iter()
+            .map(|e| e.name())
+            .collect();
+        assert_eq!(extensions, ["developer", "memory", "computercontroller"]);
+        let parameters = recipe.parameters.unwrap();
+        assert_eq!(parameters.len(), 1);
+        assert_eq!(parameters[0].default.as_deref(), Some("goose"));
+    }
+
+    #[test]
+    fn test_resolve_extends_replaces_instructions() {
+        let content = r#"
+extends:
+  recipe: ./base.yaml
+  instructions: replace
+title: Review
+description: Reviews a pull request
+instructions: Only review
+"#;
+        let files = HashMap::from([("./base.yaml", BASE)]);
+        let resolved = resolve_extends(content, Path::new("/recipes"), loader(files)).unwrap();
+        let recipe = Recipe::from_content(&resolved).unwrap();
+        assert_eq!(recipe.instructions.as_deref(), Some("Only review"));
+    }
+
+    #[test]
+    fn test_resolve_extends_follows_chains_and_rejects_cycles() {
+        let files = HashMap::from([
+            ("middle.yaml", "extends: base.yaml\ntitle: Middle\n"),
+            ("base.yaml", BASE),
+        ]);
+        let content = "extends: middle.yaml\ndescription: Top\n";
+        let resolved = resolve_extends(content, Path::new("/recipes"), loader(files)).unwrap();
+        let recipe = Recipe::from_content(&resolved).unwrap();
+        assert_eq!(recipe.title, "Middle");
+        assert_eq!(recipe.description, "Top");
+
+        let files = HashMap::from([
+            ("a.yaml", "extends: b.yaml\n"),
+            ("b.yaml", "extends: a.yaml\n"),
+        ]);
+        let err =
+            resolve_extends("extends: a.yaml\n", Path::new("/recipes"), loader(files)).unwrap_err();
+        assert!(err.to_string().contains("extends itself"));
+    }
+
+    #[test]
+    fn test_resolve_extends_rebases_base_recipe_paths() {
+        let base = r#"
+title: Base
+description: Shared setup
+instructions: Read {{ recipe_dir }}/guide.md
+sub_recipes:
+  - name: lint
+    path: ./lint.yaml
+  - name: audit
+    path: "{{recipe_dir}}/audit.yaml"
+"#;
+        let files = HashMap::from([("/shared/base.yaml", base)]);
+        let content = "extends: /shared/base.yaml\nprompt: Check {{ recipe_dir }}\n";
+        let resolved = resolve_extends(content, Path::new("/recipes"), loader(files)).unwrap();
+        let recipe = Recipe::from_content(&resolved).unwrap();
+
+        assert_eq!(
+            recipe.instructions.as_deref(),
+            Some("Read /shared/guide.md")
+        );
+        assert_eq!(recipe.prompt.as_deref(), Some("Check {{ recipe_dir }}"));
+        let paths: Vec<_> = recipe
+            .sub_recipes
+            .unwrap()
+            .into_iter()
+            .map(|sub_recipe| sub_recipe.path)
+            .collect();
+        assert_eq!(paths, ["/shared/./lint.yaml", "/shared/audit.yaml"]);
+    }
+
+    #[test]
+    fn test_resolve_extends_leaves_other_recipes_alone() {
+        let content = "title: {% if x %}A{% endif %}\n";
+        let resolved =
+            resolve_extends(content, Path::new("/recipes"), loader(HashMap::new())).unwrap();
+        assert_eq!(resolved, content);
+        assert!(!extends_recipe(content));
+
+        let content = "{% if x %}\nextends: base.yaml\n{% endif %}\n";
+        assert!(extends_recipe(content));
+        assert!(resolve_extends(content, Path::new("/recipes"), loader(HashMap::new())).is_err());
+    }
+}
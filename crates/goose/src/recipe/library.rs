@@ -0,0 +1,710 @@
+//! The recipe library indexes recipes from the directories in `GOOSE_RECIPE_PATH` and from
+//! sources installed into it, git repositories and local directories, so recipes can be
+//! listed, searched and run by name.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use anyhow::{anyhow, bail, Result};
+use chrono::{DateTime, Utc};
+use etcetera::{choose_app_strategy, AppStrategy};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::template::parse_recipe_template;
+use crate::config::{Config, APP_STRATEGY};
+
+/// Directories of recipes to index, separated like `PATH` or given as a list in the config
+pub const RECIPE_PATH_CONFIG_KEY: &str = "GOOSE_RECIPE_PATH";
+
+const LIBRARY_DIR: &str = "recipe_library";
+const MANIFEST_FILE: &str = "library.json";
+const SOURCES_DIR: &str = "sources";
+const RECIPE_FILE_EXTENSIONS: &[&str] = &["yaml", "json"];
+const TEST_FILE_SUFFIX: &str = ".test.yaml";
+
+/// Where an installed source's recipes come from
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SourceLocation {
+    /// A git repository, cloned into the library
+    Git { url: String },
+    /// A local directory, indexed where it is
+    Directory { path: PathBuf },
+}
+
+/// A source of recipes installed into the library
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstalledSource {
+    pub name: String,
+    pub location: SourceLocation,
+    /// The tag, branch or commit a git source is pinned to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned: Option<String>,
+    /// The commit a git source is at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit: Option<String>,
+    pub installed_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl InstalledSource {
+    /// A short description of the version of the source, such as `v1.2 (3f2a9c1)`
+    pub fn version(&self) -> Option<String> {
+        let commit = self.commit.as_deref().map(|c| &c[..c.len().min(7)]);
+        match (&self.pinned, commit) {
+            (Some(pinned), Some(commit)) => Some(format!("{} ({})", pinned, commit)),
+            (Some(pinned), None) => Some(pinned.clone()),
+            (None, Some(commit)) => Some(commit.to_string()),
+            (None, None) => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    sources: Vec<InstalledSource>,
+}
+
+/// A recipe found in the library
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LibraryRecipe {
+    /// The name the recipe is run by, its file name without the extension
+    pub name: String,
+    pub title: String,
+    pub description: String,
+    /// The recipe's own `version`
+    pub version: String,
+    pub activities: Vec<String>,
+    pub parameters: Vec<String>,
+    /// The installed source the recipe is from, or the directory it's in
+    pub source: String,
+    /// The version of the installed source, for git sources
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_version: Option<String>,
+    pub path: PathBuf,
+}
+
+/// RecipeLibrary installs, upgrades and removes sources of recipes, and indexes the recipes
+/// they and the configured recipe directories hold.
+///
+/// Installed sources are recorded in library.json under the library directory, and git
+/// sources are cloned under sources/.
+pub struct RecipeLibrary {
+    library_dir: PathBuf,
+    recipe_dirs: Vec<PathBuf>,
+}
+
+impl Default for RecipeLibrary {
+    fn default() -> Self {
+        let library_dir = choose_app_strategy(APP_STRATEGY.clone())
+            .expect("goose requires a home dir")
+            .data_dir()
+            .join(LIBRARY_DIR);
+        RecipeLibrary {
+            library_dir,
+            recipe_dirs: configured_recipe_dirs(),
+        }
+    }
+}
+
+fn configured_recipe_dirs() -> Vec<PathBuf> {
+    let separator = if cfg!(windows) { ';' } else { ':' };
+    match Config::global().get_param::<Value>(RECIPE_PATH_CONFIG_KEY) {
+        Ok(Value::String(dirs)) => dirs
+            .split(separator)
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        Ok(Value::Array(dirs)) => dirs
+            .iter()
+            .filter_map(|dir| dir.as_str())
+            .map(PathBuf::from)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// Source names become directory names, so keep them simple
+fn validate_source_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        bail!(
+            "'{}' is not a valid source name, use letters, digits, '-', '_' and '.'",
+            name
+        )
+    }
+}
+
+// Sources and versions are passed to git, which would read a leading '-' as an option
+fn validate_git_arg(kind: &str, value: &str) -> Result<()> {
+    if value.starts_with('-') {
+        bail!(
+            "'{}' is not a valid {}, it can't start with '-'",
+            value,
+            kind
+        );
+    }
+    Ok(())
+}
+
+fn is_git_url(source: &str) -> bool {
+    ["https://", "http://", "ssh://", "git@", "file://"]
+        .iter()
+        .any(|prefix| source.starts_with(prefix))
+        || source.ends_with(".git")
+}
+
+// `owner/repo` is shorthand for a GitHub repository
+fn git_url(source: &str) -> Option<String> {
+    if is_git_url(source) {
+        return Some(source.to_string());
+    }
+    match source.split_once('/') {
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+            Some(format!("https://github.com/{}/{}.git", owner, repo))
+        }
+        _ => None,
+    }
+}
+
+fn default_source_name(source: &str) -> String {
+    source
+        .trim_end_matches('/')
+        .rsplit(['/', '\\', ':'])
+        .next()
+        .unwrap_or(source)
+        .trim_end_matches(".git")
+        .to_string()
+}
+
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .map_err(|e| anyhow!("Failed to run git, make sure it's installed: {}", e))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn is_recipe_file(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    !name.ends_with(TEST_FILE_SUFFIX)
+        && path
+            .extension()
+            .is_some_and(|ext| RECIPE_FILE_EXTENSIONS.iter().any(|e| ext == *e))
+}
+
+// Recipe files under `dir`, skipping hidden directories such as .git
+fn recipe_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut directories = vec![dir.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let Ok(entries) = std::fs::read_dir(&directory) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            match entry.file_type() {
+                Ok(file_type) if file_type.is_dir() => directories.push(path),
+                Ok(file_type) if file_type.is_file() && is_recipe_file(&path) => files.push(path),
+                _ => {}
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+// Files that aren't recipes, such as other YAML files in a repository, are skipped
+fn index_recipe(
+    path: &Path,
+    source: &str,
+    source_version: Option<String>,
+) -> Option<LibraryRecipe> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let recipe = parse_recipe_template(&content, path.parent()?)
+        .map_err(|e| tracing::debug!("Skipping {} in the recipe library: {}", path.display(), e))
+        .ok()?;
+    Some(LibraryRecipe {
+        name: path.file_stem()?.to_string_lossy().into_owned(),
+        title: recipe.title,
+        description: recipe.description,
+        version: recipe.version,
+        activities: recipe.activities.unwrap_or_default(),
+        parameters: recipe
+            .parameters
+            .unwrap_or_default()
+            .into_iter()
+            .map(|p| p.key)
+            .collect(),
+        source: source.to_string(),
+        source_version,
+        path: path.to_path_buf(),
+    })
+}
+
+// How well a recipe matches every term of a query, weighing its name and title the most, or
+// `None` if a term doesn't match at all
+fn search_score(recipe: &LibraryRecipe, terms: &[String]) -> Option<usize> {
+    let mut score = 0;
+    for term in terms {
+        let matches = |text: &str| text.to_lowercase().contains(term.as_str());
+        let term_score = [
+            (matches(&recipe.name) || matches(&recipe.title), 3),
+            (matches(&recipe.description), 2),
+            (recipe.activities.iter().any(|a| matches(a)), 1),
+            (recipe.parameters.iter().any(|p| matches(p)), 1),
+        ]
+        .iter()
+        .filter(|(matched, _)| *matched)
+        .map(|(_, weight)| weight)
+        .sum::<usize>();
+        if term_score == 0 {
+            return None;
+        }
+        score += term_score;
+    }
+    Some(score)
+}
+
+impl RecipeLibrary {
+    pub fn new<P: AsRef<Path>>(library_dir: P, recipe_dirs: Vec<PathBuf>) -> Self {
+        RecipeLibrary {
+            library_dir: library_dir.as_ref().to_path_buf(),
+            recipe_dirs,
+        }
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.library_dir.join(MANIFEST_FILE)
+    }
+
+    fn source_dir(&self, name: &str) -> PathBuf {
+        self.library_dir.join(SOURCES_DIR).join(name)
+    }
+
+    fn load_manifest(&self) -> Result<Manifest> {
+        match std::fs::read_to_string(self.manifest_path()) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|e| anyhow!("Invalid recipe library manifest: {}", e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Manifest::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save_manifest(&self, manifest: &Manifest) -> Result<()> {
+        std::fs::create_dir_all(&self.library_dir)?;
+        std::fs::write(
+            self.manifest_path(),
+            serde_json::to_string_pretty(manifest)?,
+        )?;
+        Ok(())
+    }
+
+    /// The installed sources, in the order they were installed
+    pub fn sources(&self) -> Result<Vec<InstalledSource>> {
+        Ok(self.load_manifest()?.sources)
+    }
+
+    /// Install a source of recipes: a local directory, indexed where it is, or a git
+    /// repository URL or GitHub `owner/repo`, cloned into the library and pinned to `version`
+    /// when one is given
+    pub fn install(
+        &self,
+        source: &str,
+        name: Option<&str>,
+        version: Option<&str>,
+    ) -> Result<InstalledSource> {
+        validate_git_arg("source", source)?;
+        if let Some(version) = version {
+            validate_git_arg("version", version)?;
+        }
+        let name = name
+            .map(str::to_string)
+            .unwrap_or_else(|| default_source_name(source));
+        validate_source_name(&name)?;
+        let mut manifest = self.load_manifest()?;
+        if manifest.sources.iter().any(|s| s.name == name) {
+            bail!(
+                "A source named '{}' is already installed, remove it first or pick another name",
+                name
+            );
+        }
+
+        let local_dir = Path::new(source);
+        let location = if local_dir.is_dir() {
+            if version.is_some() {
+                bail!("Only git sources can be pinned to a version");
+            }
+            SourceLocation::Directory {
+                path: local_dir.canonicalize()?,
+            }
+        } else {
+            let url = git_url(source).ok_or_else(|| {
+                anyhow!(
+                    "'{}' is neither a directory nor a git repository URL",
+                    source
+                )
+            })?;
+            SourceLocation::Git { url }
+        };
+
+        let mut installed = InstalledSource {
+            name: name.clone(),
+            location,
+            pinned: version.map(str::to_string),
+            commit: None,
+            installed_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        if let SourceLocation::Git { url } = &installed.location {
+            let dir = self.source_dir(&name);
+            if dir.exists() {
+                std::fs::remove_dir_all(&dir)?;
+            }
+            std::fs::create_dir_all(self.library_dir.join(SOURCES_DIR))?;
+            let target = dir.to_string_lossy().into_owned();
+            git(&self.library_dir, &["clone", "--quiet", "--", url, &target])?;
+            // The trailing "--" makes git read the version as a revision, never a path
+            let checkout =
+                version.map(|version| git(&dir, &["checkout", "--quiet", version, "--"]));
+            if let Some(Err(e)) = checkout {
+                let _ = std::fs::remove_dir_all(&dir);
+                return Err(e);
+            }
+            installed.commit = Some(git(&dir, &["rev-parse", "HEAD"])?);
+        }
+        manifest.sources.push(installed.clone());
+        self.save_manifest(&manifest)?;
+        Ok(installed)
+    }
+
+    /// Remove an installed source, deleting its clone for git sources
+    pub fn remove(&self, name: &str) -> Result<InstalledSource> {
+        let mut manifest = self.load_manifest()?;
+        let index = manifest
+            .sources
+            .iter()
+            .position(|s| s.name == name)
+            .ok_or_else(|| anyhow!("No source named '{}' is installed", name))?;
+        let removed = manifest.sources.remove(index);
+        if matches!(removed.location, SourceLocation::Git { .. }) {
+            let dir = self.source_dir(name);
+            if dir.exists() {
+                std::fs::remove_dir_all(dir)?;
+            }
+        }
+        self.save_manifest(&manifest)?;
+        Ok(removed)
+    }
+
+    /// Upgrade git sources to their latest commit, or only `name` when given. Giving a
+    /// `version` pins the source to it instead; pinned sources are otherwise left alone unless
+    /// named. Returns the sources that changed.
+    pub fn upgrade(
+        &self,
+        name: Option<&str>,
+        version: Option<&str>,
+    ) -> Result<Vec<InstalledSource>> {
+        if let Some(version) = version {
+            validate_git_arg("version", version)?;
+        }
+        let mut manifest = self.load_manifest()?;
+        if let Some(name) = name {
+            if !manifest.sources.iter().any(|s| s.name == name) {
+                bail!("No source named '{}' is installed", name);
+            }
+        } else if version.is_some() {
+            bail!("Name the source to pin to a version");
+        }
+
+        let mut upgraded = Vec::new();
+        for source in manifest.sources.iter_mut() {
+            if name.is_some_and(|name| name != source.name) {
+                continue;
+            }
+            if !matches!(source.location, SourceLocation::Git { .. }) {
+                if name.is_some() && version.is_some() {
+                    bail!("Only git sources can be pinned to a version");
+                }
+                continue;
+            }
+            if name.is_none() && source.pinned.is_some() {
+                continue;
+            }
+            let dir = self.source_dir(&source.name);
+            git(&dir, &["fetch", "--quiet", "--tags", "origin"])?;
+            match version {
+                Some(version) => {
+                    git(&dir, &["checkout", "--quiet", version, "--"])?;
+                    source.pinned = Some(version.to_string());
+                }
+                None => {
+                    // Unpin and follow the default branch again
+                    let head = git(&dir, &["rev-parse", "--abbrev-ref", "origin/HEAD"])?;
+                    git(&dir, &["checkout", "--quiet", "--detach", &head, "--"])?;
+                    source.pinned = None;
+                }
+            }
+            let commit = git(&dir, &["rev-parse", "HEAD"])?;
+            if source.commit.as_deref() != Some(commit.as_str()) || version.is_some() {
+                source.commit = Some(commit);
+                source.updated_at = Utc::now();
+                upgraded.push(source.clone());
+            }
+        }
+        self.save_manifest(&manifest)?;
+        Ok(upgraded)
+    }
+
+    /// Every recipe in the installed sources and the configured recipe directories, in that
+    /// order
+    pub fn recipes(&self) -> Result<Vec<LibraryRecipe>> {
+        let mut recipes = Vec::new();
+        for source in self.sources()? {
+            let dir = match &source.location {
+                SourceLocation::Git { .. } => self.source_dir(&source.name),
+                SourceLocation::Directory { path } => path.clone(),
+            };
+            let version = source.version();
+            recipes.extend(
+                recipe_files(&dir)
+                    .iter()
+                    .filter_map(|path| index_recipe(path, &source.name, version.clone())),
+            );
+        }
+        for dir in &self.recipe_dirs {
+            let source = dir.to_string_lossy();
+            recipes.extend(
+                recipe_files(dir)
+                    .iter()
+                    .filter_map(|path| index_recipe(path, &source, None)),
+            );
+        }
+        Ok(recipes)
+    }
+
+    /// Recipes matching every word of `query` in their name, title, description, activities
+    /// or parameters, best matches first
+    pub fn search(&self, query: &str) -> Result<Vec<LibraryRecipe>> {
+        let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+        let mut matches: Vec<(usize, LibraryRecipe)> = self
+            .recipes()?
+            .into_iter()
+            .filter_map(|recipe| search_score(&recipe, &terms).map(|score| (score, recipe)))
+            .collect();
+        // Stable, so equal matches keep the library's order
+        matches.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+        Ok(matches.into_iter().map(|(_, recipe)| recipe).collect())
+    }
+
+    /// The recipe run by `name`, taking the first in library order when several share it
+    pub fn find(&self, name: &str) -> Result<Option<LibraryRecipe>> {
+        Ok(self
+            .recipes()?
+            .into_iter()
+            .find(|recipe| recipe.name == name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_recipe(dir: &Path, file: &str, title: &str, description: &str) {
+        let path = dir.join(file);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            path,
+            format!(
+                r#"
+version: 1.2.0
+title: {}
+description: {}
+instructions: Work on {{{{ repo }}}}
+activities:
+  - Triage open issues
+parameters:
+  - key: repo
+    input_type: string
+    requirement: required
+    description: the repository
+"#,
+                title, description
+            ),
+        )
+        .unwrap();
+    }
+
+    fn setup() -> (TempDir, TempDir, RecipeLibrary) {
+        let library_dir = tempfile::tempdir().unwrap();
+        let recipes_dir = tempfile::tempdir().unwrap();
+        write_recipe(
+            recipes_dir.path(),
+            "review.yaml",
+            "Code Review",
+            "Reviews a pull request",
+        );
+        write_recipe(
+            recipes_dir.path(),
+            "nested/triage.yaml",
+            "Triage",
+            "Labels new issues",
+        );
+        std::fs::write(recipes_dir.path().join("review.test.yaml"), "tests: []\n").unwrap();
+        std::fs::write(recipes_dir.path().join("notes.yaml"), "- not a recipe\n").unwrap();
+        let library = RecipeLibrary::new(library_dir.path(), Vec::new());
+        (library_dir, recipes_dir, library)
+    }
+
+    #[test]
+    fn test_index_and_search_directories() {
+        let (_library_dir, recipes_dir, library) = setup();
+        library
+            .install(recipes_dir.path().to_str().unwrap(), Some("team"), None)
+            .unwrap();
+
+        let recipes = library.recipes().unwrap();
+        let names: Vec<_> = recipes.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["triage", "review"]);
+        assert_eq!(recipes[1].title, "Code Review");
+        assert_eq!(recipes[1].version, "1.2.0");
+        assert_eq!(recipes[1].parameters, ["repo"]);
+        assert_eq!(recipes[1].source, "team");
+
+        let found: Vec<_> = library
+            .search("issues")
+            .unwrap()
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        // Triage mentions issues in its description as well as its activities
+        assert_eq!(found, ["triage", "review"]);
+        assert_eq!(library.search("review pull").unwrap().len(), 1);
+        assert!(library.search("deploy").unwrap().is_empty());
+        assert_eq!(
+            library.find("triage").unwrap().unwrap().path,
+            recipes_dir
+                .path()
+                .canonicalize()
+                .unwrap()
+                .join("nested/triage.yaml")
+        );
+
+        assert!(library
+            .install(recipes_dir.path().to_str().unwrap(), Some("team"), None)
+            .is_err());
+        library.remove("team").unwrap();
+        assert!(library.recipes().unwrap().is_empty());
+        assert!(recipes_dir.path().join("review.yaml").exists());
+    }
+
+    #[test]
+    fn test_configured_recipe_dirs_are_indexed() {
+        let (library_dir, recipes_dir, _) = setup();
+        let library =
+            RecipeLibrary::new(library_dir.path(), vec![recipes_dir.path().to_path_buf()]);
+        let recipes = library.recipes().unwrap();
+        assert_eq!(recipes.len(), 2);
+        assert_eq!(recipes[0].source, recipes_dir.path().to_string_lossy());
+    }
+
+    fn commit_all(repo: &Path, message: &str) {
+        git(repo, &["add", "-A"]).unwrap();
+        git(
+            repo,
+            &[
+                "-c",
+                "user.name=test",
+                "-c",
+                "user.email=test@example.com",
+                "commit",
+                "--quiet",
+                "-m",
+                message,
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_install_pin_and_upgrade_git_sources() {
+        let library_dir = tempfile::tempdir().unwrap();
+        let repo = tempfile::tempdir().unwrap();
+        git(repo.path(), &["init", "--quiet"]).unwrap();
+        write_recipe(repo.path(), "review.yaml", "Review", "Reviews code");
+        commit_all(repo.path(), "first");
+        git(repo.path(), &["tag", "v1"]).unwrap();
+        write_recipe(repo.path(), "triage.yaml", "Triage", "Labels issues");
+        commit_all(repo.path(), "second");
+
+        let library = RecipeLibrary::new(library_dir.path(), Vec::new());
+        let url = format!("file://{}", repo.path().display());
+        let installed = library.install(&url, Some("shared"), Some("v1")).unwrap();
+        assert_eq!(installed.pinned.as_deref(), Some("v1"));
+        assert_eq!(library.recipes().unwrap().len(), 1);
+        assert!(library.recipes().unwrap()[0]
+            .source_version
+            .as_deref()
+            .unwrap()
+            .starts_with("v1 ("));
+
+        // Pinned sources stay put unless they're named
+        assert!(library.upgrade(None, None).unwrap().is_empty());
+        let upgraded = library.upgrade(Some("shared"), None).unwrap();
+        assert_eq!(upgraded.len(), 1);
+        assert_eq!(upgraded[0].pinned, None);
+        assert_eq!(library.recipes().unwrap().len(), 2);
+
+        library.remove("shared").unwrap();
+        assert!(!library_dir.path().join(SOURCES_DIR).join("shared").exists());
+    }
+
+    #[test]
+    fn test_source_names() {
+        assert_eq!(
+            default_source_name("https://github.com/block/goose-recipes.git"),
+            "goose-recipes"
+        );
+        assert_eq!(default_source_name("/home/me/recipes/"), "recipes");
+        assert_eq!(
+            git_url("block/goose-recipes").as_deref(),
+            Some("https://github.com/block/goose-recipes.git")
+        );
+        assert_eq!(git_url("recipes"), None);
+        assert!(validate_source_name("../escape").is_err());
+    }
+
+    #[test]
+    fn test_option_like_sources_are_rejected() {
+        let library_dir = tempfile::tempdir().unwrap();
+        let library = RecipeLibrary::new(library_dir.path(), Vec::new());
+        let err = library
+            .install("--upload-pack=touch /tmp/x;.git", Some("evil"), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("can't start with '-'"));
+        let err = library
+            .install("block/goose-recipes", None, Some("--orphan=x"))
+            .unwrap_err();
+        assert!(err.to_string().contains("can't start with '-'"));
+        assert!(library.upgrade(Some("evil"), Some("-b")).is_err());
+    }
+}
@@ -0,0 +1,631 @@
+//! A recipe's lock file records the exact versions of the extensions it runs and the provider
+//! and model it ran with, so a recipe behaves the same after `latest` moves on or the default
+//! model changes. Running a locked recipe pins what the recipe leaves floating to the locked
+//! versions, and reports drift where the recipe and its lock file disagree.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, bail, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::{Recipe, Settings};
+use crate::agents::ExtensionConfig;
+
+pub const LOCK_FILE_EXTENSION: &str = "lock";
+
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+const PYPI_REGISTRY: &str = "https://pypi.org/pypi";
+
+/// The lock file of the recipe at `recipe_path`, `review.lock` for `review.yaml`
+pub fn lock_file_path(recipe_path: &Path) -> PathBuf {
+    recipe_path.with_extension(LOCK_FILE_EXTENSION)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RecipeLock {
+    /// The goose version the recipe was locked with, which builtin extensions are part of
+    pub goose_version: String,
+    pub locked_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default)]
+    pub extensions: Vec<LockedExtension>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LockedExtension {
+    pub name: String,
+    #[serde(flatten)]
+    pub source: LockedSource,
+}
+
+/// What an extension was resolved to
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LockedSource {
+    /// An npm package run with `npx`
+    Npm { package: String, version: String },
+    /// A Python package run with `uvx`
+    Pypi { package: String, version: String },
+    /// Any other command, by the hash of the executable
+    Binary {
+        command: String,
+        path: PathBuf,
+        sha256: String,
+    },
+    /// An extension reached over HTTP, whose version the server controls
+    Remote { uri: String },
+    /// Part of goose itself, locked by `goose_version`
+    Builtin,
+}
+
+/// Looks up the exact versions that package specs resolve to
+#[async_trait]
+pub trait PackageRegistry: Send + Sync {
+    /// The version an npm dist-tag such as `latest`, or an exact version, refers to
+    async fn npm_version(&self, package: &str, spec: &str) -> Result<String>;
+    /// The latest version of a Python package
+    async fn pypi_version(&self, package: &str) -> Result<String>;
+}
+
+/// The public npm and PyPI registries
+#[derive(Default)]
+pub struct PublicRegistries {
+    client: reqwest::Client,
+}
+
+impl PublicRegistries {
+    async fn get_json(&self, url: &str) -> Result<Value> {
+        let response = self
+            .client
+            .get(url)
+            .header("Accept", "application/json")
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            bail!("{} returned {}", url, response.status());
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl PackageRegistry for PublicRegistries {
+    async fn npm_version(&self, package: &str, spec: &str) -> Result<String> {
+        let url = format!("{}/{}", NPM_REGISTRY, package.replace('/', "%2F"));
+        let document = self.get_json(&url).await?;
+        if let Some(version) = document["dist-tags"][spec].as_str() {
+            return Ok(version.to_string());
+        }
+        if document["versions"].get(spec).is_some() {
+            return Ok(spec.to_string());
+        }
+        bail!(
+            "'{}' isn't a dist-tag or published version of {}; use an exact version or a tag such as latest",
+            spec,
+            package
+        )
+    }
+
+    async fn pypi_version(&self, package: &str) -> Result<String> {
+        let url = format!("{}/{}/json", PYPI_REGISTRY, package);
+        let document = self.get_json(&url).await?;
+        document["info"]["version"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| anyhow!("PyPI has no version for {}", package))
+    }
+}
+
+fn is_exact_version(spec: &str) -> bool {
+    let core = spec.split(['-', '+']).next().unwrap_or(spec);
+    core.split('.').count() >= 2
+        && core
+            .split('.')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()))
+}
+
+// The package argument of an `npx` or `uvx` command: the value of `--from` for uvx when
+// given, otherwise the first argument that isn't a flag
+fn package_arg(cmd: &str, args: &[String]) -> Option<usize> {
+    if runner(cmd) == Some(Runner::Uvx) {
+        if let Some(from) = args.iter().position(|arg| arg == "--from") {
+            return (from + 1 < args.len()).then_some(from + 1);
+        }
+    }
+    args.iter().position(|arg| !arg.starts_with('-'))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Runner {
+    Npx,
+    Uvx,
+}
+
+fn runner(cmd: &str) -> Option<Runner> {
+    let name = Path::new(cmd).file_stem()?.to_string_lossy();
+    match name.as_ref() {
+        "npx" => Some(Runner::Npx),
+        "uvx" => Some(Runner::Uvx),
+        _ => None,
+    }
+}
+
+// `@scope/name@1.2.0` into the package and its version spec, if any
+fn split_npm_spec(spec: &str) -> (&str, Option<&str>) {
+    match spec.get(1..).and_then(|rest| rest.rfind('@')) {
+        Some(at) => (&spec[..at + 1], Some(&spec[at + 2..])),
+        None => (spec, None),
+    }
+}
+
+// `name==1.2.0` or `name@1.2.0` into the package and its version, if any
+fn split_pypi_spec(spec: &str) -> (&str, Option<&str>) {
+    if let Some((package, version)) = spec.split_once("==") {
+        return (package, Some(version));
+    }
+    match spec.split_once('@') {
+        Some((package, version)) => (package, Some(version)),
+        None => (spec, None),
+    }
+}
+
+fn find_executable(cmd: &str) -> Option<PathBuf> {
+    let path = Path::new(cmd);
+    if path.components().count() > 1 {
+        return path.is_file().then(|| path.to_path_buf());
+    }
+    let extensions: &[&str] = if cfg!(windows) {
+        &["exe", "cmd", "bat"]
+    } else {
+        &[]
+    };
+    std::env::split_paths(&std::env::var_os("PATH")?).find_map(|dir| {
+        let candidate = dir.join(cmd);
+        if candidate.is_file() {
+            return Some(candidate);
+        }
+        extensions
+            .iter()
+            .map(|ext| candidate.with_extension(ext))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+fn file_sha256(path: &Path) -> Result<String> {
+    let content =
+        std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+    Ok(format!("{:x}", Sha256::digest(&content)))
+}
+
+async fn lock_extension(
+    extension: &ExtensionConfig,
+    registry: &dyn PackageRegistry,
+) -> Result<Option<LockedSource>> {
+    let source = match extension {
+        ExtensionConfig::Stdio { cmd, args, .. } => {
+            let package = package_arg(cmd, args).map(|index| args[index].as_str());
+            match (runner(cmd), package) {
+                (Some(Runner::Npx), Some(spec)) => {
+                    let (package, version) = split_npm_spec(spec);
+                    let version = match version {
+                        Some(version) if is_exact_version(version) => version.to_string(),
+                        version => {
+                            registry
+                                .npm_version(package, version.unwrap_or("latest"))
+                                .await?
+                        }
+                    };
+                    LockedSource::Npm {
+                        package: package.to_string(),
+                        version,
+                    }
+                }
+                (Some(Runner::Uvx), Some(spec)) => {
+                    let (package, version) = split_pypi_spec(spec);
+                    let version = match version {
+                        Some(version) if is_exact_version(version) => version.to_string(),
+                        Some(version) if version != "latest" => bail!(
+                            "Can't lock {} to '{}', use an exact version",
+                            package,
+                            version
+                        ),
+                        _ => registry.pypi_version(package).await?,
+                    };
+                    LockedSource::Pypi {
+                        package: package.to_string(),
+                        version,
+                    }
+                }
+                _ => {
+                    let path = find_executable(cmd)
+                        .ok_or_else(|| anyhow!("Command '{}' wasn't found", cmd))?;
+                    LockedSource::Binary {
+                        command: cmd.clone(),
+                        sha256: file_sha256(&path)?,
+                        path,
+                    }
+                }
+            }
+        }
+        ExtensionConfig::Sse { uri, .. } | ExtensionConfig::StreamableHttp { uri, .. } => {
+            LockedSource::Remote { uri: uri.clone() }
+        }
+        ExtensionConfig::Builtin { .. } => LockedSource::Builtin,
+        // Frontend tools come from the app running the recipe
+        ExtensionConfig::Frontend { .. } => return Ok(None),
+    };
+    Ok(Some(source))
+}
+
+/// Resolve the recipe's extensions to exact versions and record the provider and model it
+/// runs with, which are the recipe's settings or else the configured defaults
+pub async fn lock_recipe(
+    recipe: &Recipe,
+    provider: Option<String>,
+    model: Option<String>,
+    registry: &dyn PackageRegistry,
+) -> Result<RecipeLock> {
+    let mut extensions = Vec::new();
+    for extension in recipe.extensions.iter().flatten() {
+        let source = lock_extension(extension, registry)
+            .await
+            .map_err(|e| anyhow!("Failed to lock extension '{}': {}", extension.name(), e))?;
+        if let Some(source) = source {
+            extensions.push(LockedExtension {
+                name: extension.name(),
+                source,
+            });
+        }
+    }
+    let settings = recipe.settings.as_ref();
+    Ok(RecipeLock {
+        goose_version: env!("CARGO_PKG_VERSION").to_string(),
+        locked_at: Utc::now(),
+        provider: settings.and_then(|s| s.goose_provider.clone()).or(provider),
+        model: settings.and_then(|s| s.goose_model.clone()).or(model),
+        extensions,
+    })
+}
+
+pub fn read_lock_file(path: &Path) -> Result<RecipeLock> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read lock file {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&content)
+        .map_err(|e| anyhow!("Invalid lock file {}: {}", path.display(), e))
+}
+
+pub fn write_lock_file(path: &Path, lock: &RecipeLock) -> Result<()> {
+    std::fs::write(path, serde_yaml::to_string(lock)?)?;
+    Ok(())
+}
+
+// Pin a package the recipe leaves floating to its locked version, or report how the recipe
+// disagrees with the lock
+fn pin_package(
+    name: &str,
+    cmd: &str,
+    args: &mut [String],
+    locked: &LockedSource,
+) -> Option<String> {
+    let index = package_arg(cmd, args)?;
+    let uses_from = index > 0 && args[index - 1] == "--from";
+    let (package, version, pinned) = match (runner(cmd)?, locked) {
+        (
+            Runner::Npx,
+            LockedSource::Npm {
+                package: locked_package,
+                version: locked_version,
+            },
+        ) => {
+            let (package, version) = split_npm_spec(&args[index]);
+            (
+                package.to_string(),
+                version.map(str::to_string),
+                (
+                    locked_package,
+                    locked_version,
+                    format!("{}@{}", package, locked_version),
+                ),
+            )
+        }
+        (
+            Runner::Uvx,
+            LockedSource::Pypi {
+                package: locked_package,
+                version: locked_version,
+            },
+        ) => {
+            let (package, version) = split_pypi_spec(&args[index]);
+            let pinned = if uses_from {
+                format!("{}=={}", package, locked_version)
+            } else {
+                format!("{}@{}", package, locked_version)
+            };
+            (
+                package.to_string(),
+                version.map(str::to_string),
+                (locked_package, locked_version, pinned),
+            )
+        }
+        _ => return Some(format!("Extension '{}' changed how it runs", name)),
+    };
+    let (locked_package, locked_version, pinned) = pinned;
+    if &package != locked_package {
+        return Some(format!(
+            "Extension '{}' runs {} but {} is locked",
+            name, package, locked_package
+        ));
+    }
+    match version {
+        Some(version) if is_exact_version(&version) && &version != locked_version => Some(format!(
+            "Extension '{}' asks for {} {} but {} is locked",
+            name, package, version, locked_version
+        )),
+        _ => {
+            args[index] = pinned;
+            None
+        }
+    }
+}
+
+fn check_extension(extension: &mut ExtensionConfig, locked: &LockedSource) -> Option<String> {
+    let name = extension.name();
+    match (extension, locked) {
+        (ExtensionConfig::Stdio { cmd, args, .. }, LockedSource::Npm { .. })
+        | (ExtensionConfig::Stdio { cmd, args, .. }, LockedSource::Pypi { .. }) => {
+            pin_package(&name, cmd, args, locked)
+        }
+        (ExtensionConfig::Stdio { cmd, .. }, LockedSource::Binary { sha256, .. }) => {
+            match find_executable(cmd).map(|path| file_sha256(&path)) {
+                Some(Ok(current)) if &current == sha256 => None,
+                Some(Ok(_)) => Some(format!(
+                    "The '{}' executable of extension '{}' changed since it was locked",
+                    cmd, name
+                )),
+                _ => Some(format!(
+                    "The '{}' executable of extension '{}' wasn't found",
+                    cmd, name
+                )),
+            }
+        }
+        (
+            ExtensionConfig::Sse { uri, .. } | ExtensionConfig::StreamableHttp { uri, .. },
+            LockedSource::Remote { uri: locked_uri },
+        ) => (uri != locked_uri).then(|| {
+            format!(
+                "Extension '{}' connects to {} but {} is locked",
+                name, uri, locked_uri
+            )
+        }),
+        (ExtensionConfig::Builtin { .. }, LockedSource::Builtin) => None,
+        _ => Some(format!(
+            "Extension '{}' changed type since it was locked",
+            name
+        )),
+    }
+}
+
+/// Pin the recipe's floating package versions, provider and model to those in the lock, and
+/// return every way the recipe has drifted from it
+pub fn apply_lock(recipe: &mut Recipe, lock: &RecipeLock) -> Vec<String> {
+    let mut drift = Vec::new();
+    let mut seen = Vec::new();
+    for extension in recipe.extensions.iter_mut().flatten() {
+        if matches!(extension, ExtensionConfig::Frontend { .. }) {
+            continue;
+        }
+        let name = extension.name();
+        match lock.extensions.iter().find(|locked| locked.name == name) {
+            Some(locked) => drift.extend(check_extension(extension, &locked.source)),
+            None => drift.push(format!("Extension '{}' isn't in the lock file", name)),
+        }
+        seen.push(name);
+    }
+    for locked in &lock.extensions {
+        if !seen.contains(&locked.name) {
+            drift.push(format!(
+                "Extension '{}' is locked but the recipe no longer uses it",
+                locked.name
+            ));
+        }
+    }
+
+    let settings = recipe.settings.get_or_insert(Settings {
+        goose_provider: None,
+        goose_model: None,
+        temperature: None,
+    });
+    for (label, current, locked) in [
+        ("provider", &mut settings.goose_provider, &lock.provider),
+        ("model", &mut settings.goose_model, &lock.model),
+    ] {
+        match (current.as_ref(), locked) {
+            (Some(current), Some(locked)) if current != locked => drift.push(format!(
+                "The recipe uses {} {} but {} is locked",
+                label, current, locked
+            )),
+            (None, Some(locked)) => *current = Some(locked.clone()),
+            _ => {}
+        }
+    }
+    drift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FakeRegistry;
+
+    #[async_trait]
+    impl PackageRegistry for FakeRegistry {
+        async fn npm_version(&self, package: &str, spec: &str) -> Result<String> {
+            match (package, spec) {
+                ("@modelcontextprotocol/server-github", "latest") => Ok("2025.4.8".to_string()),
+                _ => bail!("unknown package {}@{}", package, spec),
+            }
+        }
+
+        async fn pypi_version(&self, package: &str) -> Result<String> {
+            match package {
+                "mcp-server-fetch" => Ok("2025.4.7".to_string()),
+                _ => bail!("unknown package {}", package),
+            }
+        }
+    }
+
+    fn recipe() -> Recipe {
+        Recipe::from_content(
+            r#"
+title: Review
+description: Reviews code
+instructions: Review the code
+settings:
+  goose_model: gpt-4o
+extensions:
+  - type: stdio
+    name: github
+    cmd: npx
+    args: ["-y", "@modelcontextprotocol/server-github@latest"]
+    timeout: 300
+  - type: stdio
+    name: fetch
+    cmd: uvx
+    args: ["mcp-server-fetch"]
+    timeout: 300
+  - type: stdio
+    name: pinned
+    cmd: npx
+    args: ["-y", "some-server@1.0.2"]
+    timeout: 300
+  - type: builtin
+    name: developer
+  - type: streamable_http
+    name: docs
+    uri: https://example.com/mcp
+"#,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_lock_recipe() {
+        let lock = lock_recipe(
+            &recipe(),
+            Some("openai".to_string()),
+            Some("default-model".to_string()),
+            &FakeRegistry,
+        )
+        .await
+        .unwrap();
+        assert_eq!(lock.provider.as_deref(), Some("openai"));
+        assert_eq!(lock.model.as_deref(), Some("gpt-4o"));
+        let sources: Vec<_> = lock.extensions.iter().map(|e| e.source.clone()).collect();
+        assert_eq!(
+            sources,
+            [
+                LockedSource::Npm {
+                    package: "@modelcontextprotocol/server-github".to_string(),
+                    version: "2025.4.8".to_string()
+                },
+                LockedSource::Pypi {
+                    package: "mcp-server-fetch".to_string(),
+                    version: "2025.4.7".to_string()
+                },
+                LockedSource::Npm {
+                    package: "some-server".to_string(),
+                    version: "1.0.2".to_string()
+                },
+                LockedSource::Builtin,
+                LockedSource::Remote {
+                    uri: "https://example.com/mcp".to_string()
+                },
+            ]
+        );
+
+        let yaml = serde_yaml::to_string(&lock).unwrap();
+        assert_eq!(serde_yaml::from_str::<RecipeLock>(&yaml).unwrap(), lock);
+    }
+
+    #[tokio::test]
+    async fn test_apply_lock_pins_and_reports_drift() {
+        let lock = lock_recipe(&recipe(), Some("openai".to_string()), None, &FakeRegistry)
+            .await
+            .unwrap();
+
+        let mut pinned = recipe();
+        pinned.settings.as_mut().unwrap().goose_model = None;
+        assert!(apply_lock(&mut pinned, &lock).is_empty());
+        let args: Vec<_> = pinned
+            .extensions
+            .unwrap()
+            .into_iter()
+            .filter_map(|e| match e {
+                ExtensionConfig::Stdio { args, .. } => Some(args.last().unwrap().clone()),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "@modelcontextprotocol/server-github@2025.4.8",
+                "mcp-server-fetch@2025.4.7",
+                "some-server@1.0.2"
+            ]
+        );
+        let settings = pinned.settings.unwrap();
+        assert_eq!(settings.goose_provider.as_deref(), Some("openai"));
+        assert_eq!(settings.goose_model.as_deref(), Some("gpt-4o"));
+
+        let mut drifted = recipe();
+        drifted.settings.as_mut().unwrap().goose_model = Some("gpt-5".to_string());
+        let extensions = drifted.extensions.as_mut().unwrap();
+        extensions.remove(1);
+        if let ExtensionConfig::Stdio { args, .. } = &mut extensions[1] {
+            args[1] = "some-server@1.1.0".to_string();
+        }
+        let drift = apply_lock(&mut drifted, &lock);
+        assert_eq!(
+            drift,
+            [
+                "Extension 'pinned' asks for some-server 1.1.0 but 1.0.2 is locked",
+                "Extension 'fetch' is locked but the recipe no longer uses it",
+                "The recipe uses model gpt-5 but gpt-4o is locked",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_package_specs() {
+        assert_eq!(
+            split_npm_spec("@scope/pkg@1.2.0"),
+            ("@scope/pkg", Some("1.2.0"))
+        );
+        assert_eq!(split_npm_spec("@scope/pkg"), ("@scope/pkg", None));
+        assert_eq!(split_npm_spec("pkg@next"), ("pkg", Some("next")));
+        assert_eq!(split_pypi_spec("pkg==0.3.1"), ("pkg", Some("0.3.1")));
+        assert_eq!(split_pypi_spec("pkg@latest"), ("pkg", Some("latest")));
+        assert!(is_exact_version("1.2.0"));
+        assert!(is_exact_version("2025.4.8-beta.1"));
+        assert!(!is_exact_version("^1.2.0"));
+        assert!(!is_exact_version("latest"));
+
+        let args = vec![
+            "--from".to_string(),
+            "pkg==1.0".to_string(),
+            "cmd".to_string(),
+        ];
+        assert_eq!(package_arg("uvx", &args), Some(1));
+        assert_eq!(
+            package_arg("/usr/local/bin/npx", &["-y".to_string(), "pkg".to_string()]),
+            Some(1)
+        );
+    }
+}
@@ -6,6 +6,14 @@ use std::fmt;
 use crate::agents::extension::ExtensionConfig;
 use serde::de::Deserializer;
 use serde::{Deserialize, Serialize};
+use testing::RecipeTest;
+
+pub mod extends;
+pub mod library;
+pub mod lockfile;
+pub mod parameters;
+pub mod template;
+pub mod testing;
 
 fn default_version() -> String {
     "1.0.0".to_string()
@@ -30,6 +38,9 @@ fn default_version() -> String {
 /// * `author` - Information about the Recipe's creator and metadata
 /// * `parameters` - Additional parameters for the Recipe
 /// * `response` - Response configuration including JSON schema validation
+/// * `sub_recipes` - Recipes the model can run as tools
+/// * `steps` - Prompts and sub-recipes to run in order as a workflow
+/// * `tests` - Tests that `goose recipe test` runs against the Recipe
 ///
 /// # Example
 ///
@@ -59,6 +70,8 @@ fn default_version() -> String {
 ///     parameters: None,
 ///     response: None,
 ///     sub_recipes: None,
+///     steps: None,
+///     tests: None,
 /// };
 ///
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -102,6 +115,12 @@ pub struct Recipe {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sub_recipes: Option<Vec<SubRecipe>>, // sub-recipes for the recipe
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<RecipeStep>>, // steps to run in order, making the recipe a workflow
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tests: Option<Vec<RecipeTest>>, // tests for the recipe
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -139,6 +158,61 @@ pub struct SubRecipe {
     pub values: Option<HashMap<String, String>>,
 }
 
+/// One step of a workflow recipe: either a prompt or a sub-recipe, run in its own session.
+///
+/// `prompt`, `instructions` and `values` can refer to the results of earlier steps as
+/// `{{ steps.<id>.output }}`, and `when` is an expression over them such as
+/// `steps.triage.output.severity == "high"`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipeStep {
+    pub id: String, // referred to by later steps as steps.<id>
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>, // the prompt to run, for prompt steps
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipe: Option<String>, // path of the recipe to run, for sub-recipe steps
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_value_map_as_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub values: Option<HashMap<String, String>>, // parameters for the sub-recipe
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>, // replaces the recipe's instructions for this step
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<Vec<ExtensionConfig>>, // replaces the recipe's extensions
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub settings: Option<Settings>, // replaces the recipe's settings
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<Response>, // makes the step's output structured JSON
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub when: Option<String>, // the step is skipped unless this expression is true
+
+    #[serde(default)]
+    pub on_failure: StepFailurePolicy,
+
+    #[serde(default)]
+    pub retries: u32, // attempts to make after the first one fails
+}
+
+/// What a workflow does when a step still fails after its retries
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepFailurePolicy {
+    /// Stop the workflow, skipping the remaining steps
+    #[default]
+    Stop,
+    /// Record the failure and go on with the next step
+    Continue,
+}
+
 fn deserialize_value_map_as_string<'de, D>(
     deserializer: D,
 ) -> Result<Option<HashMap<String, String>>, D::Error>
@@ -190,6 +264,11 @@ pub enum RecipeParameterInputType {
     Boolean,
     Date,
     File,
+    Select,      // one of `options`
+    Multiselect, // any of `options`, given as a JSON list or separated by commas
+    Json,
+    Secret, // read from the keyring when not given, and kept out of the conversation
+    Path,
 }
 
 impl fmt::Display for RecipeParameterInputType {
@@ -210,6 +289,21 @@ pub struct RecipeParameter {
     pub description: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub default: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub options: Option<Vec<String>>, // choices for select and multiselect parameters
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>, // regex that text values must match
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>, // smallest number, or fewest characters or choices
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>, // largest number, or most characters or choices
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required_if: Option<String>, // expression over other parameters, such as `mode == "advanced"`
 }
 
 /// Builder for creating Recipe instances
@@ -230,6 +324,7 @@ pub struct RecipeBuilder {
     parameters: Option<Vec<RecipeParameter>>,
     response: Option<Response>,
     sub_recipes: Option<Vec<SubRecipe>>,
+    steps: Option<Vec<RecipeStep>>,
 }
 
 impl Recipe {
@@ -262,6 +357,7 @@ impl Recipe {
             parameters: None,
             response: None,
             sub_recipes: None,
+            steps: None,
         }
     }
     pub fn from_content(content: &str) -> Result<Self> {
@@ -360,6 +456,12 @@ impl RecipeBuilder {
         self
     }
 
+    /// Sets the workflow steps for the Recipe
+    pub fn steps(mut self, steps: Vec<RecipeStep>) -> Self {
+        self.steps = Some(steps);
+        self
+    }
+
     /// Builds the Recipe instance
     ///
     /// Returns an error if any required fields are missing
@@ -367,8 +469,8 @@ impl RecipeBuilder {
         let title = self.title.ok_or("Title is required")?;
         let description = self.description.ok_or("Description is required")?;
 
-        if self.instructions.is_none() && self.prompt.is_none() {
-            return Err("At least one of 'prompt' or 'instructions' is required");
+        if self.instructions.is_none() && self.prompt.is_none() && self.steps.is_none() {
+            return Err("At least one of 'prompt', 'instructions' or 'steps' is required");
         }
 
         Ok(Recipe {
@@ -385,6 +487,8 @@ impl RecipeBuilder {
             parameters: self.parameters,
             response: self.response,
             sub_recipes: self.sub_recipes,
+            steps: self.steps,
+            tests: None,
         })
     }
 }
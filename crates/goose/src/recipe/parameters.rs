@@ -0,0 +1,561 @@
+//! Parameter values reach a recipe as text, from the command line, a sub-recipe call or the
+//! desktop app. This checks them against their parameter's type and constraints and turns
+//! them into typed values for the recipe template.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail, Result};
+use regex::Regex;
+use serde_json::{json, Map, Value};
+
+use super::{Recipe, RecipeParameter, RecipeParameterInputType};
+use crate::config::Config;
+
+/// Shown instead of a secret parameter's value
+pub const SECRET_MASK: &str = "********";
+
+pub fn is_secret(parameter: &RecipeParameter) -> bool {
+    matches!(parameter.input_type, RecipeParameterInputType::Secret)
+}
+
+/// Recipe secrets are kept apart from goose's own, so a recipe can't ask for a provider key
+pub const RECIPE_SECRET_PREFIX: &str = "recipe_secret_";
+
+/// The name a secret parameter is stored under
+pub fn recipe_secret_key(parameter: &RecipeParameter) -> String {
+    format!("{}{}", RECIPE_SECRET_PREFIX, parameter.key)
+}
+
+/// The value of a secret parameter stored in the keyring as `recipe_secret_<key>`, or set in
+/// the environment as `RECIPE_SECRET_<KEY>`
+pub fn secret_from_keyring(parameter: &RecipeParameter) -> Option<String> {
+    Config::global()
+        .get_secret::<String>(&recipe_secret_key(parameter))
+        .ok()
+}
+
+/// Fill in defaults for parameters that weren't given and secrets from the keyring, failing if
+/// any without a default are missing
+pub fn apply_parameter_defaults(
+    parameters: &[RecipeParameter],
+    values: &mut HashMap<String, String>,
+) -> Result<()> {
+    let mut missing = Vec::new();
+    for parameter in parameters {
+        if values.contains_key(&parameter.key) {
+            continue;
+        }
+        let value = parameter.default.clone().or_else(|| {
+            if is_secret(parameter) {
+                secret_from_keyring(parameter)
+            } else {
+                None
+            }
+        });
+        match value {
+            Some(value) => {
+                values.insert(parameter.key.clone(), value);
+            }
+            // Whether it's needed depends on the other values, which are checked once typed
+            None if parameter.required_if.is_some() => {}
+            None => missing.push(parameter.key.clone()),
+        }
+    }
+    if !missing.is_empty() {
+        bail!(
+            "Missing values for recipe parameters: {}",
+            missing.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Check the values given for a recipe's parameters and convert them to the parameters'
+/// types, reading secrets that weren't given from the keyring
+///
+/// Values for keys that aren't parameters, such as `recipe_dir`, are kept as text. Optional
+/// parameters whose `required_if` doesn't hold are set to an empty string when not given.
+pub fn typed_parameter_values(
+    parameters: &[RecipeParameter],
+    values: &HashMap<String, String>,
+) -> Result<HashMap<String, Value>> {
+    typed_values(parameters, values, secret_from_keyring)
+}
+
+fn typed_values<F>(
+    parameters: &[RecipeParameter],
+    values: &HashMap<String, String>,
+    secret: F,
+) -> Result<HashMap<String, Value>>
+where
+    F: Fn(&RecipeParameter) -> Option<String>,
+{
+    let mut typed: HashMap<String, Value> = values
+        .iter()
+        .map(|(key, value)| (key.clone(), Value::String(value.clone())))
+        .collect();
+    let mut errors = Vec::new();
+    for parameter in parameters {
+        let raw = values.get(&parameter.key).cloned().or_else(|| {
+            if is_secret(parameter) {
+                secret(parameter)
+            } else {
+                None
+            }
+        });
+        match raw.map(|raw| typed_value(parameter, &raw)) {
+            Some(Ok(value)) => {
+                typed.insert(parameter.key.clone(), value);
+            }
+            Some(Err(e)) => errors.push(e),
+            None => {}
+        }
+    }
+
+    // Conditions can refer to any parameter, so they're checked once every value is typed
+    for parameter in parameters {
+        let Some(condition) = &parameter.required_if else {
+            continue;
+        };
+        if typed.contains_key(&parameter.key) {
+            continue;
+        }
+        match condition_holds(condition, &typed) {
+            Ok(true) => errors.push(format!(
+                "Parameter '{}' is required when {}",
+                parameter.key, condition
+            )),
+            Ok(false) => {
+                typed.insert(parameter.key.clone(), Value::String(String::new()));
+            }
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+
+    if !errors.is_empty() {
+        bail!("Invalid recipe parameter values:\n{}", errors.join("\n"));
+    }
+    Ok(typed)
+}
+
+fn condition_holds(condition: &str, values: &HashMap<String, Value>) -> Result<bool> {
+    let env = minijinja::Environment::new();
+    let expression = env
+        .compile_expression(condition)
+        .map_err(|e| anyhow!("Invalid required_if condition '{}': {}", condition, e))?;
+    let value = expression
+        .eval(minijinja::Value::from_serialize(values))
+        .map_err(|e| anyhow!("Failed to evaluate required_if condition {}", e))?;
+    Ok(value.is_true())
+}
+
+// Error messages name the parameter but never repeat its value, which may be a secret
+fn typed_value(parameter: &RecipeParameter, raw: &str) -> Result<Value, String> {
+    let key = &parameter.key;
+    let value = match parameter.input_type {
+        RecipeParameterInputType::String
+        | RecipeParameterInputType::Date
+        | RecipeParameterInputType::File
+        | RecipeParameterInputType::Secret => Value::String(raw.to_string()),
+        RecipeParameterInputType::Path => Value::String(expand_home(raw)),
+        RecipeParameterInputType::Number => {
+            raw.trim()
+                .parse::<f64>()
+                .ok()
+                .and_then(number_value)
+                .ok_or_else(|| format!("Parameter '{}' must be a number", key))?
+        }
+        RecipeParameterInputType::Boolean => match raw.trim().to_lowercase().as_str() {
+            "true" | "yes" | "1" => Value::Bool(true),
+            "false" | "no" | "0" => Value::Bool(false),
+            _ => return Err(format!("Parameter '{}' must be true or false", key)),
+        },
+        RecipeParameterInputType::Select => {
+            check_option(parameter, raw)?;
+            Value::String(raw.to_string())
+        }
+        RecipeParameterInputType::Multiselect => {
+            let choices = parse_choices(raw)
+                .ok_or_else(|| format!("Parameter '{}' must be a list of choices", key))?;
+            for choice in &choices {
+                check_option(parameter, choice)?;
+                check_pattern(parameter, choice)?;
+            }
+            Value::Array(choices.into_iter().map(Value::String).collect())
+        }
+        RecipeParameterInputType::Json => serde_json::from_str(raw)
+            .map_err(|e| format!("Parameter '{}' must be valid JSON: {}", key, e))?,
+    };
+    if !matches!(
+        parameter.input_type,
+        RecipeParameterInputType::Multiselect | RecipeParameterInputType::Json
+    ) {
+        check_pattern(parameter, raw)?;
+    }
+    check_bounds(parameter, &value)?;
+    Ok(value)
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().into_owned(),
+        _ if path == "~" => dirs::home_dir()
+            .map(|home| home.to_string_lossy().into_owned())
+            .unwrap_or_else(|| path.to_string()),
+        _ => path.to_string(),
+    }
+}
+
+// Whole numbers stay integers so templates render `3` rather than `3.0`
+fn number_value(number: f64) -> Option<Value> {
+    if number.fract() == 0.0 && number.abs() < i64::MAX as f64 {
+        Some(Value::from(number as i64))
+    } else {
+        serde_json::Number::from_f64(number).map(Value::Number)
+    }
+}
+
+fn parse_choices(raw: &str) -> Option<Vec<String>> {
+    if raw.trim_start().starts_with('[') {
+        return serde_json::from_str(raw).ok();
+    }
+    Some(
+        raw.split(',')
+            .map(str::trim)
+            .filter(|choice| !choice.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+fn check_option(parameter: &RecipeParameter, choice: &str) -> Result<(), String> {
+    match &parameter.options {
+        Some(options) if options.iter().any(|option| option == choice) => Ok(()),
+        Some(options) => Err(format!(
+            "Parameter '{}' must be one of: {}",
+            parameter.key,
+            options.join(", ")
+        )),
+        None => Err(format!(
+            "Parameter '{}' is a {} but has no options",
+            parameter.key, parameter.input_type
+        )),
+    }
+}
+
+fn check_pattern(parameter: &RecipeParameter, text: &str) -> Result<(), String> {
+    let Some(pattern) = &parameter.pattern else {
+        return Ok(());
+    };
+    let regex = Regex::new(&format!("^(?:{})$", pattern)).map_err(|e| {
+        format!(
+            "Parameter '{}' has an invalid pattern: {}",
+            parameter.key, e
+        )
+    })?;
+    if regex.is_match(text) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Parameter '{}' must match the pattern {}",
+            parameter.key, pattern
+        ))
+    }
+}
+
+fn check_bounds(parameter: &RecipeParameter, value: &Value) -> Result<(), String> {
+    let (size, unit) = match value {
+        Value::Number(number) => (number.as_f64(), ""),
+        Value::String(text) => (Some(text.chars().count() as f64), " characters"),
+        Value::Array(items) => (Some(items.len() as f64), " choices"),
+        _ => (None, ""),
+    };
+    let Some(size) = size else {
+        return Ok(());
+    };
+    if let Some(min) = parameter.min.filter(|min| size < *min) {
+        return Err(format!(
+            "Parameter '{}' must be at least {}{}",
+            parameter.key, min, unit
+        ));
+    }
+    if let Some(max) = parameter.max.filter(|max| size > *max) {
+        return Err(format!(
+            "Parameter '{}' must be at most {}{}",
+            parameter.key, max, unit
+        ));
+    }
+    Ok(())
+}
+
+/// The JSON schema for a parameter's value, or `None` for secrets, which are never passed
+/// through the conversation
+pub fn parameter_schema(parameter: &RecipeParameter) -> Option<Value> {
+    let mut schema = Map::new();
+    let text_bounds = ("minLength", "maxLength", true);
+    let bounds = match parameter.input_type {
+        RecipeParameterInputType::Secret => return None,
+        RecipeParameterInputType::String
+        | RecipeParameterInputType::File
+        | RecipeParameterInputType::Path => {
+            schema.insert("type".to_string(), json!("string"));
+            Some(text_bounds)
+        }
+        RecipeParameterInputType::Date => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("format".to_string(), json!("date"));
+            Some(text_bounds)
+        }
+        RecipeParameterInputType::Number => {
+            schema.insert("type".to_string(), json!("number"));
+            Some(("minimum", "maximum", false))
+        }
+        RecipeParameterInputType::Boolean => {
+            schema.insert("type".to_string(), json!("boolean"));
+            None
+        }
+        RecipeParameterInputType::Select => {
+            schema.insert("type".to_string(), json!("string"));
+            schema.insert("enum".to_string(), json!(parameter.options));
+            None
+        }
+        RecipeParameterInputType::Multiselect => {
+            schema.insert("type".to_string(), json!("array"));
+            let mut items = json!({"type": "string", "enum": parameter.options});
+            if let Some(pattern) = &parameter.pattern {
+                items["pattern"] = json!(format!("^(?:{})$", pattern));
+            }
+            schema.insert("items".to_string(), items);
+            Some(("minItems", "maxItems", true))
+        }
+        // Any JSON value
+        RecipeParameterInputType::Json => None,
+    };
+    schema.insert("description".to_string(), json!(parameter.description));
+    if let Some(pattern) = &parameter.pattern {
+        if !matches!(
+            parameter.input_type,
+            RecipeParameterInputType::Multiselect | RecipeParameterInputType::Json
+        ) {
+            schema.insert("pattern".to_string(), json!(format!("^(?:{})$", pattern)));
+        }
+    }
+    if let Some((min_key, max_key, whole)) = bounds {
+        // Lengths and item counts must be whole numbers in JSON schema
+        if let Some(min) = parameter.min {
+            let min = if whole { min.ceil() } else { min };
+            schema.insert(min_key.to_string(), number_value(min).into());
+        }
+        if let Some(max) = parameter.max {
+            let max = if whole { max.floor() } else { max };
+            schema.insert(max_key.to_string(), number_value(max).into());
+        }
+    }
+    Some(Value::Object(schema))
+}
+
+/// Fail if a secret parameter's value ended up in the parts of a rendered recipe that become
+/// part of the conversation, and so are written into sessions
+pub fn check_secrets_not_in_conversation(
+    recipe: &Recipe,
+    values: &HashMap<String, Value>,
+) -> Result<()> {
+    let mut conversation: Vec<&str> = Vec::new();
+    conversation.extend(recipe.instructions.as_deref());
+    conversation.extend(recipe.prompt.as_deref());
+    conversation.extend(recipe.context.iter().flatten().map(String::as_str));
+    conversation.extend(recipe.activities.iter().flatten().map(String::as_str));
+    for step in recipe.steps.iter().flatten() {
+        conversation.extend(step.instructions.as_deref());
+        conversation.extend(step.prompt.as_deref());
+    }
+
+    for parameter in recipe.parameters.iter().flatten().filter(|p| is_secret(p)) {
+        let Some(Value::String(secret)) = values.get(&parameter.key) else {
+            continue;
+        };
+        if !secret.is_empty()
+            && conversation
+                .iter()
+                .any(|text| text.contains(secret.as_str()))
+        {
+            bail!(
+                "Secret parameter '{}' can't be used in instructions, prompts, context or activities, since they are saved in sessions",
+                parameter.key
+            );
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parameters() -> Vec<RecipeParameter> {
+        serde_yaml::from_str(
+            r#"
+- key: count
+  input_type: number
+  requirement: required
+  description: how many
+  min: 1
+  max: 10
+- key: verbose
+  input_type: boolean
+  requirement: required
+  description: more output
+- key: mode
+  input_type: select
+  requirement: required
+  description: how to run
+  options: [basic, advanced]
+- key: labels
+  input_type: multiselect
+  requirement: required
+  description: labels to apply
+  options: [bug, docs, ci]
+  max: 2
+- key: filter
+  input_type: json
+  requirement: required
+  description: what to include
+- key: ticket
+  input_type: string
+  requirement: required
+  description: the ticket
+  pattern: "[A-Z]+-[0-9]+"
+- key: token
+  input_type: secret
+  requirement: required
+  description: API token
+- key: depth
+  input_type: number
+  requirement: optional
+  description: how deep to look
+  required_if: mode == "advanced"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn values(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn keyring(parameter: &RecipeParameter) -> Option<String> {
+        (parameter.key == "token").then(|| "s3cret".to_string())
+    }
+
+    const VALID: &[(&str, &str)] = &[
+        ("count", "3"),
+        ("verbose", "yes"),
+        ("mode", "basic"),
+        ("labels", "bug, ci"),
+        ("filter", r#"{"state": "open"}"#),
+        ("ticket", "GOOSE-12"),
+        ("recipe_dir", "/recipes"),
+    ];
+
+    #[test]
+    fn test_typed_values() {
+        let typed = typed_values(&parameters(), &values(VALID), keyring).unwrap();
+        assert_eq!(typed["count"], json!(3));
+        assert_eq!(typed["verbose"], json!(true));
+        assert_eq!(typed["labels"], json!(["bug", "ci"]));
+        assert_eq!(typed["filter"], json!({"state": "open"}));
+        assert_eq!(typed["token"], json!("s3cret"));
+        assert_eq!(typed["depth"], json!(""));
+        assert_eq!(typed["recipe_dir"], json!("/recipes"));
+    }
+
+    #[test]
+    fn test_typed_values_enforce_constraints() {
+        let mut invalid = values(VALID);
+        invalid.extend(values(&[
+            ("count", "11"),
+            ("verbose", "maybe"),
+            ("labels", r#"["bug", "docs", "ci"]"#),
+            ("filter", "{"),
+            ("ticket", "goose-12"),
+        ]));
+        let err = typed_values(&parameters(), &invalid, keyring)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("'count' must be at most 10"), "{}", err);
+        assert!(err.contains("'verbose' must be true or false"), "{}", err);
+        assert!(
+            err.contains("'labels' must be at most 2 choices"),
+            "{}",
+            err
+        );
+        assert!(err.contains("'filter' must be valid JSON"), "{}", err);
+        assert!(err.contains("'ticket' must match the pattern"), "{}", err);
+
+        let mut invalid = values(VALID);
+        invalid.insert("mode".to_string(), "expert".to_string());
+        let err = typed_values(&parameters(), &invalid, keyring).unwrap_err();
+        assert!(err.to_string().contains("one of: basic, advanced"));
+    }
+
+    #[test]
+    fn test_required_if() {
+        let mut advanced = values(VALID);
+        advanced.insert("mode".to_string(), "advanced".to_string());
+        let err = typed_values(&parameters(), &advanced, keyring).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("'depth' is required when mode == \"advanced\""));
+
+        advanced.insert("depth".to_string(), "2.5".to_string());
+        let typed = typed_values(&parameters(), &advanced, keyring).unwrap();
+        assert_eq!(typed["depth"], json!(2.5));
+    }
+
+    #[test]
+    fn test_parameter_schema() {
+        let parameters = parameters();
+        assert_eq!(
+            parameter_schema(&parameters[0]).unwrap(),
+            json!({"type": "number", "description": "how many", "minimum": 1, "maximum": 10})
+        );
+        assert_eq!(
+            parameter_schema(&parameters[3]).unwrap(),
+            json!({
+                "type": "array",
+                "items": {"type": "string", "enum": ["bug", "docs", "ci"]},
+                "description": "labels to apply",
+                "maxItems": 2
+            })
+        );
+        assert_eq!(
+            parameter_schema(&parameters[5]).unwrap()["pattern"],
+            json!("^(?:[A-Z]+-[0-9]+)$")
+        );
+        assert!(parameter_schema(&parameters[6]).is_none());
+    }
+
+    #[test]
+    fn test_secrets_stay_out_of_the_conversation() {
+        let recipe = Recipe::from_content(
+            r#"
+title: Deploy
+description: Deploys
+instructions: Deploy with token s3cret
+parameters:
+  - key: token
+    input_type: secret
+    requirement: required
+    description: API token
+"#,
+        )
+        .unwrap();
+        let values = HashMap::from([("token".to_string(), json!("s3cret"))]);
+        let err = check_secrets_not_in_conversation(&recipe, &values).unwrap_err();
+        assert!(err.to_string().contains("'token'"));
+        assert!(!err.to_string().contains("s3cret"));
+    }
+}
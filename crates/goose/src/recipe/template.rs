@@ -0,0 +1,375 @@
+//! Recipe files are minijinja templates over their parameters. This renders them without
+//! prompting, for recipes run by the agent itself such as sub-recipes.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use minijinja::value::{Object, Value};
+use minijinja::{Environment, UndefinedBehavior};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::extends::{load_base_recipe_file, resolve_extends};
+use super::parameters::{
+    apply_parameter_defaults, check_secrets_not_in_conversation, typed_parameter_values,
+};
+use super::{Recipe, RecipeParameter};
+
+/// Parameter holding the directory of the recipe file, available to every recipe
+pub const BUILT_IN_RECIPE_DIR_PARAM: &str = "recipe_dir";
+
+/// Variable holding the results of earlier workflow steps
+pub const STEPS_TEMPLATE_VAR: &str = "steps";
+
+const CURRENT_TEMPLATE_NAME: &str = "current_template";
+
+/// A bare reference to a step result, such as `{{ steps.triage.output }}`
+static STEP_REFERENCE_IN_TEXT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*steps((?:\.\w+)+)\s*\}\}").unwrap());
+
+static STEP_REFERENCE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(&format!("^{}$", STEP_REFERENCE_IN_TEXT.as_str())).unwrap());
+
+/// Any expression or statement tag
+static TEMPLATE_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)\{\{.*?\}\}|\{%.*?%\}").unwrap());
+
+static STEPS_WORD: Lazy<Regex> = Lazy::new(|| Regex::new(r"\bsteps\b").unwrap());
+
+/// Stands in for `steps` while a recipe file is loaded, rendering references such as
+/// `{{ steps.triage.output }}` back as themselves so they can be filled in once the step
+/// has run
+#[derive(Debug)]
+struct DeferredStepOutputs(String);
+
+impl Object for DeferredStepOutputs {
+    fn get_value(self: &Arc<Self>, key: &Value) -> Option<Value> {
+        Some(Value::from_object(DeferredStepOutputs(format!(
+            "{}.{}",
+            self.0, key
+        ))))
+    }
+
+    fn render(self: &Arc<Self>, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{{{{ {} }}}}", self.0)
+    }
+}
+
+/// The value to give `steps` when loading a recipe file, leaving step outputs in place
+pub fn deferred_step_outputs() -> Value {
+    Value::from_object(DeferredStepOutputs(STEPS_TEMPLATE_VAR.to_string()))
+}
+
+/// Check that recipe content only uses step results as bare references. Step results are
+/// unknown while the recipe is loaded, so a filter or condition over them would be evaluated
+/// against the placeholder and give the wrong text.
+pub fn check_step_references(content: &str) -> Result<()> {
+    for tag in TEMPLATE_TAG.find_iter(content) {
+        let tag = tag.as_str();
+        if STEPS_WORD.is_match(tag) && !STEP_REFERENCE.is_match(tag) {
+            return Err(anyhow!(
+                "Step results can only be used as a bare reference such as {{{{ steps.triage.output }}}}, not in '{}'",
+                tag
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn template_env(
+    content: &str,
+    recipe_dir: &Path,
+    undefined_behavior: UndefinedBehavior,
+) -> Result<Environment<'static>> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(undefined_behavior);
+    let recipe_dir = recipe_dir.to_path_buf();
+    env.set_loader(
+        move |name| match std::fs::read_to_string(recipe_dir.join(name)) {
+            Ok(content) => match check_step_references(&content) {
+                Ok(()) => Ok(Some(content)),
+                Err(e) => Err(minijinja::Error::new(
+                    minijinja::ErrorKind::InvalidOperation,
+                    e.to_string(),
+                )),
+            },
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(minijinja::Error::new(
+                minijinja::ErrorKind::InvalidOperation,
+                "could not read template",
+            )
+            .with_source(e)),
+        },
+    );
+    env.add_global(STEPS_TEMPLATE_VAR, deferred_step_outputs());
+    check_step_references(content)?;
+    env.add_template_owned(CURRENT_TEMPLATE_NAME, content.to_string())?;
+    Ok(env)
+}
+
+/// Fill in defaults and secrets for parameters that weren't given, and add the recipe directory
+fn apply_recipe_defaults(
+    params: &HashMap<String, String>,
+    parameters: &[RecipeParameter],
+    recipe_dir: &Path,
+) -> Result<HashMap<String, String>> {
+    let mut values = params.clone();
+    values.insert(
+        BUILT_IN_RECIPE_DIR_PARAM.to_string(),
+        recipe_dir.to_string_lossy().into_owned(),
+    );
+    apply_parameter_defaults(parameters, &mut values)?;
+    Ok(values)
+}
+
+/// Parse recipe content without parameter values, leaving out whatever depends on them, to
+/// read what the recipe declares such as its title and parameters
+pub fn parse_recipe_template(content: &str, recipe_dir: &Path) -> Result<Recipe> {
+    let env = template_env(content, recipe_dir, UndefinedBehavior::Lenient)?;
+    let rendered = env
+        .get_template(CURRENT_TEMPLATE_NAME)?
+        .render(())
+        .map_err(|e| anyhow!("Failed to parse the recipe {}", e))?;
+    Recipe::from_content(&rendered)
+}
+
+/// Render recipe content with the given parameter values; templates it includes are
+/// resolved relative to `recipe_dir`
+pub fn render_recipe_content(
+    content: &str,
+    recipe_dir: &Path,
+    params: &HashMap<String, String>,
+) -> Result<Recipe> {
+    // Render leniently first, only to find out which parameters the recipe declares
+    let parameters = parse_recipe_template(content, recipe_dir)?
+        .parameters
+        .unwrap_or_default();
+    let values = apply_recipe_defaults(params, &parameters, recipe_dir)?;
+    let values = typed_parameter_values(&parameters, &values)?;
+
+    let env = template_env(content, recipe_dir, UndefinedBehavior::Strict)?;
+    let rendered = env
+        .get_template(CURRENT_TEMPLATE_NAME)?
+        .render(&values)
+        .map_err(|e| anyhow!("Failed to render the recipe {}", e))?;
+    let recipe = Recipe::from_content(&rendered)?;
+    check_secrets_not_in_conversation(&recipe, &values)?;
+    Ok(recipe)
+}
+
+fn step_env() -> Environment<'static> {
+    let mut env = Environment::new();
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    env
+}
+
+/// Fill in references to the results of earlier workflow steps, given as a map from step ID
+/// to its `status`, `output` and `error`. Only bare references are replaced; nothing else in
+/// `text` is evaluated, as it already holds parameter values that may carry template syntax.
+pub fn render_step_template(text: &str, steps: &serde_json::Value) -> Result<String> {
+    let mut rendered = String::with_capacity(text.len());
+    let mut last = 0;
+    for reference in STEP_REFERENCE_IN_TEXT.captures_iter(text) {
+        let whole = reference.get(0).unwrap();
+        let path = &reference[1];
+        let value = path
+            .split('.')
+            .skip(1)
+            .try_fold(steps, |value, key| value.get(key))
+            .ok_or_else(|| anyhow!("Failed to render step template: steps{} is undefined", path))?;
+        rendered.push_str(&text[last..whole.start()]);
+        match value {
+            serde_json::Value::String(value) => rendered.push_str(value),
+            value => rendered.push_str(&value.to_string()),
+        }
+        last = whole.end();
+    }
+    rendered.push_str(&text[last..]);
+    Ok(rendered)
+}
+
+/// Evaluate a step's `when` expression against the results of earlier steps
+pub fn step_condition_holds(expression: &str, steps: &serde_json::Value) -> Result<bool> {
+    let env = step_env();
+    let expression = env
+        .compile_expression(expression)
+        .map_err(|e| anyhow!("Invalid step condition '{}': {}", expression, e))?;
+    let value = expression
+        .eval(minijinja::context! { steps => Value::from_serialize(steps) })
+        .map_err(|e| anyhow!("Failed to evaluate step condition {}", e))?;
+    Ok(value.is_true())
+}
+
+/// Read and render the recipe file at `path`, along with any recipes it extends
+pub fn render_recipe_file(path: &Path, params: &HashMap<String, String>) -> Result<Recipe> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| anyhow!("Failed to read recipe file {}: {}", path.display(), e))?;
+    let recipe_dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let content = resolve_extends(&content, recipe_dir, load_base_recipe_file)?;
+    render_recipe_content(&content, recipe_dir, params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECIPE: &str = r#"
+title: Greeter
+description: Greets someone
+instructions: Greet {{ name }} in {{ language }}
+parameters:
+  - key: name
+    input_type: string
+    requirement: required
+    description: who to greet
+  - key: language
+    input_type: string
+    requirement: optional
+    description: the language to use
+    default: English
+"#;
+
+    #[test]
+    fn test_render_recipe_content_applies_defaults() {
+        let params = HashMap::from([("name".to_string(), "Ada".to_string())]);
+        let recipe = render_recipe_content(RECIPE, Path::new("/tmp"), &params).unwrap();
+        assert_eq!(recipe.instructions.as_deref(), Some("Greet Ada in English"));
+    }
+
+    #[test]
+    fn test_render_recipe_content_defers_step_outputs() {
+        let content = r#"
+title: Pipeline
+description: Two steps
+steps:
+  - id: triage
+    prompt: Triage the issue
+  - id: fix
+    prompt: "Fix it: {{ steps.triage.output }}"
+"#;
+        let recipe = render_recipe_content(content, Path::new("/tmp"), &HashMap::new()).unwrap();
+        let steps = recipe.steps.unwrap();
+        assert_eq!(
+            steps[1].prompt.as_deref(),
+            Some("Fix it: {{ steps.triage.output }}")
+        );
+    }
+
+    #[test]
+    fn test_step_outputs_must_be_bare_references() {
+        for prompt in [
+            "{{ steps.triage.output | upper }}",
+            "{% if steps.triage.status == 'success' %}Fix it{% endif %}",
+        ] {
+            let content = format!(
+                "title: Pipeline\ndescription: Two steps\nsteps:\n  - id: fix\n    prompt: \"{}\"\n",
+                prompt
+            );
+            let err =
+                render_recipe_content(&content, Path::new("/tmp"), &HashMap::new()).unwrap_err();
+            assert!(err.to_string().contains("bare reference"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_step_templates_leave_parameter_syntax_alone() {
+        let content = r#"
+title: Pipeline
+description: Two steps
+steps:
+  - id: triage
+    prompt: "Triage {{ payload }}"
+  - id: fix
+    prompt: "Fix {{ payload }}: {{ steps.triage.output }}"
+parameters:
+  - key: payload
+    input_type: string
+    requirement: required
+    description: the webhook payload
+"#;
+        let params = HashMap::from([(
+            "payload".to_string(),
+            "{% for x in range(9) %}{{ x }}{% endfor %}".to_string(),
+        )]);
+        let recipe = render_recipe_content(content, Path::new("/tmp"), &params).unwrap();
+        let prompt = recipe.steps.unwrap()[1].prompt.clone().unwrap();
+        let steps = serde_json::json!({"triage": {"output": "done"}});
+        assert_eq!(
+            render_step_template(&prompt, &steps).unwrap(),
+            "Fix {% for x in range(9) %}{{ x }}{% endfor %}: done"
+        );
+    }
+
+    #[test]
+    fn test_step_templates() {
+        let steps = serde_json::json!({
+            "triage": {"status": "success", "output": {"severity": "high"}},
+        });
+        assert_eq!(
+            render_step_template("Severity: {{ steps.triage.output.severity }}", &steps).unwrap(),
+            "Severity: high"
+        );
+        assert!(step_condition_holds("steps.triage.output.severity == 'high'", &steps).unwrap());
+        assert!(!step_condition_holds("steps.triage.status == 'failed'", &steps).unwrap());
+        assert!(step_condition_holds("steps.missing.output", &steps).is_err());
+    }
+
+    #[test]
+    fn test_render_recipe_content_types_values() {
+        let content = r#"
+title: Triage
+description: Triages issues
+instructions: "{% if verbose %}Explain {% for label in labels %}{{ label }} {% endfor %}{% endif %}up to {{ limit }}"
+parameters:
+  - key: verbose
+    input_type: boolean
+    requirement: required
+    description: explain the labels
+  - key: labels
+    input_type: multiselect
+    requirement: required
+    description: labels to triage
+    options: [bug, docs]
+  - key: limit
+    input_type: number
+    requirement: required
+    description: how many issues
+    max: 50
+"#;
+        let params = HashMap::from([
+            ("verbose".to_string(), "false".to_string()),
+            ("labels".to_string(), "bug,docs".to_string()),
+            ("limit".to_string(), "9".to_string()),
+        ]);
+        let recipe = render_recipe_content(content, Path::new("/tmp"), &params).unwrap();
+        assert_eq!(recipe.instructions.as_deref(), Some("up to 9"));
+
+        let params = HashMap::from([
+            ("verbose".to_string(), "true".to_string()),
+            ("labels".to_string(), "bug,docs".to_string()),
+            ("limit".to_string(), "9".to_string()),
+        ]);
+        let recipe = render_recipe_content(content, Path::new("/tmp"), &params).unwrap();
+        assert_eq!(
+            recipe.instructions.as_deref(),
+            Some("Explain bug docs up to 9")
+        );
+
+        let params = HashMap::from([
+            ("verbose".to_string(), "true".to_string()),
+            ("labels".to_string(), "bug,ci".to_string()),
+            ("limit".to_string(), "90".to_string()),
+        ]);
+        let err = render_recipe_content(content, Path::new("/tmp"), &params).unwrap_err();
+        assert!(err.to_string().contains("'labels' must be one of"));
+        assert!(err.to_string().contains("'limit' must be at most 50"));
+    }
+
+    #[test]
+    fn test_render_recipe_content_requires_values() {
+        let err = render_recipe_content(RECIPE, Path::new("/tmp"), &HashMap::new()).unwrap_err();
+        assert!(err.to_string().contains("name"));
+    }
+}
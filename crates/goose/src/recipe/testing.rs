@@ -0,0 +1,342 @@
+//! Tests for recipes: the parameter values to run a recipe with, the provider to answer it,
+//! and what the run must produce.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::Result;
+use mcp_core::tool::ToolCall;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::deserialize_value_map_as_string;
+use crate::message::{Message, MessageContent};
+use crate::providers::base::Provider;
+use crate::providers::scripted::ScriptedProvider;
+use crate::session::{self, Identifier};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RecipeTest {
+    pub name: String,
+
+    #[serde(
+        default,
+        deserialize_with = "deserialize_value_map_as_string",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub values: Option<HashMap<String, String>>,
+
+    /// Answers to give instead of the configured provider's
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<TestProvider>,
+
+    #[serde(default)]
+    pub expect: TestExpectations,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
+pub enum TestProvider {
+    /// Messages to answer with, one per request
+    Script(Vec<ScriptedResponse>),
+    /// A recorded session whose assistant messages are answered with, by session ID or as a
+    /// `.jsonl` file relative to the recipe
+    Replay(String),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ScriptedResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ScriptedToolCall>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ScriptedToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct TestExpectations {
+    /// Checks on the final output, which must also match the recipe's `response` schema
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<OutputExpectation>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools_called: Vec<String>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tools_not_called: Vec<String>,
+
+    /// Checks on files in the working directory the test ran in
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileExpectation>,
+}
+
+/// A check on the value at `path` in the output, a jq-style path such as `.items[0].name`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OutputExpectation {
+    #[serde(default = "root_path")]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub length: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exists: Option<bool>,
+}
+
+fn root_path() -> String {
+    ".".to_string()
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FileExpectation {
+    pub path: String,
+    #[serde(default = "default_exists")]
+    pub exists: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contains: Option<String>,
+}
+
+fn default_exists() -> bool {
+    true
+}
+
+impl TestProvider {
+    pub fn provider(&self, recipe_dir: &Path) -> Result<Arc<dyn Provider>> {
+        let provider = match self {
+            TestProvider::Script(responses) => {
+                ScriptedProvider::new(responses.iter().map(scripted_message).collect())
+            }
+            TestProvider::Replay(session) if session.ends_with(".jsonl") => {
+                ScriptedProvider::from_session_file(&recipe_dir.join(session))?
+            }
+            TestProvider::Replay(session) => ScriptedProvider::from_session_file(
+                &session::get_path(Identifier::Name(session.clone()))?,
+            )?,
+        };
+        Ok(Arc::new(provider))
+    }
+}
+
+fn scripted_message(response: &ScriptedResponse) -> Message {
+    let mut message = Message::assistant();
+    if let Some(text) = &response.text {
+        message = message.with_text(text);
+    }
+    for (index, call) in response.tool_calls.iter().enumerate() {
+        message = message.with_tool_request(
+            format!("scripted_{}", index),
+            Ok(ToolCall::new(&call.name, call.arguments.clone())),
+        );
+    }
+    message
+}
+
+// Follow a jq-style path of `.field` and `[index]` segments
+fn select<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut current = value;
+    for segment in path.split('.').filter(|s| !s.is_empty()) {
+        let (field, indexes) = match segment.find('[') {
+            Some(start) => segment.split_at(start),
+            None => (segment, ""),
+        };
+        if !field.is_empty() {
+            current = current.get(field)?;
+        }
+        for index in indexes.split('[').filter(|s| !s.is_empty()) {
+            let index: usize = index.strip_suffix(']')?.parse().ok()?;
+            current = current.get(index)?;
+        }
+    }
+    Some(current)
+}
+
+fn check_output(expectation: &OutputExpectation, output: &Value) -> Vec<String> {
+    let path = &expectation.path;
+    let Some(value) = select(output, path) else {
+        return match expectation.exists {
+            Some(false) => vec![],
+            _ => vec![format!("Output has nothing at {}", path)],
+        };
+    };
+    let mut failures = Vec::new();
+    if expectation.exists == Some(false) {
+        failures.push(format!("Output has a value at {}", path));
+    }
+    if let Some(expected) = &expectation.equals {
+        if value != expected {
+            failures.push(format!(
+                "Output at {} is {}, expected {}",
+                path, value, expected
+            ));
+        }
+    }
+    if let Some(expected) = &expectation.contains {
+        let contained = match value {
+            Value::String(text) => text.contains(expected.as_str()),
+            Value::Array(items) => items
+                .iter()
+                .any(|item| item.as_str() == Some(expected.as_str())),
+            _ => false,
+        };
+        if !contained {
+            failures.push(format!("Output at {} doesn't contain '{}'", path, expected));
+        }
+    }
+    if let Some(expected) = expectation.length {
+        let length = match value {
+            Value::String(text) => Some(text.chars().count()),
+            Value::Array(items) => Some(items.len()),
+            Value::Object(fields) => Some(fields.len()),
+            _ => None,
+        };
+        if length != Some(expected) {
+            failures.push(format!(
+                "Output at {} doesn't have length {}",
+                path, expected
+            ));
+        }
+    }
+    failures
+}
+
+/// The names of the tools called in a conversation, in order
+pub fn tools_called(messages: &[Message]) -> Vec<String> {
+    messages
+        .iter()
+        .flat_map(|message| &message.content)
+        .filter_map(|content| match content {
+            MessageContent::ToolRequest(request) => request
+                .tool_call
+                .as_ref()
+                .ok()
+                .map(|call| call.name.clone()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Every way the run failed the test's expectations, checking the output against `schema`
+/// when the recipe has one and files relative to `working_dir`
+pub fn check_expectations(
+    expectations: &TestExpectations,
+    schema: Option<&Value>,
+    output: &Value,
+    messages: &[Message],
+    working_dir: &Path,
+) -> Vec<String> {
+    let mut failures = Vec::new();
+
+    if let Some(schema) = schema {
+        match jsonschema::validator_for(schema) {
+            Ok(validator) => failures.extend(
+                validator
+                    .iter_errors(output)
+                    .map(|e| format!("Output doesn't match the response schema: {}", e)),
+            ),
+            Err(e) => failures.push(format!("Invalid response schema: {}", e)),
+        }
+    }
+    for expectation in &expectations.output {
+        failures.extend(check_output(expectation, output));
+    }
+
+    let called = tools_called(messages);
+    for tool in &expectations.tools_called {
+        if !called.contains(tool) {
+            failures.push(format!("Tool {} wasn't called", tool));
+        }
+    }
+    for tool in &expectations.tools_not_called {
+        if called.contains(tool) {
+            failures.push(format!("Tool {} was called", tool));
+        }
+    }
+
+    for file in &expectations.files {
+        let path = working_dir.join(&file.path);
+        match (path.exists(), file.exists) {
+            (false, true) => failures.push(format!("File {} wasn't created", file.path)),
+            (true, false) => failures.push(format!("File {} exists", file.path)),
+            (true, true) => {
+                if let Some(expected) = &file.contains {
+                    let content = std::fs::read_to_string(&path).unwrap_or_default();
+                    if !content.contains(expected.as_str()) {
+                        failures.push(format!("File {} doesn't contain '{}'", file.path, expected));
+                    }
+                }
+            }
+            (false, false) => {}
+        }
+    }
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_select() {
+        let value = json!({"items": [{"name": "a"}, {"name": "b", "tags": ["x"]}]});
+        assert_eq!(select(&value, "."), Some(&value));
+        assert_eq!(select(&value, ".items[1].name"), Some(&json!("b")));
+        assert_eq!(select(&value, ".items[1].tags[0]"), Some(&json!("x")));
+        assert_eq!(select(&value, ".items[2]"), None);
+        assert_eq!(select(&value, ".missing"), None);
+    }
+
+    #[test]
+    fn test_check_expectations() {
+        let expectations: TestExpectations = serde_yaml::from_str(
+            r#"
+output:
+  - path: .severity
+    equals: high
+  - path: .summary
+    contains: crash
+  - path: .steps
+    length: 2
+  - path: .owner
+    exists: false
+tools_called: [developer__shell]
+tools_not_called: [developer__text_editor]
+files:
+  - path: report.md
+    contains: crash
+  - path: scratch.txt
+    exists: false
+"#,
+        )
+        .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.md"), "The crash is fixed").unwrap();
+        let messages = vec![scripted_message(&ScriptedResponse {
+            text: None,
+            tool_calls: vec![ScriptedToolCall {
+                name: "developer__shell".to_string(),
+                arguments: json!({"command": "ls"}),
+            }],
+        })];
+        let schema = json!({"type": "object", "required": ["severity"]});
+
+        let output = json!({"severity": "high", "summary": "a crash", "steps": [1, 2]});
+        let failures =
+            check_expectations(&expectations, Some(&schema), &output, &messages, dir.path());
+        assert!(failures.is_empty(), "{:?}", failures);
+
+        let output = json!({"summary": "fine", "steps": [], "owner": "me"});
+        let failures = check_expectations(&expectations, Some(&schema), &output, &[], dir.path());
+        assert_eq!(failures.len(), 6, "{:?}", failures);
+    }
+}
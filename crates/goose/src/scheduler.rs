@@ -2,30 +2,57 @@ use std::collections::HashMap;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use etcetera::{choose_app_strategy, AppStrategy};
+use futures::future::{BoxFuture, FutureExt};
 use serde::{Deserialize, Serialize};
-use tokio::sync::Mutex;
+use serde_json::Value;
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
 use tokio_cron_scheduler::{job::JobId, Job, JobScheduler as TokioJobScheduler};
 
+use crate::agents::sub_recipe_execution_tool::lib::{run_workflow, TaskContext};
 use crate::agents::AgentEvent;
 use crate::agents::{Agent, SessionConfig};
 use crate::config::{self, Config};
 use crate::message::Message;
+use crate::metrics;
 use crate::providers::base::Provider as GooseProvider; // Alias to avoid conflict in test section
 use crate::providers::create;
+use crate::providers::pricing::get_model_pricing;
+use crate::recipe::template::render_recipe_file;
 use crate::recipe::Recipe;
+use crate::scheduler_notifiers::{notify_all, JobNotifier, RunSummary};
+use crate::scheduler_runs::{
+    append_run, missed_runs, read_runs, remove_history, run_history_dir, CatchUpPolicy, JobRun,
+    OverlapPolicy, RunPolicy, RunReason, RunStatus, MAX_CATCH_UP_RUNS,
+};
 use crate::scheduler_trait::SchedulerTrait;
+use crate::scheduler_triggers::{
+    verify_webhook_signature, watch_files, webhook_params, FileWatch, JobTrigger,
+    CHANGED_PATH_PARAM,
+};
 use crate::session;
 use crate::session::storage::SessionMetadata;
 
-// Track running tasks with their abort handles
-type RunningTasksMap = HashMap<String, tokio::task::AbortHandle>;
-type JobsMap = HashMap<String, (JobId, ScheduledJob)>;
+// Track running tasks with their abort handles, keyed by run, oldest run first
+type RunningTasksMap = HashMap<String, Vec<(String, tokio::task::AbortHandle)>>;
+type TriggerTasksMap = HashMap<String, tokio::task::AbortHandle>;
+// Slots for as many runs of each job as its max_concurrent allows, with how many there are
+type RunSlotsMap = HashMap<String, (u32, Arc<Semaphore>)>;
+// The session of each run going, keyed by run
+type RunSessionsMap = HashMap<String, Vec<RunSession>>;
+// Jobs with their ID in the cron scheduler, which jobs run by other triggers don't have
+type JobsMap = HashMap<String, (Option<JobId>, ScheduledJob)>;
+
+// How often a run that's cancelling the previous one looks for it again, when the run holding
+// the slot hasn't stored its abort handle yet
+const CANCEL_RECHECK_INTERVAL: Duration = Duration::from_millis(100);
 
 /// Normalize a cron string so that:
 /// 1. It is always in **quartz 7-field format** expected by Temporal
@@ -93,6 +120,7 @@ pub enum SchedulerError {
     AgentSetupError(String),
     PersistError(String),
     CronParseError(String),
+    InvalidWebhookSignature(String),
     SchedulerInternalError(String),
     AnyhowError(anyhow::Error),
 }
@@ -107,6 +135,9 @@ impl std::fmt::Display for SchedulerError {
             SchedulerError::AgentSetupError(e) => write!(f, "Agent setup error: {}", e),
             SchedulerError::PersistError(e) => write!(f, "Failed to persist schedules: {}", e),
             SchedulerError::CronParseError(e) => write!(f, "Invalid cron string: {}", e),
+            SchedulerError::InvalidWebhookSignature(id) => {
+                write!(f, "Invalid webhook signature for job '{}'.", id)
+            }
             SchedulerError::SchedulerInternalError(e) => {
                 write!(f, "Scheduler internal error: {}", e)
             }
@@ -159,6 +190,25 @@ pub struct ScheduledJob {
     pub process_start_time: Option<DateTime<Utc>>,
     #[serde(default)]
     pub execution_mode: Option<String>, // "foreground" or "background"
+    /// An event that runs the job instead of its cron expression
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trigger: Option<JobTrigger>,
+    /// How the job's runs are retried, overlap and catch up on missed runs
+    #[serde(flatten)]
+    pub policy: RunPolicy,
+    /// Who to tell when a run finishes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notifiers: Vec<JobNotifier>,
+}
+
+impl ScheduledJob {
+    /// The job without its webhook secret, to show to API clients
+    pub fn without_secrets(mut self) -> Self {
+        if let Some(JobTrigger::Webhook { secret, .. }) = &mut self.trigger {
+            secret.clear();
+        }
+        self
+    }
 }
 
 async fn persist_jobs_from_arc(
@@ -175,11 +225,590 @@ async fn persist_jobs_from_arc(
     Ok(())
 }
 
+// Convert a cron expression to the 6-field format tokio-cron-scheduler expects
+fn tokio_cron_expression(cron: &str) -> String {
+    let normalized_cron = normalize_cron_expression(cron);
+    let parts: Vec<&str> = normalized_cron.split_whitespace().collect();
+    if parts.len() == 7 {
+        parts[..6].join(" ")
+    } else {
+        normalized_cron.clone()
+    }
+}
+
+/// What running a job from one of its triggers needs from the scheduler
+#[derive(Clone)]
+struct JobRunContext {
+    jobs: Arc<Mutex<JobsMap>>,
+    storage_path: PathBuf,
+    running_tasks: Arc<Mutex<RunningTasksMap>>,
+    run_slots: Arc<Mutex<RunSlotsMap>>,
+    run_sessions: Arc<Mutex<RunSessionsMap>>,
+}
+
+/// The session a run started, with the provider and model it runs on when they're known
+#[derive(Clone, Debug)]
+struct RunSession {
+    session_id: String,
+    model: Option<(String, String)>,
+}
+
+/// Where a run reports the sessions its attempts start, so they are known however the run ends
+#[derive(Clone)]
+struct RunTracker {
+    context: JobRunContext,
+    job_id: String,
+    run_id: String,
+}
+
+impl RunTracker {
+    async fn started_session(&self, session: RunSession) {
+        if let Some((_, job)) = self.context.jobs.lock().await.get_mut(&self.job_id) {
+            job.current_session_id = Some(session.session_id.clone());
+        }
+        self.context
+            .run_sessions
+            .lock()
+            .await
+            .entry(self.run_id.clone())
+            .or_default()
+            .push(session);
+    }
+}
+
+/// Run a job for its cron schedule or another trigger, unless it can't run right now
+fn run_triggered_job(
+    context: JobRunContext,
+    task_job_id: String,
+    params: HashMap<String, String>,
+    reason: RunReason,
+) -> BoxFuture<'static, ()> {
+    async move {
+        if let Err(e) = run_job(context, task_job_id.clone(), params, reason).await {
+            tracing::info!("Skipping run of job '{}': {}", task_job_id, e);
+        }
+    }
+    .boxed()
+}
+
+/// Run a job, tracking it as running while it does, then record the run, start the jobs that
+/// run after it and tell its notifiers. Fails without running the job when it's gone, paused
+/// (unless it's being run now) or has no room to run under its overlap policy.
+async fn run_job(
+    context: JobRunContext,
+    task_job_id: String,
+    params: HashMap<String, String>,
+    reason: RunReason,
+) -> Result<RunResult, SchedulerError> {
+    let current_jobs_arc = context.jobs.clone();
+    let local_storage_path = context.storage_path.clone();
+    let running_tasks_arc = context.running_tasks.clone();
+
+    // Check if the job is paused before executing
+    let job_to_execute = match current_jobs_arc.lock().await.get(&task_job_id) {
+        Some((_, job)) if job.paused && reason != RunReason::RunNow => {
+            return Err(SchedulerError::AnyhowError(anyhow!(
+                "Job '{}' is paused",
+                task_job_id
+            )));
+        }
+        Some((_, job)) => job.clone(),
+        None => return Err(SchedulerError::JobNotFound(task_job_id)),
+    };
+
+    // Wait for, or make, room for the run under the job's overlap policy
+    let Some(run_slot) = acquire_run_slot(&context, &job_to_execute).await else {
+        record_run(
+            &context,
+            JobRun::skipped(
+                &task_job_id,
+                reason,
+                Utc::now(),
+                "A previous run was still going",
+            ),
+        )
+        .await;
+        return Err(SchedulerError::AnyhowError(anyhow!(
+            "A previous run of job '{}' is still going",
+            task_job_id
+        )));
+    };
+
+    // Spawn the job execution, with its retries, as an abortable task, storing the abort
+    // handle at the scheduler level as it starts so the run can always be cancelled
+    let notifiers = job_to_execute.notifiers.clone();
+    let tracker = RunTracker {
+        context: context.clone(),
+        job_id: task_job_id.clone(),
+        run_id: uuid::Uuid::new_v4().to_string(),
+    };
+    let attempts = Arc::new(AtomicU32::new(0));
+    let current_time = Utc::now();
+    let start = Instant::now();
+    let job_task = {
+        let mut running_tasks_guard = running_tasks_arc.lock().await;
+        let job_task = tokio::spawn(run_job_with_retries(
+            job_to_execute,
+            tracker.clone(),
+            params,
+            attempts.clone(),
+        ));
+        running_tasks_guard
+            .entry(task_job_id.clone())
+            .or_default()
+            .push((tracker.run_id.clone(), job_task.abort_handle()));
+        job_task
+    };
+
+    let mut needs_persist = false;
+    {
+        let mut jobs_map_guard = current_jobs_arc.lock().await;
+        if let Some((_, current_job_in_map)) = jobs_map_guard.get_mut(&task_job_id) {
+            current_job_in_map.last_run = Some(current_time);
+            current_job_in_map.currently_running = true;
+            current_job_in_map.process_start_time = Some(current_time);
+            needs_persist = true;
+        }
+    }
+
+    if needs_persist {
+        if let Err(e) = persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await {
+            tracing::error!(
+                "Failed to persist last_run update for job {}: {}",
+                &task_job_id,
+                e
+            );
+        }
+    }
+
+    // Wait for the job to complete or be aborted
+    let result = job_task.await;
+
+    // Remove the abort handle and the session of the run
+    let newest_other_run = {
+        let mut running_tasks_guard = running_tasks_arc.lock().await;
+        remove_run_task(&mut running_tasks_guard, &task_job_id, &tracker.run_id)
+    };
+    let (sessions, other_session) = {
+        let mut run_sessions_guard = context.run_sessions.lock().await;
+        let sessions = run_sessions_guard
+            .remove(&tracker.run_id)
+            .unwrap_or_default();
+        let other_session = newest_other_run
+            .as_ref()
+            .and_then(|run_id| run_sessions_guard.get(run_id))
+            .and_then(|sessions| sessions.last())
+            .map(|session| session.session_id.clone());
+        (sessions, other_session)
+    };
+
+    // Update the job status after execution
+    {
+        let mut jobs_map_guard = current_jobs_arc.lock().await;
+        if let Some((_, current_job_in_map)) = jobs_map_guard.get_mut(&task_job_id) {
+            if newest_other_run.is_none() {
+                current_job_in_map.currently_running = false;
+                current_job_in_map.current_session_id = None;
+                current_job_in_map.process_start_time = None;
+            } else if current_job_in_map.current_session_id.as_deref()
+                == sessions.last().map(|s| s.session_id.as_str())
+            {
+                current_job_in_map.current_session_id = other_session;
+            }
+            needs_persist = true;
+        }
+    }
+
+    if needs_persist {
+        if let Err(e) = persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await {
+            tracing::error!(
+                "Failed to persist running status update for job {}: {}",
+                &task_job_id,
+                e
+            );
+        }
+    }
+    // The run is over, so let the next one start while this one is recorded and reported
+    drop(run_slot);
+
+    match &result {
+        Ok(Ok(_output)) => {
+            tracing::info!("Scheduled job '{}' completed successfully", &task_job_id);
+        }
+        Ok(Err(e)) => {
+            tracing::error!(
+                "Scheduled job '{}' execution failed: {}",
+                &e.job_id,
+                e.error
+            );
+        }
+        Err(join_error) if join_error.is_cancelled() => {
+            tracing::info!("Scheduled job '{}' was cancelled/killed", &task_job_id);
+        }
+        Err(join_error) => {
+            tracing::error!(
+                "Scheduled job '{}' task failed: {}",
+                &task_job_id,
+                join_error
+            );
+        }
+    }
+
+    let run = finished_run(
+        &task_job_id,
+        reason,
+        current_time,
+        start.elapsed(),
+        attempts.load(Ordering::Relaxed),
+        &result,
+        &sessions,
+    )
+    .await;
+    record_run(&context, run.clone()).await;
+
+    start_jobs_after(&context, &task_job_id, matches!(result, Ok(Ok(_)))).await;
+    notify_run(notifiers, run, &result);
+    Ok(result)
+}
+
+/// Wait for, or make, room for another run of `job` under its overlap policy. `None` means
+/// the run should be skipped.
+async fn acquire_run_slot(
+    context: &JobRunContext,
+    job: &ScheduledJob,
+) -> Option<OwnedSemaphorePermit> {
+    let slots = {
+        let max_concurrent = job.policy.max_concurrent.max(1);
+        let mut run_slots_guard = context.run_slots.lock().await;
+        let (size, slots) = run_slots_guard.entry(job.id.clone()).or_insert_with(|| {
+            (
+                max_concurrent,
+                Arc::new(Semaphore::new(max_concurrent as usize)),
+            )
+        });
+        resize_run_slots(slots, size, max_concurrent);
+        slots.clone()
+    };
+    if let Ok(slot) = slots.clone().try_acquire_owned() {
+        return Some(slot);
+    }
+    match job.policy.overlap {
+        OverlapPolicy::Skip => None,
+        OverlapPolicy::Queue => {
+            tracing::info!(
+                "Queueing run of job '{}' until a previous run finishes",
+                job.id
+            );
+            slots.acquire_owned().await.ok()
+        }
+        OverlapPolicy::CancelPrevious => loop {
+            let cancelled = match context
+                .running_tasks
+                .lock()
+                .await
+                .get(&job.id)
+                .and_then(|runs| runs.first())
+            {
+                Some((_, oldest)) => {
+                    tracing::info!("Cancelling the oldest run of job '{}'", job.id);
+                    oldest.abort();
+                    true
+                }
+                None => false,
+            };
+            if cancelled {
+                return slots.acquire_owned().await.ok();
+            }
+            // The run holding the slot may not have stored its abort handle yet
+            if let Ok(slot) =
+                tokio::time::timeout(CANCEL_RECHECK_INTERVAL, slots.clone().acquire_owned()).await
+            {
+                return slot.ok();
+            }
+        },
+    }
+}
+
+// Grow or shrink a job's run slots to its max_concurrent. Slots taken by runs still going
+// are given up as those runs finish.
+fn resize_run_slots(slots: &Arc<Semaphore>, size: &mut u32, max_concurrent: u32) {
+    if max_concurrent > *size {
+        slots.add_permits((max_concurrent - *size) as usize);
+    } else if max_concurrent < *size {
+        let excess = *size - max_concurrent;
+        let taken = excess - slots.forget_permits(excess as usize) as u32;
+        if taken > 0 {
+            let slots = slots.clone();
+            tokio::spawn(async move {
+                if let Ok(permits) = slots.acquire_many_owned(taken).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+    *size = max_concurrent;
+}
+
+// Forget a run's abort handle, returning the newest run of the job that's still going
+fn remove_run_task(
+    running_tasks: &mut RunningTasksMap,
+    job_id: &str,
+    run_id: &str,
+) -> Option<String> {
+    let runs = running_tasks.get_mut(job_id)?;
+    runs.retain(|(id, _)| id != run_id);
+    match runs.last() {
+        Some((newest, _)) => Some(newest.clone()),
+        None => {
+            running_tasks.remove(job_id);
+            None
+        }
+    }
+}
+
+/// Run a job, retrying failed attempts as its retry policy allows
+async fn run_job_with_retries(
+    job: ScheduledJob,
+    tracker: RunTracker,
+    params: HashMap<String, String>,
+    attempts: Arc<AtomicU32>,
+) -> std::result::Result<JobOutput, JobExecutionError> {
+    let retry = job.policy.retry.clone().unwrap_or_default();
+    let mut attempt = 1;
+    loop {
+        attempts.store(attempt, Ordering::Relaxed);
+        let result =
+            run_scheduled_job_internal(job.clone(), None, Some(tracker.clone()), params.clone())
+                .await;
+        match result {
+            Err(e) if attempt < retry.max_attempts => {
+                let delay = retry.delay(attempt);
+                tracing::warn!(
+                    "Scheduled job '{}' attempt {} of {} failed: {}. Retrying in {}s",
+                    tracker.job_id,
+                    attempt,
+                    retry.max_attempts,
+                    e.error,
+                    delay.as_secs()
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Estimated cost of a run's session, from its token counts and the pricing of the model the
+/// run used, or the configured model when that isn't known
+async fn session_cost(session: &RunSession) -> Option<f64> {
+    let path = session::storage::get_path(session::storage::Identifier::Name(
+        session.session_id.clone(),
+    ))
+    .ok()?;
+    let metadata = session::storage::read_metadata(&path).ok()?;
+    let (provider, model) = match &session.model {
+        Some(model) => model.clone(),
+        None => {
+            let config = Config::global();
+            (
+                config.get_param("GOOSE_PROVIDER").ok()?,
+                config.get_param("GOOSE_MODEL").ok()?,
+            )
+        }
+    };
+    let pricing = get_model_pricing(&provider, &model).await?;
+    let input_tokens = metadata
+        .accumulated_input_tokens
+        .or(metadata.input_tokens)
+        .unwrap_or(0)
+        .max(0);
+    let output_tokens = metadata
+        .accumulated_output_tokens
+        .or(metadata.output_tokens)
+        .unwrap_or(0)
+        .max(0);
+    Some(input_tokens as f64 * pricing.input_cost + output_tokens as f64 * pricing.output_cost)
+}
+
+/// The history record of a run that started at `started_at` and finished with `result`, in
+/// the last of the sessions its attempts started, costing what all of them did together
+async fn finished_run(
+    job_id: &str,
+    reason: RunReason,
+    started_at: DateTime<Utc>,
+    duration: Duration,
+    attempts: u32,
+    result: &RunResult,
+    sessions: &[RunSession],
+) -> JobRun {
+    let (status, error) = match result {
+        Ok(Ok(_)) => (RunStatus::Success, None),
+        Ok(Err(e)) => (RunStatus::Failed, Some(e.error.clone())),
+        Err(join_error) if join_error.is_cancelled() => (RunStatus::Cancelled, None),
+        Err(join_error) => (RunStatus::Failed, Some(join_error.to_string())),
+    };
+    let session_id = match result {
+        Ok(Ok(output)) => Some(output.session_id.clone()),
+        _ => sessions.last().map(|s| s.session_id.clone()),
+    };
+    let mut cost = None;
+    for session in sessions {
+        if let Some(session_cost) = session_cost(session).await {
+            *cost.get_or_insert(0.0) += session_cost;
+        }
+    }
+    JobRun {
+        job_id: job_id.to_string(),
+        reason,
+        status,
+        started_at,
+        duration_ms: duration.as_millis() as u64,
+        attempts: attempts.max(1),
+        error,
+        session_id,
+        cost,
+    }
+}
+
+/// Tell a job's notifiers how a run went, in the background so slow notifiers don't hold up
+/// the job
+fn notify_run(notifiers: Vec<JobNotifier>, run: JobRun, result: &RunResult) {
+    if notifiers.is_empty() {
+        return;
+    }
+    let (final_message, final_output) = match result {
+        Ok(Ok(output)) => (output.final_message.clone(), output.final_output.clone()),
+        _ => (None, None),
+    };
+    let summary = RunSummary {
+        run,
+        final_message,
+        final_output,
+    };
+    tokio::spawn(async move { notify_all(&notifiers, &summary).await });
+}
+
+async fn record_run(context: &JobRunContext, run: JobRun) {
+    if let Err(e) = append_run(&run_history_dir(&context.storage_path), &run) {
+        tracing::error!("Failed to record run of job '{}': {}", run.job_id, e);
+    }
+}
+
+/// Handle the runs of a job that were due while goose wasn't running or the machine was
+/// asleep, as its catch-up policy says. `run_due` says whether a run is starting anyway, which
+/// serves as the one run of `run_once`.
+async fn catch_up_missed_runs(
+    context: JobRunContext,
+    job_id: String,
+    missed: Vec<DateTime<Utc>>,
+    run_due: bool,
+) {
+    if missed.is_empty() {
+        return;
+    }
+    let policy = match context.jobs.lock().await.get(&job_id) {
+        Some((_, job)) if !job.paused => job.policy.catch_up,
+        _ => return,
+    };
+    tracing::info!(
+        "Job '{}' missed {} run(s) since it last ran",
+        job_id,
+        missed.len()
+    );
+    match policy {
+        CatchUpPolicy::Skip => {
+            for due in missed {
+                let run = JobRun::skipped(
+                    &job_id,
+                    RunReason::CatchUp,
+                    due,
+                    "Missed while goose wasn't running",
+                );
+                record_run(&context, run).await;
+            }
+        }
+        CatchUpPolicy::RunOnce if run_due => {}
+        CatchUpPolicy::RunOnce => {
+            run_triggered_job(context, job_id, HashMap::new(), RunReason::CatchUp).await
+        }
+        CatchUpPolicy::RunAll => {
+            for _ in missed {
+                run_triggered_job(
+                    context.clone(),
+                    job_id.clone(),
+                    HashMap::new(),
+                    RunReason::CatchUp,
+                )
+                .await;
+            }
+        }
+    }
+}
+
+/// Fail if `job` would end up running after itself by following the jobs its After trigger
+/// chains it to, which would run them all again and again
+fn check_after_chain(job: &ScheduledJob, jobs: &JobsMap) -> Result<(), SchedulerError> {
+    let mut chain = vec![job.id.as_str()];
+    let mut trigger = job.trigger.as_ref();
+    while let Some(JobTrigger::After { job_id, .. }) = trigger {
+        let seen = chain.contains(&job_id.as_str());
+        chain.push(job_id);
+        if job_id == &job.id {
+            return Err(SchedulerError::AnyhowError(anyhow!(
+                "Job '{}' would run after itself: {}",
+                job.id,
+                chain.join(" runs after ")
+            )));
+        }
+        if seen {
+            break;
+        }
+        trigger = jobs.get(job_id).and_then(|(_, job)| job.trigger.as_ref());
+    }
+    Ok(())
+}
+
+/// Start the jobs chained to run after `finished_job_id` for how it finished
+async fn start_jobs_after(context: &JobRunContext, finished_job_id: &str, succeeded: bool) {
+    let chained: Vec<String> = context
+        .jobs
+        .lock()
+        .await
+        .values()
+        .filter_map(|(_, job)| match &job.trigger {
+            Some(JobTrigger::After { job_id, status })
+                if job_id == finished_job_id && status.matches(succeeded) =>
+            {
+                Some(job.id.clone())
+            }
+            _ => None,
+        })
+        .collect();
+    for job_id in chained {
+        tracing::info!(
+            "Starting job '{}' after job '{}' finished",
+            job_id,
+            finished_job_id
+        );
+        tokio::spawn(run_triggered_job(
+            context.clone(),
+            job_id,
+            HashMap::new(),
+            RunReason::Trigger,
+        ));
+    }
+}
+
 pub struct Scheduler {
     internal_scheduler: TokioJobScheduler,
     jobs: Arc<Mutex<JobsMap>>,
     storage_path: PathBuf,
     running_tasks: Arc<Mutex<RunningTasksMap>>,
+    // Tasks watching for the events of jobs run by a trigger rather than cron
+    trigger_tasks: Arc<Mutex<TriggerTasksMap>>,
+    run_slots: Arc<Mutex<RunSlotsMap>>,
+    run_sessions: Arc<Mutex<RunSessionsMap>>,
 }
 
 impl Scheduler {
@@ -190,12 +819,18 @@ impl Scheduler {
 
         let jobs = Arc::new(Mutex::new(HashMap::new()));
         let running_tasks = Arc::new(Mutex::new(HashMap::new()));
+        let trigger_tasks = Arc::new(Mutex::new(HashMap::new()));
+        let run_slots = Arc::new(Mutex::new(HashMap::new()));
+        let run_sessions = Arc::new(Mutex::new(HashMap::new()));
 
         let arc_self = Arc::new(Self {
             internal_scheduler,
             jobs,
             storage_path,
             running_tasks,
+            trigger_tasks,
+            run_slots,
+            run_sessions,
         });
 
         arc_self.load_jobs_from_storage().await?;
@@ -208,6 +843,146 @@ impl Scheduler {
         Ok(arc_self)
     }
 
+    fn run_context(&self) -> JobRunContext {
+        JobRunContext {
+            jobs: self.jobs.clone(),
+            storage_path: self.storage_path.clone(),
+            running_tasks: self.running_tasks.clone(),
+            run_slots: self.run_slots.clone(),
+            run_sessions: self.run_sessions.clone(),
+        }
+    }
+
+    fn cron_task(&self, job_id: &str, cron: &str) -> Result<Job, SchedulerError> {
+        let tokio_cron = tokio_cron_expression(cron);
+        if tokio_cron != cron {
+            tracing::info!(
+                "Converted cron expression from '{}' to '{}' for tokio-cron-scheduler",
+                cron,
+                tokio_cron
+            );
+        }
+        let context = self.run_context();
+        let job_id = job_id.to_string();
+        let cron = tokio_cron.clone();
+        // When the task last fired, to notice runs missed while the machine was asleep
+        let last_fired = Arc::new(std::sync::Mutex::new(None::<DateTime<Utc>>));
+        Job::new_async(&tokio_cron, move |_uuid, _l| {
+            let context = context.clone();
+            let job_id = job_id.clone();
+            let cron = cron.clone();
+            let last_fired = last_fired.clone();
+            async move {
+                let now = Utc::now();
+                let previous = last_fired
+                    .lock()
+                    .map(|mut last_fired| last_fired.replace(now))
+                    .unwrap_or(None);
+                if let Some(previous) = previous {
+                    // Every time the job was due since it last fired, except the run due now
+                    let mut missed = missed_runs(&cron, previous, now, MAX_CATCH_UP_RUNS + 1)
+                        .unwrap_or_default();
+                    missed.pop();
+                    catch_up_missed_runs(context.clone(), job_id.clone(), missed, true).await;
+                }
+                run_triggered_job(context, job_id, HashMap::new(), RunReason::Schedule).await;
+            }
+            .boxed()
+        })
+        .map_err(|e| SchedulerError::CronParseError(e.to_string()))
+    }
+
+    // Start whatever runs the job: its cron schedule, which returns the scheduler's ID for
+    // it, or the task watching for its trigger. Webhooks and chained jobs need neither, but
+    // are checked against `jobs`, the other jobs.
+    async fn start_job_trigger(
+        &self,
+        job: &ScheduledJob,
+        jobs: &JobsMap,
+    ) -> Result<Option<JobId>, SchedulerError> {
+        match &job.trigger {
+            None => {
+                tracing::info!(
+                    "Scheduling job '{}' with cron expression: '{}'",
+                    job.id,
+                    job.cron
+                );
+                let cron_task = self.cron_task(&job.id, &job.cron)?;
+                let job_uuid = self
+                    .internal_scheduler
+                    .add(cron_task)
+                    .await
+                    .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
+                Ok(Some(job_uuid))
+            }
+            Some(JobTrigger::FileChange {
+                path,
+                debounce_seconds,
+            }) => {
+                FileWatch::new(path).map_err(|e| {
+                    SchedulerError::AnyhowError(anyhow!("Can't watch {}: {}", path, e))
+                })?;
+                let context = self.run_context();
+                let job_id = job.id.clone();
+                let path = path.clone();
+                let debounce = Duration::from_secs(*debounce_seconds);
+                let task = tokio::spawn(async move {
+                    let watched = watch_files(path.clone(), debounce, |changed| {
+                        let params = HashMap::from([(
+                            CHANGED_PATH_PARAM.to_string(),
+                            changed.to_string_lossy().into_owned(),
+                        )]);
+                        run_triggered_job(
+                            context.clone(),
+                            job_id.clone(),
+                            params,
+                            RunReason::Trigger,
+                        )
+                    })
+                    .await;
+                    if let Err(e) = watched {
+                        tracing::error!("Stopped watching {} for job '{}': {}", path, job_id, e);
+                    }
+                });
+                self.trigger_tasks
+                    .lock()
+                    .await
+                    .insert(job.id.clone(), task.abort_handle());
+                Ok(None)
+            }
+            Some(JobTrigger::Webhook { secret, .. }) => {
+                if secret.trim().is_empty() {
+                    return Err(SchedulerError::AnyhowError(anyhow!(
+                        "Job '{}' needs a webhook secret to check signatures with",
+                        job.id
+                    )));
+                }
+                Ok(None)
+            }
+            Some(JobTrigger::After { .. }) => {
+                check_after_chain(job, jobs)?;
+                Ok(None)
+            }
+        }
+    }
+
+    async fn stop_job_trigger(
+        &self,
+        job_id: &str,
+        job_uuid: Option<JobId>,
+    ) -> Result<(), SchedulerError> {
+        if let Some(job_uuid) = job_uuid {
+            self.internal_scheduler
+                .remove(&job_uuid)
+                .await
+                .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
+        }
+        if let Some(task) = self.trigger_tasks.lock().await.remove(job_id) {
+            task.abort();
+        }
+        Ok(())
+    }
+
     pub async fn add_scheduled_job(
         &self,
         original_job_spec: ScheduledJob,
@@ -263,153 +1038,13 @@ impl Scheduler {
         stored_job.process_start_time = None;
         tracing::info!("Updated job source path to: {}", stored_job.source);
 
-        let job_for_task = stored_job.clone();
-        let jobs_arc_for_task = self.jobs.clone();
-        let storage_path_for_task = self.storage_path.clone();
-        let running_tasks_for_task = self.running_tasks.clone();
-
-        tracing::info!("Attempting to parse cron expression: '{}'", stored_job.cron);
-        let normalized_cron = normalize_cron_expression(&stored_job.cron);
-        // Convert from 7-field (Temporal format) to 6-field (tokio-cron-scheduler format)
-        let tokio_cron = {
-            let parts: Vec<&str> = normalized_cron.split_whitespace().collect();
-            if parts.len() == 7 {
-                parts[..6].join(" ")
-            } else {
-                normalized_cron.clone()
+        let job_uuid = match self.start_job_trigger(&stored_job, &jobs_guard).await {
+            Ok(job_uuid) => job_uuid,
+            Err(e) => {
+                let _ = fs::remove_file(&destination_recipe_path);
+                return Err(e);
             }
         };
-        if tokio_cron != stored_job.cron {
-            tracing::info!(
-                "Converted cron expression from '{}' to '{}' for tokio-cron-scheduler",
-                stored_job.cron,
-                tokio_cron
-            );
-        }
-        let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
-            let task_job_id = job_for_task.id.clone();
-            let current_jobs_arc = jobs_arc_for_task.clone();
-            let local_storage_path = storage_path_for_task.clone();
-            let job_to_execute = job_for_task.clone(); // Clone for run_scheduled_job_internal
-            let running_tasks_arc = running_tasks_for_task.clone();
-
-            Box::pin(async move {
-                // Check if the job is paused before executing
-                let should_execute = {
-                    let jobs_map_guard = current_jobs_arc.lock().await;
-                    if let Some((_, current_job_in_map)) = jobs_map_guard.get(&task_job_id) {
-                        !current_job_in_map.paused
-                    } else {
-                        false
-                    }
-                };
-
-                if !should_execute {
-                    tracing::info!("Skipping execution of paused job '{}'", &task_job_id);
-                    return;
-                }
-
-                let current_time = Utc::now();
-                let mut needs_persist = false;
-                {
-                    let mut jobs_map_guard = current_jobs_arc.lock().await;
-                    if let Some((_, current_job_in_map)) = jobs_map_guard.get_mut(&task_job_id) {
-                        current_job_in_map.last_run = Some(current_time);
-                        current_job_in_map.currently_running = true;
-                        current_job_in_map.process_start_time = Some(current_time);
-                        needs_persist = true;
-                    }
-                }
-
-                if needs_persist {
-                    if let Err(e) =
-                        persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                    {
-                        tracing::error!(
-                            "Failed to persist last_run update for job {}: {}",
-                            &task_job_id,
-                            e
-                        );
-                    }
-                }
-
-                // Spawn the job execution as an abortable task
-                let job_task = tokio::spawn(run_scheduled_job_internal(
-                    job_to_execute.clone(),
-                    None,
-                    Some(current_jobs_arc.clone()),
-                    Some(task_job_id.clone()),
-                ));
-
-                // Store the abort handle at the scheduler level
-                {
-                    let mut running_tasks_guard = running_tasks_arc.lock().await;
-                    running_tasks_guard.insert(task_job_id.clone(), job_task.abort_handle());
-                }
-
-                // Wait for the job to complete or be aborted
-                let result = job_task.await;
-
-                // Remove the abort handle
-                {
-                    let mut running_tasks_guard = running_tasks_arc.lock().await;
-                    running_tasks_guard.remove(&task_job_id);
-                }
-
-                // Update the job status after execution
-                {
-                    let mut jobs_map_guard = current_jobs_arc.lock().await;
-                    if let Some((_, current_job_in_map)) = jobs_map_guard.get_mut(&task_job_id) {
-                        current_job_in_map.currently_running = false;
-                        current_job_in_map.current_session_id = None;
-                        current_job_in_map.process_start_time = None;
-                        needs_persist = true;
-                    }
-                }
-
-                if needs_persist {
-                    if let Err(e) =
-                        persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                    {
-                        tracing::error!(
-                            "Failed to persist running status update for job {}: {}",
-                            &task_job_id,
-                            e
-                        );
-                    }
-                }
-
-                match result {
-                    Ok(Ok(_session_id)) => {
-                        tracing::info!("Scheduled job '{}' completed successfully", &task_job_id);
-                    }
-                    Ok(Err(e)) => {
-                        tracing::error!(
-                            "Scheduled job '{}' execution failed: {}",
-                            &e.job_id,
-                            e.error
-                        );
-                    }
-                    Err(join_error) if join_error.is_cancelled() => {
-                        tracing::info!("Scheduled job '{}' was cancelled/killed", &task_job_id);
-                    }
-                    Err(join_error) => {
-                        tracing::error!(
-                            "Scheduled job '{}' task failed: {}",
-                            &task_job_id,
-                            join_error
-                        );
-                    }
-                }
-            })
-        })
-        .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
-
-        let job_uuid = self
-            .internal_scheduler
-            .add(cron_task)
-            .await
-            .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
 
         jobs_guard.insert(stored_job.id.clone(), (job_uuid, stored_job));
         // Pass the jobs_guard by reference for the initial persist after adding a job
@@ -428,169 +1063,34 @@ impl Scheduler {
 
         let list: Vec<ScheduledJob> = serde_json::from_str(&data).map_err(|e| {
             SchedulerError::PersistError(format!("Failed to deserialize schedules.json: {}", e))
-        })?;
-
-        let mut jobs_guard = self.jobs.lock().await;
-        for job_to_load in list {
-            if !Path::new(&job_to_load.source).exists() {
-                tracing::warn!("Recipe file {} for scheduled job {} not found in shared store. Skipping job load.", job_to_load.source, job_to_load.id);
-                continue;
-            }
-
-            let job_for_task = job_to_load.clone();
-            let jobs_arc_for_task = self.jobs.clone();
-            let storage_path_for_task = self.storage_path.clone();
-            let running_tasks_for_task = self.running_tasks.clone();
-
-            tracing::info!(
-                "Loading job '{}' with cron expression: '{}'",
-                job_to_load.id,
-                job_to_load.cron
-            );
-            let normalized_cron = normalize_cron_expression(&job_to_load.cron);
-            // Convert from 7-field (Temporal format) to 6-field (tokio-cron-scheduler format)
-            let tokio_cron = {
-                let parts: Vec<&str> = normalized_cron.split_whitespace().collect();
-                if parts.len() == 7 {
-                    parts[..6].join(" ")
-                } else {
-                    normalized_cron.clone()
-                }
-            };
-            if tokio_cron != job_to_load.cron {
-                tracing::info!(
-                    "Converted cron expression from '{}' to '{}' for tokio-cron-scheduler",
-                    job_to_load.cron,
-                    tokio_cron
-                );
-            }
-            let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
-                let task_job_id = job_for_task.id.clone();
-                let current_jobs_arc = jobs_arc_for_task.clone();
-                let local_storage_path = storage_path_for_task.clone();
-                let job_to_execute = job_for_task.clone(); // Clone for run_scheduled_job_internal
-                let running_tasks_arc = running_tasks_for_task.clone();
-
-                Box::pin(async move {
-                    // Check if the job is paused before executing
-                    let should_execute = {
-                        let jobs_map_guard = current_jobs_arc.lock().await;
-                        if let Some((_, stored_job)) = jobs_map_guard.get(&task_job_id) {
-                            !stored_job.paused
-                        } else {
-                            false
-                        }
-                    };
-
-                    if !should_execute {
-                        tracing::info!("Skipping execution of paused job '{}'", &task_job_id);
-                        return;
-                    }
-
-                    let current_time = Utc::now();
-                    let mut needs_persist = false;
-                    {
-                        let mut jobs_map_guard = current_jobs_arc.lock().await;
-                        if let Some((_, stored_job)) = jobs_map_guard.get_mut(&task_job_id) {
-                            stored_job.last_run = Some(current_time);
-                            stored_job.currently_running = true;
-                            stored_job.process_start_time = Some(current_time);
-                            needs_persist = true;
-                        }
-                    }
-
-                    if needs_persist {
-                        if let Err(e) =
-                            persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                        {
-                            tracing::error!(
-                                "Failed to persist last_run update for loaded job {}: {}",
-                                &task_job_id,
-                                e
-                            );
-                        }
-                    }
-
-                    // Spawn the job execution as an abortable task
-                    let job_task = tokio::spawn(run_scheduled_job_internal(
-                        job_to_execute,
-                        None,
-                        Some(current_jobs_arc.clone()),
-                        Some(task_job_id.clone()),
-                    ));
-
-                    // Store the abort handle at the scheduler level
-                    {
-                        let mut running_tasks_guard = running_tasks_arc.lock().await;
-                        running_tasks_guard.insert(task_job_id.clone(), job_task.abort_handle());
-                    }
-
-                    // Wait for the job to complete or be aborted
-                    let result = job_task.await;
-
-                    // Remove the abort handle
-                    {
-                        let mut running_tasks_guard = running_tasks_arc.lock().await;
-                        running_tasks_guard.remove(&task_job_id);
-                    }
-
-                    // Update the job status after execution
-                    {
-                        let mut jobs_map_guard = current_jobs_arc.lock().await;
-                        if let Some((_, stored_job)) = jobs_map_guard.get_mut(&task_job_id) {
-                            stored_job.currently_running = false;
-                            stored_job.current_session_id = None;
-                            stored_job.process_start_time = None;
-                            needs_persist = true;
-                        }
-                    }
-
-                    if needs_persist {
-                        if let Err(e) =
-                            persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                        {
-                            tracing::error!(
-                                "Failed to persist running status update for job {}: {}",
-                                &task_job_id,
-                                e
-                            );
-                        }
-                    }
+        })?;
 
-                    match result {
-                        Ok(Ok(_session_id)) => {
-                            tracing::info!(
-                                "Scheduled job '{}' completed successfully",
-                                &task_job_id
-                            );
-                        }
-                        Ok(Err(e)) => {
-                            tracing::error!(
-                                "Scheduled job '{}' execution failed: {}",
-                                &e.job_id,
-                                e.error
-                            );
-                        }
-                        Err(join_error) if join_error.is_cancelled() => {
-                            tracing::info!("Scheduled job '{}' was cancelled/killed", &task_job_id);
-                        }
-                        Err(join_error) => {
-                            tracing::error!(
-                                "Scheduled job '{}' task failed: {}",
-                                &task_job_id,
-                                join_error
-                            );
-                        }
-                    }
-                })
-            })
-            .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
+        let mut jobs_guard = self.jobs.lock().await;
+        for mut job_to_load in list {
+            if !Path::new(&job_to_load.source).exists() {
+                tracing::warn!("Recipe file {} for scheduled job {} not found in shared store. Skipping job load.", job_to_load.source, job_to_load.id);
+                continue;
+            }
 
-            let job_uuid = self
-                .internal_scheduler
-                .add(cron_task)
-                .await
-                .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
+            let job_uuid = match self.start_job_trigger(&job_to_load, &jobs_guard).await {
+                Ok(job_uuid) => job_uuid,
+                // A cron expression that no longer parses fails the whole load, as before
+                Err(e @ SchedulerError::CronParseError(_)) => return Err(e),
+                // Jobs saved before loops were rejected would run each other forever
+                Err(e) if matches!(job_to_load.trigger, Some(JobTrigger::After { .. })) => {
+                    tracing::warn!("Pausing scheduled job {}: {}", job_to_load.id, e);
+                    job_to_load.paused = true;
+                    None
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to start the trigger of scheduled job {}: {}",
+                        job_to_load.id,
+                        e
+                    );
+                    None
+                }
+            };
             jobs_guard.insert(job_to_load.id.clone(), (job_uuid, job_to_load));
         }
         Ok(())
@@ -610,11 +1110,6 @@ impl Scheduler {
         Ok(())
     }
 
-    // New function that locks and calls the helper, for run_now and potentially other places
-    async fn persist_jobs(&self) -> Result<(), SchedulerError> {
-        persist_jobs_from_arc(&self.storage_path, &self.jobs).await
-    }
-
     pub async fn list_scheduled_jobs(&self) -> Vec<ScheduledJob> {
         self.jobs
             .lock()
@@ -627,10 +1122,11 @@ impl Scheduler {
     pub async fn remove_scheduled_job(&self, id: &str) -> Result<(), SchedulerError> {
         let mut jobs_guard = self.jobs.lock().await;
         if let Some((job_uuid, scheduled_job)) = jobs_guard.remove(id) {
-            self.internal_scheduler
-                .remove(&job_uuid)
-                .await
-                .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
+            self.stop_job_trigger(id, job_uuid).await?;
+            self.run_slots.lock().await.remove(id);
+            if let Err(e) = remove_history(&run_history_dir(&self.storage_path), id) {
+                tracing::warn!("Failed to remove the run history of job {}: {}", id, e);
+            }
 
             let recipe_path = Path::new(&scheduled_job.source);
             if recipe_path.exists() {
@@ -682,64 +1178,19 @@ impl Scheduler {
         Ok(result_sessions) // Return the Vec of tuples
     }
 
+    /// Run a job straight away, paused or not, as its overlap and retry policies allow,
+    /// returning the session of the run once it finishes
     pub async fn run_now(&self, sched_id: &str) -> Result<String, SchedulerError> {
-        let job_to_run: ScheduledJob = {
-            let mut jobs_guard = self.jobs.lock().await;
-            match jobs_guard.get_mut(sched_id) {
-                Some((_, job_def)) => {
-                    // Set the currently_running flag before executing
-                    job_def.currently_running = true;
-                    let job_clone = job_def.clone();
-                    // Drop the guard before persisting to avoid borrow issues
-                    drop(jobs_guard);
-
-                    // Persist the change immediately
-                    self.persist_jobs().await?;
-                    job_clone
-                }
-                None => return Err(SchedulerError::JobNotFound(sched_id.to_string())),
-            }
-        };
-
-        // Spawn the job execution as an abortable task for run_now
-        let job_task = tokio::spawn(run_scheduled_job_internal(
-            job_to_run.clone(),
-            None,
-            Some(self.jobs.clone()),
-            Some(sched_id.to_string()),
-        ));
-
-        // Store the abort handle for run_now jobs
-        {
-            let mut running_tasks_guard = self.running_tasks.lock().await;
-            running_tasks_guard.insert(sched_id.to_string(), job_task.abort_handle());
-        }
-
-        // Wait for the job to complete or be aborted
-        let run_result = job_task.await;
-
-        // Remove the abort handle
-        {
-            let mut running_tasks_guard = self.running_tasks.lock().await;
-            running_tasks_guard.remove(sched_id);
-        }
-
-        // Clear the currently_running flag after execution
-        {
-            let mut jobs_guard = self.jobs.lock().await;
-            if let Some((_tokio_job_id, job_in_map)) = jobs_guard.get_mut(sched_id) {
-                job_in_map.currently_running = false;
-                job_in_map.current_session_id = None;
-                job_in_map.process_start_time = None;
-                job_in_map.last_run = Some(Utc::now());
-            } // MutexGuard is dropped here
-        }
-
-        // Persist after the lock is released and update is made.
-        self.persist_jobs().await?;
+        let run_result = run_job(
+            self.run_context(),
+            sched_id.to_string(),
+            HashMap::new(),
+            RunReason::RunNow,
+        )
+        .await?;
 
         match run_result {
-            Ok(Ok(session_id)) => Ok(session_id),
+            Ok(Ok(output)) => Ok(output.session_id),
             Ok(Err(e)) => Err(SchedulerError::AnyhowError(anyhow!(
                 "Failed to execute job '{}' immediately: {}",
                 sched_id,
@@ -780,6 +1231,9 @@ impl Scheduler {
 
     pub async fn unpause_schedule(&self, sched_id: &str) -> Result<(), SchedulerError> {
         let mut jobs_guard = self.jobs.lock().await;
+        if let Some((_, job_def)) = jobs_guard.get(sched_id) {
+            check_after_chain(job_def, &jobs_guard)?;
+        }
         match jobs_guard.get_mut(sched_id) {
             Some((_, job_def)) => {
                 job_def.paused = false;
@@ -805,175 +1259,33 @@ impl Scheduler {
                     )));
                 }
 
+                if let Some(trigger) = &job_def.trigger {
+                    return Err(SchedulerError::AnyhowError(anyhow!(
+                        "Schedule '{}' runs {} and has no cron expression to edit",
+                        sched_id,
+                        trigger
+                    )));
+                }
+
                 if new_cron == job_def.cron {
                     // No change needed
                     return Ok(());
                 }
 
-                // Remove the old job from the scheduler
-                self.internal_scheduler
-                    .remove(job_uuid)
-                    .await
-                    .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
-
-                // Create new job with updated cron
-                let job_for_task = job_def.clone();
-                let jobs_arc_for_task = self.jobs.clone();
-                let storage_path_for_task = self.storage_path.clone();
-                let running_tasks_for_task = self.running_tasks.clone();
-
                 tracing::info!(
                     "Updating job '{}' with new cron expression: '{}'",
                     sched_id,
                     new_cron
                 );
-                let normalized_cron = normalize_cron_expression(&new_cron);
-                // Convert from 7-field (Temporal format) to 6-field (tokio-cron-scheduler format)
-                let tokio_cron = {
-                    let parts: Vec<&str> = normalized_cron.split_whitespace().collect();
-                    if parts.len() == 7 {
-                        parts[..6].join(" ")
-                    } else {
-                        normalized_cron.clone()
-                    }
-                };
-                if tokio_cron != new_cron {
-                    tracing::info!(
-                        "Converted cron expression from '{}' to '{}' for tokio-cron-scheduler",
-                        new_cron,
-                        tokio_cron
-                    );
-                }
-                let cron_task = Job::new_async(&tokio_cron, move |_uuid, _l| {
-                    let task_job_id = job_for_task.id.clone();
-                    let current_jobs_arc = jobs_arc_for_task.clone();
-                    let local_storage_path = storage_path_for_task.clone();
-                    let job_to_execute = job_for_task.clone();
-                    let running_tasks_arc = running_tasks_for_task.clone();
-
-                    Box::pin(async move {
-                        // Check if the job is paused before executing
-                        let should_execute = {
-                            let jobs_map_guard = current_jobs_arc.lock().await;
-                            if let Some((_, current_job_in_map)) = jobs_map_guard.get(&task_job_id)
-                            {
-                                !current_job_in_map.paused
-                            } else {
-                                false
-                            }
-                        };
-
-                        if !should_execute {
-                            tracing::info!("Skipping execution of paused job '{}'", &task_job_id);
-                            return;
-                        }
-
-                        let current_time = Utc::now();
-                        let mut needs_persist = false;
-                        {
-                            let mut jobs_map_guard = current_jobs_arc.lock().await;
-                            if let Some((_, current_job_in_map)) =
-                                jobs_map_guard.get_mut(&task_job_id)
-                            {
-                                current_job_in_map.last_run = Some(current_time);
-                                current_job_in_map.currently_running = true;
-                                current_job_in_map.process_start_time = Some(current_time);
-                                needs_persist = true;
-                            }
-                        }
-
-                        if needs_persist {
-                            if let Err(e) =
-                                persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                            {
-                                tracing::error!(
-                                    "Failed to persist last_run update for job {}: {}",
-                                    &task_job_id,
-                                    e
-                                );
-                            }
-                        }
-
-                        // Spawn the job execution as an abortable task
-                        let job_task = tokio::spawn(run_scheduled_job_internal(
-                            job_to_execute,
-                            None,
-                            Some(current_jobs_arc.clone()),
-                            Some(task_job_id.clone()),
-                        ));
-
-                        // Store the abort handle at the scheduler level
-                        {
-                            let mut running_tasks_guard = running_tasks_arc.lock().await;
-                            running_tasks_guard
-                                .insert(task_job_id.clone(), job_task.abort_handle());
-                        }
-
-                        // Wait for the job to complete or be aborted
-                        let result = job_task.await;
-
-                        // Remove the abort handle
-                        {
-                            let mut running_tasks_guard = running_tasks_arc.lock().await;
-                            running_tasks_guard.remove(&task_job_id);
-                        }
+                let cron_task = self.cron_task(sched_id, &new_cron)?;
 
-                        // Update the job status after execution
-                        {
-                            let mut jobs_map_guard = current_jobs_arc.lock().await;
-                            if let Some((_, current_job_in_map)) =
-                                jobs_map_guard.get_mut(&task_job_id)
-                            {
-                                current_job_in_map.currently_running = false;
-                                current_job_in_map.current_session_id = None;
-                                current_job_in_map.process_start_time = None;
-                                needs_persist = true;
-                            }
-                        }
-
-                        if needs_persist {
-                            if let Err(e) =
-                                persist_jobs_from_arc(&local_storage_path, &current_jobs_arc).await
-                            {
-                                tracing::error!(
-                                    "Failed to persist running status update for job {}: {}",
-                                    &task_job_id,
-                                    e
-                                );
-                            }
-                        }
-
-                        match result {
-                            Ok(Ok(_session_id)) => {
-                                tracing::info!(
-                                    "Scheduled job '{}' completed successfully",
-                                    &task_job_id
-                                );
-                            }
-                            Ok(Err(e)) => {
-                                tracing::error!(
-                                    "Scheduled job '{}' execution failed: {}",
-                                    &e.job_id,
-                                    e.error
-                                );
-                            }
-                            Err(join_error) if join_error.is_cancelled() => {
-                                tracing::info!(
-                                    "Scheduled job '{}' was cancelled/killed",
-                                    &task_job_id
-                                );
-                            }
-                            Err(join_error) => {
-                                tracing::error!(
-                                    "Scheduled job '{}' task failed: {}",
-                                    &task_job_id,
-                                    join_error
-                                );
-                            }
-                        }
-                    })
-                })
-                .map_err(|e| SchedulerError::CronParseError(e.to_string()))?;
+                // Remove the old job from the scheduler
+                if let Some(old_uuid) = job_uuid.take() {
+                    self.internal_scheduler
+                        .remove(&old_uuid)
+                        .await
+                        .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
+                }
 
                 let new_job_uuid = self
                     .internal_scheduler
@@ -982,7 +1294,7 @@ impl Scheduler {
                     .map_err(|e| SchedulerError::SchedulerInternalError(e.to_string()))?;
 
                 // Update the job UUID and cron expression
-                *job_uuid = new_job_uuid;
+                *job_uuid = Some(new_job_uuid);
                 job_def.cron = new_cron;
 
                 self.persist_jobs_to_storage_with_guard(&jobs_guard).await?;
@@ -1008,8 +1320,10 @@ impl Scheduler {
                 // Abort the running task if it exists
                 {
                     let mut running_tasks_guard = self.running_tasks.lock().await;
-                    if let Some(abort_handle) = running_tasks_guard.remove(sched_id) {
-                        abort_handle.abort();
+                    if let Some(runs) = running_tasks_guard.remove(sched_id) {
+                        for (_, abort_handle) in runs {
+                            abort_handle.abort();
+                        }
                         tracing::info!("Aborted running task for job '{}'", sched_id);
                     } else {
                         tracing::warn!(
@@ -1055,6 +1369,99 @@ impl Scheduler {
             None => Err(SchedulerError::JobNotFound(sched_id.to_string())),
         }
     }
+
+    /// Run a webhook-triggered job with the parameters its payload fills in, once the signature
+    /// of the payload and the `timestamp` it was signed at checks out. Jobs not triggered by
+    /// webhooks are reported as not found.
+    pub async fn trigger_webhook(
+        &self,
+        sched_id: &str,
+        payload: &[u8],
+        timestamp: &str,
+        signature: &str,
+    ) -> Result<(), SchedulerError> {
+        let (secret, mapping) = {
+            let jobs_guard = self.jobs.lock().await;
+            match jobs_guard
+                .get(sched_id)
+                .and_then(|(_, job)| job.trigger.as_ref())
+            {
+                Some(JobTrigger::Webhook { secret, params }) => (secret.clone(), params.clone()),
+                _ => return Err(SchedulerError::JobNotFound(sched_id.to_string())),
+            }
+        };
+        let now = Utc::now().timestamp();
+        if !verify_webhook_signature(&secret, timestamp, payload, signature, now) {
+            return Err(SchedulerError::InvalidWebhookSignature(
+                sched_id.to_string(),
+            ));
+        }
+        let payload: Value = if payload.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(payload).map_err(|e| {
+                SchedulerError::AnyhowError(anyhow!("Webhook payload is not JSON: {}", e))
+            })?
+        };
+
+        tracing::info!("Running job '{}' for a webhook", sched_id);
+        tokio::spawn(run_triggered_job(
+            self.run_context(),
+            sched_id.to_string(),
+            webhook_params(&payload, &mapping),
+            RunReason::Trigger,
+        ));
+        Ok(())
+    }
+
+    /// The most recent runs of a job, newest first
+    pub async fn run_history(
+        &self,
+        sched_id: &str,
+        limit: usize,
+    ) -> Result<Vec<JobRun>, SchedulerError> {
+        if !self.jobs.lock().await.contains_key(sched_id) {
+            return Err(SchedulerError::JobNotFound(sched_id.to_string()));
+        }
+        read_runs(&run_history_dir(&self.storage_path), sched_id, limit)
+            .map_err(SchedulerError::AnyhowError)
+    }
+
+    /// Handle the runs missed while goose wasn't running, as each job's catch-up policy says.
+    /// Only the long-running server calls this, so short-lived CLI commands don't start runs.
+    pub async fn catch_up(&self) -> Result<(), SchedulerError> {
+        let now = Utc::now();
+        let history_dir = run_history_dir(&self.storage_path);
+        for job in self.list_scheduled_jobs().await {
+            if job.trigger.is_some() || job.paused {
+                continue;
+            }
+            // Missed runs recorded in the history have already been handled
+            let last_recorded = read_runs(&history_dir, &job.id, 1)
+                .ok()
+                .and_then(|runs| runs.first().map(|run| run.started_at));
+            let Some(since) = job.last_run.max(last_recorded) else {
+                continue;
+            };
+            match missed_runs(
+                &tokio_cron_expression(&job.cron),
+                since,
+                now,
+                MAX_CATCH_UP_RUNS,
+            ) {
+                Ok(missed) => {
+                    tokio::spawn(catch_up_missed_runs(
+                        self.run_context(),
+                        job.id,
+                        missed,
+                        false,
+                    ));
+                }
+                Err(e) => tracing::warn!("Can't catch up on job '{}': {}", job.id, e),
+            }
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -1063,12 +1470,37 @@ struct JobExecutionError {
     error: String,
 }
 
+/// What a successful run produced
+#[derive(Debug)]
+struct JobOutput {
+    session_id: String,
+    /// The last assistant message of the session
+    final_message: Option<String>,
+    /// The recipe's final output, when it declares a response schema
+    final_output: Option<Value>,
+}
+
+type RunResult =
+    std::result::Result<std::result::Result<JobOutput, JobExecutionError>, tokio::task::JoinError>;
+
 async fn run_scheduled_job_internal(
     job: ScheduledJob,
     provider_override: Option<Arc<dyn GooseProvider>>, // New optional parameter
-    jobs_arc: Option<Arc<Mutex<JobsMap>>>,
-    job_id: Option<String>,
-) -> std::result::Result<String, JobExecutionError> {
+    tracker: Option<RunTracker>,
+    params: HashMap<String, String>,
+) -> std::result::Result<JobOutput, JobExecutionError> {
+    let start = Instant::now();
+    let result = execute_scheduled_job(job, provider_override, tracker, params).await;
+    metrics::record_scheduler_job(result.is_ok(), start.elapsed());
+    result
+}
+
+async fn execute_scheduled_job(
+    job: ScheduledJob,
+    provider_override: Option<Arc<dyn GooseProvider>>,
+    tracker: Option<RunTracker>,
+    params: HashMap<String, String>,
+) -> std::result::Result<JobOutput, JobExecutionError> {
     tracing::info!("Executing job: {} (Source: {})", job.id, job.source);
 
     let recipe_path = Path::new(&job.source);
@@ -1083,7 +1515,13 @@ async fn run_scheduled_job_internal(
         }
     };
 
-    let recipe: Recipe = {
+    let recipe: Recipe = if !params.is_empty() {
+        // Fill in the recipe parameters the job's trigger provided
+        render_recipe_file(recipe_path, &params).map_err(|e| JobExecutionError {
+            job_id: job.id.clone(),
+            error: format!("Failed to render recipe '{}': {}", job.source, e),
+        })
+    } else {
         let extension = recipe_path
             .extension()
             .and_then(|os_str| os_str.to_str())
@@ -1116,12 +1554,19 @@ async fn run_scheduled_job_internal(
     let agent: Agent = Agent::new();
 
     let agent_provider: Arc<dyn GooseProvider>; // Use the aliased GooseProvider
+    let mut model = None;
 
     if let Some(provider) = provider_override {
         agent_provider = provider;
     } else {
+        // The recipe's own provider and model, if it picks them, or the configured ones
+        let settings = recipe.settings.as_ref();
         let global_config = Config::global();
-        let provider_name: String = match global_config.get_param("GOOSE_PROVIDER") {
+        let provider_name: String = match settings
+            .and_then(|s| s.goose_provider.clone())
+            .map(Ok)
+            .unwrap_or_else(|| global_config.get_param("GOOSE_PROVIDER"))
+        {
             Ok(name) => name,
             Err(_) => return Err(JobExecutionError {
                 job_id: job.id.clone(),
@@ -1131,7 +1576,11 @@ async fn run_scheduled_job_internal(
             }),
         };
         let model_name: String =
-            match global_config.get_param("GOOSE_MODEL") {
+            match settings
+                .and_then(|s| s.goose_model.clone())
+                .map(Ok)
+                .unwrap_or_else(|| global_config.get_param("GOOSE_MODEL"))
+            {
                 Ok(name) => name,
                 Err(_) => return Err(JobExecutionError {
                     job_id: job.id.clone(),
@@ -1140,7 +1589,8 @@ async fn run_scheduled_job_internal(
                             .to_string(),
                 }),
             };
-        let model_config = crate::model::ModelConfig::new(model_name.clone());
+        let model_config = crate::model::ModelConfig::new(model_name.clone())
+            .with_temperature(settings.and_then(|s| s.temperature));
         agent_provider = create(&provider_name, model_config).map_err(|e| JobExecutionError {
             job_id: job.id.clone(),
             error: format!(
@@ -1148,9 +1598,10 @@ async fn run_scheduled_job_internal(
                 provider_name, e
             ),
         })?;
+        model = Some((provider_name, model_name));
     }
 
-    if let Err(e) = agent.update_provider(agent_provider).await {
+    if let Err(e) = agent.update_provider(agent_provider.clone()).await {
         return Err(JobExecutionError {
             job_id: job.id.clone(),
             error: format!("Failed to set provider on agent: {}", e),
@@ -1158,18 +1609,24 @@ async fn run_scheduled_job_internal(
     }
     tracing::info!("Agent configured with provider for job '{}'", job.id);
 
+    if let Some(response) = recipe.response.clone() {
+        agent.add_final_output_tool(response).await;
+    }
+
     // Log the execution mode
     let execution_mode = job.execution_mode.as_deref().unwrap_or("background");
     tracing::info!("Job '{}' running in {} mode", job.id, execution_mode);
 
     let session_id_for_return = session::generate_session_id();
 
-    // Update the job with the session ID if we have access to the jobs arc
-    if let (Some(jobs_arc), Some(job_id_str)) = (jobs_arc.as_ref(), job_id.as_ref()) {
-        let mut jobs_guard = jobs_arc.lock().await;
-        if let Some((_, job_def)) = jobs_guard.get_mut(job_id_str) {
-            job_def.current_session_id = Some(session_id_for_return.clone());
-        }
+    // Report the session ID to the run, if it's tracked
+    if let Some(tracker) = &tracker {
+        tracker
+            .started_session(RunSession {
+                session_id: session_id_for_return.clone(),
+                model,
+            })
+            .await;
     }
 
     let session_file_path = match crate::session::storage::get_path(
@@ -1184,6 +1641,18 @@ async fn run_scheduled_job_internal(
         }
     };
 
+    if recipe.steps.is_some() {
+        return execute_scheduled_workflow(
+            &job,
+            &recipe,
+            agent_provider,
+            session_id_for_return,
+            &session_file_path,
+        )
+        .await;
+    }
+
+    let mut final_message = None;
     if let Some(prompt_text) = recipe.prompt {
         let mut all_session_messages: Vec<Message> =
             vec![Message::user().with_text(prompt_text.clone())];
@@ -1230,6 +1699,17 @@ async fn run_scheduled_job_internal(
                         Ok(AgentEvent::ModelChange { .. }) => {
                             // Model change events are informational, just continue
                         }
+                        Ok(AgentEvent::HistoryReplaced(messages)) => {
+                            all_session_messages = messages;
+                        }
+                        Ok(AgentEvent::ExtensionRestarted(restart)) => {
+                            tracing::warn!(
+                                "[Job {}] Extension '{}' was restarted: {:?}",
+                                job.id,
+                                restart.name,
+                                restart.error
+                            );
+                        }
 
                         Err(e) => {
                             tracing::error!(
@@ -1242,6 +1722,12 @@ async fn run_scheduled_job_internal(
                     }
                 }
 
+                final_message = all_session_messages
+                    .iter()
+                    .rev()
+                    .find(|m| m.role == mcp_core::role::Role::Assistant)
+                    .map(|m| m.as_concat_text());
+
                 match crate::session::storage::read_metadata(&session_file_path) {
                     Ok(mut updated_metadata) => {
                         updated_metadata.message_count = all_session_messages.len();
@@ -1274,6 +1760,8 @@ async fn run_scheduled_job_internal(
                             accumulated_total_tokens: None,
                             accumulated_input_tokens: None,
                             accumulated_output_tokens: None,
+                            parent_session_id: None,
+                            recipe: None,
                         };
                         if let Err(e_fb) = crate::session::storage::save_messages_with_metadata(
                             &session_file_path,
@@ -1293,37 +1781,126 @@ async fn run_scheduled_job_internal(
             }
         }
     } else {
-        tracing::warn!(
-            "[Job {}] Recipe '{}' has no prompt to execute.",
-            job.id,
-            job.source
-        );
-        let metadata = crate::session::storage::SessionMetadata {
-            working_dir: std::env::current_dir().unwrap_or_default(),
-            description: "Empty job - no prompt".to_string(),
+        return Err(JobExecutionError {
+            job_id: job.id.clone(),
+            error: format!("Recipe '{}' has no prompt or steps to run", job.source),
+        });
+    }
+
+    let final_output = agent
+        .final_output()
+        .await
+        .map(|output| serde_json::from_str(&output).unwrap_or(Value::String(output)));
+
+    tracing::info!("Finished job: {}", job.id);
+    Ok(JobOutput {
+        session_id: session_id_for_return,
+        final_message,
+        final_output,
+    })
+}
+
+/// Run the steps of a workflow recipe, each in its own session linked to the job's session,
+/// which records the outcome of every step
+async fn execute_scheduled_workflow(
+    job: &ScheduledJob,
+    recipe: &Recipe,
+    provider: Arc<dyn GooseProvider>,
+    session_id: String,
+    session_file_path: &Path,
+) -> std::result::Result<JobOutput, JobExecutionError> {
+    let job_error = |error: String| JobExecutionError {
+        job_id: job.id.clone(),
+        error,
+    };
+
+    let working_dir = std::env::current_dir().map_err(|e| {
+        job_error(format!(
+            "Failed to get current directory for job execution: {}",
+            e
+        ))
+    })?;
+    let mut messages = vec![Message::user().with_text(format!("Run workflow: {}", recipe.title))];
+    let metadata = SessionMetadata {
+        description: recipe.title.clone(),
+        schedule_id: Some(job.id.clone()),
+        message_count: messages.len(),
+        ..SessionMetadata::new(working_dir.clone())
+    };
+    session::storage::save_messages_with_metadata(session_file_path, &metadata, &messages)
+        .map_err(|e| job_error(format!("Failed to create the workflow session: {}", e)))?;
+
+    let context = TaskContext {
+        provider: Some(provider),
+        parent_session: Some(SessionConfig {
+            id: session::storage::Identifier::Name(session_id.clone()),
+            working_dir,
             schedule_id: Some(job.id.clone()),
-            message_count: 0,
-            ..Default::default()
+            execution_mode: None,
+            max_turns: None,
+        }),
+        working_dir: None,
+    };
+    let results = run_workflow(recipe, context, |step, result| {
+        tracing::info!("[Job {}] Step '{}' {}", job.id, step.id, result.status);
+    })
+    .await
+    .map_err(|e| job_error(format!("Workflow '{}' failed: {}", job.source, e)))?;
+
+    let output_text = |output: &Value| match output {
+        Value::String(text) => text.clone(),
+        other => serde_json::to_string_pretty(other).unwrap_or_else(|_| other.to_string()),
+    };
+    for result in &results {
+        let outcome = match (&result.data, &result.error) {
+            (Some(output), _) => output_text(output),
+            (None, error) => error.clone().unwrap_or_default(),
         };
-        if let Err(e) =
-            crate::session::storage::save_messages_with_metadata(&session_file_path, &metadata, &[])
-        {
-            tracing::error!(
-                "[Job {}] Failed to persist metadata for empty job: {}",
-                job.id,
-                e
-            );
+        messages.push(Message::assistant().with_text(format!(
+            "Step {} {}:\n{}",
+            result.task_id, result.status, outcome
+        )));
+    }
+    match session::storage::read_metadata(session_file_path) {
+        Ok(mut metadata) => {
+            metadata.message_count = messages.len();
+            if let Err(e) = session::storage::save_messages_with_metadata(
+                session_file_path,
+                &metadata,
+                &messages,
+            ) {
+                tracing::error!("[Job {}] Failed to persist workflow results: {}", job.id, e);
+            }
         }
+        Err(e) => tracing::error!(
+            "[Job {}] Failed to read workflow session metadata: {}",
+            job.id,
+            e
+        ),
     }
 
+    if let Some(failed) = results.iter().find(|r| r.status == "failed") {
+        return Err(job_error(format!(
+            "Workflow step '{}' failed: {}",
+            failed.task_id,
+            failed.error.clone().unwrap_or_default()
+        )));
+    }
+    let final_output = results.iter().rev().find_map(|r| r.data.clone());
     tracing::info!("Finished job: {}", job.id);
-    Ok(session_id_for_return)
+    Ok(JobOutput {
+        session_id,
+        final_message: final_output.as_ref().map(output_text),
+        final_output,
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::recipe::Recipe;
+    use crate::scheduler_runs::RetryPolicy;
+    use crate::scheduler_triggers::CompletionStatus;
     use crate::{
         message::MessageContent,
         model::ModelConfig, // Use the actual ModelConfig for the mock's field
@@ -1420,6 +1997,8 @@ mod tests {
             settings: None,
             response: None,
             sub_recipes: None,
+            steps: None,
+            tests: None,
         };
         let mut recipe_file = File::create(&recipe_filename)?;
         writeln!(
@@ -1440,6 +2019,9 @@ mod tests {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()), // Default for test
+            trigger: None,
+            policy: RunPolicy::default(),
+            notifiers: Vec::new(),
         };
 
         // Create the mock provider instance for the test
@@ -1447,10 +2029,15 @@ mod tests {
         let mock_provider_instance = create_scheduler_test_mock_provider(mock_model_config);
 
         // Call run_scheduled_job_internal, passing the mock provider
-        let created_session_id =
-            run_scheduled_job_internal(dummy_job.clone(), Some(mock_provider_instance), None, None)
-                .await
-                .expect("run_scheduled_job_internal failed");
+        let created_session_id = run_scheduled_job_internal(
+            dummy_job.clone(),
+            Some(mock_provider_instance),
+            None,
+            HashMap::new(),
+        )
+        .await
+        .expect("run_scheduled_job_internal failed")
+        .session_id;
 
         let session_dir = session::storage::ensure_session_dir()?;
         let expected_session_path = session_dir.join(format!("{}.jsonl", created_session_id));
@@ -1493,6 +2080,270 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_failed_runs_are_retried() {
+        let job = ScheduledJob {
+            id: "test_schedule_retries".to_string(),
+            source: "/nonexistent/recipe.yaml".to_string(),
+            cron: "0 0 * * * *".to_string(),
+            last_run: None,
+            currently_running: false,
+            paused: false,
+            current_session_id: None,
+            process_start_time: None,
+            execution_mode: None,
+            trigger: None,
+            policy: RunPolicy {
+                retry: Some(RetryPolicy {
+                    max_attempts: 3,
+                    backoff_seconds: 0,
+                    max_backoff_seconds: 0,
+                }),
+                ..RunPolicy::default()
+            },
+            notifiers: Vec::new(),
+        };
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let result = run_job_with_retries(
+            job.clone(),
+            Arc::new(Mutex::new(HashMap::new())),
+            job.id.clone(),
+            HashMap::new(),
+            attempts.clone(),
+        )
+        .await;
+
+        assert!(result
+            .unwrap_err()
+            .error
+            .contains("Failed to load recipe file"));
+        assert_eq!(attempts.load(Ordering::Relaxed), 3);
+    }
+
+    // Runs of this recipe fail as soon as they load it, so triggers can be tested without a
+    // provider
+    fn broken_recipe(dir: &Path) -> String {
+        let path = dir.join("broken.yaml");
+        fs::write(&path, "title: [").unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    fn trigger_job(id: &str, source: &str, trigger: Option<JobTrigger>) -> ScheduledJob {
+        ScheduledJob {
+            id: id.to_string(),
+            source: source.to_string(),
+            cron: "0 0 0 1 1 *".to_string(),
+            last_run: None,
+            currently_running: false,
+            paused: false,
+            current_session_id: None,
+            process_start_time: None,
+            execution_mode: None,
+            trigger,
+            policy: RunPolicy::default(),
+            notifiers: Vec::new(),
+        }
+    }
+
+    async fn wait_for_run(scheduler: &Scheduler, job_id: &str) -> JobRun {
+        for _ in 0..100 {
+            if let Some(run) = scheduler.run_history(job_id, 1).await.unwrap().pop() {
+                return run;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("job '{}' never ran", job_id);
+    }
+
+    #[tokio::test]
+    async fn test_webhook_trigger() {
+        use hmac::{Hmac, Mac};
+
+        let dir = tempdir().unwrap();
+        let scheduler = Scheduler::new(dir.path().join("schedules.json"))
+            .await
+            .unwrap();
+        let source = broken_recipe(dir.path());
+        let webhook = |secret: &str| {
+            Some(JobTrigger::Webhook {
+                secret: secret.to_string(),
+                params: HashMap::new(),
+            })
+        };
+
+        let id = "test_trigger_webhook";
+        assert!(scheduler
+            .add_scheduled_job(trigger_job(id, &source, webhook("")))
+            .await
+            .is_err());
+        scheduler
+            .add_scheduled_job(trigger_job(id, &source, webhook("s3cret")))
+            .await
+            .unwrap();
+
+        let payload = br#"{"title": "Fix it"}"#;
+        let timestamp = Utc::now().timestamp().to_string();
+        assert!(matches!(
+            scheduler
+                .trigger_webhook(id, payload, &timestamp, "0000")
+                .await,
+            Err(SchedulerError::InvalidWebhookSignature(_))
+        ));
+        let listed = scheduler.list_scheduled_jobs().await;
+        let listed = serde_json::to_value(listed[0].clone().without_secrets()).unwrap();
+        assert!(!listed.to_string().contains("s3cret"));
+
+        let mut mac = Hmac::<sha2::Sha256>::new_from_slice(b"s3cret").unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        let signature: String = mac
+            .finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        scheduler
+            .trigger_webhook(id, payload, &timestamp, &signature)
+            .await
+            .unwrap();
+
+        let run = wait_for_run(&scheduler, id).await;
+        assert_eq!(run.reason, RunReason::Trigger);
+        assert_eq!(run.status, RunStatus::Failed);
+        scheduler.remove_scheduled_job(id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_after_trigger() {
+        let dir = tempdir().unwrap();
+        let scheduler = Scheduler::new(dir.path().join("schedules.json"))
+            .await
+            .unwrap();
+        let source = broken_recipe(dir.path());
+        let after = |job_id: &str| {
+            Some(JobTrigger::After {
+                job_id: job_id.to_string(),
+                status: CompletionStatus::Any,
+            })
+        };
+
+        let (first, second) = ("test_trigger_after_first", "test_trigger_after_second");
+        scheduler
+            .add_scheduled_job(trigger_job(first, &source, None))
+            .await
+            .unwrap();
+        scheduler
+            .add_scheduled_job(trigger_job(second, &source, after(first)))
+            .await
+            .unwrap();
+
+        // A job can't run after itself, or after a job that in the end runs after it
+        let third = "test_trigger_after_third";
+        let err = scheduler
+            .add_scheduled_job(trigger_job(third, &source, after(third)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("would run after itself"));
+        let fourth = "test_trigger_after_fourth";
+        scheduler
+            .add_scheduled_job(trigger_job(fourth, &source, after(third)))
+            .await
+            .unwrap();
+        let err = scheduler
+            .add_scheduled_job(trigger_job(third, &source, after(fourth)))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(&format!(
+            "{} runs after {} runs after {}",
+            third, fourth, third
+        )));
+
+        assert!(scheduler.run_now(first).await.is_err());
+        let run = wait_for_run(&scheduler, second).await;
+        assert_eq!(run.reason, RunReason::Trigger);
+        for id in [first, second, fourth] {
+            scheduler.remove_scheduled_job(id).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_change_trigger() {
+        let dir = tempdir().unwrap();
+        let scheduler = Scheduler::new(dir.path().join("schedules.json"))
+            .await
+            .unwrap();
+        let source = broken_recipe(dir.path());
+        let watched = dir.path().canonicalize().unwrap().join("notes");
+        fs::create_dir_all(&watched).unwrap();
+
+        let id = "test_trigger_file_change";
+        let trigger = JobTrigger::FileChange {
+            path: format!("{}/*.md", watched.display()),
+            debounce_seconds: 0,
+        };
+        scheduler
+            .add_scheduled_job(trigger_job(id, &source, Some(trigger)))
+            .await
+            .unwrap();
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        fs::write(watched.join("today.md"), "notes").unwrap();
+        let run = wait_for_run(&scheduler, id).await;
+        assert_eq!(run.reason, RunReason::Trigger);
+        scheduler.remove_scheduled_job(id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_now_follows_run_policy() {
+        let dir = tempdir().unwrap();
+        let scheduler = Scheduler::new(dir.path().join("schedules.json"))
+            .await
+            .unwrap();
+        let id = "test_run_now_policy";
+        let mut job = trigger_job(id, &broken_recipe(dir.path()), None);
+        job.policy.retry = Some(RetryPolicy {
+            max_attempts: 2,
+            backoff_seconds: 0,
+            max_backoff_seconds: 0,
+        });
+        scheduler.add_scheduled_job(job).await.unwrap();
+        scheduler.pause_schedule(id).await.unwrap();
+
+        // Paused jobs still run now, and failed runs are retried
+        assert!(scheduler.run_now(id).await.is_err());
+        let run = wait_for_run(&scheduler, id).await;
+        assert_eq!(run.reason, RunReason::RunNow);
+        assert_eq!(run.status, RunStatus::Failed);
+        assert_eq!(run.attempts, 2);
+
+        let job = scheduler.list_scheduled_jobs().await.pop().unwrap();
+        assert!(!job.currently_running);
+        assert!(job.current_session_id.is_none());
+        scheduler.remove_scheduled_job(id).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_slots_follow_max_concurrent() {
+        let slots = Arc::new(Semaphore::new(1));
+        let mut size = 1;
+        resize_run_slots(&slots, &mut size, 3);
+        assert_eq!(slots.available_permits(), 3);
+
+        // Slots taken by runs going are given up as the runs finish
+        let first = slots.clone().try_acquire_owned().unwrap();
+        let second = slots.clone().try_acquire_owned().unwrap();
+        resize_run_slots(&slots, &mut size, 1);
+        assert_eq!(size, 1);
+        assert_eq!(slots.available_permits(), 0);
+        drop(first);
+        tokio::task::yield_now().await;
+        assert_eq!(slots.available_permits(), 0);
+        drop(second);
+        assert_eq!(slots.available_permits(), 1);
+    }
 }
 
 #[async_trait]
@@ -1547,4 +2398,27 @@ impl SchedulerTrait for Scheduler {
     ) -> Result<Option<(String, DateTime<Utc>)>, SchedulerError> {
         self.get_running_job_info(sched_id).await
     }
+
+    async fn trigger_webhook(
+        &self,
+        sched_id: &str,
+        payload: &[u8],
+        timestamp: &str,
+        signature: &str,
+    ) -> Result<(), SchedulerError> {
+        self.trigger_webhook(sched_id, payload, timestamp, signature)
+            .await
+    }
+
+    async fn run_history(
+        &self,
+        sched_id: &str,
+        limit: usize,
+    ) -> Result<Vec<JobRun>, SchedulerError> {
+        self.run_history(sched_id, limit).await
+    }
+
+    async fn catch_up(&self) -> Result<(), SchedulerError> {
+        self.catch_up().await
+    }
 }
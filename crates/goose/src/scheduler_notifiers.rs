@@ -0,0 +1,596 @@
+//! Telling people a scheduled job finished: desktop notifications over D-Bus, webhooks,
+//! email through a local SMTP relay, and summary files.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::config::Config;
+use crate::scheduler_runs::{JobRun, RunStatus};
+
+/// Longest a notifier gets to deliver a summary before it's given up on
+pub const NOTIFY_TIMEOUT: Duration = Duration::from_secs(60);
+/// Longest to wait for the SMTP relay to accept a connection or answer a command
+pub const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where a notifier sends the summary of a finished run
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierTarget {
+    /// A desktop notification over D-Bus, on Linux
+    Desktop,
+    /// POST the summary as JSON to `url`
+    Webhook {
+        url: String,
+        #[serde(default)]
+        headers: HashMap<String, String>,
+    },
+    /// Email the summary through the SMTP relay set by `GOOSE_SMTP_HOST` and `GOOSE_SMTP_PORT`
+    Email { to: Vec<String> },
+    /// Write the summary to `path`, as JSON when it ends in `.json` and as text otherwise
+    File { path: String },
+}
+
+/// Which finished runs a notifier is told about
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum NotifyWhen {
+    #[default]
+    Always,
+    OnFailure,
+}
+
+impl NotifyWhen {
+    /// Skipped runs never notify, so missed and overlapping runs don't flood notifiers
+    pub fn matches(self, status: RunStatus) -> bool {
+        match (self, status) {
+            (_, RunStatus::Skipped) => false,
+            (NotifyWhen::Always, _) => true,
+            (NotifyWhen::OnFailure, status) => status == RunStatus::Failed,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+pub struct JobNotifier {
+    #[serde(flatten)]
+    pub target: NotifierTarget,
+    #[serde(default)]
+    pub when: NotifyWhen,
+}
+
+/// What notifiers are told about a finished run
+#[derive(Clone, Serialize, Debug, PartialEq)]
+pub struct RunSummary {
+    #[serde(flatten)]
+    pub run: JobRun,
+    /// The last assistant message of the run
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_message: Option<String>,
+    /// The recipe's final output, when it declares a response schema
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub final_output: Option<Value>,
+}
+
+impl RunSummary {
+    pub fn title(&self) -> String {
+        let outcome = match self.run.status {
+            RunStatus::Success => "succeeded",
+            RunStatus::Failed => "failed",
+            RunStatus::Cancelled => "was cancelled",
+            RunStatus::Skipped => "was skipped",
+        };
+        format!("goose: scheduled job {} {}", self.run.job_id, outcome)
+    }
+
+    pub fn text(&self) -> String {
+        let mut lines = vec![
+            format!("Status: {}", self.run.status),
+            format!("Started: {}", self.run.started_at.to_rfc3339()),
+            format!("Duration: {:.1}s", self.run.duration_ms as f64 / 1000.0),
+        ];
+        if self.run.attempts > 1 {
+            lines.push(format!("Attempts: {}", self.run.attempts));
+        }
+        if let Some(cost) = self.run.cost {
+            lines.push(format!("Cost: ${:.4}", cost));
+        }
+        if let Some(session_id) = &self.run.session_id {
+            lines.push(format!("Session: {}", session_id));
+        }
+        if let Some(error) = &self.run.error {
+            lines.push(format!("Error: {}", error));
+        }
+        if let Some(message) = &self.final_message {
+            lines.push(String::new());
+            lines.push(message.clone());
+        }
+        if let Some(output) = &self.final_output {
+            lines.push(String::new());
+            lines.push(serde_json::to_string_pretty(output).unwrap_or_else(|_| output.to_string()));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The SMTP relay email notifications go through, without authentication or TLS, so meant
+/// to be a relay on the local machine or network
+#[derive(Clone, Debug)]
+pub struct SmtpRelay {
+    pub host: String,
+    pub port: u16,
+    pub from: String,
+    pub timeout: Duration,
+}
+
+impl SmtpRelay {
+    /// The relay from the user's config, never a project's, so a cloned repository can't
+    /// redirect run summaries
+    pub fn from_config() -> Self {
+        let config = Config::global();
+        Self {
+            host: config
+                .get_user_param("GOOSE_SMTP_HOST")
+                .unwrap_or_else(|_| "localhost".to_string()),
+            port: config.get_user_param("GOOSE_SMTP_PORT").unwrap_or(25),
+            from: config
+                .get_user_param("GOOSE_SMTP_FROM")
+                .unwrap_or_else(|_| "goose@localhost".to_string()),
+            timeout: SMTP_TIMEOUT,
+        }
+    }
+}
+
+async fn smtp_reply<R: AsyncBufRead + Unpin>(reader: &mut R, timeout: Duration) -> Result<u16> {
+    loop {
+        let mut line = String::new();
+        let read = tokio::time::timeout(timeout, reader.read_line(&mut line))
+            .await
+            .map_err(|_| anyhow!("SMTP relay didn't answer within {}s", timeout.as_secs()))?;
+        if read? == 0 {
+            return Err(anyhow!("SMTP relay closed the connection"));
+        }
+        // Every line of a multi-line reply but the last has a '-' after the code
+        if line.as_bytes().get(3) == Some(&b'-') {
+            continue;
+        }
+        return line
+            .get(..3)
+            .and_then(|code| code.parse().ok())
+            .ok_or_else(|| anyhow!("Unexpected SMTP reply: {}", line.trim()));
+    }
+}
+
+async fn smtp_command<R, W>(
+    reader: &mut R,
+    writer: &mut W,
+    command: &str,
+    expected: u16,
+    timeout: Duration,
+) -> Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(format!("{}\r\n", command).as_bytes())
+        .await?;
+    writer.flush().await?;
+    match smtp_reply(reader, timeout).await? {
+        code if code == expected => Ok(()),
+        code => Err(anyhow!(
+            "SMTP relay answered {} to {}",
+            code,
+            command.split(':').next().unwrap_or(command)
+        )),
+    }
+}
+
+// A header value or command argument with its line breaks taken out, so it can't start
+// another header or command
+fn single_line(value: &str) -> String {
+    value.replace(['\r', '\n'], "")
+}
+
+// The message as sent after DATA: CRLF line endings, lines starting with '.' doubled, and
+// the terminating '.'
+fn smtp_message(from: &str, to: &[String], subject: &str, body: &str) -> String {
+    let to: Vec<String> = to.iter().map(|address| single_line(address)).collect();
+    let headers = [
+        format!("From: {}", single_line(from)),
+        format!("To: {}", to.join(", ")),
+        format!("Subject: {}", single_line(subject)),
+        format!("Date: {}", chrono::Utc::now().to_rfc2822()),
+        "MIME-Version: 1.0".to_string(),
+        "Content-Type: text/plain; charset=utf-8".to_string(),
+    ];
+    // A bare CR ends a line too, like CRLF and LF
+    let body = body
+        .replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .lines()
+        .map(|line| match line.starts_with('.') {
+            true => format!(".{}", line),
+            false => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\r\n");
+    format!("{}\r\n\r\n{}\r\n.", headers.join("\r\n"), body)
+}
+
+/// Send a plain text email through `relay`
+pub async fn send_email(relay: &SmtpRelay, to: &[String], subject: &str, body: &str) -> Result<()> {
+    if to.is_empty() {
+        return Err(anyhow!("No email recipients"));
+    }
+    let unreachable = |reason: String| {
+        anyhow!(
+            "Can't reach SMTP relay {}:{}: {}",
+            relay.host,
+            relay.port,
+            reason
+        )
+    };
+    let stream = tokio::time::timeout(
+        relay.timeout,
+        TcpStream::connect((relay.host.as_str(), relay.port)),
+    )
+    .await
+    .map_err(|_| unreachable(format!("no answer within {}s", relay.timeout.as_secs())))?
+    .map_err(|e| unreachable(e.to_string()))?;
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    if smtp_reply(&mut reader, relay.timeout).await? != 220 {
+        return Err(anyhow!("SMTP relay refused the connection"));
+    }
+    let timeout = relay.timeout;
+    smtp_command(&mut reader, &mut writer, "EHLO localhost", 250, timeout).await?;
+    smtp_command(
+        &mut reader,
+        &mut writer,
+        &format!("MAIL FROM:<{}>", single_line(&relay.from)),
+        250,
+        timeout,
+    )
+    .await?;
+    for recipient in to {
+        smtp_command(
+            &mut reader,
+            &mut writer,
+            &format!("RCPT TO:<{}>", single_line(recipient)),
+            250,
+            timeout,
+        )
+        .await?;
+    }
+    smtp_command(&mut reader, &mut writer, "DATA", 354, timeout).await?;
+    let message = smtp_message(&relay.from, to, subject, body);
+    if smtp_command(&mut reader, &mut writer, &message, 250, timeout)
+        .await
+        .is_err()
+    {
+        return Err(anyhow!("SMTP relay didn't accept the message"));
+    }
+    // The message is accepted, so a relay that hangs up without answering QUIT is fine
+    let _ = smtp_command(&mut reader, &mut writer, "QUIT", 221, timeout).await;
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+async fn notify_desktop(title: String, body: String) -> Result<()> {
+    use dbus::arg::PropMap;
+    use dbus::blocking::Connection;
+    use std::time::Duration;
+
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        let connection = Connection::new_session()?;
+        let proxy = connection.with_proxy(
+            "org.freedesktop.Notifications",
+            "/org/freedesktop/Notifications",
+            Duration::from_secs(5),
+        );
+        let (_id,): (u32,) = proxy.method_call(
+            "org.freedesktop.Notifications",
+            "Notify",
+            (
+                "goose",
+                0u32,
+                "",
+                title,
+                body,
+                Vec::<String>::new(),
+                PropMap::new(),
+                -1i32,
+            ),
+        )?;
+        Ok(())
+    })
+    .await?
+}
+
+#[cfg(not(target_os = "linux"))]
+async fn notify_desktop(_title: String, _body: String) -> Result<()> {
+    Err(anyhow!(
+        "Desktop notifications go over D-Bus, which is only available on Linux"
+    ))
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn write_summary_file(path: &str, summary: &RunSummary) -> Result<()> {
+    let path = expand_home(path);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let contents = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::to_string_pretty(summary)?,
+        _ => format!("{}\n\n{}\n", summary.title(), summary.text()),
+    };
+    std::fs::write(&path, contents)?;
+    Ok(())
+}
+
+// Shared by webhook notifiers so they reuse connections
+static WEBHOOK_CLIENT: Lazy<reqwest::Client> = Lazy::new(reqwest::Client::new);
+
+/// Send a finished run's summary to one notifier
+pub async fn notify(target: &NotifierTarget, summary: &RunSummary) -> Result<()> {
+    match target {
+        NotifierTarget::Desktop => notify_desktop(summary.title(), summary.text()).await,
+        NotifierTarget::Webhook { url, headers } => {
+            let mut request = WEBHOOK_CLIENT
+                .post(url)
+                .timeout(NOTIFY_TIMEOUT)
+                .json(summary);
+            for (name, value) in headers {
+                request = request.header(name, value);
+            }
+            request.send().await?.error_for_status()?;
+            Ok(())
+        }
+        NotifierTarget::Email { to } => {
+            send_email(
+                &SmtpRelay::from_config(),
+                to,
+                &summary.title(),
+                &summary.text(),
+            )
+            .await
+        }
+        NotifierTarget::File { path } => write_summary_file(path, summary),
+    }
+}
+
+/// Send a finished run's summary to each of a job's notifiers that wants it, giving each up to
+/// `NOTIFY_TIMEOUT`. Failing to notify is logged rather than failing the run.
+pub async fn notify_all(notifiers: &[JobNotifier], summary: &RunSummary) {
+    for notifier in notifiers {
+        if !notifier.when.matches(summary.run.status) {
+            continue;
+        }
+        let notified = tokio::time::timeout(NOTIFY_TIMEOUT, notify(&notifier.target, summary))
+            .await
+            .unwrap_or_else(|_| Err(anyhow!("No answer within {}s", NOTIFY_TIMEOUT.as_secs())));
+        if let Err(e) = notified {
+            tracing::warn!(
+                "Failed to notify about job '{}' ({:?}): {}",
+                summary.run.job_id,
+                notifier.target,
+                e
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scheduler_runs::RunReason;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use tokio::net::TcpListener;
+
+    fn summary(status: RunStatus) -> RunSummary {
+        RunSummary {
+            run: JobRun {
+                job_id: "daily-report".to_string(),
+                reason: RunReason::Schedule,
+                status,
+                started_at: Utc.with_ymd_and_hms(2025, 1, 1, 9, 0, 0).unwrap(),
+                duration_ms: 4200,
+                attempts: 2,
+                error: None,
+                session_id: Some("20250101_090000".to_string()),
+                cost: Some(0.0125),
+            },
+            final_message: Some("Report is ready.\n.hidden line".to_string()),
+            final_output: Some(json!({"pages": 3})),
+        }
+    }
+
+    #[test]
+    fn test_notifier_config() {
+        let notifiers: Vec<JobNotifier> = serde_json::from_value(json!([
+            {"type": "desktop"},
+            {"type": "webhook", "url": "http://localhost:9000/hook", "when": "on_failure"},
+            {"type": "email", "to": ["me@example.com"]},
+            {"type": "file", "path": "~/reports/latest.json"}
+        ]))
+        .unwrap();
+        assert_eq!(notifiers[0].target, NotifierTarget::Desktop);
+        assert_eq!(notifiers[0].when, NotifyWhen::Always);
+        assert_eq!(notifiers[1].when, NotifyWhen::OnFailure);
+        assert_eq!(
+            notifiers[2].target,
+            NotifierTarget::Email {
+                to: vec!["me@example.com".to_string()]
+            }
+        );
+
+        assert!(NotifyWhen::Always.matches(RunStatus::Cancelled));
+        assert!(!NotifyWhen::Always.matches(RunStatus::Skipped));
+        assert!(NotifyWhen::OnFailure.matches(RunStatus::Failed));
+        assert!(!NotifyWhen::OnFailure.matches(RunStatus::Success));
+    }
+
+    #[test]
+    fn test_summary_payload() {
+        let summary = summary(RunStatus::Success);
+        let payload = serde_json::to_value(&summary).unwrap();
+        assert_eq!(payload["job_id"], "daily-report");
+        assert_eq!(payload["status"], "success");
+        assert_eq!(payload["cost"], 0.0125);
+        assert_eq!(payload["final_output"], json!({"pages": 3}));
+        assert_eq!(payload["final_message"], "Report is ready.\n.hidden line");
+
+        assert_eq!(
+            summary.title(),
+            "goose: scheduled job daily-report succeeded"
+        );
+        let text = summary.text();
+        assert!(text.contains("Attempts: 2"));
+        assert!(text.contains("Cost: $0.0125"));
+        assert!(text.contains("\"pages\": 3"));
+    }
+
+    #[test]
+    fn test_write_summary_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let summary = summary(RunStatus::Failed);
+
+        let json_path = dir.path().join("runs/latest.json");
+        write_summary_file(json_path.to_str().unwrap(), &summary).unwrap();
+        let written: Value =
+            serde_json::from_str(&std::fs::read_to_string(&json_path).unwrap()).unwrap();
+        assert_eq!(written["status"], "failed");
+
+        let text_path = dir.path().join("latest.txt");
+        write_summary_file(text_path.to_str().unwrap(), &summary).unwrap();
+        let written = std::fs::read_to_string(&text_path).unwrap();
+        assert!(written.starts_with("goose: scheduled job daily-report failed\n"));
+    }
+
+    #[tokio::test]
+    async fn test_send_email() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let relay = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader);
+            let mut received = Vec::new();
+            writer.write_all(b"220 relay ready\r\n").await.unwrap();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_string();
+                let reply: &[u8] = if in_data {
+                    if line == "." {
+                        in_data = false;
+                        b"250 queued\r\n"
+                    } else {
+                        received.push(line);
+                        continue;
+                    }
+                } else if line.starts_with("EHLO") {
+                    b"250-relay\r\n250 8BITMIME\r\n"
+                } else if line == "DATA" {
+                    in_data = true;
+                    b"354 go ahead\r\n"
+                } else if line == "QUIT" {
+                    writer.write_all(b"221 bye\r\n").await.unwrap();
+                    received.push(line);
+                    break;
+                } else {
+                    b"250 ok\r\n"
+                };
+                received.push(line);
+                writer.write_all(reply).await.unwrap();
+            }
+            received
+        });
+
+        let smtp = SmtpRelay {
+            host: "127.0.0.1".to_string(),
+            port,
+            from: "goose@localhost".to_string(),
+            timeout: SMTP_TIMEOUT,
+        };
+        let summary = summary(RunStatus::Success);
+        send_email(
+            &smtp,
+            &["a@example.com".to_string(), "b@example.com".to_string()],
+            &summary.title(),
+            &summary.text(),
+        )
+        .await
+        .unwrap();
+
+        let received = relay.await.unwrap();
+        assert_eq!(received[1], "MAIL FROM:<goose@localhost>");
+        assert_eq!(received[2], "RCPT TO:<a@example.com>");
+        assert_eq!(received[3], "RCPT TO:<b@example.com>");
+        assert!(
+            received.contains(&"Subject: goose: scheduled job daily-report succeeded".to_string())
+        );
+        assert!(received.contains(&"..hidden line".to_string()));
+        assert_eq!(received.last().unwrap(), "QUIT");
+    }
+
+    #[tokio::test]
+    async fn test_send_email_gives_up_on_a_silent_relay() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let relay = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            drop(stream);
+        });
+
+        let smtp = SmtpRelay {
+            host: "127.0.0.1".to_string(),
+            port,
+            from: "goose@localhost".to_string(),
+            timeout: Duration::from_millis(100),
+        };
+        let err = send_email(&smtp, &["a@example.com".to_string()], "Subject", "Body")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("didn't answer"));
+        relay.abort();
+    }
+
+    #[test]
+    fn test_smtp_message_keeps_line_breaks_out_of_headers() {
+        let message = smtp_message(
+            "goose@localhost",
+            &["a@example.com\r\nBcc: b@example.com".to_string()],
+            "goose: scheduled job x\r\nBcc: c@example.com succeeded",
+            "one\rtwo\r\n.three",
+        );
+        let lines: Vec<&str> = message.split("\r\n").collect();
+        assert!(!lines.iter().any(|line| line.starts_with("Bcc:")));
+        assert!(lines.contains(&"To: a@example.comBcc: b@example.com"));
+        assert!(lines.contains(&"Subject: goose: scheduled job xBcc: c@example.com succeeded"));
+        assert!(lines.iter().all(|line| !line.contains('\r')));
+        assert!(lines.ends_with(&["one", "two", "..three", "."]));
+    }
+}
@@ -0,0 +1,365 @@
+//! How scheduled jobs run: retrying failed runs, limiting overlapping runs and catching up on
+//! runs missed while goose wasn't running, and the history of each job's runs.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use croner::Cron;
+use serde::{Deserialize, Serialize};
+
+/// Most missed runs caught up on, or recorded as skipped, at once
+pub const MAX_CATCH_UP_RUNS: usize = 10;
+
+/// Runs kept in a job's history
+pub const MAX_HISTORY_RUNS: usize = 500;
+
+fn default_backoff_seconds() -> u64 {
+    30
+}
+
+fn default_max_backoff_seconds() -> u64 {
+    600
+}
+
+fn default_max_concurrent() -> u32 {
+    1
+}
+
+/// How a failed run is retried
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each retry after it
+    #[serde(default = "default_backoff_seconds")]
+    pub backoff_seconds: u64,
+    /// Longest delay between retries
+    #[serde(default = "default_max_backoff_seconds")]
+    pub max_backoff_seconds: u64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff_seconds: default_backoff_seconds(),
+            max_backoff_seconds: default_max_backoff_seconds(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before retrying after attempt `attempt` failed, counting from 1
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt.saturating_sub(1));
+        Duration::from_secs(
+            self.backoff_seconds
+                .saturating_mul(factor)
+                .min(self.max_backoff_seconds),
+        )
+    }
+}
+
+/// What happens when a job is due while `max_concurrent` runs of it are still going
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OverlapPolicy {
+    /// Don't start the new run
+    #[default]
+    Skip,
+    /// Start the new run once one finishes
+    Queue,
+    /// Cancel the oldest run and start the new one
+    CancelPrevious,
+}
+
+/// What happens to runs missed while goose wasn't running or the machine was asleep
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CatchUpPolicy {
+    /// Record the missed runs as skipped
+    #[default]
+    Skip,
+    /// Run once for all of the missed runs
+    RunOnce,
+    /// Run once for each missed run, up to ten
+    RunAll,
+}
+
+/// How a scheduled job's runs are retried, overlap and catch up
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(default)]
+pub struct RunPolicy {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retry: Option<RetryPolicy>,
+    /// Most runs of the job going at once
+    #[serde(default = "default_max_concurrent")]
+    pub max_concurrent: u32,
+    pub overlap: OverlapPolicy,
+    pub catch_up: CatchUpPolicy,
+}
+
+impl Default for RunPolicy {
+    fn default() -> Self {
+        Self {
+            retry: None,
+            max_concurrent: default_max_concurrent(),
+            overlap: OverlapPolicy::default(),
+            catch_up: CatchUpPolicy::default(),
+        }
+    }
+}
+
+/// The times `cron` was due after `after` up to and including `until`, at most `limit` of them
+pub fn missed_runs(
+    cron: &str,
+    after: DateTime<Utc>,
+    until: DateTime<Utc>,
+    limit: usize,
+) -> Result<Vec<DateTime<Utc>>> {
+    let cron = Cron::new(cron)
+        .with_seconds_optional()
+        .parse()
+        .map_err(|e| anyhow!("Invalid cron expression '{}': {}", cron, e))?;
+    let mut runs = Vec::new();
+    let mut from = after;
+    while runs.len() < limit {
+        match cron.find_next_occurrence(&from, false) {
+            Ok(next) if next <= until => {
+                runs.push(next);
+                from = next;
+            }
+            _ => break,
+        }
+    }
+    Ok(runs)
+}
+
+/// What started a run
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunReason {
+    Schedule,
+    Trigger,
+    CatchUp,
+    RunNow,
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Success,
+    Failed,
+    Cancelled,
+    Skipped,
+}
+
+impl std::fmt::Display for RunStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RunStatus::Success => write!(f, "success"),
+            RunStatus::Failed => write!(f, "failed"),
+            RunStatus::Cancelled => write!(f, "cancelled"),
+            RunStatus::Skipped => write!(f, "skipped"),
+        }
+    }
+}
+
+/// One run of a scheduled job, as kept in its history
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+pub struct JobRun {
+    pub job_id: String,
+    pub reason: RunReason,
+    pub status: RunStatus,
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Attempts made, more than one when the run was retried
+    pub attempts: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// Estimated cost of the model calls of all the run's attempts in USD, when pricing is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost: Option<f64>,
+}
+
+impl JobRun {
+    /// A run that was due at `at` but didn't start
+    pub fn skipped(job_id: &str, reason: RunReason, at: DateTime<Utc>, why: &str) -> Self {
+        Self {
+            job_id: job_id.to_string(),
+            reason,
+            status: RunStatus::Skipped,
+            started_at: at,
+            duration_ms: 0,
+            attempts: 0,
+            error: Some(why.to_string()),
+            session_id: None,
+            cost: None,
+        }
+    }
+}
+
+/// Where run histories are kept, next to the schedules file
+pub fn run_history_dir(storage_path: &Path) -> PathBuf {
+    storage_path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join("schedule_history")
+}
+
+fn history_file(dir: &Path, job_id: &str) -> PathBuf {
+    dir.join(format!("{}.jsonl", job_id))
+}
+
+fn read_history_file(path: &Path) -> Result<Vec<JobRun>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(run) => Some(run),
+            Err(e) => {
+                tracing::warn!("Skipping unreadable run in {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Add a run to its job's history, dropping the oldest runs past [`MAX_HISTORY_RUNS`]
+pub fn append_run(dir: &Path, run: &JobRun) -> Result<()> {
+    fs::create_dir_all(dir)?;
+    let path = history_file(dir, &run.job_id);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    writeln!(file, "{}", serde_json::to_string(run)?)?;
+    drop(file);
+
+    let runs = read_history_file(&path)?;
+    if runs.len() > MAX_HISTORY_RUNS {
+        let kept = runs[runs.len() - MAX_HISTORY_RUNS..]
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<Vec<_>, _>>()?;
+        fs::write(&path, format!("{}\n", kept.join("\n")))?;
+    }
+    Ok(())
+}
+
+/// A job's most recent runs, newest first
+pub fn read_runs(dir: &Path, job_id: &str, limit: usize) -> Result<Vec<JobRun>> {
+    let mut runs = read_history_file(&history_file(dir, job_id))?;
+    runs.reverse();
+    runs.truncate(limit);
+    Ok(runs)
+}
+
+pub fn remove_history(dir: &Path, job_id: &str) -> Result<()> {
+    let path = history_file(dir, job_id);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn run(job_id: &str, status: RunStatus, minute: u32) -> JobRun {
+        JobRun {
+            job_id: job_id.to_string(),
+            reason: RunReason::Schedule,
+            status,
+            started_at: Utc.with_ymd_and_hms(2025, 1, 1, 9, minute, 0).unwrap(),
+            duration_ms: 1200,
+            attempts: 1,
+            error: None,
+            session_id: Some(format!("session_{}", minute)),
+            cost: Some(0.01),
+        }
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let retry = RetryPolicy {
+            max_attempts: 5,
+            backoff_seconds: 10,
+            max_backoff_seconds: 60,
+        };
+        assert_eq!(retry.delay(1), Duration::from_secs(10));
+        assert_eq!(retry.delay(2), Duration::from_secs(20));
+        assert_eq!(retry.delay(3), Duration::from_secs(40));
+        assert_eq!(retry.delay(4), Duration::from_secs(60));
+        assert_eq!(retry.delay(40), Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_run_policy_defaults() {
+        let policy: RunPolicy = serde_json::from_str("{}").unwrap();
+        assert_eq!(policy, RunPolicy::default());
+        assert_eq!(policy.max_concurrent, 1);
+
+        let policy: RunPolicy = serde_json::from_str(
+            r#"{"retry": {"max_attempts": 3}, "overlap": "cancel_previous", "catch_up": "run_once"}"#,
+        )
+        .unwrap();
+        assert_eq!(policy.retry.unwrap().backoff_seconds, 30);
+        assert_eq!(policy.overlap, OverlapPolicy::CancelPrevious);
+        assert_eq!(policy.catch_up, CatchUpPolicy::RunOnce);
+    }
+
+    #[test]
+    fn test_missed_runs() {
+        let after = Utc.with_ymd_and_hms(2025, 1, 1, 9, 30, 0).unwrap();
+        let until = Utc.with_ymd_and_hms(2025, 1, 1, 13, 0, 0).unwrap();
+
+        let missed = missed_runs("0 0 * * * *", after, until, MAX_CATCH_UP_RUNS).unwrap();
+        assert_eq!(
+            missed,
+            (10..=13)
+                .map(|hour| Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(missed_runs("0 * * * *", after, until, 2).unwrap().len(), 2);
+        assert!(missed_runs("0 0 * * *", after, until, 10)
+            .unwrap()
+            .is_empty());
+        assert!(missed_runs("not cron", after, until, 10).is_err());
+    }
+
+    #[test]
+    fn test_run_history() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(read_runs(dir.path(), "daily", 10).unwrap().is_empty());
+
+        append_run(dir.path(), &run("daily", RunStatus::Success, 0)).unwrap();
+        append_run(dir.path(), &run("daily", RunStatus::Failed, 1)).unwrap();
+        append_run(dir.path(), &run("other", RunStatus::Success, 2)).unwrap();
+
+        let runs = read_runs(dir.path(), "daily", 10).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].status, RunStatus::Failed);
+        assert_eq!(runs[1], run("daily", RunStatus::Success, 0));
+        assert_eq!(read_runs(dir.path(), "daily", 1).unwrap().len(), 1);
+
+        for minute in 0..MAX_HISTORY_RUNS as u32 {
+            append_run(dir.path(), &run("daily", RunStatus::Success, minute % 60)).unwrap();
+        }
+        let runs = read_runs(dir.path(), "daily", usize::MAX).unwrap();
+        assert_eq!(runs.len(), MAX_HISTORY_RUNS);
+        assert!(runs.iter().all(|run| run.status == RunStatus::Success));
+
+        remove_history(dir.path(), "daily").unwrap();
+        assert!(read_runs(dir.path(), "daily", 10).unwrap().is_empty());
+        assert_eq!(read_runs(dir.path(), "other", 10).unwrap().len(), 1);
+    }
+}
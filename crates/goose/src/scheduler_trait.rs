@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 
 use crate::scheduler::{ScheduledJob, SchedulerError};
+use crate::scheduler_runs::JobRun;
 use crate::session::storage::SessionMetadata;
 
 /// Common trait for all scheduler implementations
@@ -44,4 +45,35 @@ pub trait SchedulerTrait: Send + Sync {
         &self,
         sched_id: &str,
     ) -> Result<Option<(String, DateTime<Utc>)>, SchedulerError>;
+
+    /// Run a webhook-triggered job with a payload signed at `timestamp`
+    async fn trigger_webhook(
+        &self,
+        sched_id: &str,
+        _payload: &[u8],
+        _timestamp: &str,
+        _signature: &str,
+    ) -> Result<(), SchedulerError> {
+        Err(SchedulerError::SchedulerInternalError(format!(
+            "Webhook triggers are not supported by this scheduler (job '{}')",
+            sched_id
+        )))
+    }
+
+    /// Get the most recent runs of a scheduled job, newest first
+    async fn run_history(
+        &self,
+        sched_id: &str,
+        _limit: usize,
+    ) -> Result<Vec<JobRun>, SchedulerError> {
+        Err(SchedulerError::SchedulerInternalError(format!(
+            "Run history is not supported by this scheduler (job '{}')",
+            sched_id
+        )))
+    }
+
+    /// Handle the runs missed while the scheduler wasn't running
+    async fn catch_up(&self) -> Result<(), SchedulerError> {
+        Ok(())
+    }
 }
@@ -0,0 +1,476 @@
+//! Events that run a scheduled job besides its cron schedule: changes to files matching a
+//! glob, a signed webhook delivered to goose-server, or another job finishing.
+
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use hmac::{Hmac, Mac};
+use notify::{RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use tokio::sync::mpsc;
+
+/// Parameter holding the path whose change ran a file-change job
+pub const CHANGED_PATH_PARAM: &str = "changed_path";
+
+fn default_debounce_seconds() -> u64 {
+    2
+}
+
+// File events can arrive a little after the writes that caused them
+const RUN_WRITES_SETTLE: Duration = Duration::from_secs(1);
+
+/// How far a webhook's signed timestamp may be from now, so a captured request can't be
+/// replayed later
+pub const WEBHOOK_TOLERANCE: Duration = Duration::from_secs(300);
+
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobTrigger {
+    /// Run when a file matching `path`, a path or glob such as `~/notes/**/*.md`, changes
+    FileChange {
+        path: String,
+        /// How long to wait for changes to settle before running, so a burst of writes runs
+        /// the job once
+        #[serde(default = "default_debounce_seconds")]
+        debounce_seconds: u64,
+    },
+    /// Run when goose-server receives a webhook for the job signed with `secret`. The secret
+    /// is left out of the job as goose-server lists it.
+    Webhook {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        secret: String,
+        /// Recipe parameters to fill from the payload, as JSON pointers such as
+        /// `/pull_request/title`. Without any, top-level fields fill parameters of the same name.
+        #[serde(default)]
+        params: HashMap<String, String>,
+    },
+    /// Run when the job `job_id` finishes
+    After {
+        job_id: String,
+        #[serde(default)]
+        status: CompletionStatus,
+    },
+}
+
+/// Which outcomes of a job start the jobs that run after it
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, Default, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionStatus {
+    #[default]
+    Success,
+    Failure,
+    Any,
+}
+
+impl CompletionStatus {
+    pub fn matches(self, succeeded: bool) -> bool {
+        match self {
+            CompletionStatus::Success => succeeded,
+            CompletionStatus::Failure => !succeeded,
+            CompletionStatus::Any => true,
+        }
+    }
+}
+
+impl std::fmt::Display for JobTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobTrigger::FileChange { path, .. } => write!(f, "on changes to {}", path),
+            JobTrigger::Webhook { .. } => write!(f, "on webhook"),
+            JobTrigger::After { job_id, status } => match status {
+                CompletionStatus::Success => write!(f, "after {} succeeds", job_id),
+                CompletionStatus::Failure => write!(f, "after {} fails", job_id),
+                CompletionStatus::Any => write!(f, "after {} finishes", job_id),
+            },
+        }
+    }
+}
+
+fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    hex.as_bytes()
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => u8::from_str_radix(std::str::from_utf8(&[*high, *low]).ok()?, 16).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Check a webhook signature, the hex HMAC-SHA256 of `<timestamp>.<payload>` with an optional
+/// `sha256=` prefix, where `timestamp` is the unix time in seconds the webhook was signed at
+/// and must be within [`WEBHOOK_TOLERANCE`] of `now`
+pub fn verify_webhook_signature(
+    secret: &str,
+    timestamp: &str,
+    payload: &[u8],
+    signature: &str,
+    now: i64,
+) -> bool {
+    if secret.is_empty() {
+        return false;
+    }
+    let Ok(signed_at) = timestamp.trim().parse::<i64>() else {
+        return false;
+    };
+    if signed_at.abs_diff(now) > WEBHOOK_TOLERANCE.as_secs() {
+        return false;
+    }
+    let signature = signature.trim();
+    let signature = signature.strip_prefix("sha256=").unwrap_or(signature);
+    let Some(signature) = decode_hex(signature) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(timestamp.trim().as_bytes());
+    mac.update(b".");
+    mac.update(payload);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn param_value(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(s) => Some(s.clone()),
+        other => Some(other.to_string()),
+    }
+}
+
+/// The recipe parameters a webhook payload fills in
+pub fn webhook_params(
+    payload: &Value,
+    mapping: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    if mapping.is_empty() {
+        return payload
+            .as_object()
+            .map(|fields| {
+                fields
+                    .iter()
+                    .filter(|(_, value)| !value.is_object() && !value.is_array())
+                    .filter_map(|(key, value)| Some((key.clone(), param_value(value)?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+    mapping
+        .iter()
+        .filter_map(|(key, pointer)| {
+            let value = payload.pointer(pointer)?;
+            Some((key.clone(), param_value(value)?))
+        })
+        .collect()
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()
+            .map(|home| home.join(rest))
+            .unwrap_or_else(|| PathBuf::from(path)),
+        None => PathBuf::from(path),
+    }
+}
+
+fn has_glob(component: &str) -> bool {
+    component.contains(['*', '?', '[', '{'])
+}
+
+/// What a file-change trigger watches, and which changed paths run the job
+#[derive(Debug)]
+pub struct FileWatch {
+    /// The directory, or file, to watch
+    pub root: PathBuf,
+    pub recursive: bool,
+    pattern: Option<glob::Pattern>,
+}
+
+impl FileWatch {
+    pub fn new(path: &str) -> Result<Self> {
+        let path = expand_home(path);
+        let mut root = PathBuf::new();
+        let mut components = path.components();
+        for component in components.by_ref() {
+            if let Component::Normal(part) = component {
+                if has_glob(&part.to_string_lossy()) {
+                    let pattern = glob::Pattern::new(&path.to_string_lossy())
+                        .map_err(|e| anyhow!("Invalid glob {}: {}", path.display(), e))?;
+                    return Ok(Self {
+                        recursive: components.next().is_some()
+                            || part.to_string_lossy().contains("**"),
+                        root,
+                        pattern: Some(pattern),
+                    });
+                }
+            }
+            root.push(component);
+        }
+        if !root.exists() {
+            return Err(anyhow!("{} doesn't exist", root.display()));
+        }
+        Ok(Self {
+            recursive: root.is_dir(),
+            root,
+            pattern: None,
+        })
+    }
+
+    pub fn matches(&self, changed: &Path) -> bool {
+        match &self.pattern {
+            Some(pattern) => pattern.matches_path(changed),
+            None => changed.starts_with(&self.root),
+        }
+    }
+}
+
+/// Watch for changes matching `path`, calling `on_change` with the last changed path once
+/// changes have settled for `debounce`. Runs until the task is aborted.
+///
+/// Changes made while `on_change` runs are dropped, since they include the job's own writes,
+/// which would otherwise run it again and again.
+pub async fn watch_files<F, Fut>(path: String, debounce: Duration, on_change: F) -> Result<()>
+where
+    F: Fn(PathBuf) -> Fut,
+    Fut: std::future::Future<Output = ()>,
+{
+    let watch = FileWatch::new(&path)?;
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher =
+        notify::recommended_watcher(move |event: notify::Result<notify::Event>| match event {
+            Ok(event)
+                if event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove() =>
+            {
+                for path in event.paths {
+                    let _ = tx.send(path);
+                }
+            }
+            Ok(_) => {}
+            Err(e) => tracing::warn!("Error watching files: {}", e),
+        })?;
+    let mode = if watch.recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    watcher.watch(&watch.root, mode)?;
+    tracing::info!("Watching {} for changes to {}", watch.root.display(), path);
+
+    while let Some(changed) = rx.recv().await {
+        if !watch.matches(&changed) {
+            continue;
+        }
+        let mut last = changed;
+        // Let a burst of changes settle, keeping the last path that matched
+        while let Ok(Some(changed)) = tokio::time::timeout(debounce, rx.recv()).await {
+            if watch.matches(&changed) {
+                last = changed;
+            }
+        }
+        on_change(last).await;
+        // Drop what changed during the run, and what's still arriving from it
+        while let Ok(Some(_)) =
+            tokio::time::timeout(debounce.max(RUN_WRITES_SETTLE), rx.recv()).await
+        {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn sign(secret: &str, timestamp: &str, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(timestamp.as_bytes());
+        mac.update(b".");
+        mac.update(payload);
+        mac.finalize()
+            .into_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    #[test]
+    fn test_verify_webhook_signature() {
+        let now = 1_700_000_000;
+        let payload = br#"{"ok":true}"#;
+        let signature = sign("s3cret", "1700000000", payload);
+
+        assert!(verify_webhook_signature(
+            "s3cret",
+            "1700000000",
+            payload,
+            &signature,
+            now
+        ));
+        assert!(verify_webhook_signature(
+            "s3cret",
+            "1700000000",
+            payload,
+            &format!("sha256={}", signature),
+            now + 60
+        ));
+        assert!(!verify_webhook_signature(
+            "other",
+            "1700000000",
+            payload,
+            &signature,
+            now
+        ));
+        assert!(!verify_webhook_signature(
+            "s3cret",
+            "1700000000",
+            br#"{"ok":false}"#,
+            &signature,
+            now
+        ));
+        assert!(!verify_webhook_signature(
+            "s3cret",
+            "1700000001",
+            payload,
+            &signature,
+            now
+        ));
+        // A replay after the tolerance has passed
+        assert!(!verify_webhook_signature(
+            "s3cret",
+            "1700000000",
+            payload,
+            &signature,
+            now + 301
+        ));
+        assert!(!verify_webhook_signature(
+            "s3cret",
+            "1700000000",
+            b"",
+            "not hex",
+            now
+        ));
+        assert!(!verify_webhook_signature(
+            "",
+            "1700000000",
+            b"",
+            &sign("", "1700000000", b""),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_webhook_params() {
+        let payload = json!({
+            "action": "opened",
+            "number": 42,
+            "pull_request": {"title": "Fix it", "draft": false},
+            "labels": ["bug"],
+            "body": null
+        });
+
+        let params = webhook_params(&payload, &HashMap::new());
+        assert_eq!(params.len(), 2);
+        assert_eq!(params["action"], "opened");
+        assert_eq!(params["number"], "42");
+
+        let mapping = HashMap::from([
+            ("title".to_string(), "/pull_request/title".to_string()),
+            ("draft".to_string(), "/pull_request/draft".to_string()),
+            ("missing".to_string(), "/nope".to_string()),
+        ]);
+        let params = webhook_params(&payload, &mapping);
+        assert_eq!(params.len(), 2);
+        assert_eq!(params["title"], "Fix it");
+        assert_eq!(params["draft"], "false");
+    }
+
+    #[test]
+    fn test_file_watch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().to_string_lossy().into_owned();
+
+        let watch = FileWatch::new(&format!("{}/**/*.md", root)).unwrap();
+        assert_eq!(watch.root, dir.path());
+        assert!(watch.recursive);
+        assert!(watch.matches(&dir.path().join("notes/today.md")));
+        assert!(!watch.matches(&dir.path().join("notes/today.txt")));
+
+        let watch = FileWatch::new(&format!("{}/*.csv", root)).unwrap();
+        assert!(!watch.recursive);
+        assert!(watch.matches(&dir.path().join("data.csv")));
+
+        let watch = FileWatch::new(&root).unwrap();
+        assert!(watch.recursive);
+        assert!(watch.matches(&dir.path().join("any/file")));
+
+        assert!(FileWatch::new(&format!("{}/missing", root)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_watch_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        let path = format!("{}/*.txt", dir_path.display());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let watcher = tokio::spawn(watch_files(path, Duration::from_millis(200), move |p| {
+            let tx = tx.clone();
+            async move {
+                let _ = tx.send(p);
+            }
+        }));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        std::fs::write(dir_path.join("ignored.log"), "x").unwrap();
+        std::fs::write(dir_path.join("notes.txt"), "x").unwrap();
+        let changed = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed.file_name().unwrap(), "notes.txt");
+        watcher.abort();
+    }
+
+    #[tokio::test]
+    async fn test_watch_files_ignores_the_runs_own_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir_path = dir.path().canonicalize().unwrap();
+        let path = format!("{}/*.md", dir_path.display());
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let output = dir_path.join("summary.md");
+        let watcher = tokio::spawn(watch_files(path, Duration::from_millis(200), move |p| {
+            let tx = tx.clone();
+            let output = output.clone();
+            async move {
+                // A run that writes a file its own trigger watches
+                std::fs::write(&output, "summary").unwrap();
+                let _ = tx.send(p);
+            }
+        }));
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        std::fs::write(dir_path.join("notes.md"), "x").unwrap();
+        let changed = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed.file_name().unwrap(), "notes.md");
+        assert!(
+            tokio::time::timeout(Duration::from_secs(3), rx.recv())
+                .await
+                .is_err(),
+            "the run's own write ran the job again"
+        );
+
+        // Changes after the run has settled still run the job
+        std::fs::write(dir_path.join("notes.md"), "y").unwrap();
+        let changed = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(changed.file_name().unwrap(), "notes.md");
+        watcher.abort();
+    }
+}
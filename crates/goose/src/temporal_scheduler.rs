@@ -10,6 +10,7 @@ use tokio::time::sleep;
 use tracing::{info, warn};
 
 use crate::scheduler::{normalize_cron_expression, ScheduledJob, SchedulerError};
+use crate::scheduler_runs::RunPolicy;
 use crate::scheduler_trait::SchedulerTrait;
 use crate::session::storage::SessionMetadata;
 
@@ -546,6 +547,24 @@ impl TemporalScheduler {
             job.id
         );
 
+        if let Some(trigger) = &job.trigger {
+            return Err(SchedulerError::SchedulerInternalError(format!(
+                "Jobs that run {} are not supported by the Temporal scheduler",
+                trigger
+            )));
+        }
+        if job.policy != RunPolicy::default() {
+            return Err(SchedulerError::SchedulerInternalError(
+                "Retry, overlap and catch-up policies are not supported by the Temporal scheduler"
+                    .to_string(),
+            ));
+        }
+        if !job.notifiers.is_empty() {
+            return Err(SchedulerError::SchedulerInternalError(
+                "Job notifiers are not supported by the Temporal scheduler".to_string(),
+            ));
+        }
+
         // Normalize the cron expression to ensure it's 6-field format
         let normalized_cron = normalize_cron_expression(&job.cron);
         if normalized_cron != job.cron {
@@ -601,6 +620,9 @@ impl TemporalScheduler {
                         current_session_id: None, // Not provided by Temporal service
                         process_start_time: None, // Not provided by Temporal service
                         execution_mode: tj.execution_mode,
+                        trigger: None,
+                        policy: RunPolicy::default(),
+                        notifiers: Vec::new(),
                     }
                 })
                 .collect();
@@ -0,0 +1,246 @@
+//! Spans and metrics following the OpenTelemetry GenAI semantic conventions.
+//!
+//! Spans are plain `tracing` spans using the `otel.*` and `gen_ai.*` field names, so they
+//! reach every layer and are exported as conventional spans by the OTLP layer. Metrics
+//! are recorded against the global meter provider, which is a no-op unless OTLP export is
+//! on, and in the Prometheus metrics of [`crate::metrics`].
+
+use std::time::{Duration, Instant};
+
+use mcp_core::handler::ToolError;
+use mcp_core::tool::Tool;
+use once_cell::sync::Lazy;
+use opentelemetry::metrics::{Counter, Histogram};
+use opentelemetry::KeyValue;
+use tracing::field::Empty;
+use tracing::{Instrument, Span};
+
+use crate::message::Message;
+use crate::metrics;
+use crate::providers::base::{Provider, ProviderDescriptor, ProviderUsage};
+use crate::providers::errors::ProviderError;
+use crate::providers::pricing::get_model_pricing;
+
+struct Instruments {
+    operation_duration: Histogram<f64>,
+    token_usage: Histogram<u64>,
+    cost: Counter<f64>,
+    tool_duration: Histogram<f64>,
+}
+
+static INSTRUMENTS: Lazy<Instruments> = Lazy::new(|| {
+    let meter = opentelemetry::global::meter("goose");
+    Instruments {
+        operation_duration: meter
+            .f64_histogram("gen_ai.client.operation.duration")
+            .with_unit("s")
+            .with_description("Duration of GenAI operations")
+            .build(),
+        token_usage: meter
+            .u64_histogram("gen_ai.client.token.usage")
+            .with_unit("{token}")
+            .with_description("Number of input and output tokens used")
+            .build(),
+        cost: meter
+            .f64_counter("goose.cost")
+            .with_unit("USD")
+            .with_description("Estimated cost of model calls")
+            .build(),
+        tool_duration: meter
+            .f64_histogram("goose.tool.duration")
+            .with_unit("s")
+            .with_description("Duration of tool calls")
+            .build(),
+    }
+});
+
+/// Call the model in a chat span, recording its duration, token usage and cost
+pub async fn complete(
+    provider: &dyn Provider,
+    system_prompt: &str,
+    messages: &[Message],
+    tools: &[Tool],
+) -> Result<(Message, ProviderUsage), ProviderError> {
+    let model = provider.get_model_config().model_name;
+    let span = chat_span(&model);
+    let start = Instant::now();
+    let result = provider
+        .complete(system_prompt, messages, tools)
+        .instrument(span.clone())
+        .await;
+    match &result {
+        Ok((_, usage)) => {
+            let system = provider_name(provider, &usage.model);
+            record_chat_response(&span, &system, usage, start.elapsed()).await;
+        }
+        Err(e) => {
+            let system = provider_name(provider, &model);
+            record_chat_error(&span, &system, &model, e, start.elapsed());
+        }
+    }
+    result
+}
+
+/// The provider that served `model`, used as `gen_ai.system`
+pub fn provider_name(provider: &dyn Provider, model: &str) -> String {
+    match provider.as_lead_worker() {
+        Some(lead_worker) => lead_worker.get_provider_name(model),
+        None => provider.get_metadata().name,
+    }
+}
+
+/// Span for one model call, completed with [`record_chat_response`] or [`record_chat_error`]
+fn chat_span(model: &str) -> Span {
+    tracing::info_span!(
+        "chat",
+        otel.name = format!("chat {}", model),
+        otel.kind = "client",
+        otel.status_code = Empty,
+        gen_ai.operation.name = "chat",
+        gen_ai.system = Empty,
+        gen_ai.request.model = model,
+        gen_ai.response.model = Empty,
+        gen_ai.usage.input_tokens = Empty,
+        gen_ai.usage.output_tokens = Empty,
+        goose.cost.usd = Empty,
+        error.type = Empty,
+    )
+}
+
+async fn record_chat_response(
+    span: &Span,
+    system: &str,
+    usage: &ProviderUsage,
+    duration: Duration,
+) {
+    let attributes = [
+        KeyValue::new("gen_ai.operation.name", "chat"),
+        KeyValue::new("gen_ai.system", system.to_string()),
+        KeyValue::new("gen_ai.response.model", usage.model.clone()),
+    ];
+    INSTRUMENTS
+        .operation_duration
+        .record(duration.as_secs_f64(), &attributes);
+
+    span.record("gen_ai.system", system);
+    span.record("gen_ai.response.model", usage.model.as_str());
+    let input_tokens = usage.usage.input_tokens.unwrap_or(0).max(0) as u64;
+    let output_tokens = usage.usage.output_tokens.unwrap_or(0).max(0) as u64;
+    metrics::record_provider_request(system, &usage.model, duration, None);
+    if usage.usage.input_tokens.is_some() {
+        span.record("gen_ai.usage.input_tokens", input_tokens);
+        record_token_usage(&attributes, "input", input_tokens);
+        metrics::record_tokens(system, &usage.model, "input", input_tokens);
+    }
+    if usage.usage.output_tokens.is_some() {
+        span.record("gen_ai.usage.output_tokens", output_tokens);
+        record_token_usage(&attributes, "output", output_tokens);
+        metrics::record_tokens(system, &usage.model, "output", output_tokens);
+    }
+
+    if let Some(pricing) = get_model_pricing(system, &usage.model).await {
+        let cost =
+            input_tokens as f64 * pricing.input_cost + output_tokens as f64 * pricing.output_cost;
+        span.record("goose.cost.usd", cost);
+        INSTRUMENTS.cost.add(cost, &attributes);
+    }
+}
+
+fn record_token_usage(attributes: &[KeyValue], token_type: &'static str, tokens: u64) {
+    let mut attributes = attributes.to_vec();
+    attributes.push(KeyValue::new("gen_ai.token.type", token_type));
+    INSTRUMENTS.token_usage.record(tokens, &attributes);
+}
+
+fn record_chat_error(
+    span: &Span,
+    system: &str,
+    model: &str,
+    error: &ProviderError,
+    duration: Duration,
+) {
+    let error_type = provider_error_type(error);
+    metrics::record_provider_request(system, model, duration, Some(error_type));
+    span.record("gen_ai.system", system);
+    span.record("otel.status_code", "ERROR");
+    span.record("error.type", error_type);
+    INSTRUMENTS.operation_duration.record(
+        duration.as_secs_f64(),
+        &[
+            KeyValue::new("gen_ai.operation.name", "chat"),
+            KeyValue::new("gen_ai.system", system.to_string()),
+            KeyValue::new("gen_ai.request.model", model.to_string()),
+            KeyValue::new("error.type", error_type),
+        ],
+    );
+}
+
+fn provider_error_type(error: &ProviderError) -> &'static str {
+    match error {
+        ProviderError::Authentication(_) => "authentication",
+        ProviderError::ContextLengthExceeded(_) => "context_length_exceeded",
+        ProviderError::RateLimitExceeded(_) => "rate_limit_exceeded",
+        ProviderError::ServerError(_) => "server_error",
+        ProviderError::RequestFailed(_) => "request_failed",
+        ProviderError::ExecutionError(_) => "execution_error",
+        ProviderError::UsageError(_) => "usage_error",
+    }
+}
+
+/// Span for one tool call, completed with [`record_tool_call`]
+pub fn tool_span(tool_name: &str, call_id: &str) -> Span {
+    let extension = tool_extension(tool_name);
+    tracing::info_span!(
+        "execute_tool",
+        otel.name = format!("execute_tool {}", tool_name),
+        otel.status_code = Empty,
+        gen_ai.operation.name = "execute_tool",
+        gen_ai.tool.name = tool_name,
+        gen_ai.tool.call.id = call_id,
+        goose.extension.name = extension,
+        error.type = Empty,
+    )
+}
+
+/// Tools are prefixed with the name of their extension, as in `developer__shell`
+pub fn tool_extension(tool_name: &str) -> &str {
+    tool_name
+        .split_once("__")
+        .map(|(extension, _)| extension)
+        .unwrap_or_default()
+}
+
+/// Record the outcome of a tool call on its span and in the tool duration metric
+pub fn record_tool_call(
+    span: &Span,
+    tool_name: &str,
+    duration: Duration,
+    error: Option<&ToolError>,
+) {
+    let extension = tool_extension(tool_name);
+    let error_type = error.map(tool_error_type);
+    metrics::record_tool_call(extension, duration, error_type);
+
+    let mut attributes = vec![
+        KeyValue::new("gen_ai.operation.name", "execute_tool"),
+        KeyValue::new("gen_ai.tool.name", tool_name.to_string()),
+        KeyValue::new("goose.extension.name", extension.to_string()),
+    ];
+    if let Some(error_type) = error_type {
+        span.record("otel.status_code", "ERROR");
+        span.record("error.type", error_type);
+        attributes.push(KeyValue::new("error.type", error_type));
+    }
+    INSTRUMENTS
+        .tool_duration
+        .record(duration.as_secs_f64(), &attributes);
+}
+
+fn tool_error_type(error: &ToolError) -> &'static str {
+    match error {
+        ToolError::InvalidParameters(_) => "invalid_parameters",
+        ToolError::ExecutionError(_) => "execution_error",
+        ToolError::SchemaError(_) => "schema_error",
+        ToolError::NotFound(_) => "not_found",
+    }
+}
@@ -1,3 +1,4 @@
+use crate::redaction::Redactor;
 use crate::tracing::observation_layer::{BatchManager, ObservationLayer, SpanTracker};
 use chrono::Utc;
 use reqwest::{Client, StatusCode};
@@ -132,7 +133,8 @@ impl LangfuseBatchManager {
 }
 
 impl BatchManager for LangfuseBatchManager {
-    fn add_event(&mut self, event_type: &str, body: Value) {
+    fn add_event(&mut self, event_type: &str, mut body: Value) {
+        Redactor::global().redact_value(&mut body);
         self.batch.push(json!({
             "id": Uuid::new_v4().to_string(),
             "timestamp": Utc::now().to_rfc3339(),
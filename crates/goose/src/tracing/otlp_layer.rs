@@ -0,0 +1,215 @@
+//! Export goose's spans and metrics to an OpenTelemetry collector over OTLP.
+//!
+//! Export is enabled by setting `OTEL_EXPORTER_OTLP_ENDPOINT` (or the traces or metrics
+//! specific endpoint) and configured with the standard `OTEL_*` environment variables:
+//! `OTEL_EXPORTER_OTLP_PROTOCOL` selects `grpc`, `http/protobuf` (the default) or
+//! `http/json`, `OTEL_EXPORTER_OTLP_HEADERS` adds headers, `OTEL_SERVICE_NAME` and
+//! `OTEL_RESOURCE_ATTRIBUTES` describe the resource, and `OTEL_TRACES_EXPORTER=none` or
+//! `OTEL_METRICS_EXPORTER=none` turn off one of the signals.
+
+use std::env;
+use std::sync::Mutex;
+
+use once_cell::sync::Lazy;
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{MetricExporter, Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+const OTLP_ENDPOINT_VARS: [&str; 3] = [
+    "OTEL_EXPORTER_OTLP_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_TRACES_ENDPOINT",
+    "OTEL_EXPORTER_OTLP_METRICS_ENDPOINT",
+];
+
+struct Providers {
+    tracer: Option<SdkTracerProvider>,
+    meter: Option<SdkMeterProvider>,
+}
+
+static PROVIDERS: Lazy<Mutex<Option<Providers>>> = Lazy::new(|| Mutex::new(None));
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Signal {
+    Traces,
+    Metrics,
+}
+
+impl Signal {
+    fn env_name(self) -> &'static str {
+        match self {
+            Signal::Traces => "TRACES",
+            Signal::Metrics => "METRICS",
+        }
+    }
+
+    fn enabled(self) -> bool {
+        let exporter = env::var(format!("OTEL_{}_EXPORTER", self.env_name())).unwrap_or_default();
+        if exporter.trim().eq_ignore_ascii_case("none") {
+            return false;
+        }
+        env::var("OTEL_EXPORTER_OTLP_ENDPOINT").is_ok()
+            || env::var(format!("OTEL_EXPORTER_OTLP_{}_ENDPOINT", self.env_name())).is_ok()
+    }
+
+    fn protocol(self) -> Protocol {
+        let protocol = env::var(format!("OTEL_EXPORTER_OTLP_{}_PROTOCOL", self.env_name()))
+            .or_else(|_| env::var("OTEL_EXPORTER_OTLP_PROTOCOL"))
+            .unwrap_or_default();
+        parse_protocol(&protocol)
+    }
+}
+
+fn parse_protocol(protocol: &str) -> Protocol {
+    match protocol.trim() {
+        "grpc" => Protocol::Grpc,
+        "http/json" => Protocol::HttpJson,
+        _ => Protocol::HttpBinary,
+    }
+}
+
+/// Whether OTLP export was requested through the environment
+pub fn otlp_enabled() -> bool {
+    let disabled = env::var("OTEL_SDK_DISABLED")
+        .map(|v| v.trim().eq_ignore_ascii_case("true"))
+        .unwrap_or(false);
+    !disabled && OTLP_ENDPOINT_VARS.iter().any(|var| env::var(var).is_ok())
+}
+
+fn resource(default_service_name: &str) -> Resource {
+    let builder = Resource::builder();
+    if env::var("OTEL_SERVICE_NAME").is_ok() {
+        builder.build()
+    } else {
+        builder
+            .with_service_name(default_service_name.to_string())
+            .build()
+    }
+}
+
+fn span_exporter() -> Result<SpanExporter, opentelemetry_otlp::ExporterBuildError> {
+    match Signal::Traces.protocol() {
+        Protocol::Grpc => SpanExporter::builder().with_tonic().build(),
+        protocol => SpanExporter::builder()
+            .with_http()
+            .with_protocol(protocol)
+            .build(),
+    }
+}
+
+fn metric_exporter() -> Result<MetricExporter, opentelemetry_otlp::ExporterBuildError> {
+    match Signal::Metrics.protocol() {
+        Protocol::Grpc => MetricExporter::builder().with_tonic().build(),
+        protocol => MetricExporter::builder()
+            .with_http()
+            .with_protocol(protocol)
+            .build(),
+    }
+}
+
+/// Create a layer exporting spans over OTLP, and install the OTLP meter provider used by
+/// [`super::gen_ai`] for metrics.
+///
+/// Returns None when OTLP export is not configured. Must be called from within a tokio
+/// runtime when the gRPC protocol is used. Call [`shutdown_otlp`] before exiting so the
+/// last batch is sent.
+pub fn create_otlp_layer<S>(
+    default_service_name: &str,
+) -> Option<Box<dyn Layer<S> + Send + Sync + 'static>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    if !otlp_enabled() {
+        return None;
+    }
+    let resource = resource(default_service_name);
+
+    let meter_provider = if Signal::Metrics.enabled() {
+        match metric_exporter() {
+            Ok(exporter) => {
+                let provider = SdkMeterProvider::builder()
+                    .with_periodic_exporter(exporter)
+                    .with_resource(resource.clone())
+                    .build();
+                opentelemetry::global::set_meter_provider(provider.clone());
+                Some(provider)
+            }
+            Err(e) => {
+                eprintln!("Failed to create OTLP metric exporter: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let tracer_provider = if Signal::Traces.enabled() {
+        match span_exporter() {
+            Ok(exporter) => Some(
+                SdkTracerProvider::builder()
+                    .with_batch_exporter(exporter)
+                    .with_resource(resource)
+                    .build(),
+            ),
+            Err(e) => {
+                eprintln!("Failed to create OTLP span exporter: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    // Only export goose's own spans, not those of the HTTP clients doing the exporting
+    let layer = tracer_provider.as_ref().map(|provider| {
+        let filter = Targets::new()
+            .with_target("goose", Level::INFO)
+            .with_target("mcp_client", Level::INFO)
+            .with_default(LevelFilter::OFF);
+        tracing_opentelemetry::layer()
+            .with_tracer(provider.tracer("goose"))
+            .with_filter(filter)
+            .boxed()
+    });
+
+    *PROVIDERS.lock().unwrap() = Some(Providers {
+        tracer: tracer_provider,
+        meter: meter_provider,
+    });
+    layer
+}
+
+/// Flush and stop the OTLP exporters created by [`create_otlp_layer`]
+pub fn shutdown_otlp() {
+    let Some(providers) = PROVIDERS.lock().unwrap().take() else {
+        return;
+    };
+    if let Some(provider) = providers.tracer {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to flush OTLP spans: {}", e);
+        }
+    }
+    if let Some(provider) = providers.meter {
+        if let Err(e) = provider.shutdown() {
+            eprintln!("Failed to flush OTLP metrics: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_protocol() {
+        assert_eq!(parse_protocol("grpc"), Protocol::Grpc);
+        assert_eq!(parse_protocol("http/json"), Protocol::HttpJson);
+        assert_eq!(parse_protocol("http/protobuf"), Protocol::HttpBinary);
+        assert_eq!(parse_protocol(""), Protocol::HttpBinary);
+    }
+}
@@ -142,6 +142,8 @@ async fn run_truncate_test(
             Ok(AgentEvent::ModelChange { .. }) => {
                 // Model change events are informational, just continue
             }
+            Ok(AgentEvent::HistoryReplaced(_)) => {}
+            Ok(AgentEvent::ExtensionRestarted(_)) => {}
 
             Err(e) => {
                 println!("Error: {:?}", e);
@@ -734,6 +736,8 @@ mod max_turns_tests {
                 }
                 Ok(AgentEvent::McpNotification(_)) => {}
                 Ok(AgentEvent::ModelChange { .. }) => {}
+                Ok(AgentEvent::HistoryReplaced(_)) => {}
+                Ok(AgentEvent::ExtensionRestarted(_)) => {}
                 Err(e) => {
                     return Err(e);
                 }
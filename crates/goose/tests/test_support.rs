@@ -11,6 +11,7 @@ use tokio::sync::Mutex;
 
 use goose::agents::Agent;
 use goose::scheduler::{ScheduledJob, SchedulerError};
+use goose::scheduler_runs::RunPolicy;
 use goose::scheduler_trait::SchedulerTrait;
 use goose::session::storage::SessionMetadata;
 
@@ -362,6 +363,9 @@ impl ScheduleToolTestBuilder {
             current_session_id: None,
             process_start_time: None,
             execution_mode: Some("background".to_string()),
+            trigger: None,
+            policy: RunPolicy::default(),
+            notifiers: Vec::new(),
         };
         {
             let mut jobs = self.scheduler.jobs.lock().await;
@@ -410,5 +414,7 @@ pub fn create_test_session_metadata(message_count: usize, working_dir: &str) ->
         accumulated_total_tokens: Some(100),
         accumulated_input_tokens: Some(50),
         accumulated_output_tokens: Some(50),
+        parent_session_id: None,
+        recipe: None,
     }
 }
@@ -6,7 +6,7 @@ use mcp_core::protocol::{
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::sync::{
-    atomic::{AtomicU64, Ordering},
+    atomic::{AtomicBool, AtomicU64, Ordering},
     Arc,
 };
 use thiserror::Error;
@@ -104,6 +104,12 @@ pub trait McpClientTrait: Send + Sync {
     async fn get_prompt(&self, name: &str, arguments: Value) -> Result<GetPromptResult, Error>;
 
     async fn subscribe(&self) -> mpsc::Receiver<JsonRpcMessage>;
+
+    /// Check that the server is still responsive
+    async fn ping(&self) -> Result<(), Error>;
+
+    /// Whether the transport has closed, e.g. because the server process exited
+    fn is_closed(&self) -> bool;
 }
 
 /// The MCP client is the interface for MCP operations.
@@ -116,6 +122,7 @@ where
     server_capabilities: Option<ServerCapabilities>,
     server_info: Option<Implementation>,
     notification_subscribers: Arc<Mutex<Vec<mpsc::Sender<JsonRpcMessage>>>>,
+    closed: Arc<AtomicBool>,
 }
 
 impl<T> McpClient<T>
@@ -128,6 +135,8 @@ where
         let notification_subscribers =
             Arc::new(Mutex::new(Vec::<mpsc::Sender<JsonRpcMessage>>::new()));
         let subscribers_ptr = notification_subscribers.clone();
+        let closed = Arc::new(AtomicBool::new(false));
+        let closed_ptr = closed.clone();
 
         tokio::spawn(async move {
             loop {
@@ -146,6 +155,7 @@ where
                         }
                     }
                     Err(e) => {
+                        closed_ptr.store(true, Ordering::SeqCst);
                         service_ptr.hangup(e).await;
                         subscribers_ptr.lock().await.clear();
                         break;
@@ -162,6 +172,7 @@ where
             server_capabilities: None,
             server_info: None,
             notification_subscribers,
+            closed,
         })
     }
 
@@ -435,4 +446,13 @@ where
         self.notification_subscribers.lock().await.push(tx);
         rx
     }
+
+    async fn ping(&self) -> Result<(), Error> {
+        let _: Value = self.send_request("ping", serde_json::json!({})).await?;
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
 }
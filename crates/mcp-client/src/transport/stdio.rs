@@ -1,9 +1,11 @@
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::Arc;
 use tokio::process::{Child, ChildStderr, ChildStdin, ChildStdout, Command};
 
 use async_trait::async_trait;
+use goose_sandbox::SandboxProfile;
 use mcp_core::protocol::JsonRpcMessage;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
 use tokio::sync::{mpsc, Mutex};
@@ -192,6 +194,8 @@ pub struct StdioTransport {
     command: String,
     args: Vec<String>,
     env: HashMap<String, String>,
+    sandbox: Option<SandboxProfile>,
+    working_dir: Option<PathBuf>,
 }
 
 impl StdioTransport {
@@ -204,11 +208,36 @@ impl StdioTransport {
             command: command.into(),
             args,
             env,
+            sandbox: None,
+            working_dir: None,
         }
     }
 
+    /// Run the process inside an OS-level sandbox
+    pub fn with_sandbox(mut self, sandbox: SandboxProfile) -> Self {
+        self.sandbox = Some(sandbox);
+        self
+    }
+
+    /// Start the process in `dir` instead of the current directory
+    pub fn with_working_dir(mut self, dir: PathBuf) -> Self {
+        self.working_dir = Some(dir);
+        self
+    }
+
     async fn spawn_process(&self) -> Result<(Child, ChildStdin, ChildStdout, ChildStderr), Error> {
         let mut command = Command::new(&self.command);
+        // Applied first, since scrubbing the environment would drop the extension's own variables
+        if let Some(sandbox) = &self.sandbox {
+            // Without a workspace of its own, the process may write to the directory it runs in
+            let mut sandbox = sandbox.clone();
+            if sandbox.workspace.is_none() {
+                sandbox.workspace = self.working_dir.clone();
+            }
+            sandbox.apply(command.as_std_mut()).map_err(|e| {
+                Error::StdioProcessError(format!("Failed to sandbox {}: {}", self.command, e))
+            })?;
+        }
         command
             .envs(&self.env)
             .args(&self.args)
@@ -216,6 +245,9 @@ impl StdioTransport {
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .kill_on_drop(true);
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
 
         // Set process group and ensure signal handling on Unix systems
         #[cfg(unix)]
@@ -225,9 +257,13 @@ impl StdioTransport {
         #[cfg(windows)]
         command.creation_flags(0x08000000); // CREATE_NO_WINDOW flag
 
-        let mut process = command
-            .spawn()
-            .map_err(|e| Error::StdioProcessError(e.to_string()))?;
+        let mut process = command.spawn().map_err(|e| match self.sandbox {
+            Some(_) => Error::StdioProcessError(format!(
+                "Failed to start {} in its sandbox: {}",
+                self.command, e
+            )),
+            None => Error::StdioProcessError(e.to_string()),
+        })?;
 
         let stdin = process
             .stdin
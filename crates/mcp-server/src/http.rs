@@ -0,0 +1,672 @@
+//! Streamable HTTP transport, the server side of `mcp-client`'s `StreamableHttpTransport`.
+//!
+//! Clients POST one JSON-RPC message at a time to [`MCP_ENDPOINT`]. Requests are answered with
+//! an SSE stream carrying any notifications the router sends while handling the request followed
+//! by the response, or with a plain JSON response when the client doesn't accept event streams.
+//! Notifications and responses from the client are acknowledged with `202 Accepted`.
+//!
+//! `initialize` starts a session and returns its id in the `Mcp-Session-Id` header, which every
+//! later request has to echo back. A `DELETE` with that header ends the session.
+//!
+//! Every request has to carry the server's token as `Authorization: Bearer <token>`.
+
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::{Arc, Mutex};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::post,
+};
+use mcp_core::protocol::{
+    ErrorData, JsonRpcError, JsonRpcMessage, JsonRpcResponse, INTERNAL_ERROR, INVALID_REQUEST,
+    PARSE_ERROR,
+};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use tower_service::Service;
+
+use crate::router::McpRequest;
+use crate::{BoxError, Server, ServerError, TransportError};
+
+/// Path the MCP endpoint is served on
+pub const MCP_ENDPOINT: &str = "/mcp";
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+struct HttpState<S> {
+    service: S,
+    sessions: Mutex<HashSet<String>>,
+    token: String,
+}
+
+/// A random token for clients to authenticate with
+pub fn generate_token() -> String {
+    format!(
+        "{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+impl<S> Server<S>
+where
+    S: Service<McpRequest, Response = JsonRpcResponse> + Clone + Send + Sync + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    /// Serve the MCP endpoint over streamable HTTP until the listener fails, accepting only
+    /// requests that carry `token` as a bearer token
+    pub async fn run_http(self, listener: TcpListener, token: String) -> Result<(), ServerError> {
+        tracing::info!(
+            address = ?listener.local_addr().ok(),
+            endpoint = MCP_ENDPOINT,
+            "HTTP server started"
+        );
+        axum::serve(listener, self.into_http_router(token))
+            .await
+            .map_err(|e| ServerError::Transport(TransportError::Io(e)))
+    }
+
+    /// The axum router behind [`Server::run_http`], for embedding the endpoint in another server
+    pub fn into_http_router(self, token: String) -> axum::Router {
+        let state = Arc::new(HttpState {
+            service: self.service,
+            sessions: Mutex::new(HashSet::new()),
+            token,
+        });
+
+        axum::Router::new()
+            .route(
+                MCP_ENDPOINT,
+                post(handle_post::<S>)
+                    .get(handle_get)
+                    .delete(handle_delete::<S>),
+            )
+            .with_state(state)
+    }
+}
+
+async fn handle_post<S>(
+    State(state): State<Arc<HttpState<S>>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Response
+where
+    S: Service<McpRequest, Response = JsonRpcResponse> + Clone + Send + Sync + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    if let Some(response) =
+        reject_foreign_origin(&headers).or_else(|| reject_unauthorized(&state, &headers))
+    {
+        return response;
+    }
+
+    let message = match parse_message(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            return error_response(StatusCode::BAD_REQUEST, None, PARSE_ERROR, e.to_string());
+        }
+    };
+
+    let request = match message {
+        JsonRpcMessage::Request(request) if request.id.is_some() => request,
+        // Notifications and responses only need to be acknowledged
+        _ => {
+            if let Err(response) = check_session(&state, &headers) {
+                return response;
+            }
+            return StatusCode::ACCEPTED.into_response();
+        }
+    };
+
+    let session_id = if request.method == "initialize" {
+        let session_id = uuid::Uuid::new_v4().to_string();
+        state.sessions.lock().unwrap().insert(session_id.clone());
+        tracing::info!(session_id = %session_id, "Session started");
+        session_id
+    } else {
+        match check_session(&state, &headers) {
+            Ok(session_id) => session_id,
+            Err(response) => return response,
+        }
+    };
+
+    tracing::info!(
+        request_id = ?request.id,
+        method = ?request.method,
+        session_id = %session_id,
+        "Received request"
+    );
+
+    let id = request.id;
+    let mut service = state.service.clone();
+    let (notify_tx, mut notify_rx) = mpsc::channel(256);
+    let call = async move {
+        match service
+            .call(McpRequest {
+                request,
+                notifier: notify_tx,
+            })
+            .await
+        {
+            Ok(response) => response,
+            Err(e) => {
+                let error_msg = e.into().to_string();
+                tracing::error!(error = %error_msg, "Request processing failed");
+                JsonRpcResponse {
+                    jsonrpc: "2.0".to_string(),
+                    id,
+                    result: None,
+                    error: Some(ErrorData {
+                        code: INTERNAL_ERROR,
+                        message: error_msg,
+                        data: None,
+                    }),
+                }
+            }
+        }
+    };
+
+    let mut response = if accepts_event_stream(&headers) {
+        let (event_tx, event_rx) = mpsc::channel::<String>(32);
+        tokio::spawn(async move {
+            let mut call = std::pin::pin!(call);
+            let response = loop {
+                tokio::select! {
+                    response = &mut call => break response,
+                    Some(notification) = notify_rx.recv() => {
+                        let _ = event_tx.send(sse_event(&notification)).await;
+                    }
+                }
+            };
+            while let Ok(notification) = notify_rx.try_recv() {
+                let _ = event_tx.send(sse_event(&notification)).await;
+            }
+            let _ = event_tx
+                .send(sse_event(&JsonRpcMessage::Response(response)))
+                .await;
+        });
+
+        let stream = futures::stream::unfold(event_rx, |mut event_rx| async move {
+            let event = event_rx.recv().await?;
+            Some((Ok::<_, Infallible>(Bytes::from(event)), event_rx))
+        });
+        Response::builder()
+            .header(header::CONTENT_TYPE, "text/event-stream")
+            .header(header::CACHE_CONTROL, "no-cache")
+            .body(Body::from_stream(stream))
+            .unwrap()
+    } else {
+        // Without an event stream there is nowhere to send notifications, so drop them
+        tokio::spawn(async move { while notify_rx.recv().await.is_some() {} });
+        let response = call.await;
+        json_response(StatusCode::OK, &JsonRpcMessage::Response(response))
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&session_id) {
+        response.headers_mut().insert(SESSION_ID_HEADER, value);
+    }
+    response
+}
+
+/// Routers never send messages outside of a request, so there is no standalone event stream
+async fn handle_get() -> StatusCode {
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+async fn handle_delete<S>(State(state): State<Arc<HttpState<S>>>, headers: HeaderMap) -> Response
+where
+    S: Send + Sync + 'static,
+{
+    if let Some(response) =
+        reject_foreign_origin(&headers).or_else(|| reject_unauthorized(&state, &headers))
+    {
+        return response;
+    }
+    match check_session(&state, &headers) {
+        Ok(session_id) => {
+            state.sessions.lock().unwrap().remove(&session_id);
+            tracing::info!(session_id = %session_id, "Session ended");
+            StatusCode::OK.into_response()
+        }
+        Err(response) => response,
+    }
+}
+
+/// Same validation as the stdio transport: a JSON object with `"jsonrpc": "2.0"`
+fn parse_message(body: &[u8]) -> Result<JsonRpcMessage, TransportError> {
+    let value: serde_json::Value = serde_json::from_slice(body)?;
+    let Some(object) = value.as_object() else {
+        return Err(TransportError::InvalidMessage(
+            "Message must be a JSON object".into(),
+        ));
+    };
+    if object.get("jsonrpc").and_then(|v| v.as_str()) != Some("2.0") {
+        return Err(TransportError::InvalidMessage(
+            "Missing or invalid jsonrpc version".into(),
+        ));
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// The session id from the request headers, if it belongs to a live session
+#[allow(clippy::result_large_err)]
+fn check_session<S>(state: &HttpState<S>, headers: &HeaderMap) -> Result<String, Response> {
+    let Some(session_id) = headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return Err(error_response(
+            StatusCode::BAD_REQUEST,
+            None,
+            INVALID_REQUEST,
+            "Missing Mcp-Session-Id header".to_string(),
+        ));
+    };
+
+    if !state.sessions.lock().unwrap().contains(session_id) {
+        return Err(error_response(
+            StatusCode::NOT_FOUND,
+            None,
+            INVALID_REQUEST,
+            "Session not found".to_string(),
+        ));
+    }
+    Ok(session_id.to_string())
+}
+
+/// Browsers send an `Origin` header, other clients don't. Only accept browser requests from
+/// pages served by this machine, so a web page can't reach a local server through DNS rebinding.
+fn reject_foreign_origin(headers: &HeaderMap) -> Option<Response> {
+    let origin = headers.get(header::ORIGIN)?.to_str().unwrap_or_default();
+    let host = origin
+        .split_once("://")
+        .map(|(_, rest)| rest)
+        .unwrap_or(origin);
+    let host = if host.starts_with('[') {
+        host.split_inclusive(']').next().unwrap_or(host)
+    } else {
+        host.split(':').next().unwrap_or(host)
+    };
+
+    if matches!(host, "localhost" | "127.0.0.1" | "[::1]") {
+        None
+    } else {
+        tracing::warn!(origin = %origin, "Rejected request from foreign origin");
+        Some(StatusCode::FORBIDDEN.into_response())
+    }
+}
+
+/// Requests without the server's bearer token are turned away, so only clients that were given
+/// the token can call tools
+fn reject_unauthorized<S>(state: &HttpState<S>, headers: &HeaderMap) -> Option<Response> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .unwrap_or_default();
+    if constant_time_eq(token.as_bytes(), state.token.as_bytes()) {
+        return None;
+    }
+    tracing::warn!("Rejected request without a valid bearer token");
+    Some(
+        (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+        )
+            .into_response(),
+    )
+}
+
+// Compares every byte, so the time taken doesn't reveal how much of the token was right
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+fn accepts_event_stream(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/event-stream"))
+}
+
+fn sse_event(message: &JsonRpcMessage) -> String {
+    let json = serde_json::to_string(message).unwrap_or_default();
+    format!("event: message\ndata: {}\n\n", json)
+}
+
+fn json_response(status: StatusCode, message: &JsonRpcMessage) -> Response {
+    let json = serde_json::to_string(message).unwrap_or_default();
+    (status, [(header::CONTENT_TYPE, "application/json")], json).into_response()
+}
+
+fn error_response(status: StatusCode, id: Option<u64>, code: i32, message: String) -> Response {
+    json_response(
+        status,
+        &JsonRpcMessage::Error(JsonRpcError {
+            jsonrpc: "2.0".to_string(),
+            id,
+            error: ErrorData {
+                code,
+                message,
+                data: None,
+            },
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::router::{CapabilitiesBuilder, Router, RouterService};
+    use axum::http::Request;
+    use mcp_core::content::Content;
+    use mcp_core::handler::{PromptError, ResourceError, ToolError};
+    use mcp_core::prompt::Prompt;
+    use mcp_core::protocol::ServerCapabilities;
+    use mcp_core::resource::Resource;
+    use mcp_core::tool::Tool;
+    use serde_json::{json, Value};
+    use std::future::Future;
+    use std::pin::Pin;
+
+    #[derive(Clone)]
+    struct EchoRouter;
+
+    impl Router for EchoRouter {
+        fn name(&self) -> String {
+            "echo".to_string()
+        }
+
+        fn instructions(&self) -> String {
+            String::new()
+        }
+
+        fn capabilities(&self) -> ServerCapabilities {
+            CapabilitiesBuilder::new().with_tools(false).build()
+        }
+
+        fn list_tools(&self) -> Vec<Tool> {
+            vec![Tool::new(
+                "echo",
+                "Echo the input",
+                json!({"type": "object"}),
+                None,
+            )]
+        }
+
+        fn call_tool(
+            &self,
+            _tool_name: &str,
+            arguments: Value,
+            notifier: mpsc::Sender<JsonRpcMessage>,
+        ) -> Pin<Box<dyn Future<Output = Result<Vec<Content>, ToolError>> + Send + 'static>>
+        {
+            Box::pin(async move {
+                let _ = notifier
+                    .send(JsonRpcMessage::Notification(
+                        mcp_core::protocol::JsonRpcNotification {
+                            jsonrpc: "2.0".to_string(),
+                            method: "notifications/message".to_string(),
+                            params: Some(json!({"data": "working"})),
+                        },
+                    ))
+                    .await;
+                Ok(vec![Content::text(arguments.to_string())])
+            })
+        }
+
+        fn list_resources(&self) -> Vec<Resource> {
+            vec![]
+        }
+
+        fn read_resource(
+            &self,
+            _uri: &str,
+        ) -> Pin<Box<dyn Future<Output = Result<String, ResourceError>> + Send + 'static>> {
+            Box::pin(async { Ok(String::new()) })
+        }
+
+        fn list_prompts(&self) -> Vec<Prompt> {
+            vec![]
+        }
+
+        fn get_prompt(
+            &self,
+            prompt_name: &str,
+        ) -> Pin<Box<dyn Future<Output = Result<String, PromptError>> + Send + 'static>> {
+            let prompt_name = prompt_name.to_string();
+            Box::pin(async move { Err(PromptError::NotFound(prompt_name)) })
+        }
+    }
+
+    const TOKEN: &str = "test-token";
+
+    fn app() -> axum::Router {
+        Server::new(RouterService(EchoRouter)).into_http_router(TOKEN.to_string())
+    }
+
+    fn post(body: Value, session_id: Option<&str>, accept: &str) -> Request<Body> {
+        let mut request = Request::post(MCP_ENDPOINT)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header(header::CONTENT_TYPE, "application/json")
+            .header(header::ACCEPT, accept);
+        if let Some(session_id) = session_id {
+            request = request.header(SESSION_ID_HEADER, session_id);
+        }
+        request.body(Body::from(body.to_string())).unwrap()
+    }
+
+    async fn body_text(response: Response) -> String {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        String::from_utf8(bytes.to_vec()).unwrap()
+    }
+
+    async fn initialize(app: &axum::Router) -> String {
+        let response = app
+            .clone()
+            .call(post(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}),
+                None,
+                "application/json",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        response.headers()[SESSION_ID_HEADER]
+            .to_str()
+            .unwrap()
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_initialize_returns_session_and_json_response() {
+        let app = app();
+        let session_id = initialize(&app).await;
+
+        let response = app
+            .clone()
+            .call(post(
+                json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"}),
+                Some(&session_id),
+                "application/json",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let message: JsonRpcMessage = serde_json::from_str(&body_text(response).await).unwrap();
+        match message {
+            JsonRpcMessage::Response(response) => {
+                assert_eq!(response.id, Some(2));
+                assert_eq!(response.result.unwrap()["tools"][0]["name"], "echo");
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ping_returns_empty_result() {
+        let app = app();
+        let session_id = initialize(&app).await;
+
+        let response = app
+            .clone()
+            .call(post(
+                json!({"jsonrpc": "2.0", "id": 2, "method": "ping"}),
+                Some(&session_id),
+                "application/json",
+            ))
+            .await
+            .unwrap();
+        let message: JsonRpcMessage = serde_json::from_str(&body_text(response).await).unwrap();
+        match message {
+            JsonRpcMessage::Response(response) => {
+                assert!(response.error.is_none());
+                assert_eq!(response.result, Some(json!({})));
+            }
+            other => panic!("unexpected message {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_call_streams_notifications_before_response() {
+        let app = app();
+        let session_id = initialize(&app).await;
+
+        let response = app
+            .clone()
+            .call(post(
+                json!({
+                    "jsonrpc": "2.0",
+                    "id": 3,
+                    "method": "tools/call",
+                    "params": {"name": "echo", "arguments": {"hello": "world"}}
+                }),
+                Some(&session_id),
+                "application/json, text/event-stream",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(
+            response.headers()[header::CONTENT_TYPE],
+            "text/event-stream"
+        );
+        assert_eq!(response.headers()[SESSION_ID_HEADER], session_id.as_str());
+
+        let body = body_text(response).await;
+        let events: Vec<JsonRpcMessage> = body
+            .lines()
+            .filter_map(|line| line.strip_prefix("data: "))
+            .map(|data| serde_json::from_str(data).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], JsonRpcMessage::Notification(_)));
+        assert!(matches!(&events[1], JsonRpcMessage::Response(r) if r.id == Some(3)));
+    }
+
+    #[tokio::test]
+    async fn test_session_is_required_and_can_be_deleted() {
+        let app = app();
+        let list = json!({"jsonrpc": "2.0", "id": 2, "method": "tools/list"});
+
+        let response = app
+            .clone()
+            .call(post(list.clone(), None, "application/json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .call(post(list.clone(), Some("unknown"), "application/json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let session_id = initialize(&app).await;
+        let response = app
+            .clone()
+            .call(post(
+                json!({"jsonrpc": "2.0", "method": "notifications/initialized"}),
+                Some(&session_id),
+                "application/json",
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+
+        let delete = Request::delete(MCP_ENDPOINT)
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .header(SESSION_ID_HEADER, &session_id)
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().call(delete).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .call(post(list, Some(&session_id), "application/json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_messages_and_foreign_origins() {
+        let app = app();
+
+        let response = app
+            .clone()
+            .call(post(json!({"id": 1}), None, "application/json"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let request = Request::post(MCP_ENDPOINT)
+            .header(header::ORIGIN, "https://evil.example")
+            .body(Body::from(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let request = Request::post(MCP_ENDPOINT)
+            .header(header::ORIGIN, "http://localhost:3000")
+            .header(header::AUTHORIZATION, format!("Bearer {}", TOKEN))
+            .body(Body::from(
+                json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}).to_string(),
+            ))
+            .unwrap();
+        let response = app.clone().call(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_requires_bearer_token() {
+        let app = app();
+        let initialize = json!({"jsonrpc": "2.0", "id": 1, "method": "initialize"}).to_string();
+
+        for authorization in [None, Some("Bearer wrong-token"), Some(TOKEN)] {
+            let mut request = Request::post(MCP_ENDPOINT);
+            if let Some(authorization) = authorization {
+                request = request.header(header::AUTHORIZATION, authorization);
+            }
+            let request = request.body(Body::from(initialize.clone())).unwrap();
+            let response = app.clone().call(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(response.headers()[header::WWW_AUTHENTICATE], "Bearer");
+        }
+
+        assert!(constant_time_eq(b"abc", b"abc"));
+        assert!(!constant_time_eq(b"abc", b"abd"));
+        assert!(!constant_time_eq(b"abc", b"abcd"));
+    }
+}
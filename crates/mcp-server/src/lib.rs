@@ -16,6 +16,8 @@ use tower_service::Service;
 mod errors;
 pub use errors::{BoxError, RouterError, ServerError, TransportError};
 
+pub mod http;
+
 pub mod router;
 pub use router::Router;
 
@@ -135,7 +137,6 @@ where
         Self { service }
     }
 
-    // TODO transport trait instead of byte transport if we implement others
     pub async fn run<R, W>(self, mut transport: ByteTransport<R, W>) -> Result<(), ServerError>
     where
         R: AsyncRead + Unpin + Send + 'static,
@@ -112,6 +112,17 @@ pub trait Router: Send + Sync + 'static {
         }
     }
 
+    fn handle_ping(
+        &self,
+        req: JsonRpcRequest,
+    ) -> impl Future<Output = Result<JsonRpcResponse, RouterError>> + Send {
+        async move {
+            let mut response = self.create_response(req.id);
+            response.result = Some(Value::Object(Default::default()));
+            Ok(response)
+        }
+    }
+
     fn handle_initialize(
         &self,
         req: JsonRpcRequest,
@@ -395,6 +406,7 @@ pub trait Router: Send + Sync + 'static {
     }
 }
 
+#[derive(Clone)]
 pub struct RouterService<T>(pub T);
 
 pub struct McpRequest {
@@ -420,6 +432,7 @@ where
         Box::pin(async move {
             let result = match req.request.method.as_str() {
                 "initialize" => this.handle_initialize(req.request).await,
+                "ping" => this.handle_ping(req.request).await,
                 "tools/list" => this.handle_tools_list(req.request).await,
                 "tools/call" => this.handle_tools_call(req.request, req.notifier).await,
                 "resources/list" => this.handle_resources_list(req.request).await,
@@ -428,6 +428,16 @@ export type SessionMetadata = {
      * The number of output tokens used in the session. Retrieved from the provider's last usage.
      */
     output_tokens?: number | null;
+    /**
+     * ID of the session that ran this one as a sub-recipe, if any
+     */
+    parent_session_id?: string | null;
+    /**
+     * The recipe the session was started with, so it can be resumed with the same setup
+     */
+    recipe?: {
+        [key: string]: unknown;
+    } | null;
     /**
      * ID of the schedule that triggered this session, if any
      */
@@ -3,6 +3,7 @@ import useSWR from 'swr';
 import { getSecretKey } from '../config';
 import { Message, createUserMessage, hasCompletedToolCalls } from '../types/message';
 import { getSessionHistory } from '../api';
+import { toastError, toastInfo } from '../toasts';
 
 // Ensure TextDecoder is available in the global scope
 const TextDecoder = globalThis.TextDecoder;
@@ -39,6 +40,8 @@ type MessageEvent =
   | { type: 'Error'; error: string }
   | { type: 'Finish'; reason: string }
   | { type: 'ModelChange'; model: string; mode: string }
+  | { type: 'HistoryReplaced'; messages: Message[] }
+  | { type: 'ExtensionRestarted'; name: string; error: string | null }
   | NotificationEvent;
 
 export interface UseMessageStreamOptions {
@@ -290,6 +293,33 @@ export function useMessageStream({
                     break;
                   }
 
+                  case 'HistoryReplaced': {
+                    // The agent compacted the conversation, replace our copy of it
+                    currentMessages = parsedEvent.messages.map((message) => ({
+                      ...message,
+                      display: message.display === undefined ? true : message.display,
+                      sendToLLM: message.sendToLLM === undefined ? true : message.sendToLLM,
+                    }));
+                    mutate(currentMessages, false);
+                    break;
+                  }
+
+                  case 'ExtensionRestarted': {
+                    // An extension crashed mid-session, let the user know what happened to it
+                    if (parsedEvent.error) {
+                      toastError({
+                        title: `Extension ${parsedEvent.name} stopped responding`,
+                        msg: parsedEvent.error,
+                      });
+                    } else {
+                      toastInfo({
+                        title: `Extension ${parsedEvent.name} restarted`,
+                        msg: 'It stopped responding and was restarted automatically',
+                      });
+                    }
+                    break;
+                  }
+
                   case 'Notification': {
                     const newNotification = {
                       ...parsedEvent,